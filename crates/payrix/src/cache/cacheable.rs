@@ -0,0 +1,727 @@
+//! Resource types that can be stored in the entity cache.
+//!
+//! Each cached type has its own table holding the full JSON document plus a
+//! few columns copied out of it for indexing and filtering. A type declares
+//! those columns through [`Cacheable`]; the generic
+//! [`EntityCache::get`](super::EntityCache::get),
+//! [`upsert`](super::EntityCache::upsert) and
+//! [`query`](super::EntityCache::query) methods do the rest.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::entity::EntityType;
+use crate::types::{
+    Batch, Chargeback, ChargebackDocument, ChargebackMessage, ChargebackStatus, Customer,
+    Disbursement, Entry, Fund, Merchant, Plan, Subscription, Token, Transaction,
+};
+
+// =============================================================================
+// Column Types
+// =============================================================================
+
+/// SQL type of an indexed column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    /// `TEXT` (IDs, names, string enums).
+    Text,
+    /// `INTEGER` (integer enums, small counts).
+    Integer,
+    /// `BIGINT` (amounts in cents).
+    BigInt,
+}
+
+impl ColumnType {
+    /// The PostgreSQL type name.
+    pub fn sql_type(&self) -> &'static str {
+        match self {
+            ColumnType::Text => "TEXT",
+            ColumnType::Integer => "INTEGER",
+            ColumnType::BigInt => "BIGINT",
+        }
+    }
+}
+
+/// An indexed column in a cache table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexedColumn {
+    /// Column name.
+    pub name: &'static str,
+    /// Column type.
+    pub column_type: ColumnType,
+    /// The API field the column holds, if it stores that field's value as-is.
+    ///
+    /// [`CacheFilter`](super::CacheFilter) conditions on this field use the
+    /// column; conditions on other fields read the JSON document instead.
+    pub field: Option<&'static str>,
+}
+
+impl IndexedColumn {
+    /// A `TEXT` column.
+    pub const fn text(name: &'static str) -> Self {
+        Self { name, column_type: ColumnType::Text, field: None }
+    }
+
+    /// An `INTEGER` column.
+    pub const fn integer(name: &'static str) -> Self {
+        Self { name, column_type: ColumnType::Integer, field: None }
+    }
+
+    /// A `BIGINT` column.
+    pub const fn bigint(name: &'static str) -> Self {
+        Self { name, column_type: ColumnType::BigInt, field: None }
+    }
+
+    /// Mark the column as a copy of the API field `field`.
+    ///
+    /// Leave it unset when the stored value differs from the API's, such as
+    /// a string enum stored by discriminant.
+    pub const fn from_field(mut self, field: &'static str) -> Self {
+        self.field = Some(field);
+        self
+    }
+}
+
+/// The value of an indexed column for one record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnValue {
+    /// Value for a [`ColumnType::Text`] column.
+    Text(Option<String>),
+    /// Value for a [`ColumnType::Integer`] column.
+    Integer(Option<i32>),
+    /// Value for a [`ColumnType::BigInt`] column.
+    BigInt(Option<i64>),
+}
+
+impl ColumnValue {
+    /// A text value from an optional string-like field.
+    pub fn text(value: Option<impl AsRef<str>>) -> Self {
+        ColumnValue::Text(value.map(|v| v.as_ref().to_string()))
+    }
+
+    /// A text value holding a field's serde name, for string enums.
+    pub fn serde_text<T: Serialize>(value: Option<&T>) -> Self {
+        ColumnValue::Text(value.and_then(|v| match serde_json::to_value(v) {
+            Ok(serde_json::Value::String(s)) => Some(s),
+            Ok(other) => Some(other.to_string()),
+            Err(_) => None,
+        }))
+    }
+
+    /// The column type this value belongs in.
+    pub fn column_type(&self) -> ColumnType {
+        match self {
+            ColumnValue::Text(_) => ColumnType::Text,
+            ColumnValue::Integer(_) => ColumnType::Integer,
+            ColumnValue::BigInt(_) => ColumnType::BigInt,
+        }
+    }
+}
+
+// =============================================================================
+// Cacheable Trait
+// =============================================================================
+
+/// A Payrix resource that can be stored in the entity cache.
+///
+/// Implementations name the cache table and its indexed columns, and extract
+/// the column values from a record. Every table also has `id`, `data`
+/// (the full JSON document), `created_at`, `modified_at` and `synced_at`.
+///
+/// # Example
+///
+/// ```
+/// use payrix::cache::{Cacheable, ColumnValue, IndexedColumn};
+/// use payrix::{EntityType, Vendor};
+///
+/// // Newtype so the impl lives in your crate
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// #[serde(transparent)]
+/// struct CachedVendor(Vendor);
+///
+/// impl Cacheable for CachedVendor {
+///     const ENTITY_TYPE: EntityType = EntityType::Vendors;
+///     const TABLE: &'static str = "payrix_vendors";
+///     const COLUMNS: &'static [IndexedColumn] =
+///         &[IndexedColumn::text("entity_id").from_field("entity")];
+///
+///     fn cache_id(&self) -> &str { self.0.id.as_str() }
+///     fn created(&self) -> Option<&str> { self.0.created.as_deref() }
+///     fn modified(&self) -> Option<&str> { self.0.modified.as_deref() }
+///     fn column_values(&self) -> Vec<ColumnValue> {
+///         vec![ColumnValue::text(self.0.entity.as_ref())]
+///     }
+/// }
+/// ```
+pub trait Cacheable: Serialize + DeserializeOwned + Send + Sync + Unpin + 'static {
+    /// The Payrix resource type, used to fetch records.
+    const ENTITY_TYPE: EntityType;
+
+    /// Cache table name.
+    const TABLE: &'static str;
+
+    /// Indexed columns, in the order [`column_values`](Self::column_values) returns them.
+    const COLUMNS: &'static [IndexedColumn];
+
+    /// The record's ID.
+    fn cache_id(&self) -> &str;
+
+    /// Creation timestamp in Payrix format.
+    fn created(&self) -> Option<&str>;
+
+    /// Modification timestamp in Payrix format.
+    fn modified(&self) -> Option<&str>;
+
+    /// Values for [`COLUMNS`](Self::COLUMNS).
+    fn column_values(&self) -> Vec<ColumnValue>;
+
+    /// Human-readable type name for error messages.
+    fn type_name() -> &'static str {
+        Self::TABLE.trim_start_matches("payrix_")
+    }
+}
+
+/// Entity types with a built-in [`Cacheable`] implementation.
+pub const CACHED_ENTITY_TYPES: &[EntityType] = &[
+    EntityType::Chargebacks,
+    EntityType::Txns,
+    EntityType::Merchants,
+    EntityType::Customers,
+    EntityType::Tokens,
+    EntityType::Subscriptions,
+    EntityType::Plans,
+    EntityType::Disbursements,
+    EntityType::Entries,
+    EntityType::Funds,
+    EntityType::Batches,
+    EntityType::ChargebackMessages,
+    EntityType::ChargebackDocuments,
+    EntityType::ChargebackStatuses,
+];
+
+/// Evaluate `$body` with `$t` aliased to the built-in [`Cacheable`] type for
+/// `$entity_type`, or evaluate `$default` if the type is not cached.
+macro_rules! dispatch_cacheable {
+    ($entity_type:expr, $t:ident => $body:expr, _ => $default:expr) => {{
+        use $crate::entity::EntityType as Et;
+        match $entity_type {
+            Et::Chargebacks => { type $t = $crate::types::Chargeback; $body }
+            Et::Txns => { type $t = $crate::types::Transaction; $body }
+            Et::Merchants => { type $t = $crate::types::Merchant; $body }
+            Et::Customers => { type $t = $crate::types::Customer; $body }
+            Et::Tokens => { type $t = $crate::types::Token; $body }
+            Et::Subscriptions => { type $t = $crate::types::Subscription; $body }
+            Et::Plans => { type $t = $crate::types::Plan; $body }
+            Et::Disbursements => { type $t = $crate::types::Disbursement; $body }
+            Et::Entries => { type $t = $crate::types::Entry; $body }
+            Et::Funds => { type $t = $crate::types::Fund; $body }
+            Et::Batches => { type $t = $crate::types::Batch; $body }
+            Et::ChargebackMessages => { type $t = $crate::types::ChargebackMessage; $body }
+            Et::ChargebackDocuments => { type $t = $crate::types::ChargebackDocument; $body }
+            Et::ChargebackStatuses => { type $t = $crate::types::ChargebackStatus; $body }
+            _ => $default,
+        }
+    }};
+}
+pub(super) use dispatch_cacheable;
+
+// =============================================================================
+// Original Tables
+// =============================================================================
+
+impl Cacheable for Chargeback {
+    const ENTITY_TYPE: EntityType = EntityType::Chargebacks;
+    const TABLE: &'static str = "payrix_chargebacks";
+    const COLUMNS: &'static [IndexedColumn] = &[
+        IndexedColumn::text("merchant_id").from_field("merchant"),
+        IndexedColumn::text("txn_id").from_field("txn"),
+        IndexedColumn::text("cycle"),
+        IndexedColumn::integer("status"),
+        IndexedColumn::bigint("total").from_field("total"),
+        IndexedColumn::text("reason_code").from_field("reasonCode"),
+    ];
+
+    fn cache_id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn created(&self) -> Option<&str> {
+        self.created.as_deref()
+    }
+
+    fn modified(&self) -> Option<&str> {
+        self.modified.as_deref()
+    }
+
+    fn column_values(&self) -> Vec<ColumnValue> {
+        vec![
+            ColumnValue::text(self.merchant.as_ref()),
+            ColumnValue::text(self.txn.as_ref()),
+            ColumnValue::Text(self.cycle.as_ref().map(|c| format!("{:?}", c))),
+            ColumnValue::Integer(self.status.map(|s| s as i32)),
+            ColumnValue::BigInt(self.total),
+            ColumnValue::text(self.reason_code.as_ref()),
+        ]
+    }
+}
+
+impl Cacheable for Transaction {
+    const ENTITY_TYPE: EntityType = EntityType::Txns;
+    const TABLE: &'static str = "payrix_transactions";
+    const COLUMNS: &'static [IndexedColumn] = &[
+        IndexedColumn::text("merchant_id").from_field("merchant"),
+        IndexedColumn::text("token_id").from_field("token"),
+        IndexedColumn::integer("status").from_field("status"),
+        IndexedColumn::integer("type").from_field("type"),
+        IndexedColumn::bigint("total").from_field("total"),
+    ];
+
+    fn cache_id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn created(&self) -> Option<&str> {
+        self.created.as_deref()
+    }
+
+    fn modified(&self) -> Option<&str> {
+        self.modified.as_deref()
+    }
+
+    fn column_values(&self) -> Vec<ColumnValue> {
+        vec![
+            ColumnValue::text(self.merchant.as_ref()),
+            ColumnValue::text(self.token.as_ref()),
+            ColumnValue::Integer(self.status.map(|s| s as i32)),
+            ColumnValue::Integer(Some(self.txn_type as i32)),
+            ColumnValue::BigInt(self.total),
+        ]
+    }
+}
+
+impl Cacheable for Merchant {
+    const ENTITY_TYPE: EntityType = EntityType::Merchants;
+    const TABLE: &'static str = "payrix_merchants";
+    const COLUMNS: &'static [IndexedColumn] = &[
+        IndexedColumn::text("entity_id").from_field("entity"),
+        IndexedColumn::integer("status").from_field("status"),
+        IndexedColumn::text("dba").from_field("dba"),
+    ];
+
+    fn cache_id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn created(&self) -> Option<&str> {
+        self.created.as_deref()
+    }
+
+    fn modified(&self) -> Option<&str> {
+        self.modified.as_deref()
+    }
+
+    fn column_values(&self) -> Vec<ColumnValue> {
+        vec![
+            ColumnValue::text(self.entity.as_ref()),
+            ColumnValue::Integer(self.status.map(|s| s as i32)),
+            ColumnValue::text(self.dba.as_ref()),
+        ]
+    }
+}
+
+impl Cacheable for Customer {
+    const ENTITY_TYPE: EntityType = EntityType::Customers;
+    const TABLE: &'static str = "payrix_customers";
+    const COLUMNS: &'static [IndexedColumn] = &[
+        IndexedColumn::text("merchant_id").from_field("merchant"),
+        IndexedColumn::text("email").from_field("email"),
+        IndexedColumn::text("first_name").from_field("first"),
+        IndexedColumn::text("last_name").from_field("last"),
+    ];
+
+    fn cache_id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn created(&self) -> Option<&str> {
+        self.created.as_deref()
+    }
+
+    fn modified(&self) -> Option<&str> {
+        self.modified.as_deref()
+    }
+
+    fn column_values(&self) -> Vec<ColumnValue> {
+        vec![
+            ColumnValue::text(self.merchant.as_ref()),
+            ColumnValue::text(self.email.as_ref()),
+            ColumnValue::text(self.first.as_ref()),
+            ColumnValue::text(self.last.as_ref()),
+        ]
+    }
+}
+
+impl Cacheable for Token {
+    const ENTITY_TYPE: EntityType = EntityType::Tokens;
+    const TABLE: &'static str = "payrix_tokens";
+    const COLUMNS: &'static [IndexedColumn] = &[
+        IndexedColumn::text("customer_id").from_field("customer"),
+        IndexedColumn::integer("payment_type"),
+        IndexedColumn::integer("status"),
+    ];
+
+    fn cache_id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn created(&self) -> Option<&str> {
+        self.created.as_deref()
+    }
+
+    fn modified(&self) -> Option<&str> {
+        self.modified.as_deref()
+    }
+
+    fn column_values(&self) -> Vec<ColumnValue> {
+        vec![
+            ColumnValue::text(self.customer.as_ref()),
+            ColumnValue::Integer(self.payment.map(|p| p as i32)),
+            ColumnValue::Integer(self.status.map(|s| s as i32)),
+        ]
+    }
+}
+
+// =============================================================================
+// Additional Tables
+// =============================================================================
+
+impl Cacheable for Subscription {
+    const ENTITY_TYPE: EntityType = EntityType::Subscriptions;
+    const TABLE: &'static str = "payrix_subscriptions";
+    const COLUMNS: &'static [IndexedColumn] = &[
+        IndexedColumn::text("plan_id").from_field("plan"),
+        IndexedColumn::integer("failures").from_field("failures"),
+    ];
+
+    fn cache_id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn created(&self) -> Option<&str> {
+        self.created.as_deref()
+    }
+
+    fn modified(&self) -> Option<&str> {
+        self.modified.as_deref()
+    }
+
+    fn column_values(&self) -> Vec<ColumnValue> {
+        vec![
+            ColumnValue::text(self.plan.as_ref()),
+            ColumnValue::Integer(self.failures),
+        ]
+    }
+}
+
+impl Cacheable for Plan {
+    const ENTITY_TYPE: EntityType = EntityType::Plans;
+    const TABLE: &'static str = "payrix_plans";
+    const COLUMNS: &'static [IndexedColumn] = &[
+        IndexedColumn::text("merchant_id").from_field("merchant"),
+        IndexedColumn::text("name").from_field("name"),
+        IndexedColumn::bigint("amount").from_field("amount"),
+    ];
+
+    fn cache_id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn created(&self) -> Option<&str> {
+        self.created.as_deref()
+    }
+
+    fn modified(&self) -> Option<&str> {
+        self.modified.as_deref()
+    }
+
+    fn column_values(&self) -> Vec<ColumnValue> {
+        vec![
+            ColumnValue::text(self.merchant.as_ref()),
+            ColumnValue::text(self.name.as_ref()),
+            ColumnValue::BigInt(self.amount),
+        ]
+    }
+}
+
+impl Cacheable for Disbursement {
+    const ENTITY_TYPE: EntityType = EntityType::Disbursements;
+    const TABLE: &'static str = "payrix_disbursements";
+    const COLUMNS: &'static [IndexedColumn] = &[
+        IndexedColumn::text("entity_id").from_field("entity"),
+        IndexedColumn::text("account_id").from_field("account"),
+        IndexedColumn::integer("status").from_field("status"),
+        IndexedColumn::bigint("amount").from_field("amount"),
+    ];
+
+    fn cache_id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn created(&self) -> Option<&str> {
+        self.created.as_deref()
+    }
+
+    fn modified(&self) -> Option<&str> {
+        self.modified.as_deref()
+    }
+
+    fn column_values(&self) -> Vec<ColumnValue> {
+        vec![
+            ColumnValue::text(self.entity.as_ref()),
+            ColumnValue::text(self.account.as_ref()),
+            ColumnValue::Integer(self.status.map(|s| s as i32)),
+            ColumnValue::BigInt(self.amount),
+        ]
+    }
+}
+
+impl Cacheable for Entry {
+    const ENTITY_TYPE: EntityType = EntityType::Entries;
+    const TABLE: &'static str = "payrix_entries";
+    const COLUMNS: &'static [IndexedColumn] = &[
+        IndexedColumn::text("entity_id").from_field("entity"),
+        IndexedColumn::text("fund_id").from_field("fund"),
+        IndexedColumn::text("txn_id").from_field("txn"),
+        IndexedColumn::text("disbursement_id").from_field("disbursement"),
+        IndexedColumn::integer("event").from_field("event"),
+    ];
+
+    fn cache_id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn created(&self) -> Option<&str> {
+        self.created.as_deref()
+    }
+
+    fn modified(&self) -> Option<&str> {
+        self.modified.as_deref()
+    }
+
+    fn column_values(&self) -> Vec<ColumnValue> {
+        vec![
+            ColumnValue::text(self.entity.as_ref()),
+            ColumnValue::text(self.fund.as_ref()),
+            ColumnValue::text(self.txn.as_ref()),
+            ColumnValue::text(self.disbursement.as_ref()),
+            ColumnValue::Integer(self.event.map(|e| e as i32)),
+        ]
+    }
+}
+
+impl Cacheable for Fund {
+    const ENTITY_TYPE: EntityType = EntityType::Funds;
+    const TABLE: &'static str = "payrix_funds";
+    const COLUMNS: &'static [IndexedColumn] = &[
+        IndexedColumn::text("entity_id").from_field("entity"),
+        IndexedColumn::text("currency").from_field("currency"),
+    ];
+
+    fn cache_id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn created(&self) -> Option<&str> {
+        self.created.as_deref()
+    }
+
+    fn modified(&self) -> Option<&str> {
+        self.modified.as_deref()
+    }
+
+    fn column_values(&self) -> Vec<ColumnValue> {
+        vec![
+            ColumnValue::text(self.entity.as_ref()),
+            ColumnValue::text(self.currency.as_ref()),
+        ]
+    }
+}
+
+impl Cacheable for Batch {
+    const ENTITY_TYPE: EntityType = EntityType::Batches;
+    const TABLE: &'static str = "payrix_batches";
+    const COLUMNS: &'static [IndexedColumn] = &[
+        IndexedColumn::text("merchant_id").from_field("merchant"),
+        IndexedColumn::text("status").from_field("status"),
+        IndexedColumn::text("date").from_field("date"),
+    ];
+
+    fn cache_id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn created(&self) -> Option<&str> {
+        self.created.as_deref()
+    }
+
+    fn modified(&self) -> Option<&str> {
+        self.modified.as_deref()
+    }
+
+    fn column_values(&self) -> Vec<ColumnValue> {
+        vec![
+            ColumnValue::text(self.merchant.as_ref()),
+            ColumnValue::serde_text(self.status.as_ref()),
+            ColumnValue::text(self.date.as_ref()),
+        ]
+    }
+}
+
+impl Cacheable for ChargebackMessage {
+    const ENTITY_TYPE: EntityType = EntityType::ChargebackMessages;
+    const TABLE: &'static str = "payrix_chargeback_messages";
+    const COLUMNS: &'static [IndexedColumn] = &[
+        IndexedColumn::text("chargeback_id").from_field("chargeback"),
+        IndexedColumn::text("message_type").from_field("type"),
+        IndexedColumn::text("status").from_field("status"),
+    ];
+
+    fn cache_id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn created(&self) -> Option<&str> {
+        self.created.as_deref()
+    }
+
+    fn modified(&self) -> Option<&str> {
+        self.modified.as_deref()
+    }
+
+    fn column_values(&self) -> Vec<ColumnValue> {
+        vec![
+            ColumnValue::text(Some(&self.chargeback)),
+            ColumnValue::serde_text(self.message_type.as_ref()),
+            ColumnValue::serde_text(self.status.as_ref()),
+        ]
+    }
+}
+
+impl Cacheable for ChargebackDocument {
+    const ENTITY_TYPE: EntityType = EntityType::ChargebackDocuments;
+    const TABLE: &'static str = "payrix_chargeback_documents";
+    const COLUMNS: &'static [IndexedColumn] = &[
+        IndexedColumn::text("chargeback_id").from_field("chargeback"),
+        IndexedColumn::text("chargeback_message_id").from_field("chargebackMessage"),
+    ];
+
+    fn cache_id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn created(&self) -> Option<&str> {
+        self.created.as_deref()
+    }
+
+    fn modified(&self) -> Option<&str> {
+        self.modified.as_deref()
+    }
+
+    fn column_values(&self) -> Vec<ColumnValue> {
+        vec![
+            ColumnValue::text(Some(&self.chargeback)),
+            ColumnValue::text(self.chargeback_message.as_ref()),
+        ]
+    }
+}
+
+impl Cacheable for ChargebackStatus {
+    const ENTITY_TYPE: EntityType = EntityType::ChargebackStatuses;
+    const TABLE: &'static str = "payrix_chargeback_statuses";
+    const COLUMNS: &'static [IndexedColumn] = &[
+        IndexedColumn::text("chargeback_id").from_field("chargeback"),
+        IndexedColumn::text("status").from_field("status"),
+    ];
+
+    fn cache_id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn created(&self) -> Option<&str> {
+        self.created.as_deref()
+    }
+
+    fn modified(&self) -> Option<&str> {
+        self.modified.as_deref()
+    }
+
+    fn column_values(&self) -> Vec<ColumnValue> {
+        vec![
+            ColumnValue::text(self.chargeback.as_ref()),
+            ColumnValue::serde_text(self.status.as_ref()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_columns_match<T: Cacheable>(record: &T) {
+        let values = record.column_values();
+        assert_eq!(values.len(), T::COLUMNS.len(), "{}", T::TABLE);
+        for (value, column) in values.iter().zip(T::COLUMNS) {
+            assert_eq!(value.column_type(), column.column_type, "{}.{}", T::TABLE, column.name);
+        }
+    }
+
+    fn from_json<T: Cacheable>(json: serde_json::Value) -> T {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_column_values_match_declared_columns() {
+        let base = serde_json::json!({
+            "id": "t1_xxx_12345678901234567890123",
+            "chargeback": "t1_chb_12345678901234567890123",
+            "created": "2024-01-01 00:00:00.0000",
+        });
+        assert_columns_match(&from_json::<Chargeback>(base.clone()));
+        assert_columns_match(&from_json::<Merchant>(base.clone()));
+        assert_columns_match(&from_json::<Customer>(base.clone()));
+        assert_columns_match(&from_json::<Subscription>(base.clone()));
+        assert_columns_match(&from_json::<Plan>(base.clone()));
+        assert_columns_match(&from_json::<Disbursement>(base.clone()));
+        assert_columns_match(&from_json::<Entry>(base.clone()));
+        assert_columns_match(&from_json::<Fund>(base.clone()));
+        assert_columns_match(&from_json::<Batch>(base.clone()));
+        assert_columns_match(&from_json::<ChargebackMessage>(base.clone()));
+        assert_columns_match(&from_json::<ChargebackDocument>(base.clone()));
+        assert_columns_match(&from_json::<ChargebackStatus>(base));
+    }
+
+    #[test]
+    fn test_serde_text_uses_serde_name() {
+        let value = ColumnValue::serde_text(Some(&crate::types::ChargebackStatusValue::Open));
+        assert_eq!(value, ColumnValue::Text(Some("open".to_string())));
+        assert_eq!(
+            ColumnValue::serde_text::<crate::types::ChargebackStatusValue>(None),
+            ColumnValue::Text(None)
+        );
+    }
+
+    #[test]
+    fn test_dispatch_covers_cached_types() {
+        for entity_type in CACHED_ENTITY_TYPES {
+            let dispatched = dispatch_cacheable!(*entity_type, T => Some(T::ENTITY_TYPE), _ => None);
+            assert_eq!(dispatched, Some(*entity_type));
+        }
+        assert_eq!(dispatch_cacheable!(EntityType::Vendors, T => Some(T::TABLE), _ => None), None);
+    }
+
+    #[test]
+    fn test_type_name() {
+        assert_eq!(Transaction::type_name(), "transactions");
+        assert_eq!(ChargebackMessage::type_name(), "chargeback_messages");
+    }
+}
@@ -2,11 +2,16 @@
 //!
 //! This module provides the main `EntityCache` struct for caching Payrix entities.
 
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
 use chrono::{DateTime, Utc};
 use sqlx::postgres::PgPoolOptions;
 use sqlx::{PgPool, Row};
 #[cfg(feature = "webhooks")]
-use tracing::{debug, warn};
+use tracing::debug;
+use tracing::warn;
 
 use crate::entity::EntityType;
 use crate::error::{Error, Result};
@@ -16,6 +21,11 @@ use crate::PayrixClient;
 #[cfg(feature = "webhooks")]
 use crate::webhooks::WebhookEvent;
 
+use super::cacheable::{Cacheable, ColumnValue};
+#[cfg(feature = "webhooks")]
+use super::cacheable::{dispatch_cacheable, CACHED_ENTITY_TYPES};
+use super::policy::{CachePolicy, ReadAction};
+use super::query::CacheFilter;
 use super::schema::ensure_schema;
 
 // =============================================================================
@@ -36,6 +46,12 @@ pub struct CacheConfig {
 
     /// Whether to sync on startup.
     pub sync_on_startup: bool,
+
+    /// Read policy for entity types without their own.
+    pub policy: CachePolicy,
+
+    /// Per-entity-type read policies.
+    pub entity_policies: HashMap<EntityType, CachePolicy>,
 }
 
 impl CacheConfig {
@@ -46,6 +62,8 @@ impl CacheConfig {
             max_connections: 10,
             auto_create_schema: true,
             sync_on_startup: false,
+            policy: CachePolicy::default(),
+            entity_policies: HashMap::new(),
         }
     }
 
@@ -66,6 +84,26 @@ impl CacheConfig {
         self.sync_on_startup = enabled;
         self
     }
+
+    /// Set the default read policy.
+    pub fn with_policy(mut self, policy: CachePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set the read policy for one entity type.
+    pub fn with_entity_policy(mut self, entity_type: EntityType, policy: CachePolicy) -> Self {
+        self.entity_policies.insert(entity_type, policy);
+        self
+    }
+
+    /// The read policy that applies to `entity_type`.
+    pub fn policy_for(&self, entity_type: EntityType) -> CachePolicy {
+        self.entity_policies
+            .get(&entity_type)
+            .copied()
+            .unwrap_or(self.policy)
+    }
 }
 
 // =============================================================================
@@ -104,6 +142,22 @@ impl SyncStats {
     }
 }
 
+/// Result of an incremental sync of one entity type.
+#[derive(Debug, Clone, Default)]
+pub struct IncrementalSyncResult {
+    /// Number of records fetched and upserted.
+    pub records: usize,
+
+    /// Number of pages requested.
+    pub pages: usize,
+
+    /// Watermark the sync started from (`None` for a first sync).
+    pub resumed_from: Option<String>,
+
+    /// Latest `modified` timestamp checkpointed, used by the next sync.
+    pub watermark: Option<String>,
+}
+
 // =============================================================================
 // Entity Cache
 // =============================================================================
@@ -111,10 +165,14 @@ impl SyncStats {
 /// A local database cache for Payrix entities.
 ///
 /// The cache provides fast local queries and can be kept in sync with Payrix
-/// via webhooks or periodic syncs.
+/// via webhooks or periodic syncs. Clones share the connection pool.
+#[derive(Clone)]
 pub struct EntityCache {
     pool: PgPool,
     client: PayrixClient,
+    config: Arc<CacheConfig>,
+    /// Records with a background refresh in flight, by table and ID.
+    refreshing: Arc<Mutex<HashSet<(&'static str, String)>>>,
 }
 
 impl EntityCache {
@@ -159,9 +217,15 @@ impl EntityCache {
             ensure_schema(&pool).await?;
         }
 
-        let cache = Self { pool, client };
+        let sync_on_startup = config.sync_on_startup;
+        let cache = Self {
+            pool,
+            client,
+            config: Arc::new(config),
+            refreshing: Arc::default(),
+        };
 
-        if config.sync_on_startup {
+        if sync_on_startup {
             cache.initial_sync().await?;
         }
 
@@ -178,119 +242,236 @@ impl EntityCache {
         &self.client
     }
 
+    /// The read policy that applies to `entity_type`.
+    pub fn policy_for(&self, entity_type: EntityType) -> CachePolicy {
+        self.config.policy_for(entity_type)
+    }
+
     // =========================================================================
-    // Chargeback Methods
+    // Generic Methods
     // =========================================================================
 
-    /// Get a chargeback from the cache by ID.
+    /// Get a record of any [`Cacheable`] type from the cache by ID.
     ///
-    /// Returns `None` if the chargeback is not in the cache.
-    pub async fn get_chargeback(&self, id: &str) -> Result<Option<Chargeback>> {
-        let row = sqlx::query("SELECT data FROM payrix_chargebacks WHERE id = $1")
+    /// This is a raw cache read: it ignores the [`CachePolicy`] and never calls
+    /// the API. Returns `None` if the record is not in the cache. Use
+    /// [`get_or_fetch`](Self::get_or_fetch) for a read that honors the policy.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use payrix::cache::EntityCache;
+    /// use payrix::Subscription;
+    ///
+    /// # async fn example(cache: EntityCache) -> payrix::Result<()> {
+    /// let subscription = cache.get::<Subscription>("t1_sbn_12345678901234567890123").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get<T: Cacheable>(&self, id: &str) -> Result<Option<T>> {
+        let sql = format!("SELECT data FROM {} WHERE id = $1", T::TABLE);
+        let row = sqlx::query(&sql)
             .bind(id)
             .fetch_optional(&self.pool)
             .await?;
 
-        match row {
-            Some(row) => {
-                let data: serde_json::Value = row.get("data");
-                let chargeback: Chargeback = serde_json::from_value(data)
-                    .map_err(|e| Error::Internal(format!("Failed to deserialize chargeback: {}", e)))?;
-                Ok(Some(chargeback))
-            }
-            None => Ok(None),
-        }
+        row.map(|row| decode_row::<T>(&row)).transpose()
     }
 
-    /// Get a chargeback, fetching from API if not in cache.
-    pub async fn get_or_fetch_chargeback(&self, id: &str) -> Result<Option<Chargeback>> {
-        // Try cache first
-        if let Some(chargeback) = self.get_chargeback(id).await? {
-            return Ok(Some(chargeback));
+    /// Get a record, going to the API as the type's [`CachePolicy`] requires.
+    ///
+    /// With the default policy the API is only called for records missing
+    /// from the cache. Fetched records are written to the cache. Under
+    /// [`CachePolicy::MaxAge`], if refreshing an expired record fails the
+    /// cached copy is returned and the error is logged.
+    pub async fn get_or_fetch<T: Cacheable>(&self, id: &str) -> Result<Option<T>> {
+        let policy = self.policy_for(T::ENTITY_TYPE);
+        let cached = if policy == CachePolicy::ApiOnly {
+            None
+        } else {
+            self.get_with_age::<T>(id).await?
+        };
+
+        let (record, age) = match cached {
+            Some((record, age)) => (Some(record), Some(age)),
+            None => (None, None),
+        };
+        match policy.action(age) {
+            ReadAction::UseCached => Ok(record),
+            ReadAction::Fetch => match (self.refresh::<T>(id).await, record) {
+                // A stale copy beats an error when the API is unavailable
+                (Err(e), Some(stale)) if matches!(policy, CachePolicy::MaxAge(_)) => {
+                    warn!(
+                        error = %e,
+                        id = %id,
+                        "Refresh of stale {} failed; serving cached copy",
+                        T::type_name()
+                    );
+                    Ok(Some(stale))
+                }
+                (result, _) => result,
+            },
+            ReadAction::UseCachedAndRefresh => {
+                self.spawn_refresh::<T>(id);
+                Ok(record)
+            }
         }
+    }
 
-        // Fetch from API
-        let chargeback: Option<Chargeback> = self.client.get_one(EntityType::Chargebacks, id).await?;
+    /// Fetch a record from the API and write it to the cache.
+    ///
+    /// Returns `None` (leaving any cached copy alone) if the API has no such record.
+    pub async fn refresh<T: Cacheable>(&self, id: &str) -> Result<Option<T>> {
+        let record: Option<T> = self.client.get_one(T::ENTITY_TYPE, id).await?;
 
-        // Store in cache if found
-        if let Some(ref cb) = chargeback {
-            self.upsert_chargeback(cb).await?;
+        if let Some(ref r) = record {
+            self.upsert(r).await?;
         }
 
-        Ok(chargeback)
+        Ok(record)
     }
 
-    /// Find chargebacks by merchant ID.
-    pub async fn find_chargebacks_by_merchant(&self, merchant_id: &str) -> Result<Vec<Chargeback>> {
-        let rows = sqlx::query("SELECT data FROM payrix_chargebacks WHERE merchant_id = $1 ORDER BY created_at DESC")
-            .bind(merchant_id)
-            .fetch_all(&self.pool)
+    /// Get a cached record with the time since it was last synced.
+    async fn get_with_age<T: Cacheable>(&self, id: &str) -> Result<Option<(T, Duration)>> {
+        // Age is measured by the database clock, the same one that set synced_at
+        let sql = format!(
+            "SELECT data, EXTRACT(EPOCH FROM NOW() - synced_at)::float8 AS age FROM {} WHERE id = $1",
+            T::TABLE
+        );
+        let row = sqlx::query(&sql)
+            .bind(id)
+            .fetch_optional(&self.pool)
             .await?;
 
-        let mut chargebacks = Vec::with_capacity(rows.len());
-        for row in rows {
-            let data: serde_json::Value = row.get("data");
-            let chargeback: Chargeback = serde_json::from_value(data)
-                .map_err(|e| Error::Internal(format!("Failed to deserialize chargeback: {}", e)))?;
-            chargebacks.push(chargeback);
+        row.map(|row| {
+            let age: f64 = row.get("age");
+            Ok((decode_row::<T>(&row)?, Duration::from_secs_f64(age.max(0.0))))
+        })
+        .transpose()
+    }
+
+    /// Refresh a record in the background, unless a refresh is already running.
+    fn spawn_refresh<T: Cacheable>(&self, id: &str) {
+        let key = (T::TABLE, id.to_string());
+        {
+            let mut refreshing = self.refreshing.lock().unwrap_or_else(|e| e.into_inner());
+            if !refreshing.insert(key.clone()) {
+                return;
+            }
         }
 
-        Ok(chargebacks)
+        let cache = self.clone();
+        let guard = RefreshGuard {
+            refreshing: Arc::clone(&self.refreshing),
+            key,
+        };
+        tokio::spawn(async move {
+            // Dropped when the task ends, even by panic or cancellation
+            let guard = guard;
+            if let Err(e) = cache.refresh::<T>(&guard.key.1).await {
+                warn!(error = %e, id = %guard.key.1, "Background refresh of {} failed", T::type_name());
+            }
+        });
     }
 
-    /// Find chargebacks by transaction ID.
-    pub async fn find_chargebacks_by_transaction(&self, txn_id: &str) -> Result<Vec<Chargeback>> {
-        let rows = sqlx::query("SELECT data FROM payrix_chargebacks WHERE txn_id = $1 ORDER BY created_at DESC")
-            .bind(txn_id)
-            .fetch_all(&self.pool)
-            .await?;
+    /// Upsert a record of any [`Cacheable`] type into the cache.
+    pub async fn upsert<T: Cacheable>(&self, record: &T) -> Result<()> {
+        let data = serde_json::to_value(record).map_err(|e| {
+            Error::Internal(format!("Failed to serialize {}: {}", T::type_name(), e))
+        })?;
+        let values = record.column_values();
+        if values.len() != T::COLUMNS.len() {
+            return Err(Error::Internal(format!(
+                "{} returned {} column values for {} columns",
+                T::type_name(),
+                values.len(),
+                T::COLUMNS.len()
+            )));
+        }
+
+        let names: Vec<&str> = T::COLUMNS.iter().map(|c| c.name).collect();
+        let placeholders: Vec<String> = (3..3 + names.len()).map(|i| format!("${}", i)).collect();
+        let updates: Vec<String> = names
+            .iter()
+            .map(|n| format!("{n} = EXCLUDED.{n}"))
+            .collect();
+        let next = 3 + names.len();
+        let sql = format!(
+            "INSERT INTO {table} (id, data{cols}, created_at, modified_at, synced_at) \
+             VALUES ($1, $2{vals}, ${created}, ${modified}, NOW()) \
+             ON CONFLICT (id) DO UPDATE SET data = EXCLUDED.data{updates}, \
+             modified_at = EXCLUDED.modified_at, synced_at = NOW()",
+            table = T::TABLE,
+            cols = names.iter().map(|n| format!(", {n}")).collect::<String>(),
+            vals = placeholders.iter().map(|p| format!(", {p}")).collect::<String>(),
+            created = next,
+            modified = next + 1,
+            updates = updates.iter().map(|u| format!(", {u}")).collect::<String>(),
+        );
 
-        let mut chargebacks = Vec::with_capacity(rows.len());
-        for row in rows {
-            let data: serde_json::Value = row.get("data");
-            let chargeback: Chargeback = serde_json::from_value(data)
-                .map_err(|e| Error::Internal(format!("Failed to deserialize chargeback: {}", e)))?;
-            chargebacks.push(chargeback);
+        let mut query = sqlx::query(&sql).bind(record.cache_id()).bind(&data);
+        for value in values {
+            query = match value {
+                ColumnValue::Text(v) => query.bind(v),
+                ColumnValue::Integer(v) => query.bind(v),
+                ColumnValue::BigInt(v) => query.bind(v),
+            };
         }
+        query
+            .bind(parse_payrix_datetime(record.created()))
+            .bind(parse_payrix_datetime(record.modified()))
+            .execute(&self.pool)
+            .await?;
 
-        Ok(chargebacks)
+        Ok(())
     }
 
-    /// Upsert a chargeback into the cache.
-    pub async fn upsert_chargeback(&self, chargeback: &Chargeback) -> Result<()> {
-        let data = serde_json::to_value(chargeback)
-            .map_err(|e| Error::Internal(format!("Failed to serialize chargeback: {}", e)))?;
+    /// Query cached records of any [`Cacheable`] type.
+    ///
+    /// This is a raw cache read: it ignores the [`CachePolicy`] and never calls
+    /// the API, so results are only as fresh as the last sync. Results are
+    /// newest first unless the filter sorts them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`] if the filter names an invalid field or
+    /// compares a field against a value of the wrong type.
+    pub async fn query<T: Cacheable>(&self, filter: CacheFilter) -> Result<Vec<T>> {
+        let mut query = filter.build::<T>()?;
+        let rows = query.build().fetch_all(&self.pool).await?;
+        rows.iter().map(decode_row::<T>).collect()
+    }
 
-        sqlx::query(
-            r#"
-            INSERT INTO payrix_chargebacks (id, data, merchant_id, txn_id, cycle, status, total, reason_code, created_at, modified_at, synced_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, NOW())
-            ON CONFLICT (id) DO UPDATE SET
-                data = EXCLUDED.data,
-                merchant_id = EXCLUDED.merchant_id,
-                txn_id = EXCLUDED.txn_id,
-                cycle = EXCLUDED.cycle,
-                status = EXCLUDED.status,
-                total = EXCLUDED.total,
-                reason_code = EXCLUDED.reason_code,
-                modified_at = EXCLUDED.modified_at,
-                synced_at = NOW()
-            "#,
-        )
-        .bind(chargeback.id.as_str())
-        .bind(&data)
-        .bind(chargeback.merchant.as_ref().map(|m| m.as_str()))
-        .bind(chargeback.txn.as_ref().map(|t| t.as_str()))
-        .bind(chargeback.cycle.as_ref().map(|c| format!("{:?}", c)))
-        .bind(chargeback.status.map(|s| s as i32))
-        .bind(chargeback.total)
-        .bind(chargeback.reason_code.as_deref())
-        .bind(parse_payrix_datetime(chargeback.created.as_deref()))
-        .bind(parse_payrix_datetime(chargeback.modified.as_deref()))
-        .execute(&self.pool)
-        .await?;
+    // =========================================================================
+    // Chargeback Methods
+    // =========================================================================
 
-        Ok(())
+    /// Get a chargeback from the cache by ID.
+    ///
+    /// This is a raw cache read that ignores the [`CachePolicy`]; returns
+    /// `None` if the chargeback is not in the cache.
+    pub async fn get_chargeback(&self, id: &str) -> Result<Option<Chargeback>> {
+        self.get(id).await
+    }
+
+    /// Get a chargeback, fetching from the API as its [`CachePolicy`] requires.
+    pub async fn get_or_fetch_chargeback(&self, id: &str) -> Result<Option<Chargeback>> {
+        self.get_or_fetch(id).await
+    }
+
+    /// Find chargebacks by merchant ID.
+    pub async fn find_chargebacks_by_merchant(&self, merchant_id: &str) -> Result<Vec<Chargeback>> {
+        self.query(CacheFilter::new().eq("merchant", merchant_id)).await
+    }
+
+    /// Find chargebacks by transaction ID.
+    pub async fn find_chargebacks_by_transaction(&self, txn_id: &str) -> Result<Vec<Chargeback>> {
+        self.query(CacheFilter::new().eq("txn", txn_id)).await
+    }
+
+    /// Upsert a chargeback into the cache.
+    pub async fn upsert_chargeback(&self, chargeback: &Chargeback) -> Result<()> {
+        self.upsert(chargeback).await
     }
 
     // =========================================================================
@@ -298,71 +479,21 @@ impl EntityCache {
     // =========================================================================
 
     /// Get a transaction from the cache by ID.
+    ///
+    /// This is a raw cache read that ignores the [`CachePolicy`]; returns
+    /// `None` if the transaction is not in the cache.
     pub async fn get_transaction(&self, id: &str) -> Result<Option<Transaction>> {
-        let row = sqlx::query("SELECT data FROM payrix_transactions WHERE id = $1")
-            .bind(id)
-            .fetch_optional(&self.pool)
-            .await?;
-
-        match row {
-            Some(row) => {
-                let data: serde_json::Value = row.get("data");
-                let txn: Transaction = serde_json::from_value(data)
-                    .map_err(|e| Error::Internal(format!("Failed to deserialize transaction: {}", e)))?;
-                Ok(Some(txn))
-            }
-            None => Ok(None),
-        }
+        self.get(id).await
     }
 
-    /// Get a transaction, fetching from API if not in cache.
+    /// Get a transaction, fetching from the API as its [`CachePolicy`] requires.
     pub async fn get_or_fetch_transaction(&self, id: &str) -> Result<Option<Transaction>> {
-        if let Some(txn) = self.get_transaction(id).await? {
-            return Ok(Some(txn));
-        }
-
-        let txn: Option<Transaction> = self.client.get_one(EntityType::Txns, id).await?;
-
-        if let Some(ref t) = txn {
-            self.upsert_transaction(t).await?;
-        }
-
-        Ok(txn)
+        self.get_or_fetch(id).await
     }
 
     /// Upsert a transaction into the cache.
     pub async fn upsert_transaction(&self, txn: &Transaction) -> Result<()> {
-        let data = serde_json::to_value(txn)
-            .map_err(|e| Error::Internal(format!("Failed to serialize transaction: {}", e)))?;
-
-        sqlx::query(
-            r#"
-            INSERT INTO payrix_transactions (id, data, merchant_id, token_id, status, type, total, created_at, modified_at, synced_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW())
-            ON CONFLICT (id) DO UPDATE SET
-                data = EXCLUDED.data,
-                merchant_id = EXCLUDED.merchant_id,
-                token_id = EXCLUDED.token_id,
-                status = EXCLUDED.status,
-                type = EXCLUDED.type,
-                total = EXCLUDED.total,
-                modified_at = EXCLUDED.modified_at,
-                synced_at = NOW()
-            "#,
-        )
-        .bind(txn.id.as_str())
-        .bind(&data)
-        .bind(txn.merchant.as_ref().map(|m| m.as_str()))
-        .bind(txn.token.as_deref())
-        .bind(txn.status.map(|s| s as i32))
-        .bind(txn.txn_type as i32)
-        .bind(txn.total)
-        .bind(parse_payrix_datetime(txn.created.as_deref()))
-        .bind(parse_payrix_datetime(txn.modified.as_deref()))
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
+        self.upsert(txn).await
     }
 
     // =========================================================================
@@ -370,67 +501,21 @@ impl EntityCache {
     // =========================================================================
 
     /// Get a merchant from the cache by ID.
+    ///
+    /// This is a raw cache read that ignores the [`CachePolicy`]; returns
+    /// `None` if the merchant is not in the cache.
     pub async fn get_merchant(&self, id: &str) -> Result<Option<Merchant>> {
-        let row = sqlx::query("SELECT data FROM payrix_merchants WHERE id = $1")
-            .bind(id)
-            .fetch_optional(&self.pool)
-            .await?;
-
-        match row {
-            Some(row) => {
-                let data: serde_json::Value = row.get("data");
-                let merchant: Merchant = serde_json::from_value(data)
-                    .map_err(|e| Error::Internal(format!("Failed to deserialize merchant: {}", e)))?;
-                Ok(Some(merchant))
-            }
-            None => Ok(None),
-        }
+        self.get(id).await
     }
 
-    /// Get a merchant, fetching from API if not in cache.
+    /// Get a merchant, fetching from the API as its [`CachePolicy`] requires.
     pub async fn get_or_fetch_merchant(&self, id: &str) -> Result<Option<Merchant>> {
-        if let Some(merchant) = self.get_merchant(id).await? {
-            return Ok(Some(merchant));
-        }
-
-        let merchant: Option<Merchant> = self.client.get_one(EntityType::Merchants, id).await?;
-
-        if let Some(ref m) = merchant {
-            self.upsert_merchant(m).await?;
-        }
-
-        Ok(merchant)
+        self.get_or_fetch(id).await
     }
 
     /// Upsert a merchant into the cache.
     pub async fn upsert_merchant(&self, merchant: &Merchant) -> Result<()> {
-        let data = serde_json::to_value(merchant)
-            .map_err(|e| Error::Internal(format!("Failed to serialize merchant: {}", e)))?;
-
-        sqlx::query(
-            r#"
-            INSERT INTO payrix_merchants (id, data, entity_id, status, dba, created_at, modified_at, synced_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
-            ON CONFLICT (id) DO UPDATE SET
-                data = EXCLUDED.data,
-                entity_id = EXCLUDED.entity_id,
-                status = EXCLUDED.status,
-                dba = EXCLUDED.dba,
-                modified_at = EXCLUDED.modified_at,
-                synced_at = NOW()
-            "#,
-        )
-        .bind(merchant.id.as_str())
-        .bind(&data)
-        .bind(merchant.entity.as_ref().map(|e| e.as_str()))
-        .bind(merchant.status.map(|s| s as i32))
-        .bind(merchant.dba.as_deref())
-        .bind(parse_payrix_datetime(merchant.created.as_deref()))
-        .bind(parse_payrix_datetime(merchant.modified.as_deref()))
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
+        self.upsert(merchant).await
     }
 
     // =========================================================================
@@ -438,69 +523,21 @@ impl EntityCache {
     // =========================================================================
 
     /// Get a customer from the cache by ID.
+    ///
+    /// This is a raw cache read that ignores the [`CachePolicy`]; returns
+    /// `None` if the customer is not in the cache.
     pub async fn get_customer(&self, id: &str) -> Result<Option<Customer>> {
-        let row = sqlx::query("SELECT data FROM payrix_customers WHERE id = $1")
-            .bind(id)
-            .fetch_optional(&self.pool)
-            .await?;
-
-        match row {
-            Some(row) => {
-                let data: serde_json::Value = row.get("data");
-                let customer: Customer = serde_json::from_value(data)
-                    .map_err(|e| Error::Internal(format!("Failed to deserialize customer: {}", e)))?;
-                Ok(Some(customer))
-            }
-            None => Ok(None),
-        }
+        self.get(id).await
     }
 
-    /// Get a customer, fetching from API if not in cache.
+    /// Get a customer, fetching from the API as its [`CachePolicy`] requires.
     pub async fn get_or_fetch_customer(&self, id: &str) -> Result<Option<Customer>> {
-        if let Some(customer) = self.get_customer(id).await? {
-            return Ok(Some(customer));
-        }
-
-        let customer: Option<Customer> = self.client.get_one(EntityType::Customers, id).await?;
-
-        if let Some(ref c) = customer {
-            self.upsert_customer(c).await?;
-        }
-
-        Ok(customer)
+        self.get_or_fetch(id).await
     }
 
     /// Upsert a customer into the cache.
     pub async fn upsert_customer(&self, customer: &Customer) -> Result<()> {
-        let data = serde_json::to_value(customer)
-            .map_err(|e| Error::Internal(format!("Failed to serialize customer: {}", e)))?;
-
-        sqlx::query(
-            r#"
-            INSERT INTO payrix_customers (id, data, merchant_id, email, first_name, last_name, created_at, modified_at, synced_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW())
-            ON CONFLICT (id) DO UPDATE SET
-                data = EXCLUDED.data,
-                merchant_id = EXCLUDED.merchant_id,
-                email = EXCLUDED.email,
-                first_name = EXCLUDED.first_name,
-                last_name = EXCLUDED.last_name,
-                modified_at = EXCLUDED.modified_at,
-                synced_at = NOW()
-            "#,
-        )
-        .bind(customer.id.as_str())
-        .bind(&data)
-        .bind(customer.merchant.as_ref().map(|m| m.as_str()))
-        .bind(customer.email.as_deref())
-        .bind(customer.first.as_deref())
-        .bind(customer.last.as_deref())
-        .bind(parse_payrix_datetime(customer.created.as_deref()))
-        .bind(parse_payrix_datetime(customer.modified.as_deref()))
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
+        self.upsert(customer).await
     }
 
     // =========================================================================
@@ -508,67 +545,21 @@ impl EntityCache {
     // =========================================================================
 
     /// Get a token from the cache by ID.
+    ///
+    /// This is a raw cache read that ignores the [`CachePolicy`]; returns
+    /// `None` if the token is not in the cache.
     pub async fn get_token(&self, id: &str) -> Result<Option<Token>> {
-        let row = sqlx::query("SELECT data FROM payrix_tokens WHERE id = $1")
-            .bind(id)
-            .fetch_optional(&self.pool)
-            .await?;
-
-        match row {
-            Some(row) => {
-                let data: serde_json::Value = row.get("data");
-                let token: Token = serde_json::from_value(data)
-                    .map_err(|e| Error::Internal(format!("Failed to deserialize token: {}", e)))?;
-                Ok(Some(token))
-            }
-            None => Ok(None),
-        }
+        self.get(id).await
     }
 
-    /// Get a token, fetching from API if not in cache.
+    /// Get a token, fetching from the API as its [`CachePolicy`] requires.
     pub async fn get_or_fetch_token(&self, id: &str) -> Result<Option<Token>> {
-        if let Some(token) = self.get_token(id).await? {
-            return Ok(Some(token));
-        }
-
-        let token: Option<Token> = self.client.get_one(EntityType::Tokens, id).await?;
-
-        if let Some(ref t) = token {
-            self.upsert_token(t).await?;
-        }
-
-        Ok(token)
+        self.get_or_fetch(id).await
     }
 
     /// Upsert a token into the cache.
     pub async fn upsert_token(&self, token: &Token) -> Result<()> {
-        let data = serde_json::to_value(token)
-            .map_err(|e| Error::Internal(format!("Failed to serialize token: {}", e)))?;
-
-        sqlx::query(
-            r#"
-            INSERT INTO payrix_tokens (id, data, customer_id, payment_type, status, created_at, modified_at, synced_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
-            ON CONFLICT (id) DO UPDATE SET
-                data = EXCLUDED.data,
-                customer_id = EXCLUDED.customer_id,
-                payment_type = EXCLUDED.payment_type,
-                status = EXCLUDED.status,
-                modified_at = EXCLUDED.modified_at,
-                synced_at = NOW()
-            "#,
-        )
-        .bind(token.id.as_str())
-        .bind(&data)
-        .bind(token.customer.as_ref().map(|c| c.as_str()))
-        .bind(token.payment.map(|p| p as i32))
-        .bind(token.status.map(|s| s as i32))
-        .bind(parse_payrix_datetime(token.created.as_deref()))
-        .bind(parse_payrix_datetime(token.modified.as_deref()))
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
+        self.upsert(token).await
     }
 
     // =========================================================================
@@ -588,43 +579,15 @@ impl EntityCache {
             "Processing webhook for cache"
         );
 
-        match event.resource_type.as_str() {
-            "chargebacks" => {
-                if let Ok(chargeback) = serde_json::from_value::<Chargeback>(event.data.clone()) {
-                    self.upsert_chargeback(&chargeback).await?;
-                } else {
-                    warn!("Failed to parse chargeback from webhook data");
-                }
-            }
-            "txns" => {
-                if let Ok(txn) = serde_json::from_value::<Transaction>(event.data.clone()) {
-                    self.upsert_transaction(&txn).await?;
-                } else {
-                    warn!("Failed to parse transaction from webhook data");
-                }
-            }
-            "merchants" => {
-                if let Ok(merchant) = serde_json::from_value::<Merchant>(event.data.clone()) {
-                    self.upsert_merchant(&merchant).await?;
-                } else {
-                    warn!("Failed to parse merchant from webhook data");
-                }
-            }
-            "customers" => {
-                if let Ok(customer) = serde_json::from_value::<Customer>(event.data.clone()) {
-                    self.upsert_customer(&customer).await?;
-                } else {
-                    warn!("Failed to parse customer from webhook data");
-                }
-            }
-            "tokens" => {
-                if let Ok(token) = serde_json::from_value::<Token>(event.data.clone()) {
-                    self.upsert_token(&token).await?;
-                } else {
-                    warn!("Failed to parse token from webhook data");
-                }
+        let entity_type = CACHED_ENTITY_TYPES
+            .iter()
+            .find(|t| t.as_str() == event.resource_type);
+
+        match entity_type {
+            Some(&entity_type) => {
+                dispatch_cacheable!(entity_type, T => self.upsert_from_webhook::<T>(event).await?, _ => ())
             }
-            _ => {
+            None => {
                 debug!(resource_type = %event.resource_type, "Ignoring webhook for uncached entity type");
             }
         }
@@ -632,6 +595,17 @@ impl EntityCache {
         Ok(())
     }
 
+    #[cfg(feature = "webhooks")]
+    async fn upsert_from_webhook<T: Cacheable>(&self, event: &WebhookEvent) -> Result<()> {
+        match T::deserialize(&event.data) {
+            Ok(record) => self.upsert(&record).await,
+            Err(e) => {
+                warn!(error = %e, "Failed to parse {} from webhook data", T::type_name());
+                Ok(())
+            }
+        }
+    }
+
     // =========================================================================
     // Sync Methods (in sync.rs)
     // =========================================================================
@@ -642,7 +616,8 @@ impl EntityCache {
     /// from the Payrix API and stores them in the local cache.
     ///
     /// **Note:** This can take a long time for accounts with many entities.
-    /// Consider using `sync_entity_type` for incremental syncs.
+    /// Consider using [`incremental_sync`](Self::incremental_sync) to keep the
+    /// cache current afterwards.
     pub async fn initial_sync(&self) -> Result<SyncStats> {
         super::sync::initial_sync(self).await
     }
@@ -652,6 +627,34 @@ impl EntityCache {
         super::sync::sync_entity_type(self, entity_type).await
     }
 
+    /// Sync only the entities of one type modified since the last sync.
+    ///
+    /// Starts from the watermark returned by [`sync_watermark`](Self::sync_watermark),
+    /// fetches changed records in ascending `modified` order and checkpoints
+    /// after every page, so an interrupted sync picks up where it stopped.
+    /// With no previous sync, every record is fetched.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use payrix::EntityType;
+    /// use payrix::cache::EntityCache;
+    ///
+    /// # async fn example(cache: EntityCache) -> payrix::Result<()> {
+    /// let result = cache.incremental_sync(EntityType::Txns).await?;
+    /// println!("{} changed transactions, now at {:?}", result.records, result.watermark);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn incremental_sync(&self, entity_type: EntityType) -> Result<IncrementalSyncResult> {
+        super::sync::incremental_sync(self, entity_type).await
+    }
+
+    /// Get the watermark the next incremental sync of an entity type starts from.
+    pub async fn sync_watermark(&self, entity_type: EntityType) -> Result<Option<String>> {
+        super::sync::sync_watermark(self, entity_type).await
+    }
+
     /// Get the last sync time for an entity type.
     pub async fn last_sync_time(&self, entity_type: &str) -> Result<Option<DateTime<Utc>>> {
         let row = sqlx::query(
@@ -670,12 +673,34 @@ impl EntityCache {
     }
 }
 
+/// Clears a record's in-flight marker when its background refresh ends.
+struct RefreshGuard {
+    refreshing: Arc<Mutex<HashSet<(&'static str, String)>>>,
+    key: (&'static str, String),
+}
+
+impl Drop for RefreshGuard {
+    fn drop(&mut self) {
+        self.refreshing
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.key);
+    }
+}
+
 // =============================================================================
 // Helper Functions
 // =============================================================================
 
+/// Deserialize the `data` column of a cache row.
+fn decode_row<T: Cacheable>(row: &sqlx::postgres::PgRow) -> Result<T> {
+    let data: serde_json::Value = row.get("data");
+    serde_json::from_value(data)
+        .map_err(|e| Error::Internal(format!("Failed to deserialize {}: {}", T::type_name(), e)))
+}
+
 /// Parse a Payrix datetime string into a chrono DateTime.
-fn parse_payrix_datetime(s: Option<&str>) -> Option<DateTime<Utc>> {
+pub(super) fn parse_payrix_datetime(s: Option<&str>) -> Option<DateTime<Utc>> {
     s.and_then(|s| {
         // Payrix format: "YYYY-MM-DD HH:MM:SS.SSSS"
         chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f")
@@ -67,6 +67,69 @@
 //! # }
 //! ```
 //!
+//! # Cached Types
+//!
+//! Every type implementing [`Cacheable`] gets its own table and works with
+//! the generic [`EntityCache::get`], [`EntityCache::upsert`] and
+//! [`EntityCache::query`] methods. Built-in implementations cover
+//! chargebacks (with their messages, documents and statuses), transactions,
+//! merchants, customers, tokens, subscriptions, plans, disbursements,
+//! entries, funds and batches:
+//!
+//! ```no_run
+//! use payrix::cache::{CacheFilter, EntityCache};
+//! use payrix::{ChargebackMessage, Subscription};
+//!
+//! # async fn example(cache: EntityCache) -> payrix::Result<()> {
+//! let subscription: Option<Subscription> = cache.get("t1_sbn_12345678901234567890123").await?;
+//!
+//! let messages: Vec<ChargebackMessage> = cache
+//!     .query(CacheFilter::new().eq("chargeback", "t1_chb_12345678901234567890123"))
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`CacheFilter`] mirrors [`SearchBuilder`](crate::search::SearchBuilder):
+//! the same fields and operators, plus sorting and limit/offset, run against
+//! the cache's indexed columns or, for other fields, the stored JSON. Its
+//! [`to_search`](CacheFilter::to_search) sends the same conditions to the API.
+//!
+//! # Read Policies
+//!
+//! [`EntityCache::get_or_fetch`] (and the typed `get_or_fetch_*` methods)
+//! follow the [`CachePolicy`] configured for the entity type: read-through
+//! (the default), a maximum age, stale-while-revalidate with background
+//! refresh, cache-only or API-only. Staleness is measured from each row's
+//! `synced_at`. [`EntityCache::get`], [`EntityCache::query`] and the typed
+//! `get_*` methods are raw cache reads that ignore the policy.
+//!
+//! # Incremental Sync
+//!
+//! After the initial sync, [`EntityCache::incremental_sync`] fetches only
+//! records modified since the last sync, checkpointing a watermark in
+//! `payrix_sync_log` after each page:
+//!
+//! ```no_run
+//! use payrix::EntityType;
+//! use payrix::cache::EntityCache;
+//!
+//! # async fn example(cache: EntityCache) -> payrix::Result<()> {
+//! for entity_type in [EntityType::Txns, EntityType::Chargebacks] {
+//!     let result = cache.incremental_sync(entity_type).await?;
+//!     println!("{:?}: {} changed", entity_type, result.records);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Schema Migrations
+//!
+//! [`ensure_schema`] (run by [`EntityCache::new`] by default) applies
+//! versioned migrations, each in its own transaction, and records them in
+//! `payrix_schema_version`. It refuses to run against a database migrated by
+//! a newer version of this crate.
+//!
 //! # Webhook Integration
 //!
 //! The cache can be kept in sync via webhooks:
@@ -89,13 +152,20 @@
 //!
 //! The cache stores tokenized data only - no raw card numbers:
 //!
-//! - **Safe to cache**: Transactions, Chargebacks, Merchants, Customers, Tokens
+//! - **Safe to cache**: Transactions, Chargebacks, Merchants, Customers, Tokens,
+//!   Subscriptions, Plans, Disbursements, Entries, Funds, Batches
 //! - **Payrix handles PCI**: Card numbers are tokenized before storage
 //! - **No raw PAN data**: Webhooks contain the same tokenized data
 
+mod cacheable;
 mod entity_cache;
+mod policy;
+mod query;
 mod schema;
 mod sync;
 
-pub use entity_cache::{CacheConfig, EntityCache, SyncStats};
-pub use schema::ensure_schema;
+pub use cacheable::{Cacheable, ColumnType, ColumnValue, IndexedColumn, CACHED_ENTITY_TYPES};
+pub use entity_cache::{CacheConfig, EntityCache, IncrementalSyncResult, SyncStats};
+pub use policy::CachePolicy;
+pub use query::{CacheFilter, FilterValue, SortOrder};
+pub use schema::{ensure_cache_table, ensure_schema, schema_version, SCHEMA_VERSION};
@@ -0,0 +1,115 @@
+//! Read policies deciding when cached records are refreshed from the API.
+
+use std::time::Duration;
+
+/// How [`EntityCache::get_or_fetch`](super::EntityCache::get_or_fetch) treats
+/// cached records.
+///
+/// A record's age is the time since it was last written to the cache by a
+/// sync, webhook or fetch (`synced_at`). Configure a default and per-type
+/// overrides on [`CacheConfig`](super::CacheConfig):
+///
+/// ```
+/// use std::time::Duration;
+/// use payrix::cache::{CacheConfig, CachePolicy};
+/// use payrix::EntityType;
+///
+/// let config = CacheConfig::new("postgres://localhost/payrix_cache")
+///     .with_policy(CachePolicy::MaxAge(Duration::from_secs(3600)))
+///     .with_entity_policy(
+///         EntityType::Merchants,
+///         CachePolicy::StaleWhileRevalidate { max_age: Duration::from_secs(300) },
+///     )
+///     .with_entity_policy(EntityType::Txns, CachePolicy::ApiOnly);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CachePolicy {
+    /// Serve cached records regardless of age; fetch only missing ones.
+    #[default]
+    ReadThrough,
+    /// Fetch records older than the given age before returning them. If the
+    /// fetch fails the stale record is returned and the error logged.
+    MaxAge(Duration),
+    /// Return records older than `max_age` immediately and refresh them in
+    /// the background, so the next read sees the update.
+    StaleWhileRevalidate {
+        /// Age after which a record is refreshed.
+        max_age: Duration,
+    },
+    /// Never call the API; missing records read as `None`.
+    CacheOnly,
+    /// Always fetch from the API, writing the result to the cache.
+    ApiOnly,
+}
+
+/// What a read should do with the cached copy of a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ReadAction {
+    /// Return the cached record (or `None` if there is none).
+    UseCached,
+    /// Fetch from the API and cache the result.
+    Fetch,
+    /// Return the cached record and refresh it in the background.
+    UseCachedAndRefresh,
+}
+
+impl CachePolicy {
+    /// Decide what to do given the cached record's age, or `None` if the
+    /// record is not cached.
+    pub(super) fn action(&self, age: Option<Duration>) -> ReadAction {
+        let Some(age) = age else {
+            return match self {
+                CachePolicy::CacheOnly => ReadAction::UseCached,
+                _ => ReadAction::Fetch,
+            };
+        };
+
+        match *self {
+            CachePolicy::ReadThrough | CachePolicy::CacheOnly => ReadAction::UseCached,
+            CachePolicy::ApiOnly => ReadAction::Fetch,
+            CachePolicy::MaxAge(max_age) if age > max_age => ReadAction::Fetch,
+            CachePolicy::StaleWhileRevalidate { max_age } if age > max_age => {
+                ReadAction::UseCachedAndRefresh
+            }
+            CachePolicy::MaxAge(_) | CachePolicy::StaleWhileRevalidate { .. } => {
+                ReadAction::UseCached
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn test_missing_records() {
+        assert_eq!(CachePolicy::ReadThrough.action(None), ReadAction::Fetch);
+        assert_eq!(CachePolicy::MaxAge(MINUTE).action(None), ReadAction::Fetch);
+        assert_eq!(
+            CachePolicy::StaleWhileRevalidate { max_age: MINUTE }.action(None),
+            ReadAction::Fetch
+        );
+        assert_eq!(CachePolicy::ApiOnly.action(None), ReadAction::Fetch);
+        assert_eq!(CachePolicy::CacheOnly.action(None), ReadAction::UseCached);
+    }
+
+    #[test]
+    fn test_cached_records_by_age() {
+        let fresh = Some(Duration::from_secs(30));
+        let stale = Some(Duration::from_secs(90));
+
+        assert_eq!(CachePolicy::ReadThrough.action(stale), ReadAction::UseCached);
+        assert_eq!(CachePolicy::CacheOnly.action(stale), ReadAction::UseCached);
+        assert_eq!(CachePolicy::ApiOnly.action(fresh), ReadAction::Fetch);
+
+        assert_eq!(CachePolicy::MaxAge(MINUTE).action(fresh), ReadAction::UseCached);
+        assert_eq!(CachePolicy::MaxAge(MINUTE).action(stale), ReadAction::Fetch);
+
+        let swr = CachePolicy::StaleWhileRevalidate { max_age: MINUTE };
+        assert_eq!(swr.action(fresh), ReadAction::UseCached);
+        assert_eq!(swr.action(stale), ReadAction::UseCachedAndRefresh);
+    }
+}
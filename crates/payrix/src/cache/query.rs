@@ -0,0 +1,611 @@
+//! Filters for querying cache tables.
+//!
+//! [`CacheFilter`] mirrors [`SearchBuilder`]: conditions name Payrix API
+//! fields and take the same [`SearchOperator`]s, so one filter can run against
+//! the cache with [`EntityCache::query`](super::EntityCache::query) or be sent
+//! to the API as a search string with [`CacheFilter::to_search`].
+//!
+//! A condition on a field backed by an indexed column compares that column.
+//! Any other field is read from the stored JSON document, with `.` separating
+//! nested keys (`"customer.email"`). `created` and `modified` compare the
+//! record's timestamps and accept the Payrix date (`YYYYMMDD`) or datetime
+//! format.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::error::{Error, Result};
+use crate::search::{parse_payrix_date, SearchBuilder, SearchOperator};
+
+use super::cacheable::{Cacheable, ColumnType};
+use super::entity_cache::parse_payrix_datetime;
+
+/// A value to compare a field against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterValue {
+    /// A string value.
+    Text(String),
+    /// An integer value (amounts in cents, integer enums).
+    Integer(i64),
+}
+
+impl std::fmt::Display for FilterValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterValue::Text(text) => f.write_str(text),
+            FilterValue::Integer(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+impl From<&str> for FilterValue {
+    fn from(value: &str) -> Self {
+        FilterValue::Text(value.to_string())
+    }
+}
+
+impl From<String> for FilterValue {
+    fn from(value: String) -> Self {
+        FilterValue::Text(value)
+    }
+}
+
+impl From<i32> for FilterValue {
+    fn from(value: i32) -> Self {
+        FilterValue::Integer(value.into())
+    }
+}
+
+impl From<i64> for FilterValue {
+    fn from(value: i64) -> Self {
+        FilterValue::Integer(value)
+    }
+}
+
+/// Sort direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    /// Smallest first.
+    #[default]
+    Asc,
+    /// Largest first.
+    Desc,
+}
+
+impl SortOrder {
+    /// The Payrix sort value.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+}
+
+/// One `field[operator]=values` condition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Condition {
+    field: String,
+    operator: SearchOperator,
+    values: Vec<FilterValue>,
+}
+
+/// Query over a cache table, mirroring [`SearchBuilder`].
+///
+/// Without a [`sort`](Self::sort), results are ordered newest first by
+/// `created`. Ties are always broken by ID so [`offset`](Self::offset)
+/// pagination is stable.
+///
+/// # Example
+///
+/// ```no_run
+/// use payrix::cache::{CacheFilter, EntityCache, SortOrder};
+/// use payrix::search::SearchOperator;
+/// use payrix::{EntityType, PayrixClient, Plan};
+///
+/// # async fn example(cache: EntityCache, client: PayrixClient) -> payrix::Result<()> {
+/// let filter = CacheFilter::new()
+///     .eq("merchant", "t1_mer_12345678901234567890123")
+///     .field_with_op("amount", 1000, SearchOperator::Greater)
+///     .field_with_op("name", "Gold%", SearchOperator::Like)
+///     .sort("amount", SortOrder::Desc)
+///     .limit(50);
+///
+/// // From the cache...
+/// let cached: Vec<Plan> = cache.query(filter.clone()).await?;
+///
+/// // ...or the same conditions against the API
+/// let live: Vec<Plan> = client
+///     .search(EntityType::Plans, &filter.to_search::<Plan>())
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CacheFilter {
+    conditions: Vec<Condition>,
+    sort: Vec<(String, SortOrder)>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+impl CacheFilter {
+    /// A filter matching every record.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require `field` to equal `value`.
+    pub fn eq(self, field: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        self.field_with_op(field, value, SearchOperator::Equals)
+    }
+
+    /// Add a condition with a specific operator.
+    ///
+    /// As in [`SearchBuilder::field_with_op`], [`SearchOperator::Sort`] takes
+    /// `"asc"` or `"desc"`, and `In`/`NotIn` take a comma-separated list.
+    pub fn field_with_op(
+        mut self,
+        field: impl Into<String>,
+        value: impl Into<FilterValue>,
+        operator: SearchOperator,
+    ) -> Self {
+        let field = field.into();
+        let value = value.into();
+        match (operator, value) {
+            (SearchOperator::Sort, value) => {
+                let order = if value.to_string().eq_ignore_ascii_case("desc") {
+                    SortOrder::Desc
+                } else {
+                    SortOrder::Asc
+                };
+                self.sort.push((field, order));
+            }
+            (SearchOperator::In | SearchOperator::NotIn, FilterValue::Text(list)) => {
+                let values = list.split(',').map(FilterValue::from).collect();
+                self.conditions.push(Condition { field, operator, values });
+            }
+            (operator, value) => {
+                self.conditions.push(Condition {
+                    field,
+                    operator,
+                    values: vec![value],
+                });
+            }
+        }
+        self
+    }
+
+    /// Add a condition with several values, for `In` and `NotIn`.
+    pub fn field_multi<V: Into<FilterValue>>(
+        mut self,
+        field: impl Into<String>,
+        values: impl IntoIterator<Item = V>,
+        operator: SearchOperator,
+    ) -> Self {
+        self.conditions.push(Condition {
+            field: field.into(),
+            operator,
+            values: values.into_iter().map(Into::into).collect(),
+        });
+        self
+    }
+
+    /// Sort by `field`. Later calls break ties left by earlier ones.
+    pub fn sort(mut self, field: impl Into<String>, order: SortOrder) -> Self {
+        self.sort.push((field.into(), order));
+        self
+    }
+
+    /// Return at most `limit` records.
+    pub fn limit(mut self, limit: i64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Skip the first `offset` matching records.
+    pub fn offset(mut self, offset: i64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// The conditions and sort as a Payrix search string.
+    ///
+    /// Cache column names are translated back to their API fields. Limit and
+    /// offset are not part of a search; page through the results instead.
+    pub fn to_search<T: Cacheable>(&self) -> String {
+        let mut search = SearchBuilder::new();
+        for condition in &self.conditions {
+            let values: Vec<String> = condition.values.iter().map(ToString::to_string).collect();
+            let values: Vec<&str> = values.iter().map(String::as_str).collect();
+            search = search.field_multi(
+                api_field::<T>(&condition.field),
+                &values,
+                condition.operator,
+            );
+        }
+        for (field, order) in &self.sort {
+            search = search.field_with_op(api_field::<T>(field), order.as_str(), SearchOperator::Sort);
+        }
+        search.build()
+    }
+
+    /// Build the `SELECT data` query for `T`'s table.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`] for an invalid field name, a value that does
+    /// not fit the field, or an operator used with the wrong number of values.
+    pub(super) fn build<T: Cacheable>(&self) -> Result<QueryBuilder<'static, Postgres>> {
+        let mut query = QueryBuilder::new(format!("SELECT data FROM {}", T::TABLE));
+
+        for (i, condition) in self.conditions.iter().enumerate() {
+            query.push(if i == 0 { " WHERE " } else { " AND " });
+            push_condition::<T>(&mut query, condition)?;
+        }
+
+        query.push(" ORDER BY ");
+        for (field, order) in &self.sort {
+            match resolve::<T>(field)? {
+                // jsonb ordering compares numbers numerically and strings lexically
+                Target::Json(path) => {
+                    query.push("data #> ");
+                    query.push_bind(path);
+                }
+                target => {
+                    query.push(target.sql());
+                }
+            }
+            query.push(match order {
+                SortOrder::Asc => " ASC, ",
+                SortOrder::Desc => " DESC, ",
+            });
+        }
+        if self.sort.is_empty() {
+            query.push("created_at DESC, ");
+        }
+        query.push("id");
+
+        if let Some(limit) = self.limit {
+            query.push(" LIMIT ");
+            query.push_bind(limit);
+        }
+        if let Some(offset) = self.offset {
+            query.push(" OFFSET ");
+            query.push_bind(offset);
+        }
+
+        Ok(query)
+    }
+}
+
+// =============================================================================
+// SQL Generation
+// =============================================================================
+
+/// Where a field's value is stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Target {
+    /// An indexed column (or `id`).
+    Column(&'static str, ColumnType),
+    /// `created_at` or `modified_at`.
+    Timestamp(&'static str),
+    /// A path into the JSON document.
+    Json(Vec<String>),
+}
+
+impl Target {
+    /// SQL for a column target. Column names come from the type's
+    /// declaration, never from input.
+    fn sql(&self) -> &'static str {
+        match self {
+            Target::Column(name, _) | Target::Timestamp(name) => name,
+            Target::Json(_) => unreachable!("JSON paths are bound as parameters"),
+        }
+    }
+}
+
+/// A filter value converted for its target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Operand {
+    Text(String),
+    Integer(i64),
+    Timestamp(DateTime<Utc>),
+}
+
+/// Find where `field` is stored for `T`.
+///
+/// API field names map to the column copied from them; cache column names
+/// are also accepted for columns that hold the field's value as-is.
+fn resolve<T: Cacheable>(field: &str) -> Result<Target> {
+    match field {
+        "id" => return Ok(Target::Column("id", ColumnType::Text)),
+        "created" | "created_at" => return Ok(Target::Timestamp("created_at")),
+        "modified" | "modified_at" => return Ok(Target::Timestamp("modified_at")),
+        _ => {}
+    }
+
+    let column = T::COLUMNS
+        .iter()
+        .find(|c| c.field == Some(field))
+        .or_else(|| T::COLUMNS.iter().find(|c| c.name == field && c.field.is_some()));
+    if let Some(column) = column {
+        return Ok(Target::Column(column.name, column.column_type));
+    }
+
+    let path: Vec<String> = field.split('.').map(str::to_string).collect();
+    let valid = path.iter().all(|key| {
+        !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    });
+    if !valid {
+        return Err(Error::Config(format!(
+            "{:?} is not a valid field of {}",
+            field,
+            T::type_name()
+        )));
+    }
+    Ok(Target::Json(path))
+}
+
+/// The API field name for a cache column name, for [`CacheFilter::to_search`].
+fn api_field<T: Cacheable>(field: &str) -> &str {
+    match field {
+        "created_at" => "created",
+        "modified_at" => "modified",
+        _ => T::COLUMNS
+            .iter()
+            .find(|c| c.name == field)
+            .and_then(|c| c.field)
+            .unwrap_or(field),
+    }
+}
+
+/// Convert `value` to the type stored at `target`.
+fn operand(target: &Target, value: &FilterValue) -> Option<Operand> {
+    match (target, value) {
+        (Target::Column(_, ColumnType::Text), value) => Some(Operand::Text(value.to_string())),
+        (Target::Column(..), FilterValue::Integer(n)) => Some(Operand::Integer(*n)),
+        (Target::Column(..), FilterValue::Text(text)) => text.parse().ok().map(Operand::Integer),
+        (Target::Timestamp(_), value) => {
+            let value = value.to_string();
+            parse_payrix_datetime(Some(&value))
+                .or_else(|| {
+                    parse_payrix_date(&value)
+                        .and_then(|d| d.and_hms_opt(0, 0, 0))
+                        .map(|dt| dt.and_utc())
+                })
+                .map(Operand::Timestamp)
+        }
+        (Target::Json(_), FilterValue::Text(text)) => Some(Operand::Text(text.clone())),
+        (Target::Json(_), FilterValue::Integer(n)) => Some(Operand::Integer(*n)),
+    }
+}
+
+/// Push `expr <operator> values` for one condition.
+fn push_condition<T: Cacheable>(
+    query: &mut QueryBuilder<'static, Postgres>,
+    condition: &Condition,
+) -> Result<()> {
+    let invalid = |reason: &str| {
+        Error::Config(format!(
+            "Invalid filter on {}.{}: {}",
+            T::type_name(),
+            condition.field,
+            reason
+        ))
+    };
+
+    let target = resolve::<T>(&condition.field)?;
+    let operands = condition
+        .values
+        .iter()
+        .map(|value| operand(&target, value))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| invalid("value does not match the field's type"))?;
+
+    let numeric = operands.iter().any(|o| matches!(o, Operand::Integer(_)));
+    if numeric && operands.iter().any(|o| matches!(o, Operand::Text(_))) {
+        return Err(invalid("mixes text and integer values"));
+    }
+
+    match &target {
+        Target::Json(path) => {
+            query.push(if numeric { "(data #>> " } else { "data #>> " });
+            query.push_bind(path.clone());
+            if numeric {
+                query.push(")::numeric");
+            }
+        }
+        target => {
+            query.push(target.sql());
+        }
+    }
+
+    let list = matches!(condition.operator, SearchOperator::In | SearchOperator::NotIn);
+    if list {
+        query.push(if condition.operator == SearchOperator::In {
+            " = ANY("
+        } else {
+            " <> ALL("
+        });
+        push_list(query, operands);
+        query.push(")");
+        return Ok(());
+    }
+
+    let [operand] = <[Operand; 1]>::try_from(operands)
+        .map_err(|_| invalid("operator takes exactly one value"))?;
+    let is_text = matches!(operand, Operand::Text(_));
+    query.push(match condition.operator {
+        SearchOperator::Equals | SearchOperator::Exact => " = ",
+        SearchOperator::Diff => " IS DISTINCT FROM ",
+        SearchOperator::Greater => " > ",
+        SearchOperator::Less => " < ",
+        SearchOperator::Like if is_text => " LIKE ",
+        SearchOperator::NotLike if is_text => " NOT LIKE ",
+        SearchOperator::Like | SearchOperator::NotLike => {
+            return Err(invalid("pattern matching needs a text value"));
+        }
+        SearchOperator::In | SearchOperator::NotIn | SearchOperator::Sort => {
+            unreachable!("handled above")
+        }
+    });
+    match operand {
+        Operand::Text(text) => query.push_bind(text),
+        Operand::Integer(n) => query.push_bind(n),
+        Operand::Timestamp(ts) => query.push_bind(ts),
+    };
+    Ok(())
+}
+
+/// Bind a list of operands as one array parameter.
+fn push_list(query: &mut QueryBuilder<'static, Postgres>, operands: Vec<Operand>) {
+    match operands.first() {
+        Some(Operand::Integer(_)) => {
+            let values: Vec<i64> = operands
+                .into_iter()
+                .filter_map(|o| match o {
+                    Operand::Integer(n) => Some(n),
+                    _ => None,
+                })
+                .collect();
+            query.push_bind(values);
+        }
+        Some(Operand::Timestamp(_)) => {
+            let values: Vec<DateTime<Utc>> = operands
+                .into_iter()
+                .filter_map(|o| match o {
+                    Operand::Timestamp(ts) => Some(ts),
+                    _ => None,
+                })
+                .collect();
+            query.push_bind(values);
+        }
+        // Text, or an empty list that matches nothing
+        _ => {
+            let values: Vec<String> = operands
+                .into_iter()
+                .filter_map(|o| match o {
+                    Operand::Text(text) => Some(text),
+                    _ => None,
+                })
+                .collect();
+            query.push_bind(values);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Chargeback, Plan, Transaction};
+
+    #[test]
+    fn test_build_query() {
+        let filter = CacheFilter::new()
+            .eq("merchant_id", "t1_mer_123")
+            .eq("status", 1)
+            .limit(10);
+        let query = filter.build::<Transaction>().unwrap();
+        assert_eq!(
+            query.sql(),
+            "SELECT data FROM payrix_transactions WHERE merchant_id = $1 AND status = $2 ORDER BY created_at DESC, id LIMIT $3"
+        );
+    }
+
+    #[test]
+    fn test_api_fields_use_indexed_columns() {
+        let query = CacheFilter::new()
+            .eq("merchant", "t1_mer_123")
+            .field_with_op("amount", "1000", SearchOperator::Greater)
+            .field_with_op("created", "20240101", SearchOperator::Less)
+            .build::<Plan>()
+            .unwrap();
+        assert_eq!(
+            query.sql(),
+            "SELECT data FROM payrix_plans WHERE merchant_id = $1 AND amount > $2 AND created_at < $3 ORDER BY created_at DESC, id"
+        );
+    }
+
+    #[test]
+    fn test_json_fallback_and_sort() {
+        let query = CacheFilter::new()
+            .field_with_op("schedule", 3, SearchOperator::Diff)
+            .field_with_op("description", "%gold%", SearchOperator::Like)
+            .field_with_op("schedule", "desc", SearchOperator::Sort)
+            .limit(20)
+            .offset(40)
+            .build::<Plan>()
+            .unwrap();
+        assert_eq!(
+            query.sql(),
+            "SELECT data FROM payrix_plans WHERE (data #>> $1)::numeric IS DISTINCT FROM $2 \
+             AND data #>> $3 LIKE $4 ORDER BY data #> $5 DESC, id LIMIT $6 OFFSET $7"
+        );
+    }
+
+    #[test]
+    fn test_columns_not_copied_from_api_use_json() {
+        // Chargeback status is stored by discriminant, but the API sends "open"
+        let query = CacheFilter::new()
+            .eq("status", "open")
+            .field_multi("cycle", ["first", "retrieval"], SearchOperator::NotIn)
+            .build::<Chargeback>()
+            .unwrap();
+        assert_eq!(
+            query.sql(),
+            "SELECT data FROM payrix_chargebacks WHERE data #>> $1 = $2 AND data #>> $3 <> ALL($4) ORDER BY created_at DESC, id"
+        );
+    }
+
+    #[test]
+    fn test_in_splits_comma_list() {
+        let filter = CacheFilter::new().field_with_op("id", "a,b,c", SearchOperator::In);
+        assert_eq!(filter.conditions[0].values.len(), 3);
+        let query = filter.build::<Plan>().unwrap();
+        assert!(query.sql().contains("WHERE id = ANY($1)"));
+    }
+
+    #[test]
+    fn test_to_search() {
+        let filter = CacheFilter::new()
+            .eq("merchant_id", "t1_mer_123")
+            .field_multi("status", [1, 3], SearchOperator::In)
+            .field_with_op("created_at", "20240101", SearchOperator::Greater)
+            .sort("total", SortOrder::Desc);
+        assert_eq!(
+            filter.to_search::<Transaction>(),
+            "merchant[equals]=t1_mer_123&status[in]=1,3&created[greater]=20240101&total[sort]=desc"
+        );
+    }
+
+    #[test]
+    fn test_build_rejects_invalid_field() {
+        let Err(err) = CacheFilter::new()
+            .eq("merchant_id; DROP TABLE x", "a")
+            .build::<Plan>()
+        else {
+            panic!("expected an error");
+        };
+        assert!(err.to_string().contains("not a valid field"));
+    }
+
+    #[test]
+    fn test_build_rejects_wrong_value_type() {
+        assert!(CacheFilter::new().eq("amount", "lots").build::<Plan>().is_err());
+        assert!(CacheFilter::new().eq("created", "yesterday").build::<Plan>().is_err());
+        assert!(CacheFilter::new()
+            .field_with_op("amount", 100, SearchOperator::Like)
+            .build::<Plan>()
+            .is_err());
+        assert!(CacheFilter::new()
+            .field_multi("amount", [1, 2], SearchOperator::Equals)
+            .build::<Plan>()
+            .is_err());
+        assert!(CacheFilter::new()
+            .field_multi("schedule", [FilterValue::from(1), FilterValue::from("x")], SearchOperator::In)
+            .build::<Plan>()
+            .is_err());
+        assert!(CacheFilter::new().eq("id", "t1_pln_1").build::<Plan>().is_ok());
+    }
+}
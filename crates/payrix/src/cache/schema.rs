@@ -5,7 +5,11 @@
 
 use sqlx::PgPool;
 
-use crate::error::Result;
+use tracing::info;
+
+use crate::error::{Error, Result};
+
+use super::cacheable::Cacheable;
 
 /// SQL to create the chargebacks cache table.
 const CREATE_CHARGEBACKS_TABLE: &str = r#"
@@ -142,10 +146,271 @@ CREATE INDEX IF NOT EXISTS idx_sync_log_entity_type ON payrix_sync_log(entity_ty
 CREATE INDEX IF NOT EXISTS idx_sync_log_started ON payrix_sync_log(started_at DESC)
 "#;
 
-/// Ensure all cache tables and indexes exist.
+/// SQL for migration 3: subscription, plan, disbursement, entry, fund, batch
+/// and chargeback child tables.
+///
+/// Frozen copy of what [`cache_table_ddl`] produced for the built-in
+/// [`Cacheable`] types when the migration was released. Column changes to
+/// those types need a new migration, not an edit here.
+const CREATE_V3_TABLES: &str = r#"
+CREATE TABLE IF NOT EXISTS payrix_subscriptions (
+    id VARCHAR(50) PRIMARY KEY,
+    data JSONB NOT NULL,
+    plan_id TEXT,
+    failures INTEGER,
+    created_at TIMESTAMPTZ,
+    modified_at TIMESTAMPTZ,
+    synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_subscriptions_plan_id ON payrix_subscriptions(plan_id);
+CREATE INDEX IF NOT EXISTS idx_subscriptions_failures ON payrix_subscriptions(failures);
+CREATE INDEX IF NOT EXISTS idx_subscriptions_modified_at ON payrix_subscriptions(modified_at);
+CREATE INDEX IF NOT EXISTS idx_subscriptions_synced_at ON payrix_subscriptions(synced_at);
+CREATE TABLE IF NOT EXISTS payrix_plans (
+    id VARCHAR(50) PRIMARY KEY,
+    data JSONB NOT NULL,
+    merchant_id TEXT,
+    name TEXT,
+    amount BIGINT,
+    created_at TIMESTAMPTZ,
+    modified_at TIMESTAMPTZ,
+    synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_plans_merchant_id ON payrix_plans(merchant_id);
+CREATE INDEX IF NOT EXISTS idx_plans_name ON payrix_plans(name);
+CREATE INDEX IF NOT EXISTS idx_plans_amount ON payrix_plans(amount);
+CREATE INDEX IF NOT EXISTS idx_plans_modified_at ON payrix_plans(modified_at);
+CREATE INDEX IF NOT EXISTS idx_plans_synced_at ON payrix_plans(synced_at);
+CREATE TABLE IF NOT EXISTS payrix_disbursements (
+    id VARCHAR(50) PRIMARY KEY,
+    data JSONB NOT NULL,
+    entity_id TEXT,
+    account_id TEXT,
+    status INTEGER,
+    amount BIGINT,
+    created_at TIMESTAMPTZ,
+    modified_at TIMESTAMPTZ,
+    synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_disbursements_entity_id ON payrix_disbursements(entity_id);
+CREATE INDEX IF NOT EXISTS idx_disbursements_account_id ON payrix_disbursements(account_id);
+CREATE INDEX IF NOT EXISTS idx_disbursements_status ON payrix_disbursements(status);
+CREATE INDEX IF NOT EXISTS idx_disbursements_amount ON payrix_disbursements(amount);
+CREATE INDEX IF NOT EXISTS idx_disbursements_modified_at ON payrix_disbursements(modified_at);
+CREATE INDEX IF NOT EXISTS idx_disbursements_synced_at ON payrix_disbursements(synced_at);
+CREATE TABLE IF NOT EXISTS payrix_entries (
+    id VARCHAR(50) PRIMARY KEY,
+    data JSONB NOT NULL,
+    entity_id TEXT,
+    fund_id TEXT,
+    txn_id TEXT,
+    disbursement_id TEXT,
+    event INTEGER,
+    created_at TIMESTAMPTZ,
+    modified_at TIMESTAMPTZ,
+    synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_entries_entity_id ON payrix_entries(entity_id);
+CREATE INDEX IF NOT EXISTS idx_entries_fund_id ON payrix_entries(fund_id);
+CREATE INDEX IF NOT EXISTS idx_entries_txn_id ON payrix_entries(txn_id);
+CREATE INDEX IF NOT EXISTS idx_entries_disbursement_id ON payrix_entries(disbursement_id);
+CREATE INDEX IF NOT EXISTS idx_entries_event ON payrix_entries(event);
+CREATE INDEX IF NOT EXISTS idx_entries_modified_at ON payrix_entries(modified_at);
+CREATE INDEX IF NOT EXISTS idx_entries_synced_at ON payrix_entries(synced_at);
+CREATE TABLE IF NOT EXISTS payrix_funds (
+    id VARCHAR(50) PRIMARY KEY,
+    data JSONB NOT NULL,
+    entity_id TEXT,
+    currency TEXT,
+    created_at TIMESTAMPTZ,
+    modified_at TIMESTAMPTZ,
+    synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_funds_entity_id ON payrix_funds(entity_id);
+CREATE INDEX IF NOT EXISTS idx_funds_currency ON payrix_funds(currency);
+CREATE INDEX IF NOT EXISTS idx_funds_modified_at ON payrix_funds(modified_at);
+CREATE INDEX IF NOT EXISTS idx_funds_synced_at ON payrix_funds(synced_at);
+CREATE TABLE IF NOT EXISTS payrix_batches (
+    id VARCHAR(50) PRIMARY KEY,
+    data JSONB NOT NULL,
+    merchant_id TEXT,
+    status TEXT,
+    date TEXT,
+    created_at TIMESTAMPTZ,
+    modified_at TIMESTAMPTZ,
+    synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_batches_merchant_id ON payrix_batches(merchant_id);
+CREATE INDEX IF NOT EXISTS idx_batches_status ON payrix_batches(status);
+CREATE INDEX IF NOT EXISTS idx_batches_date ON payrix_batches(date);
+CREATE INDEX IF NOT EXISTS idx_batches_modified_at ON payrix_batches(modified_at);
+CREATE INDEX IF NOT EXISTS idx_batches_synced_at ON payrix_batches(synced_at);
+CREATE TABLE IF NOT EXISTS payrix_chargeback_messages (
+    id VARCHAR(50) PRIMARY KEY,
+    data JSONB NOT NULL,
+    chargeback_id TEXT,
+    message_type TEXT,
+    status TEXT,
+    created_at TIMESTAMPTZ,
+    modified_at TIMESTAMPTZ,
+    synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_chargeback_messages_chargeback_id ON payrix_chargeback_messages(chargeback_id);
+CREATE INDEX IF NOT EXISTS idx_chargeback_messages_message_type ON payrix_chargeback_messages(message_type);
+CREATE INDEX IF NOT EXISTS idx_chargeback_messages_status ON payrix_chargeback_messages(status);
+CREATE INDEX IF NOT EXISTS idx_chargeback_messages_modified_at ON payrix_chargeback_messages(modified_at);
+CREATE INDEX IF NOT EXISTS idx_chargeback_messages_synced_at ON payrix_chargeback_messages(synced_at);
+CREATE TABLE IF NOT EXISTS payrix_chargeback_documents (
+    id VARCHAR(50) PRIMARY KEY,
+    data JSONB NOT NULL,
+    chargeback_id TEXT,
+    chargeback_message_id TEXT,
+    created_at TIMESTAMPTZ,
+    modified_at TIMESTAMPTZ,
+    synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_chargeback_documents_chargeback_id ON payrix_chargeback_documents(chargeback_id);
+CREATE INDEX IF NOT EXISTS idx_chargeback_documents_chargeback_message_id ON payrix_chargeback_documents(chargeback_message_id);
+CREATE INDEX IF NOT EXISTS idx_chargeback_documents_modified_at ON payrix_chargeback_documents(modified_at);
+CREATE INDEX IF NOT EXISTS idx_chargeback_documents_synced_at ON payrix_chargeback_documents(synced_at);
+CREATE TABLE IF NOT EXISTS payrix_chargeback_statuses (
+    id VARCHAR(50) PRIMARY KEY,
+    data JSONB NOT NULL,
+    chargeback_id TEXT,
+    status TEXT,
+    created_at TIMESTAMPTZ,
+    modified_at TIMESTAMPTZ,
+    synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_chargeback_statuses_chargeback_id ON payrix_chargeback_statuses(chargeback_id);
+CREATE INDEX IF NOT EXISTS idx_chargeback_statuses_status ON payrix_chargeback_statuses(status);
+CREATE INDEX IF NOT EXISTS idx_chargeback_statuses_modified_at ON payrix_chargeback_statuses(modified_at);
+CREATE INDEX IF NOT EXISTS idx_chargeback_statuses_synced_at ON payrix_chargeback_statuses(synced_at)
+"#;
+
+/// SQL to create the table recording applied schema migrations.
+const CREATE_SCHEMA_VERSION_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS payrix_schema_version (
+    version INTEGER PRIMARY KEY,
+    description TEXT NOT NULL,
+    applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+)
+"#;
+
+/// Advisory lock key held while migrating, so concurrent processes starting
+/// against the same database apply each migration once.
+const MIGRATION_LOCK_KEY: i64 = 0x7061_7972_6978; // "payrix"
+
+/// The cache schema version this build of the crate expects.
+pub const SCHEMA_VERSION: i32 = 3;
+
+// =============================================================================
+// Migrations
+// =============================================================================
+
+/// A forward schema migration.
+///
+/// Released migrations must never change: alter a table by adding a new
+/// migration. Migrations are literal SQL so that changing the column
+/// declarations of a built-in [`Cacheable`] type cannot rewrite one; such a
+/// change needs its own migration.
+struct Migration {
+    version: i32,
+    description: &'static str,
+    statements: fn() -> Vec<String>,
+}
+
+/// All migrations, in version order.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "chargeback, transaction, merchant, customer, token and sync log tables",
+        statements: v1_statements,
+    },
+    Migration {
+        version: 2,
+        description: "incremental sync watermark",
+        statements: v2_statements,
+    },
+    Migration {
+        version: 3,
+        description: "subscription, plan, disbursement, entry, fund, batch and chargeback child tables",
+        statements: v3_statements,
+    },
+];
+
+/// The original schema. `IF NOT EXISTS` lets this adopt databases created
+/// before versioning, which have these tables but no version table.
+fn v1_statements() -> Vec<String> {
+    [
+        CREATE_CHARGEBACKS_TABLE,
+        CREATE_TRANSACTIONS_TABLE,
+        CREATE_MERCHANTS_TABLE,
+        CREATE_CUSTOMERS_TABLE,
+        CREATE_TOKENS_TABLE,
+        CREATE_SYNC_LOG_TABLE,
+        CREATE_CHARGEBACKS_INDEXES,
+        CREATE_TRANSACTIONS_INDEXES,
+        CREATE_MERCHANTS_INDEXES,
+        CREATE_CUSTOMERS_INDEXES,
+        CREATE_TOKENS_INDEXES,
+        CREATE_SYNC_LOG_INDEXES,
+    ]
+    .iter()
+    .flat_map(|sql| split_statements(sql))
+    .collect()
+}
+
+fn v2_statements() -> Vec<String> {
+    vec!["ALTER TABLE payrix_sync_log ADD COLUMN IF NOT EXISTS watermark VARCHAR(50)".to_string()]
+}
+
+fn v3_statements() -> Vec<String> {
+    split_statements(CREATE_V3_TABLES)
+}
+
+/// Split a block of `;`-separated SQL into individual statements.
+fn split_statements(sql: &str) -> Vec<String> {
+    sql.split(';')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Migrations still to apply to a database at `current` version.
 ///
-/// This function creates the necessary database schema for the entity cache.
-/// It is safe to call multiple times (uses CREATE IF NOT EXISTS).
+/// # Errors
+///
+/// Returns [`Error::Config`] if the database is newer than [`SCHEMA_VERSION`].
+fn pending_migrations(current: i32) -> Result<&'static [Migration]> {
+    if current > SCHEMA_VERSION {
+        return Err(Error::Config(format!(
+            "Cache schema version {} is newer than version {} supported by this build; \
+             upgrade the payrix crate before using this database",
+            current, SCHEMA_VERSION
+        )));
+    }
+    let applied = MIGRATIONS.iter().take_while(|m| m.version <= current).count();
+    Ok(&MIGRATIONS[applied..])
+}
+
+// =============================================================================
+// Schema Management
+// =============================================================================
+
+/// Bring the cache schema up to [`SCHEMA_VERSION`].
+///
+/// Applies each pending migration in its own transaction and records it in
+/// `payrix_schema_version`. Databases created before versioning are adopted
+/// as version 1 and upgraded from there. Safe to call on every startup and
+/// from several processes at once.
+///
+/// # Errors
+///
+/// Returns [`Error::Config`] if the database was migrated by a newer version
+/// of this crate, and a database error if a migration fails (that migration
+/// is rolled back; earlier ones stay applied).
 ///
 /// # Arguments
 ///
@@ -164,56 +429,170 @@ CREATE INDEX IF NOT EXISTS idx_sync_log_started ON payrix_sync_log(started_at DE
 /// # }
 /// ```
 pub async fn ensure_schema(pool: &PgPool) -> Result<()> {
-    // Create tables
-    sqlx::query(CREATE_CHARGEBACKS_TABLE).execute(pool).await?;
-    sqlx::query(CREATE_TRANSACTIONS_TABLE).execute(pool).await?;
-    sqlx::query(CREATE_MERCHANTS_TABLE).execute(pool).await?;
-    sqlx::query(CREATE_CUSTOMERS_TABLE).execute(pool).await?;
-    sqlx::query(CREATE_TOKENS_TABLE).execute(pool).await?;
-    sqlx::query(CREATE_SYNC_LOG_TABLE).execute(pool).await?;
-
-    // Create indexes (split by semicolon and execute individually)
-    for index_sql in CREATE_CHARGEBACKS_INDEXES.split(';') {
-        let sql = index_sql.trim();
-        if !sql.is_empty() {
-            sqlx::query(sql).execute(pool).await?;
+    sqlx::query(CREATE_SCHEMA_VERSION_TABLE).execute(pool).await?;
+
+    let current = schema_version(pool).await?;
+    for migration in pending_migrations(current)? {
+        let mut tx = pool.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(MIGRATION_LOCK_KEY)
+            .execute(&mut *tx)
+            .await?;
+
+        // Another process may have applied it while we waited for the lock.
+        let current: i32 =
+            sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM payrix_schema_version")
+                .fetch_one(&mut *tx)
+                .await?;
+        if current >= migration.version {
+            tx.rollback().await?;
+            continue;
         }
-    }
 
-    for index_sql in CREATE_TRANSACTIONS_INDEXES.split(';') {
-        let sql = index_sql.trim();
-        if !sql.is_empty() {
-            sqlx::query(sql).execute(pool).await?;
+        info!(
+            version = migration.version,
+            description = migration.description,
+            "Applying cache schema migration"
+        );
+        for sql in (migration.statements)() {
+            sqlx::query(&sql).execute(&mut *tx).await?;
         }
+        sqlx::query("INSERT INTO payrix_schema_version (version, description) VALUES ($1, $2)")
+            .bind(migration.version)
+            .bind(migration.description)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
     }
 
-    for index_sql in CREATE_MERCHANTS_INDEXES.split(';') {
-        let sql = index_sql.trim();
-        if !sql.is_empty() {
-            sqlx::query(sql).execute(pool).await?;
-        }
+    Ok(())
+}
+
+/// The schema version of a cache database (0 if no migration has run).
+pub async fn schema_version(pool: &PgPool) -> Result<i32> {
+    let exists: bool =
+        sqlx::query_scalar("SELECT to_regclass('payrix_schema_version') IS NOT NULL")
+            .fetch_one(pool)
+            .await?;
+    if !exists {
+        return Ok(0);
     }
 
-    for index_sql in CREATE_CUSTOMERS_INDEXES.split(';') {
-        let sql = index_sql.trim();
-        if !sql.is_empty() {
-            sqlx::query(sql).execute(pool).await?;
-        }
+    let version: i32 =
+        sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM payrix_schema_version")
+            .fetch_one(pool)
+            .await?;
+    Ok(version)
+}
+
+/// Ensure the cache table and indexes for a [`Cacheable`] type exist.
+///
+/// Built-in types are created by [`ensure_schema`]'s migrations; call this
+/// for types you implement `Cacheable` for. It only creates missing tables
+/// and indexes, so changing a custom type's columns later needs your own
+/// `ALTER TABLE`.
+pub async fn ensure_cache_table<T: Cacheable>(pool: &PgPool) -> Result<()> {
+    for sql in cache_table_ddl::<T>() {
+        sqlx::query(&sql).execute(pool).await?;
     }
+    Ok(())
+}
 
-    for index_sql in CREATE_TOKENS_INDEXES.split(';') {
-        let sql = index_sql.trim();
-        if !sql.is_empty() {
-            sqlx::query(sql).execute(pool).await?;
-        }
+/// `CREATE TABLE` and `CREATE INDEX` statements for a [`Cacheable`] type.
+fn cache_table_ddl<T: Cacheable>() -> Vec<String> {
+    let mut columns = vec![
+        "id VARCHAR(50) PRIMARY KEY".to_string(),
+        "data JSONB NOT NULL".to_string(),
+    ];
+    columns.extend(
+        T::COLUMNS
+            .iter()
+            .map(|c| format!("{} {}", c.name, c.column_type.sql_type())),
+    );
+    columns.push("created_at TIMESTAMPTZ".to_string());
+    columns.push("modified_at TIMESTAMPTZ".to_string());
+    columns.push("synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW()".to_string());
+
+    let mut statements = vec![format!(
+        "CREATE TABLE IF NOT EXISTS {} (\n    {}\n)",
+        T::TABLE,
+        columns.join(",\n    ")
+    )];
+    let indexed = T::COLUMNS
+        .iter()
+        .map(|c| c.name)
+        .chain(["modified_at", "synced_at"]);
+    for column in indexed {
+        statements.push(format!(
+            "CREATE INDEX IF NOT EXISTS idx_{}_{} ON {}({})",
+            T::type_name(),
+            column,
+            T::TABLE,
+            column
+        ));
     }
+    statements
+}
 
-    for index_sql in CREATE_SYNC_LOG_INDEXES.split(';') {
-        let sql = index_sql.trim();
-        if !sql.is_empty() {
-            sqlx::query(sql).execute(pool).await?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Disbursement, Plan};
+
+    #[test]
+    fn test_migrations_are_contiguous() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, i as i32 + 1);
+            assert!(!(migration.statements)().is_empty());
         }
+        assert_eq!(MIGRATIONS.last().unwrap().version, SCHEMA_VERSION);
     }
 
-    Ok(())
+    #[test]
+    fn test_pending_migrations() {
+        let versions = |current| -> Vec<i32> {
+            pending_migrations(current).unwrap().iter().map(|m| m.version).collect()
+        };
+        assert_eq!(versions(0), vec![1, 2, 3]);
+        assert_eq!(versions(1), vec![2, 3]);
+        assert!(versions(SCHEMA_VERSION).is_empty());
+    }
+
+    #[test]
+    fn test_newer_schema_is_refused() {
+        let err = pending_migrations(SCHEMA_VERSION + 1).err().unwrap();
+        assert!(err.to_string().contains("newer than version"));
+    }
+
+    #[test]
+    fn test_v1_matches_original_schema() {
+        let v1 = v1_statements();
+        assert_eq!(v1.iter().filter(|s| s.starts_with("CREATE TABLE")).count(), 6);
+        assert!(v1.iter().all(|s| s.contains("IF NOT EXISTS")));
+        assert!(!v1.iter().any(|s| s.contains("watermark")));
+        assert!(v1.iter().any(|s| s.contains("idx_sync_log_started")));
+    }
+
+    #[test]
+    fn test_v3_creates_child_tables() {
+        let v3 = v3_statements();
+        assert_eq!(v3.iter().filter(|s| s.starts_with("CREATE TABLE")).count(), 9);
+        assert!(v3.iter().all(|s| s.contains("IF NOT EXISTS")));
+        assert!(v3.iter().any(|s| s.contains("idx_chargeback_statuses_synced_at")));
+        assert!(v3.contains(&cache_table_ddl::<Disbursement>()[0]));
+    }
+
+    #[test]
+    fn test_cache_table_ddl() {
+        let ddl = cache_table_ddl::<Plan>();
+        assert!(ddl[0].starts_with("CREATE TABLE IF NOT EXISTS payrix_plans ("));
+        assert!(ddl[0].contains("merchant_id TEXT"));
+        assert!(ddl[0].contains("amount BIGINT"));
+        assert!(ddl[0].contains("synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW()"));
+        assert!(ddl.contains(
+            &"CREATE INDEX IF NOT EXISTS idx_plans_merchant_id ON payrix_plans(merchant_id)"
+                .to_string()
+        ));
+        assert_eq!(ddl.len(), 1 + Plan::COLUMNS.len() + 2);
+    }
 }
@@ -3,16 +3,23 @@
 //! This module handles initial sync and incremental sync of entities
 //! from the Payrix API to the local cache.
 
+use std::collections::HashMap;
 use std::time::Instant;
 
+use serde::Deserialize;
 use sqlx::Row;
 use tracing::{debug, error, info, warn};
 
 use crate::entity::EntityType;
-use crate::error::Result;
+use crate::error::{Error, Result};
+use crate::search::{SearchBuilder, SearchOperator};
 use crate::types::{Chargeback, Customer, Merchant, Token, Transaction};
 
-use super::entity_cache::{EntityCache, SyncStats};
+use super::cacheable::{dispatch_cacheable, Cacheable};
+use super::entity_cache::{EntityCache, IncrementalSyncResult, SyncStats};
+
+/// Page size for incremental sync requests (the Payrix maximum).
+const INCREMENTAL_PAGE_LIMIT: i32 = 100;
 
 // =============================================================================
 // Initial Sync
@@ -29,7 +36,7 @@ pub async fn initial_sync(cache: &EntityCache) -> Result<SyncStats> {
     info!("Starting initial sync from Payrix API");
 
     // Sync each entity type, collecting stats
-    match sync_chargebacks(cache).await {
+    match sync_all::<Chargeback>(cache).await {
         Ok(count) => {
             stats.chargebacks = count;
             info!(count, "Synced chargebacks");
@@ -40,7 +47,7 @@ pub async fn initial_sync(cache: &EntityCache) -> Result<SyncStats> {
         }
     }
 
-    match sync_transactions(cache).await {
+    match sync_all::<Transaction>(cache).await {
         Ok(count) => {
             stats.transactions = count;
             info!(count, "Synced transactions");
@@ -51,7 +58,7 @@ pub async fn initial_sync(cache: &EntityCache) -> Result<SyncStats> {
         }
     }
 
-    match sync_merchants(cache).await {
+    match sync_all::<Merchant>(cache).await {
         Ok(count) => {
             stats.merchants = count;
             info!(count, "Synced merchants");
@@ -62,7 +69,7 @@ pub async fn initial_sync(cache: &EntityCache) -> Result<SyncStats> {
         }
     }
 
-    match sync_customers(cache).await {
+    match sync_all::<Customer>(cache).await {
         Ok(count) => {
             stats.customers = count;
             info!(count, "Synced customers");
@@ -73,7 +80,7 @@ pub async fn initial_sync(cache: &EntityCache) -> Result<SyncStats> {
         }
     }
 
-    match sync_tokens(cache).await {
+    match sync_all::<Token>(cache).await {
         Ok(count) => {
             stats.tokens = count;
             info!(count, "Synced tokens");
@@ -100,37 +107,31 @@ pub async fn initial_sync(cache: &EntityCache) -> Result<SyncStats> {
 
 /// Sync a specific entity type from Payrix.
 ///
+/// Supports every type in [`CACHED_ENTITY_TYPES`](super::CACHED_ENTITY_TYPES).
 /// Returns the number of entities synced.
 pub async fn sync_entity_type(cache: &EntityCache, entity_type: EntityType) -> Result<usize> {
-    match entity_type {
-        EntityType::Chargebacks => sync_chargebacks(cache).await,
-        EntityType::Txns => sync_transactions(cache).await,
-        EntityType::Merchants => sync_merchants(cache).await,
-        EntityType::Customers => sync_customers(cache).await,
-        EntityType::Tokens => sync_tokens(cache).await,
-        _ => {
-            warn!(entity_type = ?entity_type, "Entity type not supported for caching");
-            Ok(0)
-        }
-    }
+    dispatch_cacheable!(entity_type, T => sync_all::<T>(cache).await, _ => {
+        warn!(entity_type = ?entity_type, "Entity type not supported for caching");
+        Ok(0)
+    })
 }
 
 // =============================================================================
-// Individual Entity Syncs
+// Full Sync
 // =============================================================================
 
-async fn sync_chargebacks(cache: &EntityCache) -> Result<usize> {
-    let entity_type = "chargebacks";
-    let log_id = start_sync_log(cache, entity_type, "full").await?;
+/// Fetch every record of type `T` and upsert it into the cache.
+async fn sync_all<T: Cacheable>(cache: &EntityCache) -> Result<usize> {
+    let log_id = start_sync_log(cache, T::type_name(), "full").await?;
 
     let result = async {
-        debug!("Fetching all chargebacks from Payrix API");
-        let chargebacks: Vec<Chargeback> = cache.client().get_all(EntityType::Chargebacks).await?;
-        let count = chargebacks.len();
+        debug!(entity_type = T::type_name(), "Fetching all records from Payrix API");
+        let records: Vec<T> = cache.client().get_all(T::ENTITY_TYPE).await?;
+        let count = records.len();
 
-        debug!(count, "Upserting chargebacks to cache");
-        for chargeback in &chargebacks {
-            cache.upsert_chargeback(chargeback).await?;
+        debug!(entity_type = T::type_name(), count, "Upserting records to cache");
+        for record in &records {
+            cache.upsert(record).await?;
         }
 
         Ok::<_, crate::error::Error>(count)
@@ -145,108 +146,193 @@ async fn sync_chargebacks(cache: &EntityCache) -> Result<usize> {
     result
 }
 
-async fn sync_transactions(cache: &EntityCache) -> Result<usize> {
-    let entity_type = "transactions";
-    let log_id = start_sync_log(cache, entity_type, "full").await?;
+// =============================================================================
+// Incremental Sync
+// =============================================================================
 
-    let result = async {
-        debug!("Fetching all transactions from Payrix API");
-        let transactions: Vec<Transaction> = cache.client().get_all(EntityType::Txns).await?;
-        let count = transactions.len();
+/// Sync only the entities modified since the last sync.
+///
+/// Pages are requested in ascending `modified` order with a
+/// `modified[greater]` filter. After each page the watermark is written to
+/// the sync log, so a sync that is interrupted resumes from its last page.
+pub async fn incremental_sync(
+    cache: &EntityCache,
+    entity_type: EntityType,
+) -> Result<IncrementalSyncResult> {
+    let Some(log_name) = sync_log_name(entity_type) else {
+        warn!(entity_type = ?entity_type, "Entity type not supported for caching");
+        return Ok(IncrementalSyncResult::default());
+    };
+
+    let start_watermark = sync_watermark(cache, entity_type).await?;
+    let log_id = start_sync_log(cache, log_name, "incremental").await?;
+    info!(
+        entity_type = log_name,
+        watermark = start_watermark.as_deref().unwrap_or("none"),
+        "Starting incremental sync"
+    );
 
-        debug!(count, "Upserting transactions to cache");
-        for txn in &transactions {
-            cache.upsert_transaction(txn).await?;
+    let mut result = IncrementalSyncResult {
+        watermark: start_watermark.clone(),
+        resumed_from: start_watermark,
+        ..Default::default()
+    };
+
+    let outcome = async {
+        let mut page = 1;
+        loop {
+            let mut search = SearchBuilder::new();
+            if let Some(ref watermark) = result.watermark {
+                search = search.field_with_op("modified", watermark, SearchOperator::Greater);
+            }
+            let search = search.field_with_op("modified", "asc", SearchOperator::Sort).build();
+
+            let (items, page_info) = cache
+                .client()
+                .get_page::<serde_json::Value>(
+                    entity_type,
+                    page,
+                    INCREMENTAL_PAGE_LIMIT,
+                    &HashMap::new(),
+                    Some(&search),
+                )
+                .await?;
+            result.pages += 1;
+
+            for item in &items {
+                upsert_value(cache, entity_type, item).await?;
+            }
+            result.records += items.len();
+
+            let modified: Vec<&str> = items
+                .iter()
+                .filter_map(|item| item.get("modified").and_then(|m| m.as_str()))
+                .collect();
+
+            if !page_info.has_more || items.is_empty() {
+                if let Some(last) = modified.last() {
+                    result.watermark = Some(last.to_string());
+                }
+                checkpoint_sync_log(cache, log_id, result.records, result.watermark.as_deref())
+                    .await?;
+                break;
+            }
+
+            // Records sharing the page's last timestamp may continue onto the
+            // next page, so only advance past timestamps that are complete.
+            // When the whole page shares one timestamp, step to the next page
+            // under the same filter instead.
+            match checkpoint_before_last(&modified) {
+                Some(next) if result.watermark.as_deref().is_none_or(|w| next > w) => {
+                    result.watermark = Some(next.to_string());
+                    page = 1;
+                }
+                _ => page += 1,
+            }
+            checkpoint_sync_log(cache, log_id, result.records, result.watermark.as_deref())
+                .await?;
+            debug!(
+                entity_type = log_name,
+                records = result.records,
+                watermark = result.watermark.as_deref().unwrap_or("none"),
+                "Checkpointed incremental sync"
+            );
         }
 
-        Ok::<_, crate::error::Error>(count)
+        Ok::<_, Error>(())
     }
     .await;
 
-    match &result {
-        Ok(count) => complete_sync_log(cache, log_id, *count, None).await?,
-        Err(e) => complete_sync_log(cache, log_id, 0, Some(&e.to_string())).await?,
-    }
-
-    result
-}
-
-async fn sync_merchants(cache: &EntityCache) -> Result<usize> {
-    let entity_type = "merchants";
-    let log_id = start_sync_log(cache, entity_type, "full").await?;
-
-    let result = async {
-        debug!("Fetching all merchants from Payrix API");
-        let merchants: Vec<Merchant> = cache.client().get_all(EntityType::Merchants).await?;
-        let count = merchants.len();
-
-        debug!(count, "Upserting merchants to cache");
-        for merchant in &merchants {
-            cache.upsert_merchant(merchant).await?;
+    match &outcome {
+        Ok(()) => {
+            complete_sync_log(cache, log_id, result.records, None).await?;
+            info!(
+                entity_type = log_name,
+                records = result.records,
+                pages = result.pages,
+                "Incremental sync complete"
+            );
+        }
+        Err(e) => {
+            error!(entity_type = log_name, error = %e, "Incremental sync failed");
+            complete_sync_log(cache, log_id, result.records, Some(&e.to_string())).await?;
         }
-
-        Ok::<_, crate::error::Error>(count)
-    }
-    .await;
-
-    match &result {
-        Ok(count) => complete_sync_log(cache, log_id, *count, None).await?,
-        Err(e) => complete_sync_log(cache, log_id, 0, Some(&e.to_string())).await?,
     }
 
-    result
+    outcome.map(|()| result)
 }
 
-async fn sync_customers(cache: &EntityCache) -> Result<usize> {
-    let entity_type = "customers";
-    let log_id = start_sync_log(cache, entity_type, "full").await?;
-
-    let result = async {
-        debug!("Fetching all customers from Payrix API");
-        let customers: Vec<Customer> = cache.client().get_all(EntityType::Customers).await?;
-        let count = customers.len();
+/// The watermark an incremental sync of `entity_type` would start from.
+///
+/// This is the latest checkpoint from an earlier incremental sync (complete or
+/// not). Without one, it is the start time of the last successful full sync;
+/// `None` means no sync has completed and the next incremental sync fetches
+/// everything.
+pub async fn sync_watermark(cache: &EntityCache, entity_type: EntityType) -> Result<Option<String>> {
+    let Some(log_name) = sync_log_name(entity_type) else {
+        return Ok(None);
+    };
 
-        debug!(count, "Upserting customers to cache");
-        for customer in &customers {
-            cache.upsert_customer(customer).await?;
-        }
+    let row = sqlx::query(
+        r#"
+        SELECT watermark FROM payrix_sync_log
+        WHERE entity_type = $1 AND watermark IS NOT NULL
+        ORDER BY id DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(log_name)
+    .fetch_optional(cache.pool())
+    .await?;
 
-        Ok::<_, crate::error::Error>(count)
+    if let Some(row) = row {
+        return Ok(row.get("watermark"));
     }
-    .await;
 
-    match &result {
-        Ok(count) => complete_sync_log(cache, log_id, *count, None).await?,
-        Err(e) => complete_sync_log(cache, log_id, 0, Some(&e.to_string())).await?,
-    }
+    let row = sqlx::query(
+        r#"
+        SELECT started_at FROM payrix_sync_log
+        WHERE entity_type = $1 AND operation = 'full'
+          AND completed_at IS NOT NULL AND error_message IS NULL
+        ORDER BY completed_at DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(log_name)
+    .fetch_optional(cache.pool())
+    .await?;
 
-    result
+    Ok(row.map(|r| {
+        let started: chrono::DateTime<chrono::Utc> = r.get("started_at");
+        started.format("%Y-%m-%d %H:%M:%S").to_string()
+    }))
 }
 
-async fn sync_tokens(cache: &EntityCache) -> Result<usize> {
-    let entity_type = "tokens";
-    let log_id = start_sync_log(cache, entity_type, "full").await?;
-
-    let result = async {
-        debug!("Fetching all tokens from Payrix API");
-        let tokens: Vec<Token> = cache.client().get_all(EntityType::Tokens).await?;
-        let count = tokens.len();
-
-        debug!(count, "Upserting tokens to cache");
-        for token in &tokens {
-            cache.upsert_token(token).await?;
-        }
-
-        Ok::<_, crate::error::Error>(count)
-    }
-    .await;
+/// The sync log name used for a cached entity type.
+fn sync_log_name(entity_type: EntityType) -> Option<&'static str> {
+    dispatch_cacheable!(entity_type, T => Some(T::type_name()), _ => None)
+}
 
-    match &result {
-        Ok(count) => complete_sync_log(cache, log_id, *count, None).await?,
-        Err(e) => complete_sync_log(cache, log_id, 0, Some(&e.to_string())).await?,
-    }
+/// The greatest timestamp on a page that is strictly before its last one.
+///
+/// `modified` must be in ascending order.
+fn checkpoint_before_last<'a>(modified: &[&'a str]) -> Option<&'a str> {
+    let last = modified.last()?;
+    modified.iter().rev().find(|m| *m < last).copied()
+}
 
-    result
+/// Deserialize a raw API record and upsert it into the matching table.
+async fn upsert_value(
+    cache: &EntityCache,
+    entity_type: EntityType,
+    value: &serde_json::Value,
+) -> Result<()> {
+    dispatch_cacheable!(entity_type, T => {
+        let record = T::deserialize(value).map_err(|e| {
+            Error::Internal(format!("Failed to deserialize synced {}: {}", T::type_name(), e))
+        })?;
+        cache.upsert(&record).await
+    }, _ => Ok(()))
 }
 
 // =============================================================================
@@ -292,3 +378,56 @@ async fn complete_sync_log(
 
     Ok(())
 }
+
+async fn checkpoint_sync_log(
+    cache: &EntityCache,
+    log_id: i32,
+    records_processed: usize,
+    watermark: Option<&str>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE payrix_sync_log
+        SET records_processed = $2,
+            watermark = $3
+        WHERE id = $1
+        "#,
+    )
+    .bind(log_id)
+    .bind(records_processed as i32)
+    .bind(watermark)
+    .execute(cache.pool())
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_before_last_skips_trailing_ties() {
+        let modified = [
+            "2024-01-01 10:00:00.0001",
+            "2024-01-01 10:00:01.0000",
+            "2024-01-01 10:00:02.0000",
+            "2024-01-01 10:00:02.0000",
+        ];
+        assert_eq!(checkpoint_before_last(&modified), Some("2024-01-01 10:00:01.0000"));
+    }
+
+    #[test]
+    fn test_checkpoint_before_last_single_timestamp() {
+        let modified = ["2024-01-01 10:00:00.0000"; 3];
+        assert_eq!(checkpoint_before_last(&modified), None);
+        assert_eq!(checkpoint_before_last(&[]), None);
+    }
+
+    #[test]
+    fn test_sync_log_name() {
+        assert_eq!(sync_log_name(EntityType::Txns), Some("transactions"));
+        assert_eq!(sync_log_name(EntityType::Plans), Some("plans"));
+        assert_eq!(sync_log_name(EntityType::Vendors), None);
+    }
+}
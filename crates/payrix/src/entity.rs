@@ -41,12 +41,16 @@ pub enum EntityType {
     FeeRules,
     /// Fees
     Fees,
+    /// Holds
+    Holds,
     /// Organization entities
     OrgEntities,
     /// Reserve entries
     ReserveEntries,
     /// Reserves
     Reserves,
+    /// Settlements
+    Settlements,
     /// Vendors
     Vendors,
     /// Account verifications
@@ -112,9 +116,11 @@ impl EntityType {
             EntityType::EntityReserves => "entityReserves",
             EntityType::FeeRules => "feeRules",
             EntityType::Fees => "fees",
+            EntityType::Holds => "holds",
             EntityType::OrgEntities => "orgEntities",
             EntityType::ReserveEntries => "reserveEntries",
             EntityType::Reserves => "reserves",
+            EntityType::Settlements => "settlements",
             EntityType::Vendors => "vendors",
             EntityType::AccountVerifications => "accountVerifications",
             EntityType::Adjustments => "adjustments",
@@ -0,0 +1,560 @@
+//! Bulk export of any resource type to CSV or JSON Lines.
+//!
+//! An [`Exporter`] pages through a search one page at a time and writes each
+//! page to an [`AsyncWrite`] before fetching the next, so memory use is
+//! bounded by the page size however large the export is.
+//!
+//! # Formats
+//!
+//! - [`ExportFormat::Csv`] flattens nested objects into dotted columns
+//!   (`payment.method`), writes arrays as JSON, and shows well-known enum
+//!   codes by name (a `txns` `type` of `1` is written as `CreditCardSale`,
+//!   a payment method of `2` as `Visa`).
+//! - [`ExportFormat::JsonLines`] writes one JSON object per line with the
+//!   values exactly as Payrix returned them.
+//!
+//! Selected [`fields`](ExportRequest::fields) are dotted paths. Paths into a
+//! related resource (`payment.method`) expand that resource automatically.
+//! Without a selection, JSON Lines writes whole records. CSV needs a
+//! selection, since Payrix omits null fields and a later page may carry
+//! columns the first did not; [`discover_columns`] lists the paths found on
+//! sample records.
+//!
+//! # Resuming
+//!
+//! After every page is written and flushed, [`Exporter::cursor`] records the
+//! last `id` written, the next page number, the records and bytes written so
+//! far and the CSV columns. Persist it, and pass it to [`Exporter::resume`]
+//! with the same request to continue after a crash.
+//!
+//! Without a sort in the query, records are exported in `id` order and each
+//! page is fetched as the records after the last `id` written, so records
+//! added or removed between pages (or between runs) never cause others to be
+//! skipped or written twice. Records created during the export appear if
+//! their `id` sorts after the last one written.
+//!
+//! A query with its own sort is paged by page number, with `id` appended as a
+//! tiebreaker so the order is stable. Records added or removed while such an
+//! export runs shift the page boundaries, so rows near them can be skipped
+//! or repeated; leave the sort out when that matters.
+//!
+//! A crash part-way through writing a page leaves some of its rows in the
+//! output but not in the cursor, and resuming writes the whole page again.
+//! Truncate the output to [`ExportCursor::bytes`] before resuming to avoid
+//! duplicate rows.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use payrix::{PayrixClient, Environment, EntityType, SearchBuilder};
+//! use payrix::export::{ExportFormat, ExportRequest, Exporter};
+//!
+//! # async fn example() -> payrix::Result<()> {
+//! let client = PayrixClient::new("api-key", Environment::Test)?;
+//!
+//! let request = ExportRequest::new(EntityType::Txns)
+//!     .search(
+//!         SearchBuilder::new()
+//!             .field("merchant", "t1_mer_12345678901234567890123")
+//!             .field("type", "5"),
+//!     )
+//!     .fields(&["id", "created", "type", "status", "total", "payment.method"])
+//!     .format(ExportFormat::Csv);
+//!
+//! // Any AsyncWrite works: a file, a socket, or an in-memory buffer.
+//! let mut csv: Vec<u8> = Vec::new();
+//! let mut exporter = Exporter::new(&client, request);
+//! while exporter.write_page(&mut csv).await? {
+//!     // Save exporter.cursor() here to resume later.
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::client::PayrixClient;
+use crate::entity::EntityType;
+use crate::error::{Error, Result};
+use crate::search::SearchBuilder;
+use crate::types::{
+    ChargebackCycle, ChargebackStatusValue, DisbursementStatus, EventType, MerchantStatus,
+    PaymentMethod, TokenStatus, TransactionOrigin, TransactionStatus, TransactionType,
+};
+
+/// Largest page Payrix returns.
+const MAX_PAGE_SIZE: i32 = 100;
+
+// =============================================================================
+// Section 1: Request
+// =============================================================================
+
+/// Output format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row.
+    #[default]
+    Csv,
+    /// One JSON object per line.
+    JsonLines,
+}
+
+/// What to export and how.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportRequest {
+    /// Resource type to export.
+    pub entity_type: EntityType,
+    /// Payrix search string (see [`SearchBuilder`]).
+    pub search: Option<String>,
+    /// Dotted field paths to include, in column order. Required for CSV;
+    /// empty exports whole records as JSON Lines.
+    pub fields: Vec<String>,
+    /// Output format.
+    pub format: ExportFormat,
+    /// Records per page (1-100).
+    pub page_size: i32,
+}
+
+impl ExportRequest {
+    /// Export every record of `entity_type` as CSV.
+    pub fn new(entity_type: EntityType) -> Self {
+        Self {
+            entity_type,
+            search: None,
+            fields: Vec::new(),
+            format: ExportFormat::default(),
+            page_size: MAX_PAGE_SIZE,
+        }
+    }
+
+    /// Filter with a search.
+    pub fn search(mut self, search: SearchBuilder) -> Self {
+        self.search = Some(search.build()).filter(|s| !s.is_empty());
+        self
+    }
+
+    /// Select the fields to export, in order.
+    pub fn fields(mut self, fields: &[&str]) -> Self {
+        self.fields = fields.iter().map(|f| f.to_string()).collect();
+        self
+    }
+
+    /// Set the output format.
+    pub fn format(mut self, format: ExportFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Set the page size, clamped to 1-100.
+    pub fn page_size(mut self, page_size: i32) -> Self {
+        self.page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+        self
+    }
+
+    /// Whether pages are fetched by the last `id` written rather than by
+    /// number, which is the case unless the query sorts.
+    fn keyset(&self) -> bool {
+        !self.search.as_deref().is_some_and(|s| s.contains("[sort]"))
+    }
+
+    /// The search sent to Payrix: the query, records after `after` when
+    /// paging by `id`, and `id` as the last sort key.
+    fn page_search(&self, after: Option<&str>) -> String {
+        let mut parts: Vec<String> = self.search.iter().cloned().collect();
+        if let Some(after) = after.filter(|_| self.keyset()) {
+            parts.push(format!("id[greater]={}", after));
+        }
+        if !self.search.as_deref().is_some_and(|s| s.contains("id[sort]")) {
+            parts.push("id[sort]=asc".to_string());
+        }
+        parts.join("&")
+    }
+
+    /// `expand` parameters for related resources named in dotted fields.
+    fn expand_params(&self) -> HashMap<String, String> {
+        self.fields
+            .iter()
+            .filter_map(|f| f.split_once('.').map(|(related, _)| related))
+            .map(|related| (format!("expand[{}][]", related), String::new()))
+            .collect()
+    }
+}
+
+// =============================================================================
+// Section 2: Cursor and Exporter
+// =============================================================================
+
+/// Progress through an export, for resuming it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportCursor {
+    /// Next page to fetch (1-based). Only used as the page number when the
+    /// query has its own sort; otherwise pages follow [`last_id`](Self::last_id).
+    pub next_page: i32,
+    /// `id` of the last record written.
+    #[serde(default)]
+    pub last_id: Option<String>,
+    /// Records written so far.
+    pub records: u64,
+    /// Bytes written so far: the length the output should be truncated to
+    /// before resuming.
+    #[serde(default)]
+    pub bytes: u64,
+    /// CSV columns, fixed once the header is written.
+    pub columns: Vec<String>,
+    /// Whether every page has been written.
+    pub complete: bool,
+}
+
+impl Default for ExportCursor {
+    fn default() -> Self {
+        Self {
+            next_page: 1,
+            last_id: None,
+            records: 0,
+            bytes: 0,
+            columns: Vec::new(),
+            complete: false,
+        }
+    }
+}
+
+/// Streams an [`ExportRequest`] to a writer page by page.
+#[derive(Debug)]
+pub struct Exporter<'a> {
+    client: &'a PayrixClient,
+    request: ExportRequest,
+    cursor: ExportCursor,
+}
+
+impl<'a> Exporter<'a> {
+    /// Start a new export.
+    pub fn new(client: &'a PayrixClient, request: ExportRequest) -> Self {
+        Self::resume(client, request, ExportCursor::default())
+    }
+
+    /// Continue an export from a saved cursor. The request must be the one
+    /// the cursor came from.
+    pub fn resume(client: &'a PayrixClient, request: ExportRequest, cursor: ExportCursor) -> Self {
+        Self {
+            client,
+            request,
+            cursor,
+        }
+    }
+
+    /// Progress so far; save it to resume later.
+    pub fn cursor(&self) -> &ExportCursor {
+        &self.cursor
+    }
+
+    /// Fetch and write the next page. Returns whether more pages remain.
+    ///
+    /// The cursor only advances once the page has been written and flushed,
+    /// so a failed page is fetched again on resume.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`] for a CSV export with no fields selected, or
+    /// an error if the API call or the write fails.
+    pub async fn write_page<W: AsyncWrite + Unpin>(&mut self, writer: &mut W) -> Result<bool> {
+        if self.cursor.complete {
+            return Ok(false);
+        }
+        if self.request.format == ExportFormat::Csv && self.request.fields.is_empty() {
+            return Err(Error::Config(
+                "CSV export requires at least one selected field".to_string(),
+            ));
+        }
+
+        let search = self.request.page_search(self.cursor.last_id.as_deref());
+        let page_number = if self.request.keyset() { 1 } else { self.cursor.next_page };
+        let (records, page) = self
+            .client
+            .get_page::<Value>(
+                self.request.entity_type,
+                page_number,
+                self.request.page_size,
+                &self.request.expand_params(),
+                Some(&search),
+            )
+            .await?;
+
+        let first_page = self.cursor.next_page == 1;
+        let mut columns = self.cursor.columns.clone();
+        if first_page && columns.is_empty() {
+            columns = self.request.fields.clone();
+        }
+
+        let out = match self.request.format {
+            ExportFormat::Csv => {
+                let mut out = if first_page && !columns.is_empty() {
+                    csv_row(columns.iter().map(String::as_str))
+                } else {
+                    String::new()
+                };
+                for record in &records {
+                    out.push_str(&csv_record(self.request.entity_type, record, &columns));
+                }
+                out
+            }
+            ExportFormat::JsonLines => {
+                let mut out = String::new();
+                for record in &records {
+                    out.push_str(&json_line(record, &self.request.fields)?);
+                }
+                out
+            }
+        };
+
+        writer.write_all(out.as_bytes()).await.map_err(io_error)?;
+        writer.flush().await.map_err(io_error)?;
+
+        self.cursor.columns = columns;
+        self.cursor.records += records.len() as u64;
+        self.cursor.bytes += out.len() as u64;
+        self.cursor.next_page += 1;
+        if let Some(id) = records.last().and_then(|r| r.get("id")).and_then(Value::as_str) {
+            self.cursor.last_id = Some(id.to_string());
+        }
+        self.cursor.complete = !page.has_more || records.is_empty();
+        Ok(!self.cursor.complete)
+    }
+
+    /// Write every remaining page. Returns the total records written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an API call or write fails; [`cursor`](Self::cursor)
+    /// then points at the page that failed.
+    pub async fn write_all<W: AsyncWrite + Unpin>(&mut self, writer: &mut W) -> Result<u64> {
+        while self.write_page(writer).await? {}
+        Ok(self.cursor.records)
+    }
+}
+
+fn io_error(e: std::io::Error) -> Error {
+    Error::Io(e.to_string())
+}
+
+// =============================================================================
+// Section 3: Formatting
+// =============================================================================
+
+/// Flattened field paths of the records, in order of first appearance
+/// (keys within a record are alphabetical), for choosing CSV fields from a
+/// sample.
+pub fn discover_columns(records: &[Value]) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    for record in records {
+        let mut fields = Vec::new();
+        flatten("", record, &mut fields);
+        for (path, _) in fields {
+            if !columns.contains(&path) {
+                columns.push(path);
+            }
+        }
+    }
+    columns
+}
+
+/// Collect the leaf values of `value` under dotted paths.
+fn flatten<'v>(prefix: &str, value: &'v Value, out: &mut Vec<(String, &'v Value)>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(&path, child, out);
+            }
+        }
+        _ => out.push((prefix.to_string(), value)),
+    }
+}
+
+/// The value at a dotted path.
+fn lookup<'v>(record: &'v Value, path: &str) -> Option<&'v Value> {
+    path.split('.').try_fold(record, |value, key| value.get(key))
+}
+
+fn csv_record(entity_type: EntityType, record: &Value, columns: &[String]) -> String {
+    let cells: Vec<String> = columns
+        .iter()
+        .map(|path| {
+            let value = lookup(record, path).unwrap_or(&Value::Null);
+            enum_label(entity_type, path, value).unwrap_or_else(|| cell_text(value))
+        })
+        .collect();
+    csv_row(cells.iter().map(String::as_str))
+}
+
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn csv_row<'s>(cells: impl Iterator<Item = &'s str>) -> String {
+    let mut row = cells
+        .map(|cell| {
+            if cell.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    row.push('\n');
+    row
+}
+
+fn json_line(record: &Value, fields: &[String]) -> Result<String> {
+    let mut line = if fields.is_empty() {
+        serde_json::to_string(record)?
+    } else {
+        // Written by hand so keys keep the selection order.
+        let mut line = String::from("{");
+        for (i, path) in fields.iter().enumerate() {
+            if i > 0 {
+                line.push(',');
+            }
+            line.push_str(&serde_json::to_string(path)?);
+            line.push(':');
+            line.push_str(&serde_json::to_string(lookup(record, path).unwrap_or(&Value::Null))?);
+        }
+        line.push('}');
+        line
+    };
+    line.push('\n');
+    Ok(line)
+}
+
+/// Name of a well-known enum code, or `None` to write the raw value.
+fn enum_label(entity_type: EntityType, path: &str, value: &Value) -> Option<String> {
+    if value.is_null() {
+        return None;
+    }
+    match (entity_type, path) {
+        (_, "payment.method") | (EntityType::Tokens, "method") => {
+            parse::<PaymentMethod>(value).map(|m| m.display_name().to_string())
+        }
+        (EntityType::Txns, "type") => debug_name::<TransactionType>(value),
+        (EntityType::Txns, "status") => debug_name::<TransactionStatus>(value),
+        (EntityType::Txns, "origin") => debug_name::<TransactionOrigin>(value),
+        (EntityType::Chargebacks, "cycle") => debug_name::<ChargebackCycle>(value),
+        (EntityType::Chargebacks, "status") => debug_name::<ChargebackStatusValue>(value),
+        (EntityType::Disbursements, "status") => debug_name::<DisbursementStatus>(value),
+        (EntityType::Tokens, "status") => debug_name::<TokenStatus>(value),
+        (EntityType::Merchants, "status") => debug_name::<MerchantStatus>(value),
+        (EntityType::Entries | EntityType::PendingEntries, "event") => debug_name::<EventType>(value),
+        _ => None,
+    }
+}
+
+fn parse<T: DeserializeOwned>(value: &Value) -> Option<T> {
+    // Payrix sometimes returns numeric codes as strings.
+    serde_json::from_value(value.clone()).ok().or_else(|| {
+        let number: i64 = value.as_str()?.parse().ok()?;
+        serde_json::from_value(Value::from(number)).ok()
+    })
+}
+
+fn debug_name<T: DeserializeOwned + Debug>(value: &Value) -> Option<String> {
+    parse::<T>(value).map(|v| format!("{:?}", v))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn txn() -> Value {
+        json!({
+            "id": "t1_txn_00000000000000000000001",
+            "type": 5,
+            "status": "3",
+            "total": 2500,
+            "description": "Refund, \"damaged\"",
+            "payment": { "method": 2, "number": "1111" },
+            "fees": [],
+            "batch": null,
+        })
+    }
+
+    #[test]
+    fn test_csv_flattening_and_labels() {
+        let columns = discover_columns(&[txn()]);
+        assert_eq!(
+            columns,
+            vec!["batch", "description", "fees", "id", "payment.method", "payment.number", "status", "total", "type"]
+        );
+
+        let row = csv_record(EntityType::Txns, &txn(), &columns);
+        assert_eq!(
+            row,
+            ",\"Refund, \"\"damaged\"\"\",[],t1_txn_00000000000000000000001,Visa,1111,Captured,2500,CreditCardRefund\n"
+        );
+
+        // Other entity types keep raw codes.
+        let row = csv_record(EntityType::Refunds, &txn(), &["type".to_string()]);
+        assert_eq!(row, "5\n");
+    }
+
+    #[test]
+    fn test_json_lines_selection() {
+        let fields = vec!["id".to_string(), "payment.method".to_string(), "missing".to_string()];
+        assert_eq!(
+            json_line(&txn(), &fields).unwrap(),
+            "{\"id\":\"t1_txn_00000000000000000000001\",\"payment.method\":2,\"missing\":null}\n"
+        );
+    }
+
+    #[test]
+    fn test_request_search_and_expansion() {
+        let request = ExportRequest::new(EntityType::Txns)
+            .search(SearchBuilder::new().field("merchant", "t1_mer_00000000000000000000001"))
+            .fields(&["id", "payment.method", "payment.number"])
+            .page_size(500);
+        assert_eq!(request.page_size, 100);
+        assert_eq!(
+            request.page_search(None),
+            "merchant[equals]=t1_mer_00000000000000000000001&id[sort]=asc"
+        );
+        assert_eq!(
+            request.page_search(Some("t1_txn_00000000000000000000009")),
+            "merchant[equals]=t1_mer_00000000000000000000001&id[greater]=t1_txn_00000000000000000000009&id[sort]=asc"
+        );
+        assert_eq!(request.expand_params().len(), 1);
+        assert!(request.expand_params().contains_key("expand[payment][]"));
+
+        let sorted = ExportRequest::new(EntityType::Txns).search(SearchBuilder::new().raw("created[sort]=desc"));
+        assert!(!sorted.keyset());
+        assert_eq!(
+            sorted.page_search(Some("t1_txn_00000000000000000000009")),
+            "created[sort]=desc&id[sort]=asc"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_csv_requires_fields() {
+        let client = PayrixClient::new("api-key", crate::Environment::Test).unwrap();
+        let mut out = Vec::new();
+
+        let mut exporter = Exporter::new(&client, ExportRequest::new(EntityType::Txns));
+        let err = exporter.write_page(&mut out).await.unwrap_err();
+        assert!(matches!(err, Error::Config(_)));
+        assert!(out.is_empty());
+        assert_eq!(exporter.cursor(), &ExportCursor::default());
+    }
+}
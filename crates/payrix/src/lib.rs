@@ -101,6 +101,11 @@
 //! # }
 //! ```
 //!
+//! ## Bulk Export
+//!
+//! The [`export`] module streams any resource type to CSV or JSON Lines
+//! through pagination, with resumable cursors for large exports.
+//!
 //! ## Feature Flags
 //!
 //! - `sqlx` - Enable `sqlx::FromRow` derives for database storage
@@ -115,6 +120,7 @@
 mod client;
 pub mod entity;
 mod error;
+pub mod export;
 mod rate_limiter;
 pub mod search;
 pub mod types;
@@ -1430,6 +1430,50 @@ pub enum TxnResultCode {
     ThreeDsAuthPassedLiabilityShifted = 32,
 }
 
+/// A processor, fraud or verification result recorded against a Transaction.
+///
+/// Returned by expanding `txnResults` on a transaction.
+///
+/// **OpenAPI schema:** `txnResultsResponse`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxnResult {
+    /// The ID of this resource.
+    ///
+    /// **OpenAPI type:** string
+    pub id: PayrixId,
+
+    /// The date and time at which this resource was created.
+    ///
+    /// **OpenAPI type:** string
+    #[serde(default)]
+    pub created: Option<String>,
+
+    /// The Transaction this result belongs to.
+    ///
+    /// **OpenAPI type:** string (ref: txnResultsModelTxn)
+    #[serde(default)]
+    pub txn: Option<PayrixId>,
+
+    /// The kind of check that produced this result.
+    ///
+    /// **OpenAPI type:** integer enum (txnResultType)
+    #[serde(default, rename = "type")]
+    pub result_type: Option<TxnResultType>,
+
+    /// The outcome of the check.
+    ///
+    /// **OpenAPI type:** integer enum (txnResultCode)
+    #[serde(default)]
+    pub code: Option<TxnResultCode>,
+
+    /// The processor's message. Not intended for customers.
+    ///
+    /// **OpenAPI type:** string
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
 // ==================== Request Types ====================
 
 // CreateTransaction is generated by the PayrixEntity derive macro.
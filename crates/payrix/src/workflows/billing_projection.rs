@@ -0,0 +1,556 @@
+//! Offline billing schedule projection for subscriptions.
+//!
+//! [`next_payment`](super::subscription_management::next_payment) predicts a
+//! single date from the last payment. This module instead projects the full
+//! billing calendar of a [`Subscription`] on its [`Plan`] without touching the
+//! network, so it can be shown to customers and unit tested directly.
+//!
+//! # Rules
+//!
+//! - Charges fall on the subscription `start` date and every `schedule_factor`
+//!   periods after it. Each date is computed from `start`, not from the
+//!   previous charge, so month-end dates clamp without drifting: a subscription
+//!   starting on January 31 bills on February 29 (or 28), March 31, April 30.
+//! - No charge is projected after the subscription `finish` date.
+//! - Cancelled (`inactive`) subscriptions, and subscriptions that have already
+//!   reached `max_failures` consecutive failures, project no charges.
+//! - Paused (`frozen`) subscriptions project no charges unless
+//!   [`ProjectionOptions::resume_on`] is given. Planned pauses can be added as
+//!   [`PauseWindow`]s; charges that fall inside a pause are skipped, not
+//!   deferred, matching how Payrix treats frozen subscriptions.
+//! - Each charge is the plan `amount` plus the subscription `tax`, both in cents.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use chrono::NaiveDate;
+//! use payrix::{PayrixClient, Environment, EntityType, Plan, Subscription};
+//! use payrix::workflows::billing_projection::{project_billing_schedule, ProjectionOptions};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = PayrixClient::new("api-key", Environment::Test)?;
+//! let sub: Subscription = client
+//!     .get_one(EntityType::Subscriptions, "t1_sbn_12345678901234567890123")
+//!     .await?
+//!     .unwrap();
+//! let plan: Plan = client
+//!     .get_one(EntityType::Plans, sub.plan.as_ref().unwrap().as_str())
+//!     .await?
+//!     .unwrap();
+//!
+//! let today = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+//! let horizon = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+//! let calendar = project_billing_schedule(&sub, &plan, &ProjectionOptions::new(today, horizon))?;
+//!
+//! for charge in &calendar.charges {
+//!     println!("{}: ${:.2}", charge.date, charge.total as f64 / 100.0);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use chrono::{Duration, Months, NaiveDate};
+
+use crate::search::parse_payrix_date;
+use crate::types::{Plan, PlanSchedule, Subscription};
+
+use super::subscription_management::{SubscriptionError, SubscriptionResult};
+
+// ============================================================================
+// Projection Types
+// ============================================================================
+
+/// A date range during which no charges are made.
+///
+/// Both ends are inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PauseWindow {
+    /// First paused day.
+    pub start: NaiveDate,
+
+    /// Last paused day.
+    pub end: NaiveDate,
+}
+
+impl PauseWindow {
+    /// Create a pause window.
+    pub fn new(start: NaiveDate, end: NaiveDate) -> Self {
+        Self { start, end }
+    }
+
+    /// Whether a date falls inside this window.
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        date >= self.start && date <= self.end
+    }
+}
+
+/// Options controlling a billing projection.
+#[derive(Debug, Clone)]
+pub struct ProjectionOptions {
+    /// Project charges on or after this date (usually today).
+    pub from: NaiveDate,
+
+    /// Project charges on or before this date.
+    pub horizon: NaiveDate,
+
+    /// Planned pauses; charges inside them are skipped.
+    pub pauses: Vec<PauseWindow>,
+
+    /// For a currently frozen subscription, the date it will be resumed.
+    ///
+    /// Without this a frozen subscription projects no charges.
+    pub resume_on: Option<NaiveDate>,
+}
+
+impl ProjectionOptions {
+    /// Project charges between `from` and `horizon`, inclusive.
+    pub fn new(from: NaiveDate, horizon: NaiveDate) -> Self {
+        Self {
+            from,
+            horizon,
+            pauses: Vec::new(),
+            resume_on: None,
+        }
+    }
+
+    /// Add a planned pause.
+    pub fn with_pause(mut self, pause: PauseWindow) -> Self {
+        self.pauses.push(pause);
+        self
+    }
+
+    /// Set the resume date for a frozen subscription.
+    pub fn with_resume_on(mut self, date: NaiveDate) -> Self {
+        self.resume_on = Some(date);
+        self
+    }
+}
+
+/// A single projected charge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProjectedCharge {
+    /// Billing cycle number, counting the charge on the start date as 1.
+    pub cycle: u32,
+
+    /// Charge date.
+    pub date: NaiveDate,
+
+    /// Plan amount in cents.
+    pub amount: i64,
+
+    /// Tax in cents.
+    pub tax: i64,
+
+    /// Amount plus tax in cents.
+    pub total: i64,
+}
+
+impl ProjectedCharge {
+    /// Get the total as dollars.
+    pub fn total_dollars(&self) -> f64 {
+        self.total as f64 / 100.0
+    }
+}
+
+/// Why a projection stops where it does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectionEnd {
+    /// The projection reached the requested horizon.
+    Horizon,
+
+    /// The subscription finishes on this date before the horizon.
+    Finished(NaiveDate),
+
+    /// The subscription is cancelled.
+    Cancelled,
+
+    /// The subscription is frozen with no known resume date.
+    Paused,
+
+    /// The subscription has reached its maximum consecutive failures.
+    FailureLimit,
+}
+
+/// Projected billing calendar for a subscription.
+#[derive(Debug, Clone)]
+pub struct BillingCalendar {
+    /// Subscription ID.
+    pub subscription_id: String,
+
+    /// Plan ID.
+    pub plan_id: String,
+
+    /// Projected charges in date order.
+    pub charges: Vec<ProjectedCharge>,
+
+    /// Why the projection stops.
+    pub end: ProjectionEnd,
+
+    /// Failures allowed before Payrix inactivates the subscription, if limited.
+    pub failures_remaining: Option<i32>,
+}
+
+impl BillingCalendar {
+    /// The first projected charge.
+    pub fn next_charge(&self) -> Option<&ProjectedCharge> {
+        self.charges.first()
+    }
+
+    /// Sum of all projected charges in cents, including tax.
+    pub fn total(&self) -> i64 {
+        self.charges.iter().map(|c| c.total).sum()
+    }
+
+    /// Sum of projected tax in cents.
+    pub fn total_tax(&self) -> i64 {
+        self.charges.iter().map(|c| c.tax).sum()
+    }
+}
+
+// ============================================================================
+// Projection
+// ============================================================================
+
+/// Compute the date of a billing cycle.
+///
+/// `cycle` 0 is the anchor date itself. Monthly and annual dates are computed
+/// from the anchor so that month-end dates clamp without drifting.
+/// Returns `None` if the date is out of range.
+pub fn billing_date(
+    anchor: NaiveDate,
+    schedule: PlanSchedule,
+    factor: u32,
+    cycle: u32,
+) -> Option<NaiveDate> {
+    let periods = factor.checked_mul(cycle)?;
+    match schedule {
+        PlanSchedule::Daily => anchor.checked_add_signed(Duration::days(periods as i64)),
+        PlanSchedule::Weekly => anchor.checked_add_signed(Duration::weeks(periods as i64)),
+        PlanSchedule::Monthly => anchor.checked_add_months(Months::new(periods)),
+        PlanSchedule::Annually => anchor.checked_add_months(Months::new(periods.checked_mul(12)?)),
+    }
+}
+
+/// Project the billing calendar of a subscription.
+///
+/// This is a pure function: it makes no API calls and depends only on its
+/// arguments. See the [module documentation](self) for the rules applied.
+///
+/// # Errors
+///
+/// - [`SubscriptionError::InvalidDate`] if the subscription has no valid
+///   `start` date, or `finish` is not a valid date
+/// - [`SubscriptionError::CalculationError`] if the plan has no amount or a
+///   non-positive `schedule_factor`
+pub fn project_billing_schedule(
+    subscription: &Subscription,
+    plan: &Plan,
+    options: &ProjectionOptions,
+) -> SubscriptionResult<BillingCalendar> {
+    let start = subscription
+        .start
+        .and_then(|d| parse_payrix_date(&d.to_string()))
+        .ok_or_else(|| {
+            SubscriptionError::InvalidDate(format!(
+                "Subscription {} has no valid start date",
+                subscription.id
+            ))
+        })?;
+
+    let finish = match subscription.finish {
+        Some(d) => Some(parse_payrix_date(&d.to_string()).ok_or_else(|| {
+            SubscriptionError::InvalidDate(format!("Invalid finish date: {}", d))
+        })?),
+        None => None,
+    };
+
+    let amount = plan
+        .amount
+        .ok_or_else(|| SubscriptionError::CalculationError("Plan has no amount".to_string()))?;
+    let tax = subscription.tax.unwrap_or(0);
+
+    let factor = plan.schedule_factor.unwrap_or(1);
+    if factor <= 0 {
+        return Err(SubscriptionError::CalculationError(format!(
+            "Invalid schedule factor: {}",
+            factor
+        )));
+    }
+    let schedule = plan.schedule.unwrap_or(PlanSchedule::Monthly);
+
+    // The subscription's own limit takes precedence over the plan default
+    let max_failures = subscription
+        .max_failures
+        .or(plan.max_failures)
+        .filter(|max| *max > 0);
+    let failures_remaining =
+        max_failures.map(|max| (max - subscription.failures.unwrap_or(0)).max(0));
+
+    let mut calendar = BillingCalendar {
+        subscription_id: subscription.id.to_string(),
+        plan_id: plan.id.to_string(),
+        charges: Vec::new(),
+        end: ProjectionEnd::Horizon,
+        failures_remaining,
+    };
+
+    if subscription.inactive {
+        calendar.end = ProjectionEnd::Cancelled;
+        return Ok(calendar);
+    }
+    if failures_remaining == Some(0) {
+        calendar.end = ProjectionEnd::FailureLimit;
+        return Ok(calendar);
+    }
+
+    let resume_on = if subscription.frozen {
+        match options.resume_on {
+            Some(date) => Some(date),
+            None => {
+                calendar.end = ProjectionEnd::Paused;
+                return Ok(calendar);
+            }
+        }
+    } else {
+        None
+    };
+
+    let last_day = match finish {
+        Some(finish) if finish < options.horizon => {
+            calendar.end = ProjectionEnd::Finished(finish);
+            finish
+        }
+        _ => options.horizon,
+    };
+
+    let mut cycle = 0;
+    while let Some(date) = billing_date(start, schedule, factor as u32, cycle) {
+        if date > last_day {
+            break;
+        }
+        cycle += 1;
+
+        if date < options.from
+            || resume_on.is_some_and(|resume| date < resume)
+            || options.pauses.iter().any(|p| p.contains(date))
+        {
+            continue;
+        }
+
+        calendar.charges.push(ProjectedCharge {
+            cycle,
+            date,
+            amount,
+            tax,
+            total: amount + tax,
+        });
+    }
+
+    Ok(calendar)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn make_plan(schedule: PlanSchedule, factor: i32, amount: i64) -> Plan {
+        serde_json::from_value(serde_json::json!({
+            "id": "t1_pln_12345678901234567890123",
+            "schedule": schedule as i32,
+            "scheduleFactor": factor,
+            "amount": amount,
+        }))
+        .unwrap()
+    }
+
+    fn make_subscription(start: i32, finish: Option<i32>) -> Subscription {
+        serde_json::from_value(serde_json::json!({
+            "id": "t1_sbn_12345678901234567890123",
+            "plan": "t1_pln_12345678901234567890123",
+            "start": start,
+            "finish": finish,
+            "tax": 100,
+        }))
+        .unwrap()
+    }
+
+    fn dates(calendar: &BillingCalendar) -> Vec<NaiveDate> {
+        calendar.charges.iter().map(|c| c.date).collect()
+    }
+
+    #[test]
+    fn monthly_month_end_clamping() {
+        let sub = make_subscription(20240131, None);
+        let plan = make_plan(PlanSchedule::Monthly, 1, 1000);
+        let options = ProjectionOptions::new(date(2024, 1, 1), date(2024, 5, 31));
+
+        let calendar = project_billing_schedule(&sub, &plan, &options).unwrap();
+
+        assert_eq!(
+            dates(&calendar),
+            vec![
+                date(2024, 1, 31),
+                date(2024, 2, 29),
+                date(2024, 3, 31),
+                date(2024, 4, 30),
+                date(2024, 5, 31),
+            ]
+        );
+        assert_eq!(calendar.end, ProjectionEnd::Horizon);
+    }
+
+    #[test]
+    fn amounts_include_tax() {
+        let sub = make_subscription(20240101, None);
+        let plan = make_plan(PlanSchedule::Monthly, 1, 1000);
+        let options = ProjectionOptions::new(date(2024, 1, 1), date(2024, 3, 1));
+
+        let calendar = project_billing_schedule(&sub, &plan, &options).unwrap();
+
+        assert_eq!(calendar.charges.len(), 3);
+        assert_eq!(calendar.charges[0].amount, 1000);
+        assert_eq!(calendar.charges[0].tax, 100);
+        assert_eq!(calendar.charges[0].total, 1100);
+        assert_eq!(calendar.total(), 3300);
+        assert_eq!(calendar.total_tax(), 300);
+    }
+
+    #[test]
+    fn schedule_factor_is_applied() {
+        let sub = make_subscription(20240101, None);
+        let plan = make_plan(PlanSchedule::Weekly, 2, 500);
+        let options = ProjectionOptions::new(date(2024, 1, 1), date(2024, 2, 1));
+
+        let calendar = project_billing_schedule(&sub, &plan, &options).unwrap();
+
+        assert_eq!(
+            dates(&calendar),
+            vec![date(2024, 1, 1), date(2024, 1, 15), date(2024, 1, 29)]
+        );
+    }
+
+    #[test]
+    fn annual_leap_day_clamps() {
+        let sub = make_subscription(20240229, None);
+        let plan = make_plan(PlanSchedule::Annually, 1, 12000);
+        let options = ProjectionOptions::new(date(2024, 3, 1), date(2028, 12, 31));
+
+        let calendar = project_billing_schedule(&sub, &plan, &options).unwrap();
+
+        assert_eq!(
+            dates(&calendar),
+            vec![
+                date(2025, 2, 28),
+                date(2026, 2, 28),
+                date(2027, 2, 28),
+                date(2028, 2, 29),
+            ]
+        );
+        // Cycle numbers count from the start date
+        assert_eq!(calendar.charges[0].cycle, 2);
+    }
+
+    #[test]
+    fn finish_date_bounds_projection() {
+        let sub = make_subscription(20240115, Some(20240410));
+        let plan = make_plan(PlanSchedule::Monthly, 1, 1000);
+        let options = ProjectionOptions::new(date(2024, 2, 1), date(2024, 12, 31));
+
+        let calendar = project_billing_schedule(&sub, &plan, &options).unwrap();
+
+        assert_eq!(
+            dates(&calendar),
+            vec![date(2024, 2, 15), date(2024, 3, 15)]
+        );
+        assert_eq!(calendar.end, ProjectionEnd::Finished(date(2024, 4, 10)));
+    }
+
+    #[test]
+    fn pause_window_skips_charges() {
+        let sub = make_subscription(20240101, None);
+        let plan = make_plan(PlanSchedule::Monthly, 1, 1000);
+        let options = ProjectionOptions::new(date(2024, 1, 1), date(2024, 5, 1))
+            .with_pause(PauseWindow::new(date(2024, 2, 1), date(2024, 3, 15)));
+
+        let calendar = project_billing_schedule(&sub, &plan, &options).unwrap();
+
+        assert_eq!(
+            dates(&calendar),
+            vec![date(2024, 1, 1), date(2024, 4, 1), date(2024, 5, 1)]
+        );
+    }
+
+    #[test]
+    fn frozen_subscription() {
+        let mut sub = make_subscription(20240101, None);
+        sub.frozen = true;
+        let plan = make_plan(PlanSchedule::Monthly, 1, 1000);
+        let options = ProjectionOptions::new(date(2024, 1, 1), date(2024, 6, 1));
+
+        let calendar = project_billing_schedule(&sub, &plan, &options).unwrap();
+        assert!(calendar.charges.is_empty());
+        assert_eq!(calendar.end, ProjectionEnd::Paused);
+
+        let calendar =
+            project_billing_schedule(&sub, &plan, &options.with_resume_on(date(2024, 4, 15)))
+                .unwrap();
+        assert_eq!(dates(&calendar), vec![date(2024, 5, 1), date(2024, 6, 1)]);
+    }
+
+    #[test]
+    fn cancelled_subscription() {
+        let mut sub = make_subscription(20240101, None);
+        sub.inactive = true;
+        let plan = make_plan(PlanSchedule::Monthly, 1, 1000);
+        let options = ProjectionOptions::new(date(2024, 1, 1), date(2024, 6, 1));
+
+        let calendar = project_billing_schedule(&sub, &plan, &options).unwrap();
+        assert!(calendar.charges.is_empty());
+        assert_eq!(calendar.end, ProjectionEnd::Cancelled);
+    }
+
+    #[test]
+    fn failure_limit() {
+        let mut sub = make_subscription(20240101, None);
+        sub.failures = Some(2);
+        sub.max_failures = Some(3);
+        let plan = make_plan(PlanSchedule::Monthly, 1, 1000);
+        let options = ProjectionOptions::new(date(2024, 1, 1), date(2024, 2, 1));
+
+        let calendar = project_billing_schedule(&sub, &plan, &options).unwrap();
+        assert_eq!(calendar.failures_remaining, Some(1));
+        assert_eq!(calendar.charges.len(), 2);
+
+        sub.failures = Some(3);
+        let calendar = project_billing_schedule(&sub, &plan, &options).unwrap();
+        assert!(calendar.charges.is_empty());
+        assert_eq!(calendar.end, ProjectionEnd::FailureLimit);
+    }
+
+    #[test]
+    fn invalid_inputs() {
+        let plan = make_plan(PlanSchedule::Monthly, 0, 1000);
+        let sub = make_subscription(20240101, None);
+        let options = ProjectionOptions::new(date(2024, 1, 1), date(2024, 2, 1));
+        assert!(matches!(
+            project_billing_schedule(&sub, &plan, &options),
+            Err(SubscriptionError::CalculationError(_))
+        ));
+
+        let plan = make_plan(PlanSchedule::Monthly, 1, 1000);
+        let mut sub = make_subscription(20240101, None);
+        sub.start = None;
+        assert!(matches!(
+            project_billing_schedule(&sub, &plan, &options),
+            Err(SubscriptionError::InvalidDate(_))
+        ));
+    }
+}
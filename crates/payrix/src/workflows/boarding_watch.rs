@@ -0,0 +1,569 @@
+//! Watch merchants until boarding finishes.
+//!
+//! After [`onboard_merchant()`](super::merchant_onboarding::onboard_merchant) a
+//! merchant is usually `Submitted` or `Pending` while automated underwriting
+//! runs. [`wait_for_boarding()`] polls the merchant with exponential backoff
+//! until its status leaves those states or a timeout expires, and returns
+//! every status it observed along the way.
+//!
+//! With the `webhooks` feature, [`wait_for_boarding_with_webhooks()`] also
+//! listens for `merchant.boarded`, `merchant.failed` and `merchant.held`
+//! events and finishes as soon as one arrives for the merchant, instead of
+//! waiting for the next poll.
+//!
+//! To follow many merchants at once, [`watch_boarding()`] runs one watcher per
+//! merchant in the background and streams [`BoardingUpdate`]s over a channel.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use payrix::{PayrixClient, Environment};
+//! use payrix::workflows::boarding_watch::{wait_for_boarding, WaitForBoardingOptions};
+//!
+//! # async fn example() -> payrix::Result<()> {
+//! let client = PayrixClient::new("api-key", Environment::Test)?;
+//!
+//! let opts = WaitForBoardingOptions::default().with_timeout(Duration::from_secs(120));
+//! let result = wait_for_boarding(&client, "t1_mer_12345678901234567890123", &opts).await?;
+//!
+//! for transition in &result.history {
+//!     println!("{}: {:?} -> {}", transition.observed_at, transition.from, transition.to);
+//! }
+//! if result.timed_out {
+//!     println!("Still {} after two minutes", result.status);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+
+use crate::client::PayrixClient;
+use crate::error::{Error, Result};
+
+use super::merchant_onboarding::{check_boarding_status, BoardingStatus};
+
+#[cfg(feature = "webhooks")]
+use crate::webhooks::WebhookEvent;
+
+// ============================================================================
+// Options
+// ============================================================================
+
+/// Polling and timeout settings for [`wait_for_boarding()`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaitForBoardingOptions {
+    /// Delay before the second poll. The first poll happens immediately.
+    ///
+    /// Defaults to 2 seconds.
+    pub initial_interval: Duration,
+
+    /// Upper bound on the delay between polls.
+    ///
+    /// Defaults to 30 seconds.
+    pub max_interval: Duration,
+
+    /// Factor the delay is multiplied by after each poll.
+    ///
+    /// Defaults to 2.0.
+    pub backoff_factor: f64,
+
+    /// Total time to wait before giving up.
+    ///
+    /// Defaults to 10 minutes.
+    pub timeout: Duration,
+}
+
+impl Default for WaitForBoardingOptions {
+    fn default() -> Self {
+        Self {
+            initial_interval: Duration::from_secs(2),
+            max_interval: Duration::from_secs(30),
+            backoff_factor: 2.0,
+            timeout: Duration::from_secs(600),
+        }
+    }
+}
+
+impl WaitForBoardingOptions {
+    /// Set the delay before the second poll.
+    pub fn with_initial_interval(mut self, interval: Duration) -> Self {
+        self.initial_interval = interval;
+        self
+    }
+
+    /// Set the maximum delay between polls.
+    pub fn with_max_interval(mut self, interval: Duration) -> Self {
+        self.max_interval = interval;
+        self
+    }
+
+    /// Set the backoff factor. Values below 1.0 are treated as 1.0 (fixed interval).
+    pub fn with_backoff_factor(mut self, factor: f64) -> Self {
+        self.backoff_factor = factor;
+        self
+    }
+
+    /// Set the total timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The delay to use after a poll that waited `current`.
+    fn next_interval(&self, current: Duration) -> Duration {
+        current
+            .mul_f64(self.backoff_factor.max(1.0))
+            .min(self.max_interval)
+    }
+}
+
+// ============================================================================
+// Results
+// ============================================================================
+
+/// How a status observation was made.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransitionSource {
+    /// Read by polling the merchant.
+    Poll,
+
+    /// Read after a webhook event for the merchant arrived.
+    Webhook {
+        /// The webhook event type, e.g. `merchant.boarded`.
+        event_type: String,
+    },
+}
+
+/// A change in a merchant's boarding status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardingTransition {
+    /// Previous status, or `None` for the first observation.
+    pub from: Option<BoardingStatus>,
+
+    /// New status.
+    pub to: BoardingStatus,
+
+    /// When the new status was observed.
+    pub observed_at: DateTime<Utc>,
+
+    /// How the new status was observed.
+    pub source: TransitionSource,
+}
+
+/// Outcome of watching one merchant.
+#[derive(Debug, Clone)]
+pub struct BoardingWatchResult {
+    /// The merchant that was watched.
+    pub merchant_id: String,
+
+    /// The last observed status.
+    pub status: BoardingStatus,
+
+    /// Every status change observed, oldest first.
+    ///
+    /// The first entry is the initial status, with `from: None`.
+    pub history: Vec<BoardingTransition>,
+
+    /// Number of times the merchant was fetched.
+    pub polls: u32,
+
+    /// Whether the timeout expired while the merchant was still in progress.
+    pub timed_out: bool,
+}
+
+impl BoardingWatchResult {
+    /// Whether the merchant finished boarding successfully.
+    pub fn is_boarded(&self) -> bool {
+        self.status == BoardingStatus::Boarded
+    }
+}
+
+/// Whether boarding is still in progress for a status.
+///
+/// Only `Submitted` and `Pending` are in progress; every other status needs
+/// no further waiting (either it is final or it needs action from someone).
+pub fn is_boarding_in_progress(status: BoardingStatus) -> bool {
+    matches!(status, BoardingStatus::Submitted | BoardingStatus::Pending)
+}
+
+// ============================================================================
+// Single Merchant
+// ============================================================================
+
+/// Poll a merchant until boarding leaves `Submitted`/`Pending` or the timeout expires.
+///
+/// The merchant is fetched immediately, then after `initial_interval`, with
+/// the delay growing by `backoff_factor` up to `max_interval`.
+///
+/// Reaching the timeout is not an error: the result has `timed_out` set and
+/// the last observed status.
+///
+/// # Errors
+///
+/// Returns an error if the merchant does not exist or a poll fails.
+pub async fn wait_for_boarding(
+    client: &PayrixClient,
+    merchant_id: &str,
+    opts: &WaitForBoardingOptions,
+) -> Result<BoardingWatchResult> {
+    watch(client, merchant_id, opts, None::<&mut mpsc::Receiver<NoSignal>>, None).await
+}
+
+/// Like [`wait_for_boarding()`], but also finishes when a boarding webhook arrives.
+///
+/// Events on `events` for this merchant with type `merchant.boarded`,
+/// `merchant.failed` or `merchant.held` trigger an immediate fetch of the
+/// merchant, and the watch ends with the fetched status. Polling continues
+/// between events, so a missed webhook only delays the result. If the
+/// channel closes, the watch falls back to polling alone.
+///
+/// Every event read from `events` is consumed, including events for other
+/// resources; give the watcher its own receiver (for example by forwarding
+/// merchant events from the [`WebhookServer`](crate::webhooks::WebhookServer)
+/// channel) if other code needs them.
+#[cfg(feature = "webhooks")]
+pub async fn wait_for_boarding_with_webhooks(
+    client: &PayrixClient,
+    merchant_id: &str,
+    opts: &WaitForBoardingOptions,
+    events: &mut mpsc::Receiver<WebhookEvent>,
+) -> Result<BoardingWatchResult> {
+    watch(client, merchant_id, opts, Some(events), None).await
+}
+
+/// An early-finish signal that can end a watch before the next poll.
+trait BoardingSignal {
+    /// The event type if this signal ends boarding for `merchant_id`.
+    fn boarding_event(&self, merchant_id: &str) -> Option<&str>;
+}
+
+/// Signal type for watches without an early-finish channel.
+enum NoSignal {}
+
+impl BoardingSignal for NoSignal {
+    fn boarding_event(&self, _merchant_id: &str) -> Option<&str> {
+        match *self {}
+    }
+}
+
+#[cfg(feature = "webhooks")]
+impl BoardingSignal for WebhookEvent {
+    fn boarding_event(&self, merchant_id: &str) -> Option<&str> {
+        let is_final = matches!(
+            self.event_type.as_str(),
+            "merchant.boarded" | "merchant.failed" | "merchant.held"
+        );
+        (is_final && self.resource_id == merchant_id).then_some(self.event_type.as_str())
+    }
+}
+
+/// Shared watch loop.
+///
+/// `updates` receives every transition as it is observed (used by
+/// [`watch_boarding()`]).
+async fn watch<S: BoardingSignal>(
+    client: &PayrixClient,
+    merchant_id: &str,
+    opts: &WaitForBoardingOptions,
+    mut signals: Option<&mut mpsc::Receiver<S>>,
+    updates: Option<&mpsc::Sender<BoardingUpdate>>,
+) -> Result<BoardingWatchResult> {
+    let deadline = Instant::now() + opts.timeout;
+    let mut interval = opts.initial_interval;
+    let mut result = BoardingWatchResult {
+        merchant_id: merchant_id.to_string(),
+        status: BoardingStatus::NotReady,
+        history: Vec::new(),
+        polls: 0,
+        timed_out: false,
+    };
+    let mut source = TransitionSource::Poll;
+
+    loop {
+        let status = check_boarding_status(client, merchant_id).await?.status;
+        result.polls += 1;
+
+        let from = result.history.last().map(|t| t.to);
+        if from != Some(status) {
+            let transition = BoardingTransition {
+                from,
+                to: status,
+                observed_at: Utc::now(),
+                source: source.clone(),
+            };
+            if let Some(tx) = updates {
+                // The receiver may have been dropped; the watch still completes.
+                let _ = tx
+                    .send(BoardingUpdate::Transition {
+                        merchant_id: merchant_id.to_string(),
+                        transition: transition.clone(),
+                    })
+                    .await;
+            }
+            result.history.push(transition);
+        }
+        result.status = status;
+
+        if !is_boarding_in_progress(status) || source != TransitionSource::Poll {
+            return Ok(result);
+        }
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            result.timed_out = true;
+            return Ok(result);
+        }
+        let delay = interval.min(remaining);
+        interval = opts.next_interval(interval);
+
+        source = match signals.as_deref_mut() {
+            Some(rx) => match wait_for_signal(rx, merchant_id, delay).await {
+                SignalWait::Event(event_type) => TransitionSource::Webhook { event_type },
+                SignalWait::Elapsed => TransitionSource::Poll,
+                SignalWait::Closed(left) => {
+                    signals = None;
+                    tokio::time::sleep(left).await;
+                    TransitionSource::Poll
+                }
+            },
+            None => {
+                tokio::time::sleep(delay).await;
+                TransitionSource::Poll
+            }
+        };
+    }
+}
+
+enum SignalWait {
+    /// A matching event arrived.
+    Event(String),
+    /// The delay passed without a matching event.
+    Elapsed,
+    /// The channel closed with this much of the delay left.
+    Closed(Duration),
+}
+
+/// Wait up to `delay` for a signal that ends boarding for `merchant_id`.
+async fn wait_for_signal<S: BoardingSignal>(
+    rx: &mut mpsc::Receiver<S>,
+    merchant_id: &str,
+    delay: Duration,
+) -> SignalWait {
+    let until = Instant::now() + delay;
+    loop {
+        match tokio::time::timeout_at(until, rx.recv()).await {
+            Err(_) => return SignalWait::Elapsed,
+            Ok(None) => return SignalWait::Closed(until.saturating_duration_since(Instant::now())),
+            Ok(Some(signal)) => {
+                if let Some(event_type) = signal.boarding_event(merchant_id) {
+                    return SignalWait::Event(event_type.to_string());
+                }
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Many Merchants
+// ============================================================================
+
+/// Progress reported by [`watch_boarding()`].
+#[derive(Debug)]
+pub enum BoardingUpdate {
+    /// A merchant's status changed (including its first observed status).
+    Transition {
+        /// The merchant.
+        merchant_id: String,
+        /// The observed change.
+        transition: BoardingTransition,
+    },
+
+    /// Watching a merchant finished.
+    Finished(BoardingWatchResult),
+
+    /// Watching a merchant failed.
+    Failed {
+        /// The merchant.
+        merchant_id: String,
+        /// Why the watch failed.
+        error: Error,
+    },
+}
+
+/// Watch many merchants concurrently, streaming updates over a channel.
+///
+/// Spawns one watcher per merchant using the same options as
+/// [`wait_for_boarding()`]. Each merchant produces zero or more
+/// [`BoardingUpdate::Transition`]s followed by exactly one
+/// [`BoardingUpdate::Finished`] or [`BoardingUpdate::Failed`]. The channel
+/// closes once every merchant is done. All watchers share the client's rate
+/// limiter.
+///
+/// Dropping the receiver does not stop the watchers early; they run until
+/// their merchant finishes or times out.
+///
+/// Must be called from within a Tokio runtime.
+///
+/// # Example
+///
+/// ```no_run
+/// use payrix::{PayrixClient, Environment};
+/// use payrix::workflows::boarding_watch::{watch_boarding, BoardingUpdate, WaitForBoardingOptions};
+///
+/// # async fn example(client: PayrixClient, merchant_ids: Vec<String>) {
+/// let mut updates = watch_boarding(client, merchant_ids, WaitForBoardingOptions::default());
+/// while let Some(update) = updates.recv().await {
+///     match update {
+///         BoardingUpdate::Transition { merchant_id, transition } => {
+///             println!("{}: now {}", merchant_id, transition.to);
+///         }
+///         BoardingUpdate::Finished(result) => {
+///             println!("{} finished as {}", result.merchant_id, result.status);
+///         }
+///         BoardingUpdate::Failed { merchant_id, error } => {
+///             eprintln!("{} failed: {}", merchant_id, error);
+///         }
+///     }
+/// }
+/// # }
+/// ```
+pub fn watch_boarding(
+    client: PayrixClient,
+    merchant_ids: Vec<String>,
+    opts: WaitForBoardingOptions,
+) -> mpsc::Receiver<BoardingUpdate> {
+    let (tx, rx) = mpsc::channel(merchant_ids.len().max(1) * 4);
+
+    tokio::spawn(async move {
+        let mut tasks = JoinSet::new();
+        for merchant_id in merchant_ids {
+            let client = client.clone();
+            let opts = opts.clone();
+            let tx = tx.clone();
+            tasks.spawn(async move {
+                let outcome = watch(
+                    &client,
+                    &merchant_id,
+                    &opts,
+                    None::<&mut mpsc::Receiver<NoSignal>>,
+                    Some(&tx),
+                )
+                .await;
+                let update = match outcome {
+                    Ok(result) => BoardingUpdate::Finished(result),
+                    Err(error) => BoardingUpdate::Failed { merchant_id, error },
+                };
+                let _ = tx.send(update).await;
+            });
+        }
+        drop(tx);
+        while tasks.join_next().await.is_some() {}
+    });
+
+    rx
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_options() {
+        let opts = WaitForBoardingOptions::default();
+        assert_eq!(opts.initial_interval, Duration::from_secs(2));
+        assert_eq!(opts.max_interval, Duration::from_secs(30));
+        assert_eq!(opts.timeout, Duration::from_secs(600));
+    }
+
+    #[test]
+    fn backoff_grows_to_max() {
+        let opts = WaitForBoardingOptions::default();
+        let mut interval = opts.initial_interval;
+        let mut seen = Vec::new();
+        for _ in 0..6 {
+            interval = opts.next_interval(interval);
+            seen.push(interval.as_secs());
+        }
+        assert_eq!(seen, vec![4, 8, 16, 30, 30, 30]);
+    }
+
+    #[test]
+    fn backoff_factor_below_one_is_fixed() {
+        let opts = WaitForBoardingOptions::default().with_backoff_factor(0.5);
+        assert_eq!(opts.next_interval(Duration::from_secs(5)), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn in_progress_statuses() {
+        assert!(is_boarding_in_progress(BoardingStatus::Submitted));
+        assert!(is_boarding_in_progress(BoardingStatus::Pending));
+        assert!(!is_boarding_in_progress(BoardingStatus::Boarded));
+        assert!(!is_boarding_in_progress(BoardingStatus::ManualReview));
+        assert!(!is_boarding_in_progress(BoardingStatus::Incomplete));
+        assert!(!is_boarding_in_progress(BoardingStatus::Closed));
+        assert!(!is_boarding_in_progress(BoardingStatus::NotReady));
+    }
+
+    #[cfg(feature = "webhooks")]
+    #[test]
+    fn webhook_signal_matching() {
+        let ip = "127.0.0.1".parse().unwrap();
+        let merchant = "t1_mer_12345678901234567890123";
+        let event = |event_type: &str, id: &str| {
+            WebhookEvent::new(event_type, "merchants", id, serde_json::Value::Null, ip)
+        };
+
+        assert_eq!(
+            event("merchant.boarded", merchant).boarding_event(merchant),
+            Some("merchant.boarded")
+        );
+        assert_eq!(
+            event("merchant.held", merchant).boarding_event(merchant),
+            Some("merchant.held")
+        );
+        assert!(event("merchant.boarding", merchant).boarding_event(merchant).is_none());
+        assert!(event("merchant.failed", "t1_mer_other").boarding_event(merchant).is_none());
+    }
+
+    #[tokio::test]
+    async fn closed_signal_channel_reports_remaining_delay() {
+        let (tx, mut rx) = mpsc::channel::<NoSignal>(1);
+        drop(tx);
+        let wait = wait_for_signal(&mut rx, "t1_mer_x", Duration::from_secs(5)).await;
+        assert!(matches!(wait, SignalWait::Closed(left) if left > Duration::from_secs(4)));
+    }
+
+    #[cfg(feature = "webhooks")]
+    #[tokio::test]
+    async fn webhook_signal_skips_unrelated_events() {
+        let ip: std::net::IpAddr = "127.0.0.1".parse().unwrap();
+        let merchant = "t1_mer_12345678901234567890123";
+        let (tx, mut rx) = mpsc::channel(4);
+        for (event_type, id) in [
+            ("txn.approved", "t1_txn_12345678901234567890123"),
+            ("merchant.boarded", "t1_mer_other"),
+            ("merchant.failed", merchant),
+        ] {
+            tx.send(WebhookEvent::new(event_type, "merchants", id, serde_json::Value::Null, ip))
+                .await
+                .unwrap();
+        }
+
+        let wait = wait_for_signal(&mut rx, merchant, Duration::from_secs(5)).await;
+        assert!(matches!(wait, SignalWait::Event(ref e) if e == "merchant.failed"));
+
+        let wait = wait_for_signal(&mut rx, merchant, Duration::from_millis(10)).await;
+        assert!(matches!(wait, SignalWait::Elapsed));
+    }
+}
@@ -0,0 +1,759 @@
+//! Chargeback ratio monitoring against card brand dispute programmes.
+//!
+//! Card brands watch the ratio of disputes to sales for every merchant and
+//! place merchants that exceed their limits into monitoring programmes (Visa's
+//! dispute monitoring programme, Mastercard's excessive chargeback programme).
+//! This module links a merchant's [`Chargeback`]s to its settled sale volume so
+//! you can see how close a merchant is to those limits before the brand does.
+//!
+//! # How the Ratio Is Computed
+//!
+//! For a merchant and calendar month:
+//!
+//! - **Chargebacks** are counted by their `created` date, or by their `issued`
+//!   date when [`ChargebackDateBasis::Issued`] is selected.
+//! - **Sales** are settled card sales (`CreditCardSale` and `CreditCardCapture`
+//!   transactions in `Settled` status), bucketed by transaction creation date.
+//! - Both are grouped by card brand. Chargebacks are mapped from
+//!   [`ChargebackPaymentMethod`] and sales from the expanded [`Payment`] method,
+//!   so both sides use [`PaymentMethod`] as the brand key.
+//!
+//! Each brand's ratio is compared against its [`RatioThresholds`] and given a
+//! [`ChargebackRatioLevel`]. Brands are judged separately because each one runs
+//! its own programme with its own limits.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use payrix::{PayrixClient, Environment};
+//! use payrix::workflows::chargeback_monitoring::{
+//!     check_chargeback_ratio, ChargebackMonitorConfig, ChargebackRatioLevel,
+//! };
+//!
+//! # async fn example() -> payrix::Result<()> {
+//! let client = PayrixClient::new("api-key", Environment::Test)?;
+//! let config = ChargebackMonitorConfig::default();
+//!
+//! let report = check_chargeback_ratio(
+//!     &client,
+//!     "t1_mer_12345678901234567890123",
+//!     2024,
+//!     3,
+//!     &config,
+//! )
+//! .await?;
+//!
+//! for brand in &report.brands {
+//!     if brand.level != ChargebackRatioLevel::Normal {
+//!         println!(
+//!             "{}: {} chargebacks / {} sales = {:.2}% ({:?})",
+//!             brand.brand.display_name(),
+//!             brand.chargebacks,
+//!             brand.sales,
+//!             brand.ratio * 100.0,
+//!             brand.level,
+//!         );
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Payment`]: crate::types::Payment
+
+use std::collections::HashMap;
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::client::PayrixClient;
+use crate::entity::EntityType;
+use crate::error::{Error, Result};
+use crate::search::{make_payrix_date, SearchBuilder, SearchOperator};
+use crate::types::{
+    Chargeback, ChargebackPaymentMethod, PaymentMethod, TransactionExpanded, TransactionStatus,
+    TransactionType,
+};
+
+// =============================================================================
+// Section 1: Configuration
+// =============================================================================
+
+/// Which chargeback date decides the month a chargeback counts toward.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChargebackDateBasis {
+    /// Use the date the chargeback record was created in Payrix.
+    #[default]
+    Created,
+    /// Use the date the issuer raised the chargeback.
+    ///
+    /// Chargebacks without an `issued` date fall back to `created`.
+    Issued,
+}
+
+/// How close a brand's chargeback ratio is to its programme limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum ChargebackRatioLevel {
+    /// Below the early-warning threshold.
+    #[default]
+    Normal,
+    /// At or above the early-warning threshold, but below excessive.
+    EarlyWarning,
+    /// At or above the excessive threshold.
+    Excessive,
+}
+
+/// Ratio limits for a single card brand.
+///
+/// Ratios are fractions, not percentages: `0.009` means 0.9%.
+/// Brand programmes also require a minimum number of chargebacks before a
+/// merchant is flagged, so a merchant with a handful of sales and one dispute
+/// is not reported as excessive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RatioThresholds {
+    /// Ratio at which the merchant enters early warning.
+    pub early_warning: f64,
+
+    /// Ratio at which the merchant is considered excessive.
+    pub excessive: f64,
+
+    /// Minimum monthly chargeback count before any level above `Normal` applies.
+    pub min_chargebacks: u64,
+}
+
+impl RatioThresholds {
+    /// Create thresholds with explicit limits.
+    pub const fn new(early_warning: f64, excessive: f64, min_chargebacks: u64) -> Self {
+        Self {
+            early_warning,
+            excessive,
+            min_chargebacks,
+        }
+    }
+
+    /// Visa dispute monitoring limits: 0.65% early warning, 0.9% excessive,
+    /// with at least 75 disputes in the month.
+    pub const fn visa() -> Self {
+        Self::new(0.0065, 0.009, 75)
+    }
+
+    /// Mastercard chargeback monitoring limits: 1.0% early warning, 1.5%
+    /// excessive, with at least 100 chargebacks in the month.
+    pub const fn mastercard() -> Self {
+        Self::new(0.01, 0.015, 100)
+    }
+
+    /// Classify a monthly chargeback count and ratio against these limits.
+    pub fn classify(&self, chargebacks: u64, ratio: f64) -> ChargebackRatioLevel {
+        if chargebacks < self.min_chargebacks {
+            ChargebackRatioLevel::Normal
+        } else if ratio >= self.excessive {
+            ChargebackRatioLevel::Excessive
+        } else if ratio >= self.early_warning {
+            ChargebackRatioLevel::EarlyWarning
+        } else {
+            ChargebackRatioLevel::Normal
+        }
+    }
+}
+
+/// Configuration for chargeback ratio monitoring.
+///
+/// The default configuration uses [`RatioThresholds::visa`] for Visa,
+/// [`RatioThresholds::mastercard`] for Mastercard and for every other brand,
+/// and counts chargebacks by their `created` date.
+///
+/// # Example
+///
+/// ```
+/// use payrix::PaymentMethod;
+/// use payrix::workflows::chargeback_monitoring::{
+///     ChargebackDateBasis, ChargebackMonitorConfig, RatioThresholds,
+/// };
+///
+/// // Stricter internal limits so we hear about problems early
+/// let config = ChargebackMonitorConfig::default()
+///     .with_date_basis(ChargebackDateBasis::Issued)
+///     .with_brand_thresholds(PaymentMethod::Visa, RatioThresholds::new(0.004, 0.0065, 10));
+///
+/// assert_eq!(config.thresholds_for(PaymentMethod::Visa).min_chargebacks, 10);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChargebackMonitorConfig {
+    /// Which chargeback date assigns a chargeback to a month.
+    pub date_basis: ChargebackDateBasis,
+
+    /// Thresholds for brands without an entry in `brand_thresholds`.
+    pub default_thresholds: RatioThresholds,
+
+    /// Per-brand threshold overrides.
+    pub brand_thresholds: HashMap<PaymentMethod, RatioThresholds>,
+}
+
+impl Default for ChargebackMonitorConfig {
+    fn default() -> Self {
+        let mut brand_thresholds = HashMap::new();
+        brand_thresholds.insert(PaymentMethod::Visa, RatioThresholds::visa());
+        brand_thresholds.insert(PaymentMethod::Mastercard, RatioThresholds::mastercard());
+
+        Self {
+            date_basis: ChargebackDateBasis::default(),
+            default_thresholds: RatioThresholds::mastercard(),
+            brand_thresholds,
+        }
+    }
+}
+
+impl ChargebackMonitorConfig {
+    /// Set which chargeback date assigns a chargeback to a month.
+    pub fn with_date_basis(mut self, date_basis: ChargebackDateBasis) -> Self {
+        self.date_basis = date_basis;
+        self
+    }
+
+    /// Set the thresholds used for brands without an override.
+    pub fn with_default_thresholds(mut self, thresholds: RatioThresholds) -> Self {
+        self.default_thresholds = thresholds;
+        self
+    }
+
+    /// Override the thresholds for one brand.
+    pub fn with_brand_thresholds(mut self, brand: PaymentMethod, thresholds: RatioThresholds) -> Self {
+        self.brand_thresholds.insert(brand, thresholds);
+        self
+    }
+
+    /// Get the thresholds that apply to a brand.
+    pub fn thresholds_for(&self, brand: PaymentMethod) -> RatioThresholds {
+        self.brand_thresholds
+            .get(&brand)
+            .copied()
+            .unwrap_or(self.default_thresholds)
+    }
+}
+
+// =============================================================================
+// Section 2: Report Types
+// =============================================================================
+
+/// Chargeback ratio for one card brand in one month.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrandChargebackRatio {
+    /// The card brand.
+    pub brand: PaymentMethod,
+
+    /// Chargebacks counted in the month.
+    pub chargebacks: u64,
+
+    /// Settled sales counted in the month.
+    pub sales: u64,
+
+    /// `chargebacks / sales`, or `0.0` when there were no sales and no chargebacks.
+    ///
+    /// Chargebacks without any sales in the month yield `f64::INFINITY`.
+    pub ratio: f64,
+
+    /// Level relative to the brand's thresholds.
+    pub level: ChargebackRatioLevel,
+}
+
+/// Monthly chargeback ratio report for a merchant.
+#[derive(Debug, Clone)]
+pub struct ChargebackRatioReport {
+    /// The merchant ID.
+    pub merchant_id: String,
+
+    /// Calendar year of the report.
+    pub year: i32,
+
+    /// Calendar month of the report (1-12).
+    pub month: u32,
+
+    /// Per-brand ratios, ordered by brand.
+    ///
+    /// Only brands with at least one chargeback or sale are included.
+    pub brands: Vec<BrandChargebackRatio>,
+
+    /// Chargebacks in the month whose payment method is not a card brand
+    /// (for example eCheck returns or unbranded debit).
+    pub unattributed_chargebacks: u64,
+}
+
+impl ChargebackRatioReport {
+    /// Total card chargebacks across all brands.
+    pub fn total_chargebacks(&self) -> u64 {
+        self.brands.iter().map(|b| b.chargebacks).sum()
+    }
+
+    /// Total settled card sales across all brands.
+    pub fn total_sales(&self) -> u64 {
+        self.brands.iter().map(|b| b.sales).sum()
+    }
+
+    /// The highest level reached by any brand.
+    pub fn level(&self) -> ChargebackRatioLevel {
+        self.brands
+            .iter()
+            .map(|b| b.level)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Whether any brand is at early warning or worse.
+    pub fn needs_attention(&self) -> bool {
+        self.level() != ChargebackRatioLevel::Normal
+    }
+
+    /// Get the ratio for a specific brand.
+    pub fn brand(&self, brand: PaymentMethod) -> Option<&BrandChargebackRatio> {
+        self.brands.iter().find(|b| b.brand == brand)
+    }
+}
+
+// =============================================================================
+// Section 3: Ratio Calculation
+// =============================================================================
+
+/// Map a chargeback payment method to the card brand it belongs to.
+///
+/// Returns `None` for bank accounts, gift cards, EBT/WIC, PayPal and unbranded
+/// debit, which are not covered by card brand dispute programmes.
+pub fn chargeback_brand(method: ChargebackPaymentMethod) -> Option<PaymentMethod> {
+    match method {
+        ChargebackPaymentMethod::AmericanExpress => Some(PaymentMethod::AmericanExpress),
+        ChargebackPaymentMethod::Visa => Some(PaymentMethod::Visa),
+        ChargebackPaymentMethod::Mastercard => Some(PaymentMethod::Mastercard),
+        ChargebackPaymentMethod::DinersClub => Some(PaymentMethod::DinersClub),
+        ChargebackPaymentMethod::Discover => Some(PaymentMethod::Discover),
+        _ => None,
+    }
+}
+
+/// Whether a transaction counts as a settled card sale.
+pub fn is_settled_sale(txn: &TransactionExpanded) -> bool {
+    matches!(
+        txn.txn_type,
+        Some(TransactionType::CreditCardSale) | Some(TransactionType::CreditCardCapture)
+    ) && txn.status == Some(TransactionStatus::Settled)
+}
+
+/// Calculate a merchant's chargeback ratios for a calendar month.
+///
+/// This is the offline half of [`check_chargeback_ratio`]: it takes the
+/// merchant's chargebacks and transactions (with `payment` expanded) and does
+/// all filtering itself, so the inputs may span more than the target month.
+///
+/// Sales whose expanded payment is missing or is not a card are ignored.
+///
+/// # Errors
+///
+/// Returns `Error::Validation` if `month` is not in 1-12.
+pub fn calculate_chargeback_ratios(
+    merchant_id: &str,
+    year: i32,
+    month: u32,
+    chargebacks: &[Chargeback],
+    transactions: &[TransactionExpanded],
+    config: &ChargebackMonitorConfig,
+) -> Result<ChargebackRatioReport> {
+    month_bounds(year, month)?;
+
+    let mut counts: HashMap<PaymentMethod, (u64, u64)> = HashMap::new();
+    let mut unattributed_chargebacks = 0;
+
+    for chargeback in chargebacks {
+        if chargeback_month(chargeback, config.date_basis) != Some((year, month)) {
+            continue;
+        }
+        match chargeback.payment_method.and_then(chargeback_brand) {
+            Some(brand) => counts.entry(brand).or_default().0 += 1,
+            None => unattributed_chargebacks += 1,
+        }
+    }
+
+    for txn in transactions {
+        if !is_settled_sale(txn) || txn.created.as_deref().and_then(year_month) != Some((year, month)) {
+            continue;
+        }
+        let brand = txn.payment.as_ref().and_then(|p| p.method);
+        if let Some(brand) = brand.filter(|m| m.is_card()) {
+            counts.entry(brand).or_default().1 += 1;
+        }
+    }
+
+    let mut brands: Vec<BrandChargebackRatio> = counts
+        .into_iter()
+        .map(|(brand, (chargebacks, sales))| {
+            let ratio = match (chargebacks, sales) {
+                (0, _) => 0.0,
+                (_, 0) => f64::INFINITY,
+                (c, s) => c as f64 / s as f64,
+            };
+            BrandChargebackRatio {
+                brand,
+                chargebacks,
+                sales,
+                ratio,
+                level: config.thresholds_for(brand).classify(chargebacks, ratio),
+            }
+        })
+        .collect();
+    brands.sort_by_key(|b| b.brand as i32);
+
+    Ok(ChargebackRatioReport {
+        merchant_id: merchant_id.to_string(),
+        year,
+        month,
+        brands,
+        unattributed_chargebacks,
+    })
+}
+
+// =============================================================================
+// Section 4: API Functions
+// =============================================================================
+
+/// Fetch a merchant's chargebacks and settled sales and compute its ratios.
+///
+/// Both searches are narrowed to the target month on the server; the results
+/// are then filtered again by [`calculate_chargeback_ratios`].
+///
+/// # Arguments
+///
+/// * `client` - The Payrix client
+/// * `merchant_id` - The merchant to check
+/// * `year` - Calendar year
+/// * `month` - Calendar month (1-12)
+/// * `config` - Thresholds and date basis
+pub async fn check_chargeback_ratio(
+    client: &PayrixClient,
+    merchant_id: &str,
+    year: i32,
+    month: u32,
+    config: &ChargebackMonitorConfig,
+) -> Result<ChargebackRatioReport> {
+    let (first, last) = month_bounds(year, month)?;
+    let after = make_payrix_date(&(first - Duration::days(1)));
+    let before = make_payrix_date(&(last + Duration::days(1)));
+
+    let date_field = match config.date_basis {
+        ChargebackDateBasis::Created => "created",
+        ChargebackDateBasis::Issued => "issued",
+    };
+    let chargeback_search = SearchBuilder::new()
+        .field("merchant", merchant_id)
+        .field_with_op(date_field, &after, SearchOperator::Greater)
+        .field_with_op(date_field, &before, SearchOperator::Less)
+        .build();
+    let mut chargebacks: Vec<Chargeback> = client
+        .search(EntityType::Chargebacks, &chargeback_search)
+        .await?;
+
+    // Chargebacks without an issue date fall back to created, so they have to
+    // be fetched separately when counting by issue date.
+    if config.date_basis == ChargebackDateBasis::Issued {
+        let fallback_search = SearchBuilder::new()
+            .field("merchant", merchant_id)
+            .field_with_op("created", &after, SearchOperator::Greater)
+            .field_with_op("created", &before, SearchOperator::Less)
+            .build();
+        let fallback: Vec<Chargeback> = client
+            .search(EntityType::Chargebacks, &fallback_search)
+            .await?;
+        chargebacks.extend(fallback.into_iter().filter(|cb| cb.issued.is_none()));
+    }
+
+    let sale_types = [
+        (TransactionType::CreditCardSale as i32).to_string(),
+        (TransactionType::CreditCardCapture as i32).to_string(),
+    ];
+    let sale_types: Vec<&str> = sale_types.iter().map(String::as_str).collect();
+    let txn_search = SearchBuilder::new()
+        .field("merchant", merchant_id)
+        .field("status", &(TransactionStatus::Settled as i32).to_string())
+        .field_multi("type", &sale_types, SearchOperator::In)
+        .field_with_op("created", &after, SearchOperator::Greater)
+        .field_with_op("created", &before, SearchOperator::Less)
+        .build();
+    let mut params = HashMap::new();
+    params.insert("expand[payment][]".to_string(), String::new());
+    let transactions: Vec<TransactionExpanded> = client
+        .get_all_with_params(EntityType::Txns, params, Some(&txn_search))
+        .await?;
+
+    calculate_chargeback_ratios(merchant_id, year, month, &chargebacks, &transactions, config)
+}
+
+/// Check several merchants and return the reports that need attention.
+///
+/// Merchants are checked one after another; the client's rate limiter keeps
+/// the request rate within Payrix limits.
+pub async fn find_flagged_merchants(
+    client: &PayrixClient,
+    merchant_ids: &[&str],
+    year: i32,
+    month: u32,
+    config: &ChargebackMonitorConfig,
+) -> Result<Vec<ChargebackRatioReport>> {
+    let mut flagged = Vec::new();
+    for merchant_id in merchant_ids {
+        let report = check_chargeback_ratio(client, merchant_id, year, month, config).await?;
+        if report.needs_attention() {
+            flagged.push(report);
+        }
+    }
+    Ok(flagged)
+}
+
+// =============================================================================
+// Section 5: Helper Functions
+// =============================================================================
+
+/// First and last day of a calendar month.
+pub(super) fn month_bounds(year: i32, month: u32) -> Result<(NaiveDate, NaiveDate)> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)
+        .ok_or_else(|| Error::Validation(format!("Invalid month: {}-{}", year, month)))?;
+    let next = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)
+    }
+    .ok_or_else(|| Error::Validation(format!("Invalid month: {}-{}", year, month)))?;
+    Ok((first, next - Duration::days(1)))
+}
+
+/// Extract (year, month) from a Payrix date or timestamp.
+///
+/// Accepts both `YYYY-MM-DD HH:MM:SS` timestamps and `YYYYMMDD` dates.
+fn year_month(value: &str) -> Option<(i32, u32)> {
+    let date = if value.len() >= 10 && value.as_bytes()[4] == b'-' {
+        NaiveDate::parse_from_str(&value[..10], "%Y-%m-%d").ok()?
+    } else {
+        NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()?
+    };
+    Some((date.year(), date.month()))
+}
+
+/// The month a chargeback counts toward under the given date basis.
+fn chargeback_month(chargeback: &Chargeback, basis: ChargebackDateBasis) -> Option<(i32, u32)> {
+    let issued = match basis {
+        ChargebackDateBasis::Issued => chargeback.issued.and_then(|d| year_month(&d.to_string())),
+        ChargebackDateBasis::Created => None,
+    };
+    issued.or_else(|| chargeback.created.as_deref().and_then(year_month))
+}
+
+// =============================================================================
+// Section 6: Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_chargeback(
+        created: &str,
+        issued: Option<i32>,
+        method: Option<ChargebackPaymentMethod>,
+    ) -> Chargeback {
+        serde_json::from_value(serde_json::json!({
+            "id": "t1_chb_12345678901234567890123",
+            "created": created,
+            "issued": issued,
+            "paymentMethod": method.map(|m| m as i32),
+            "total": 5000,
+        }))
+        .unwrap()
+    }
+
+    fn make_sale(created: &str, method: PaymentMethod, status: TransactionStatus) -> TransactionExpanded {
+        serde_json::from_value(serde_json::json!({
+            "id": "t1_txn_12345678901234567890123",
+            "created": created,
+            "type": TransactionType::CreditCardSale as i32,
+            "status": status as i32,
+            "total": 1000,
+            "payment": { "method": method as i32 },
+        }))
+        .unwrap()
+    }
+
+    fn sales(count: usize, method: PaymentMethod) -> Vec<TransactionExpanded> {
+        (0..count)
+            .map(|_| make_sale("2024-03-10 12:00:00.0000", method, TransactionStatus::Settled))
+            .collect()
+    }
+
+    #[test]
+    fn test_chargeback_brand_mapping() {
+        assert_eq!(chargeback_brand(ChargebackPaymentMethod::Visa), Some(PaymentMethod::Visa));
+        assert_eq!(
+            chargeback_brand(ChargebackPaymentMethod::Mastercard),
+            Some(PaymentMethod::Mastercard)
+        );
+        assert_eq!(
+            chargeback_brand(ChargebackPaymentMethod::AmericanExpress),
+            Some(PaymentMethod::AmericanExpress)
+        );
+        assert_eq!(chargeback_brand(ChargebackPaymentMethod::Checking), None);
+        assert_eq!(chargeback_brand(ChargebackPaymentMethod::Debit), None);
+        assert_eq!(chargeback_brand(ChargebackPaymentMethod::GiftCard), None);
+    }
+
+    #[test]
+    fn test_threshold_classification() {
+        let visa = RatioThresholds::visa();
+        assert_eq!(visa.classify(100, 0.005), ChargebackRatioLevel::Normal);
+        assert_eq!(visa.classify(100, 0.0065), ChargebackRatioLevel::EarlyWarning);
+        assert_eq!(visa.classify(100, 0.009), ChargebackRatioLevel::Excessive);
+        // Below the minimum count nothing is flagged, whatever the ratio
+        assert_eq!(visa.classify(74, 0.5), ChargebackRatioLevel::Normal);
+    }
+
+    #[test]
+    fn test_config_thresholds_for() {
+        let config = ChargebackMonitorConfig::default();
+        assert_eq!(config.thresholds_for(PaymentMethod::Visa), RatioThresholds::visa());
+        assert_eq!(
+            config.thresholds_for(PaymentMethod::Discover),
+            RatioThresholds::mastercard()
+        );
+    }
+
+    #[test]
+    fn test_year_month_parsing() {
+        assert_eq!(year_month("2024-03-15 10:30:00.0000"), Some((2024, 3)));
+        assert_eq!(year_month("20240315"), Some((2024, 3)));
+        assert_eq!(year_month("bogus"), None);
+    }
+
+    #[test]
+    fn test_month_bounds() {
+        let (first, last) = month_bounds(2024, 2).unwrap();
+        assert_eq!(first, NaiveDate::from_ymd_opt(2024, 2, 1).unwrap());
+        assert_eq!(last, NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+
+        let (_, last) = month_bounds(2024, 12).unwrap();
+        assert_eq!(last, NaiveDate::from_ymd_opt(2024, 12, 31).unwrap());
+
+        assert!(month_bounds(2024, 13).is_err());
+    }
+
+    #[test]
+    fn test_ratio_per_brand() {
+        let config = ChargebackMonitorConfig::default()
+            .with_brand_thresholds(PaymentMethod::Visa, RatioThresholds::new(0.01, 0.02, 1))
+            .with_brand_thresholds(PaymentMethod::Mastercard, RatioThresholds::new(0.01, 0.02, 1));
+
+        let chargebacks = vec![
+            make_chargeback("2024-03-02 09:00:00", None, Some(ChargebackPaymentMethod::Visa)),
+            make_chargeback("2024-03-05 09:00:00", None, Some(ChargebackPaymentMethod::Visa)),
+            make_chargeback("2024-03-07 09:00:00", None, Some(ChargebackPaymentMethod::Mastercard)),
+            // Outside the month
+            make_chargeback("2024-02-28 09:00:00", None, Some(ChargebackPaymentMethod::Visa)),
+            // Not a card brand
+            make_chargeback("2024-03-07 09:00:00", None, Some(ChargebackPaymentMethod::Checking)),
+        ];
+
+        let mut transactions = sales(100, PaymentMethod::Visa);
+        transactions.extend(sales(200, PaymentMethod::Mastercard));
+        // Not settled - ignored
+        transactions.push(make_sale(
+            "2024-03-10 12:00:00",
+            PaymentMethod::Visa,
+            TransactionStatus::Approved,
+        ));
+
+        let report = calculate_chargeback_ratios(
+            "t1_mer_12345678901234567890123",
+            2024,
+            3,
+            &chargebacks,
+            &transactions,
+            &config,
+        )
+        .unwrap();
+
+        let visa = report.brand(PaymentMethod::Visa).unwrap();
+        assert_eq!(visa.chargebacks, 2);
+        assert_eq!(visa.sales, 100);
+        assert!((visa.ratio - 0.02).abs() < f64::EPSILON);
+        assert_eq!(visa.level, ChargebackRatioLevel::Excessive);
+
+        let mastercard = report.brand(PaymentMethod::Mastercard).unwrap();
+        assert_eq!(mastercard.chargebacks, 1);
+        assert_eq!(mastercard.sales, 200);
+        assert_eq!(mastercard.level, ChargebackRatioLevel::Normal);
+
+        assert_eq!(report.unattributed_chargebacks, 1);
+        assert_eq!(report.total_chargebacks(), 3);
+        assert_eq!(report.total_sales(), 300);
+        assert_eq!(report.level(), ChargebackRatioLevel::Excessive);
+        assert!(report.needs_attention());
+    }
+
+    #[test]
+    fn test_issued_date_basis() {
+        let config = ChargebackMonitorConfig::default()
+            .with_date_basis(ChargebackDateBasis::Issued)
+            .with_default_thresholds(RatioThresholds::new(0.01, 0.02, 1));
+
+        let chargebacks = vec![
+            // Created in April but issued in March
+            make_chargeback("2024-04-02 09:00:00", Some(20240329), Some(ChargebackPaymentMethod::Discover)),
+            // Created in March but issued in February
+            make_chargeback("2024-03-02 09:00:00", Some(20240227), Some(ChargebackPaymentMethod::Discover)),
+            // No issue date - falls back to created
+            make_chargeback("2024-03-20 09:00:00", None, Some(ChargebackPaymentMethod::Discover)),
+        ];
+
+        let report = calculate_chargeback_ratios(
+            "t1_mer_12345678901234567890123",
+            2024,
+            3,
+            &chargebacks,
+            &sales(1000, PaymentMethod::Discover),
+            &config,
+        )
+        .unwrap();
+
+        let discover = report.brand(PaymentMethod::Discover).unwrap();
+        assert_eq!(discover.chargebacks, 2);
+        assert_eq!(discover.level, ChargebackRatioLevel::Normal);
+    }
+
+    #[test]
+    fn test_chargebacks_without_sales() {
+        let config = ChargebackMonitorConfig::default()
+            .with_default_thresholds(RatioThresholds::new(0.01, 0.02, 1));
+        let chargebacks = vec![make_chargeback(
+            "2024-03-02 09:00:00",
+            None,
+            Some(ChargebackPaymentMethod::AmericanExpress),
+        )];
+
+        let report =
+            calculate_chargeback_ratios("t1_mer_12345678901234567890123", 2024, 3, &chargebacks, &[], &config)
+                .unwrap();
+
+        let amex = report.brand(PaymentMethod::AmericanExpress).unwrap();
+        assert!(amex.ratio.is_infinite());
+        assert_eq!(amex.level, ChargebackRatioLevel::Excessive);
+    }
+
+    #[test]
+    fn test_empty_report_is_normal() {
+        let report = calculate_chargeback_ratios(
+            "t1_mer_12345678901234567890123",
+            2024,
+            3,
+            &[],
+            &[],
+            &ChargebackMonitorConfig::default(),
+        )
+        .unwrap();
+
+        assert!(report.brands.is_empty());
+        assert_eq!(report.level(), ChargebackRatioLevel::Normal);
+        assert!(!report.needs_attention());
+    }
+}
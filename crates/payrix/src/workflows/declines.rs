@@ -0,0 +1,461 @@
+//! Classify declined payments and decide what to do next.
+//!
+//! A failed [`Transaction`] carries little on its own: the reason is in the
+//! [`TxnResult`]s Payrix records against it (a [`TxnResultType`] saying which
+//! check ran and a [`TxnResultCode`] saying how it went). [`DeclineInfo`]
+//! turns those into:
+//!
+//! - a [`DeclineReason`] such as insufficient funds or a CVV mismatch,
+//! - a [`DeclineCategory`]: soft (retry later), hard (do not retry), needs a
+//!   new card, or suspected fraud,
+//! - a [`DeclineAction`] recommending what to do next, and
+//! - a customer-safe message that never reveals fraud screening or raw
+//!   processor text.
+//!
+//! When a transaction has several results, the most severe one wins. The
+//! [`dunning`](super::dunning) engine records a `DeclineInfo` for every failed
+//! retry and stops retrying declines that will not succeed on their own.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use payrix::{PayrixClient, Environment};
+//! use payrix::workflows::declines::{DeclineAction, DeclineInfo};
+//!
+//! # async fn example() -> payrix::Result<()> {
+//! let client = PayrixClient::new("api-key", Environment::Test)?;
+//!
+//! if let Some(decline) = DeclineInfo::fetch(&client, "t1_txn_12345678901234567890123").await? {
+//!     println!("Tell the customer: {}", decline.customer_message());
+//!     match decline.action {
+//!         DeclineAction::RetryLater { after_days } => println!("Retry in {} days", after_days),
+//!         DeclineAction::UpdatePaymentMethod => println!("Ask for a new card"),
+//!         _ => println!("Do not retry: {:?}", decline.reason),
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::client::PayrixClient;
+use crate::entity::EntityType;
+use crate::error::Result;
+use crate::types::{
+    PayrixId, Transaction, TransactionStatus, TxnResult, TxnResultCode, TxnResultType,
+    UnauthReason,
+};
+
+// =============================================================================
+// Section 1: Classification
+// =============================================================================
+
+/// How a decline should be handled, from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum DeclineCategory {
+    /// Temporary; the same payment may succeed later.
+    Soft,
+    /// The payment method must be updated or replaced before retrying.
+    NeedsNewCard,
+    /// Retrying will not succeed.
+    Hard,
+    /// Fraud screening stopped the payment; do not retry without review.
+    FraudSuspected,
+}
+
+/// Why a payment was declined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DeclineReason {
+    /// Not enough funds in the account.
+    InsufficientFunds,
+    /// The issuer declined without saying why.
+    IssuerDeclined,
+    /// A processor error or uncaptured transaction.
+    ProcessorError,
+    /// The processor could not be reached.
+    NetworkError,
+    /// The card expired before the payment.
+    CardExpired,
+    /// The card or bank account is invalid or closed.
+    InvalidAccount,
+    /// Account verification failed.
+    VerificationFailed,
+    /// The billing name, address, ZIP, phone or email did not match.
+    BillingMismatch,
+    /// The CVV did not match.
+    CvvMismatch,
+    /// The account holder did not authorize the debit (eCheck).
+    AccountUnauthorized,
+    /// 3D Secure authentication failed or was not completed.
+    AuthenticationFailed,
+    /// Fraud screening declined the payment.
+    FraudSuspected,
+    /// The transaction failed without a recognizable result.
+    Unknown,
+}
+
+/// The recommended next step after a decline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DeclineAction {
+    /// Retry the same payment method after this many days.
+    RetryLater {
+        /// Days to wait before retrying.
+        after_days: u32,
+    },
+    /// Ask the customer to update or replace their payment method.
+    UpdatePaymentMethod,
+    /// Do not retry; contact the customer to arrange payment.
+    ContactCustomer,
+    /// Do not retry; review the payment for fraud first.
+    ReviewForFraud,
+}
+
+impl DeclineReason {
+    /// The category this reason falls in.
+    pub fn category(self) -> DeclineCategory {
+        match self {
+            Self::InsufficientFunds
+            | Self::IssuerDeclined
+            | Self::ProcessorError
+            | Self::NetworkError
+            | Self::Unknown => DeclineCategory::Soft,
+            Self::CardExpired
+            | Self::InvalidAccount
+            | Self::VerificationFailed
+            | Self::BillingMismatch
+            | Self::CvvMismatch => DeclineCategory::NeedsNewCard,
+            Self::AccountUnauthorized | Self::AuthenticationFailed => DeclineCategory::Hard,
+            Self::FraudSuspected => DeclineCategory::FraudSuspected,
+        }
+    }
+
+    /// The recommended next step.
+    pub fn action(self) -> DeclineAction {
+        match self {
+            // Funds usually arrive with the next paycheck or statement cycle.
+            Self::InsufficientFunds => DeclineAction::RetryLater { after_days: 3 },
+            Self::IssuerDeclined | Self::Unknown => DeclineAction::RetryLater { after_days: 1 },
+            Self::ProcessorError | Self::NetworkError => DeclineAction::RetryLater { after_days: 0 },
+            Self::CardExpired
+            | Self::InvalidAccount
+            | Self::VerificationFailed
+            | Self::BillingMismatch
+            | Self::CvvMismatch => DeclineAction::UpdatePaymentMethod,
+            Self::AccountUnauthorized | Self::AuthenticationFailed => DeclineAction::ContactCustomer,
+            Self::FraudSuspected => DeclineAction::ReviewForFraud,
+        }
+    }
+
+    /// A message that is safe to show the customer.
+    pub fn customer_message(self) -> &'static str {
+        match self {
+            Self::InsufficientFunds => {
+                "Your payment was declined for insufficient funds. Please try again later or use a different payment method."
+            }
+            Self::IssuerDeclined => {
+                "Your card issuer declined the payment. Please contact your bank or use a different payment method."
+            }
+            Self::ProcessorError | Self::NetworkError => {
+                "We couldn't complete your payment because of a temporary problem. Please try again."
+            }
+            Self::CardExpired => "Your card has expired. Please update your payment details.",
+            Self::InvalidAccount => {
+                "The card or account on file is no longer valid. Please update your payment details."
+            }
+            Self::VerificationFailed | Self::BillingMismatch => {
+                "We couldn't verify your billing details. Please check them or use a different payment method."
+            }
+            Self::CvvMismatch => "The security code didn't match. Please re-enter your card details.",
+            Self::AccountUnauthorized => {
+                "Your bank did not authorize this payment. Please contact us to arrange another payment method."
+            }
+            Self::AuthenticationFailed => {
+                "We couldn't verify your card with your bank. Please contact us to arrange another payment method."
+            }
+            // Never reveal fraud screening to the customer.
+            Self::FraudSuspected | Self::Unknown => {
+                "Your payment could not be processed. Please use a different payment method or contact us."
+            }
+        }
+    }
+}
+
+/// The decline reason a single result indicates, if it is a failure.
+pub fn classify_result(result: &TxnResult) -> Option<DeclineReason> {
+    use TxnResultCode as Code;
+
+    let code = result.code?;
+    let failed = !matches!(
+        code,
+        Code::Approved | Code::PartiallyApproved | Code::VerificationSuccessful | Code::ThreeDsPassed
+            | Code::ThreeDsAuthPassedLiabilityShifted
+    );
+    // Fraud screening and CVV checks are failures whatever the code detail.
+    match result.result_type {
+        Some(TxnResultType::FraudPrevention) if failed => return Some(DeclineReason::FraudSuspected),
+        Some(TxnResultType::CvvMismatch) if failed => return Some(DeclineReason::CvvMismatch),
+        _ => {}
+    }
+
+    match code {
+        Code::Declined | Code::VerificationUnsuccessful => Some(match result.result_type {
+            Some(TxnResultType::AvsCheck | TxnResultType::AavsCheck) => DeclineReason::BillingMismatch,
+            Some(TxnResultType::ThreeDsAlert) => DeclineReason::AuthenticationFailed,
+            Some(TxnResultType::NetworkError) => DeclineReason::NetworkError,
+            _ if code == Code::VerificationUnsuccessful => DeclineReason::VerificationFailed,
+            _ => DeclineReason::IssuerDeclined,
+        }),
+        Code::ZipCodeMismatch
+        | Code::AddressMismatch
+        | Code::NameMismatch
+        | Code::NameAndPhoneMismatch
+        | Code::NameAndEmailMismatch
+        | Code::PhoneMismatch
+        | Code::PhoneAndEmailMismatch
+        | Code::EmailMismatch => Some(DeclineReason::BillingMismatch),
+        Code::NonSufficientFunds => Some(DeclineReason::InsufficientFunds),
+        Code::AccountInvalid => Some(DeclineReason::InvalidAccount),
+        Code::AccountUnauthorized => Some(DeclineReason::AccountUnauthorized),
+        Code::GeneralError if result.result_type == Some(TxnResultType::NetworkError) => {
+            Some(DeclineReason::NetworkError)
+        }
+        Code::GeneralError | Code::NotCaptured => Some(DeclineReason::ProcessorError),
+        Code::ThreeDsInvalid | Code::ThreeDsFailed | Code::ThreeDsNotValidated => {
+            Some(DeclineReason::AuthenticationFailed)
+        }
+        // Approvals and "not in transaction data" notices are not declines.
+        _ => None,
+    }
+}
+
+// =============================================================================
+// Section 2: DeclineInfo
+// =============================================================================
+
+/// Why a transaction was declined and what to do about it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeclineInfo {
+    /// The declined transaction.
+    pub transaction_id: PayrixId,
+    /// Why it was declined.
+    pub reason: DeclineReason,
+    /// How the decline should be handled.
+    pub category: DeclineCategory,
+    /// The recommended next step.
+    pub action: DeclineAction,
+    /// The result type behind the decline, if Payrix recorded one.
+    pub result_type: Option<TxnResultType>,
+    /// The result code behind the decline, if Payrix recorded one.
+    pub code: Option<TxnResultCode>,
+    /// The processor's message. For logs and support staff, not customers.
+    pub processor_message: Option<String>,
+}
+
+impl DeclineInfo {
+    /// Classify a failed transaction from its results.
+    ///
+    /// Returns `None` if the transaction did not fail, including while it is
+    /// still pending with the processor; check it again once it settles (the
+    /// dunning engine reports such retries as
+    /// [`DunningEvent::RetryProcessing`](super::dunning::DunningEvent::RetryProcessing)).
+    /// A failed transaction
+    /// without a recognizable result is classified from its `unauthReason`
+    /// and card expiration, and otherwise as [`DeclineReason::Unknown`].
+    pub fn from_transaction(txn: &Transaction, results: &[TxnResult]) -> Option<Self> {
+        if txn.status != Some(TransactionStatus::Failed) {
+            return None;
+        }
+
+        // The most severe result wins; the first one on ties.
+        let worst = results
+            .iter()
+            .filter_map(|r| classify_result(r).map(|reason| (reason, r)))
+            .fold(None::<(DeclineReason, &TxnResult)>, |worst, (reason, r)| match worst {
+                Some((w, _)) if w.category() >= reason.category() => worst,
+                _ => Some((reason, r)),
+            });
+
+        let fallback = match txn.unauth_reason {
+            Some(UnauthReason::SuspectedFraud) => DeclineReason::FraudSuspected,
+            Some(UnauthReason::Timeout | UnauthReason::HardwareFailure) => DeclineReason::ProcessorError,
+            _ => DeclineReason::Unknown,
+        };
+        let reason = worst.map_or(fallback, |(reason, _)| reason);
+        let reason = if matches!(reason, DeclineReason::IssuerDeclined | DeclineReason::Unknown)
+            && is_expired(txn)
+        {
+            DeclineReason::CardExpired
+        } else {
+            reason
+        };
+
+        let result = worst.map(|(_, r)| r);
+        Some(Self {
+            transaction_id: txn.id.clone(),
+            reason,
+            category: reason.category(),
+            action: reason.action(),
+            result_type: result.and_then(|r| r.result_type),
+            code: result.and_then(|r| r.code),
+            processor_message: result.and_then(|r| r.message.clone()),
+        })
+    }
+
+    /// Load a transaction with its results and classify it.
+    ///
+    /// Returns `None` if the transaction does not exist or did not fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API call fails or the transaction cannot be parsed.
+    pub async fn fetch(client: &PayrixClient, txn_id: &str) -> Result<Option<Self>> {
+        let Some(value) = client
+            .get_one_expanded::<Value>(EntityType::Txns, txn_id, &["txnResults"])
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        // Results that do not parse (unknown codes) are skipped rather than
+        // failing the whole classification.
+        let results: Vec<TxnResult> = value
+            .get("txnResults")
+            .and_then(Value::as_array)
+            .map(|results| {
+                results
+                    .iter()
+                    .filter_map(|r| serde_json::from_value(r.clone()).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let txn: Transaction = serde_json::from_value(value)?;
+        Ok(Self::from_transaction(&txn, &results))
+    }
+
+    /// A message that is safe to show the customer.
+    pub fn customer_message(&self) -> &'static str {
+        self.reason.customer_message()
+    }
+
+    /// Whether retrying the same payment method may succeed.
+    pub fn is_retryable(&self) -> bool {
+        self.category == DeclineCategory::Soft
+    }
+}
+
+/// Whether the card had expired by the time of the transaction.
+fn is_expired(txn: &Transaction) -> bool {
+    let Some(expiration) = &txn.expiration else {
+        return false;
+    };
+    let Some(on) = txn
+        .created
+        .as_deref()
+        .and_then(|c| NaiveDate::parse_from_str(c.get(..10)?, "%Y-%m-%d").ok())
+    else {
+        return false;
+    };
+    // Cards are valid through the end of their expiration month.
+    (i32::from(expiration.full_year()), u32::from(expiration.month())) < (on.year(), on.month())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn failed(extra: Value) -> Transaction {
+        let mut txn = json!({ "id": "t1_txn_00000000000000000000001", "type": 1, "status": 2 });
+        txn.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(txn).unwrap()
+    }
+
+    fn result(result_type: i32, code: i32) -> TxnResult {
+        serde_json::from_value(json!({
+            "id": "t1_txr_00000000000000000000001",
+            "type": result_type,
+            "code": code,
+            "message": "05 DO NOT HONOR",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_classify_results() {
+        let reason = |t, c| classify_result(&result(t, c));
+        assert_eq!(reason(1, 20), Some(DeclineReason::InsufficientFunds));
+        assert_eq!(reason(1, 2), Some(DeclineReason::IssuerDeclined));
+        assert_eq!(reason(2, 2), Some(DeclineReason::FraudSuspected));
+        assert_eq!(reason(4, 4), Some(DeclineReason::CvvMismatch));
+        assert_eq!(reason(5, 5), Some(DeclineReason::BillingMismatch));
+        assert_eq!(reason(7, 23), Some(DeclineReason::NetworkError));
+        assert_eq!(reason(8, 30), Some(DeclineReason::AuthenticationFailed));
+        assert_eq!(reason(1, 21), Some(DeclineReason::InvalidAccount));
+        assert_eq!(reason(1, 0), None);
+        assert_eq!(reason(5, 24), None);
+        assert_eq!(reason(2, 0), None);
+    }
+
+    #[test]
+    fn test_decline_info_picks_most_severe() {
+        let txn = failed(json!({}));
+        let results = vec![result(1, 20), result(4, 4), result(1, 0)];
+        let info = DeclineInfo::from_transaction(&txn, &results).unwrap();
+        assert_eq!(info.reason, DeclineReason::CvvMismatch);
+        assert_eq!(info.category, DeclineCategory::NeedsNewCard);
+        assert_eq!(info.action, DeclineAction::UpdatePaymentMethod);
+        assert_eq!(info.code, Some(TxnResultCode::VerificationUnsuccessful));
+        assert!(!info.is_retryable());
+
+        let soft = DeclineInfo::from_transaction(&txn, &[result(1, 20)]).unwrap();
+        assert!(soft.is_retryable());
+        assert_eq!(soft.action, DeclineAction::RetryLater { after_days: 3 });
+
+        // Fraud is never revealed to the customer.
+        let fraud = DeclineInfo::from_transaction(&txn, &[result(2, 2)]).unwrap();
+        assert_eq!(fraud.category, DeclineCategory::FraudSuspected);
+        assert!(!fraud.customer_message().to_lowercase().contains("fraud"));
+
+        let approved = failed(json!({ "status": 1 }));
+        assert!(DeclineInfo::from_transaction(&approved, &[result(1, 2)]).is_none());
+        // A pending transaction is in flight, not declined.
+        let pending = failed(json!({ "status": 0 }));
+        assert!(DeclineInfo::from_transaction(&pending, &[]).is_none());
+    }
+
+    #[test]
+    fn test_messages_match_retry_advice() {
+        use DeclineReason::*;
+        for reason in [
+            InsufficientFunds, IssuerDeclined, ProcessorError, NetworkError, CardExpired, InvalidAccount,
+            VerificationFailed, BillingMismatch, CvvMismatch, AccountUnauthorized, AuthenticationFailed,
+            FraudSuspected, Unknown,
+        ] {
+            let retryable = matches!(reason.action(), DeclineAction::RetryLater { .. });
+            assert_eq!(retryable, reason.category() == DeclineCategory::Soft, "{:?}", reason);
+            if !retryable {
+                assert!(!reason.customer_message().contains("try again"), "{:?}", reason);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decline_info_without_results() {
+        let expired = failed(json!({ "expiration": "0124", "created": "2024-02-01 10:00:00.0000" }));
+        let info = DeclineInfo::from_transaction(&expired, &[]).unwrap();
+        assert_eq!(info.reason, DeclineReason::CardExpired);
+        assert_eq!(info.category, DeclineCategory::NeedsNewCard);
+
+        let current = failed(json!({ "expiration": "0124", "created": "2024-01-31 10:00:00.0000" }));
+        let info = DeclineInfo::from_transaction(&current, &[]).unwrap();
+        assert_eq!(info.reason, DeclineReason::Unknown);
+        assert!(info.is_retryable());
+
+        let fraud = failed(json!({ "unauthReason": "suspectedFraud" }));
+        let info = DeclineInfo::from_transaction(&fraud, &[]).unwrap();
+        assert_eq!(info.action, DeclineAction::ReviewForFraud);
+    }
+}
@@ -0,0 +1,599 @@
+//! Bulk dispute operations with concurrency control.
+//!
+//! Operations teams often need to act on many chargebacks at once, for
+//! example accepting liability on every low-value dispute for a merchant
+//! rather than fighting each one. [`DisputeBatch`] selects disputes with a
+//! [`DisputeFilter`], applies a single [`DisputeAction`] to each of them, and
+//! returns a [`DisputeBatchReport`] with the outcome for every dispute.
+//!
+//! # Concurrency
+//!
+//! Disputes are processed concurrently, up to [`DisputeBatch::with_concurrency`]
+//! at a time (default [`DEFAULT_BATCH_CONCURRENCY`]). All tasks share the
+//! client's rate limiter, so a large batch slows down rather than tripping
+//! Payrix's request limits. A failure on one dispute is recorded in the report
+//! and does not stop the rest of the batch.
+//!
+//! # Dry Run
+//!
+//! With [`DisputeBatch::dry_run`] enabled, the batch fetches and filters
+//! disputes but makes no changes. Each matching dispute is reported as
+//! [`DisputeOutcome::WouldApply`] (or `Skipped` if the action is not valid in
+//! its current state), so the list can be reviewed before running for real.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use payrix::{PayrixClient, Environment};
+//! use payrix::workflows::dispute_batch::{DisputeAction, DisputeBatch, DisputeFilter};
+//!
+//! # async fn example() -> payrix::Result<()> {
+//! let client = PayrixClient::new("api-key", Environment::Test)?;
+//!
+//! // Accept liability on every open dispute of $25.00 or less
+//! let filter = DisputeFilter::new()
+//!     .merchant("t1_mer_12345678901234567890123")
+//!     .max_amount(2500);
+//!
+//! let batch = DisputeBatch::new(filter, DisputeAction::AcceptLiability);
+//!
+//! // Review first...
+//! let preview = batch.clone().dry_run(true).execute(&client).await?;
+//! println!("Would accept liability on {} disputes", preview.would_apply());
+//!
+//! // ...then run it
+//! let report = batch.execute(&client).await?;
+//! println!("{} applied, {} failed", report.applied(), report.failed());
+//! # Ok(())
+//! # }
+//! ```
+
+use tokio::task::JoinSet;
+
+use crate::client::PayrixClient;
+use crate::entity::EntityType;
+use crate::error::{Error, Result};
+use crate::search::SearchBuilder;
+use crate::types::{Chargeback, ChargebackCycle, PayrixId};
+
+use super::dispute_handling::{ActiveDispute, ChargebackDispute, Evidence};
+
+/// Default number of disputes processed at the same time.
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 4;
+
+// =============================================================================
+// Section 1: Filter and Action
+// =============================================================================
+
+/// Selects which disputes a batch operates on.
+///
+/// Only open, actionable chargebacks are ever considered. Every criterion that
+/// is set must match; unset criteria match everything.
+#[derive(Debug, Clone, Default)]
+pub struct DisputeFilter {
+    /// Only disputes for this merchant.
+    pub merchant: Option<String>,
+
+    /// Only disputes whose total (in cents) is at or below this amount.
+    pub max_amount: Option<i64>,
+
+    /// Only disputes with one of these reason codes.
+    pub reason_codes: Vec<String>,
+
+    /// Only disputes in one of these cycles.
+    pub cycles: Vec<ChargebackCycle>,
+}
+
+impl DisputeFilter {
+    /// Create a filter that matches every open, actionable dispute.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only include disputes for a merchant.
+    pub fn merchant(mut self, merchant_id: impl Into<String>) -> Self {
+        self.merchant = Some(merchant_id.into());
+        self
+    }
+
+    /// Only include disputes at or below an amount in cents.
+    pub fn max_amount(mut self, cents: i64) -> Self {
+        self.max_amount = Some(cents);
+        self
+    }
+
+    /// Add an accepted reason code.
+    pub fn reason_code(mut self, code: impl Into<String>) -> Self {
+        self.reason_codes.push(code.into());
+        self
+    }
+
+    /// Add an accepted cycle.
+    pub fn cycle(mut self, cycle: ChargebackCycle) -> Self {
+        self.cycles.push(cycle);
+        self
+    }
+
+    /// Check whether a chargeback matches this filter.
+    ///
+    /// Chargebacks without a total never match a `max_amount` filter, and
+    /// chargebacks without a reason code never match a `reason_codes` filter.
+    pub fn matches(&self, chargeback: &Chargeback) -> bool {
+        let merchant_ok = self.merchant.as_ref().is_none_or(|merchant| {
+            chargeback
+                .merchant
+                .as_ref()
+                .is_some_and(|m| m.as_str() == merchant)
+        });
+        let amount_ok = self
+            .max_amount
+            .is_none_or(|max| chargeback.total.is_some_and(|total| total <= max));
+        let reason_ok = self.reason_codes.is_empty()
+            || chargeback
+                .reason_code
+                .as_ref()
+                .is_some_and(|code| self.reason_codes.contains(code));
+        let cycle_ok = self.cycles.is_empty()
+            || chargeback.cycle.is_some_and(|cycle| self.cycles.contains(&cycle));
+
+        merchant_ok && amount_ok && reason_ok && cycle_ok
+    }
+
+    /// Build the Payrix search used to fetch candidate chargebacks.
+    ///
+    /// The search narrows by merchant, status and actionability; the remaining
+    /// criteria are applied locally by [`matches`](Self::matches).
+    fn to_search(&self) -> String {
+        let mut search = SearchBuilder::new();
+        if let Some(merchant) = &self.merchant {
+            search = search.field("merchant", merchant);
+        }
+        search.field("status", "open").field("actionable", "1").build()
+    }
+}
+
+/// The action applied to every dispute in a batch.
+#[derive(Debug, Clone)]
+pub enum DisputeAction {
+    /// Accept liability and close the dispute.
+    AcceptLiability,
+    /// Represent the dispute with the same evidence for every chargeback.
+    Represent(Evidence),
+}
+
+impl DisputeAction {
+    /// Short name of the action for reporting.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::AcceptLiability => "accept_liability",
+            Self::Represent(_) => "represent",
+        }
+    }
+
+    /// Whether the action is allowed for a dispute in its current state.
+    fn is_available(&self, dispute: &ChargebackDispute) -> bool {
+        matches!(
+            dispute,
+            ChargebackDispute::Active(
+                ActiveDispute::First(_)
+                    | ActiveDispute::PreArbitration(_)
+                    | ActiveDispute::SecondChargeback(_)
+            )
+        )
+    }
+
+    /// Apply the action, returning the new state name.
+    async fn apply(&self, client: &PayrixClient, dispute: ChargebackDispute) -> Result<&'static str> {
+        let active = match dispute {
+            ChargebackDispute::Active(active) => active,
+            ChargebackDispute::Terminal(_) => {
+                return Err(Error::Validation("Dispute is already closed".to_string()));
+            }
+        };
+
+        // The typed transitions reload the chargeback, so the reported state
+        // reflects what Payrix recorded rather than the expected transition.
+        let updated = match (self, active) {
+            (Self::AcceptLiability, ActiveDispute::First(d)) => {
+                d.accept_liability(client).await?.into_inner()
+            }
+            (Self::AcceptLiability, ActiveDispute::PreArbitration(d)) => {
+                d.accept_liability(client).await?.into_inner()
+            }
+            (Self::AcceptLiability, ActiveDispute::SecondChargeback(d)) => {
+                d.accept_liability(client).await?.into_inner()
+            }
+            (Self::Represent(evidence), ActiveDispute::First(d)) => {
+                d.represent(client, evidence.clone()).await?.into_inner()
+            }
+            (Self::Represent(evidence), ActiveDispute::PreArbitration(d)) => {
+                d.represent(client, evidence.clone()).await?.into_inner()
+            }
+            (Self::Represent(evidence), ActiveDispute::SecondChargeback(d)) => {
+                d.represent(client, evidence.clone()).await?.into_inner()
+            }
+            (_, other) => {
+                return Err(Error::Validation(format!(
+                    "Action {} is not available in state {}",
+                    self.name(),
+                    other.state_name()
+                )));
+            }
+        };
+
+        Ok(ChargebackDispute::from_chargeback(updated).state_name())
+    }
+}
+
+// =============================================================================
+// Section 2: Report Types
+// =============================================================================
+
+/// What happened to a single dispute in a batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisputeOutcome {
+    /// Dry run: the action would be applied.
+    WouldApply,
+    /// The action was applied; the dispute is now in `new_state`.
+    Applied {
+        /// State name after the action.
+        new_state: &'static str,
+    },
+    /// The action is not valid for this dispute and was not attempted.
+    Skipped {
+        /// Why the dispute was skipped.
+        reason: String,
+    },
+    /// The action was attempted and failed.
+    Failed {
+        /// The error message.
+        error: String,
+    },
+}
+
+/// Result for one dispute in a batch.
+#[derive(Debug, Clone)]
+pub struct DisputeBatchItem {
+    /// The chargeback ID.
+    pub chargeback_id: PayrixId,
+
+    /// State name before the action.
+    pub state: &'static str,
+
+    /// Disputed amount in cents.
+    pub total: Option<i64>,
+
+    /// Card network reason code.
+    pub reason_code: Option<String>,
+
+    /// What happened.
+    pub outcome: DisputeOutcome,
+}
+
+/// Per-dispute report for a batch run.
+#[derive(Debug, Clone)]
+pub struct DisputeBatchReport {
+    /// The action that was (or would have been) applied.
+    pub action: &'static str,
+
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+
+    /// One entry per matching dispute, in the order they were fetched.
+    pub items: Vec<DisputeBatchItem>,
+}
+
+impl DisputeBatchReport {
+    /// Number of disputes the action was applied to.
+    pub fn applied(&self) -> usize {
+        self.count(|o| matches!(o, DisputeOutcome::Applied { .. }))
+    }
+
+    /// Number of disputes the action would be applied to in a dry run.
+    pub fn would_apply(&self) -> usize {
+        self.count(|o| matches!(o, DisputeOutcome::WouldApply))
+    }
+
+    /// Number of disputes that were skipped.
+    pub fn skipped(&self) -> usize {
+        self.count(|o| matches!(o, DisputeOutcome::Skipped { .. }))
+    }
+
+    /// Number of disputes where the action failed.
+    pub fn failed(&self) -> usize {
+        self.count(|o| matches!(o, DisputeOutcome::Failed { .. }))
+    }
+
+    /// Sum of dispute totals (in cents) the action was applied to.
+    pub fn applied_total(&self) -> i64 {
+        self.items
+            .iter()
+            .filter(|i| matches!(i.outcome, DisputeOutcome::Applied { .. }))
+            .filter_map(|i| i.total)
+            .sum()
+    }
+
+    fn count(&self, predicate: impl Fn(&DisputeOutcome) -> bool) -> usize {
+        self.items.iter().filter(|i| predicate(&i.outcome)).count()
+    }
+}
+
+// =============================================================================
+// Section 3: Batch Execution
+// =============================================================================
+
+/// A bulk operation over disputes matching a filter.
+#[derive(Debug, Clone)]
+pub struct DisputeBatch {
+    filter: DisputeFilter,
+    action: DisputeAction,
+    concurrency: usize,
+    dry_run: bool,
+}
+
+impl DisputeBatch {
+    /// Create a batch applying `action` to disputes matching `filter`.
+    pub fn new(filter: DisputeFilter, action: DisputeAction) -> Self {
+        Self {
+            filter,
+            action,
+            concurrency: DEFAULT_BATCH_CONCURRENCY,
+            dry_run: false,
+        }
+    }
+
+    /// Set the maximum number of disputes processed at once (minimum 1).
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Enable or disable dry-run mode.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Fetch the disputes this batch would act on.
+    pub async fn select(&self, client: &PayrixClient) -> Result<Vec<ChargebackDispute>> {
+        let chargebacks: Vec<Chargeback> = client
+            .search(EntityType::Chargebacks, &self.filter.to_search())
+            .await?;
+
+        Ok(chargebacks
+            .into_iter()
+            .filter(|cb| self.filter.matches(cb))
+            .map(ChargebackDispute::from_chargeback)
+            .collect())
+    }
+
+    /// Fetch matching disputes and apply the action to each of them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error only if the evidence is invalid or the disputes cannot
+    /// be fetched. Per-dispute failures are reported in the returned
+    /// [`DisputeBatchReport`].
+    pub async fn execute(&self, client: &PayrixClient) -> Result<DisputeBatchReport> {
+        if let DisputeAction::Represent(evidence) = &self.action {
+            evidence.validate()?;
+        }
+
+        let disputes = self.select(client).await?;
+        Ok(self.execute_on(client, disputes).await)
+    }
+
+    /// Apply the action to an already-selected list of disputes.
+    ///
+    /// The filter is not re-applied; use this to act on disputes obtained from
+    /// [`select`](Self::select) or from webhooks.
+    pub async fn execute_on(
+        &self,
+        client: &PayrixClient,
+        disputes: Vec<ChargebackDispute>,
+    ) -> DisputeBatchReport {
+        let mut items: Vec<DisputeBatchItem> = disputes
+            .iter()
+            .map(|dispute| DisputeBatchItem {
+                chargeback_id: dispute.id().clone(),
+                state: dispute.state_name(),
+                total: dispute.inner().total,
+                reason_code: dispute.inner().reason_code.clone(),
+                outcome: if self.action.is_available(dispute) {
+                    DisputeOutcome::WouldApply
+                } else {
+                    DisputeOutcome::Skipped {
+                        reason: format!(
+                            "{} is not available in state {}",
+                            self.action.name(),
+                            dispute.state_name()
+                        ),
+                    }
+                },
+            })
+            .collect();
+
+        if !self.dry_run {
+            let pending: Vec<(usize, ChargebackDispute)> = disputes
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| items[*i].outcome == DisputeOutcome::WouldApply)
+                .collect();
+
+            let mut tasks = JoinSet::new();
+            for (index, dispute) in pending {
+                // Keep at most `concurrency` requests in flight
+                while tasks.len() >= self.concurrency {
+                    if let Some(done) = tasks.join_next().await {
+                        record_outcome(&mut items, done);
+                    }
+                }
+
+                let client = client.clone();
+                let action = self.action.clone();
+                tasks.spawn(async move { (index, action.apply(&client, dispute).await) });
+            }
+
+            while let Some(done) = tasks.join_next().await {
+                record_outcome(&mut items, done);
+            }
+
+            for item in items.iter_mut().filter(|i| i.outcome == DisputeOutcome::WouldApply) {
+                item.outcome = DisputeOutcome::Failed {
+                    error: "Dispute task panicked".to_string(),
+                };
+            }
+        }
+
+        DisputeBatchReport {
+            action: self.action.name(),
+            dry_run: self.dry_run,
+            items,
+        }
+    }
+}
+
+/// Store the result of a finished task in its report slot.
+fn record_outcome(
+    items: &mut [DisputeBatchItem],
+    done: std::result::Result<(usize, Result<&'static str>), tokio::task::JoinError>,
+) {
+    match done {
+        Ok((index, Ok(new_state))) => items[index].outcome = DisputeOutcome::Applied { new_state },
+        Ok((index, Err(e))) => {
+            items[index].outcome = DisputeOutcome::Failed {
+                error: e.to_string(),
+            }
+        }
+        // A panicked task loses its index; its slot stays `WouldApply` and is
+        // marked failed once every task has finished.
+        Err(_) => {}
+    }
+}
+
+// =============================================================================
+// Section 4: Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ChargebackStatusValue;
+
+    fn make_chargeback(
+        total: Option<i64>,
+        reason_code: Option<&str>,
+        cycle: Option<ChargebackCycle>,
+    ) -> Chargeback {
+        serde_json::from_value(serde_json::json!({
+            "id": "t1_chb_12345678901234567890123",
+            "merchant": "t1_mer_12345678901234567890123",
+            "total": total,
+            "reasonCode": reason_code,
+            "cycle": cycle,
+            "status": ChargebackStatusValue::Open,
+            "actionable": 1,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_empty_filter_matches_everything() {
+        let filter = DisputeFilter::new();
+        assert!(filter.matches(&make_chargeback(None, None, None)));
+        assert!(filter.matches(&make_chargeback(Some(1_000_000), Some("10.4"), None)));
+    }
+
+    #[test]
+    fn test_filter_merchant() {
+        let cb = make_chargeback(Some(1000), None, None);
+        assert!(DisputeFilter::new().merchant("t1_mer_12345678901234567890123").matches(&cb));
+        assert!(!DisputeFilter::new().merchant("t1_mer_99999999999999999999999").matches(&cb));
+    }
+
+    #[test]
+    fn test_filter_max_amount() {
+        let filter = DisputeFilter::new().max_amount(2500);
+        assert!(filter.matches(&make_chargeback(Some(2500), None, None)));
+        assert!(!filter.matches(&make_chargeback(Some(2501), None, None)));
+        assert!(!filter.matches(&make_chargeback(None, None, None)));
+    }
+
+    #[test]
+    fn test_filter_reason_codes() {
+        let filter = DisputeFilter::new().reason_code("10.4").reason_code("4853");
+        assert!(filter.matches(&make_chargeback(None, Some("4853"), None)));
+        assert!(!filter.matches(&make_chargeback(None, Some("13.1"), None)));
+        assert!(!filter.matches(&make_chargeback(None, None, None)));
+    }
+
+    #[test]
+    fn test_filter_cycles() {
+        let filter = DisputeFilter::new().cycle(ChargebackCycle::First);
+        assert!(filter.matches(&make_chargeback(None, None, Some(ChargebackCycle::First))));
+        assert!(!filter.matches(&make_chargeback(
+            None,
+            None,
+            Some(ChargebackCycle::PreArbitration)
+        )));
+    }
+
+    #[test]
+    fn test_filter_search() {
+        let filter = DisputeFilter::new()
+            .merchant("t1_mer_12345678901234567890123")
+            .max_amount(100);
+        assert_eq!(
+            filter.to_search(),
+            "merchant[equals]=t1_mer_12345678901234567890123&status[equals]=open&actionable[equals]=1"
+        );
+    }
+
+    #[test]
+    fn test_action_availability() {
+        let action = DisputeAction::AcceptLiability;
+        let first =
+            ChargebackDispute::from_chargeback(make_chargeback(None, None, Some(ChargebackCycle::First)));
+        let retrieval = ChargebackDispute::from_chargeback(make_chargeback(
+            None,
+            None,
+            Some(ChargebackCycle::Retrieval),
+        ));
+        assert!(action.is_available(&first));
+        assert!(!action.is_available(&retrieval));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_makes_no_requests() {
+        // An unroutable base URL: any request would fail the test
+        let client = PayrixClient::with_config(
+            crate::Config::new("key", crate::Environment::Test).with_base_url("http://127.0.0.1:9/"),
+        )
+        .unwrap();
+
+        let disputes = vec![
+            ChargebackDispute::from_chargeback(make_chargeback(
+                Some(1000),
+                None,
+                Some(ChargebackCycle::First),
+            )),
+            ChargebackDispute::from_chargeback(make_chargeback(
+                Some(2000),
+                None,
+                Some(ChargebackCycle::Representment),
+            )),
+        ];
+
+        let report = DisputeBatch::new(DisputeFilter::new(), DisputeAction::AcceptLiability)
+            .dry_run(true)
+            .execute_on(&client, disputes)
+            .await;
+
+        assert!(report.dry_run);
+        assert_eq!(report.action, "accept_liability");
+        assert_eq!(report.would_apply(), 1);
+        assert_eq!(report.skipped(), 1);
+        assert_eq!(report.applied(), 0);
+        assert_eq!(report.items[1].state, "representment");
+    }
+}
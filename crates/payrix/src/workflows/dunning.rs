@@ -0,0 +1,753 @@
+//! Dunning: scheduled retries for failed subscription payments.
+//!
+//! When a recurring payment fails, the [`DunningEngine`] retries it on a
+//! configurable schedule (for example 1, 3 and 7 days after the failure),
+//! charging the token linked to the subscription through `subscriptionTokens`.
+//! If every retry fails, or the subscription reaches its `max_failures` limit,
+//! the engine pauses or cancels the subscription.
+//!
+//! Each failed retry is classified with [`DeclineInfo`]. Declines that will
+//! not succeed on a later retry (an invalid card, suspected fraud, ...) end
+//! dunning straight away unless [`DunningConfig::retry_hard_declines`] is set.
+//!
+//! # Persistence
+//!
+//! The engine itself is stateless. Each subscription in dunning has a
+//! [`DunningState`], which is `Serialize`/`Deserialize` so it can be stored in
+//! a database or file and survive restarts. A scheduler calls
+//! [`DunningEngine::process`] for every state that [`DunningState::is_due`],
+//! then saves the updated state.
+//!
+//! # Events
+//!
+//! Every call to [`DunningEngine::process`] returns the [`DunningEvent`]s it
+//! produced, in order. Use them to notify the customer, for example emailing a
+//! "please update your card" message on [`DunningEvent::RetryFailed`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use chrono::Utc;
+//! use payrix::{PayrixClient, Environment};
+//! use payrix::workflows::dunning::{DunningConfig, DunningEngine, DunningEvent};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = PayrixClient::new("api-key", Environment::Test)?;
+//! let engine = DunningEngine::new(DunningConfig::default());
+//! let today = Utc::now().naive_utc().date();
+//!
+//! // When a payment failure is reported (e.g. by webhook)
+//! let mut state = engine.start("t1_sbn_12345678901234567890123", today);
+//! let saved = serde_json::to_string(&state)?;
+//!
+//! // Later, from a daily job
+//! let mut state: payrix::workflows::dunning::DunningState = serde_json::from_str(&saved)?;
+//! if state.is_due(today) {
+//!     for event in engine.process(&client, &mut state, today).await? {
+//!         if let DunningEvent::RetryFailed { next_attempt, .. } = event {
+//!             println!("Retry failed, next attempt on {:?}", next_attempt);
+//!         }
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::client::PayrixClient;
+use crate::entity::EntityType;
+use crate::types::{Plan, Subscription, Transaction, TransactionStatus};
+
+use super::declines::DeclineInfo;
+use super::subscription_management::{
+    charge_subscription, find_order_txn, get_subscription_token_id, SubscriptionError,
+    SubscriptionResult,
+};
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// What to do with a subscription once dunning is exhausted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DunningFinalAction {
+    /// Pause the subscription (`frozen = 1`) so it can be resumed later.
+    #[default]
+    Pause,
+
+    /// Cancel the subscription (`inactive = 1`).
+    Cancel,
+
+    /// Leave the subscription as it is.
+    None,
+}
+
+/// Dunning configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DunningConfig {
+    /// Days after the original failure on which to retry, in increasing order.
+    ///
+    /// Defaults to `[1, 3, 7]`.
+    pub retry_days: Vec<u32>,
+
+    /// Action taken after the final failed retry.
+    pub final_action: DunningFinalAction,
+
+    /// Stop retrying once the subscription's `failures` reaches `max_failures`
+    /// (falling back to the plan's `max_failures`).
+    pub respect_max_failures: bool,
+
+    /// Keep retrying after a decline that is not [retryable](DeclineInfo::is_retryable).
+    ///
+    /// Off by default: retrying a closed account or a fraud decline only adds
+    /// failures, so dunning ends and the final action is applied.
+    #[serde(default)]
+    pub retry_hard_declines: bool,
+}
+
+impl Default for DunningConfig {
+    fn default() -> Self {
+        Self {
+            retry_days: vec![1, 3, 7],
+            final_action: DunningFinalAction::Pause,
+            respect_max_failures: true,
+            retry_hard_declines: false,
+        }
+    }
+}
+
+impl DunningConfig {
+    /// Validate the configuration.
+    ///
+    /// Retry days must be non-empty and strictly increasing.
+    pub fn validate(&self) -> SubscriptionResult<()> {
+        if self.retry_days.is_empty() {
+            return Err(SubscriptionError::CalculationError(
+                "Dunning schedule needs at least one retry".to_string(),
+            ));
+        }
+        if self.retry_days.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(SubscriptionError::CalculationError(
+                "Dunning retry days must be strictly increasing".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// State and Events
+// ============================================================================
+
+/// Where a subscription is in the dunning process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DunningStatus {
+    /// Retries are still scheduled.
+    Active,
+
+    /// A retry's payment is still being processed (an eCheck sale, or a card
+    /// sale awaiting the processor). The next run checks its outcome instead
+    /// of charging again.
+    Processing,
+
+    /// A retry succeeded.
+    Recovered,
+
+    /// All retries failed or the failure limit was reached, but the final
+    /// action could not be applied yet. The next run retries only the final
+    /// action; nothing is charged again.
+    FinalActionPending,
+
+    /// All retries failed or the failure limit was reached.
+    Exhausted,
+
+    /// The subscription was cancelled outside of dunning.
+    Abandoned,
+}
+
+/// A single retry attempt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DunningAttempt {
+    /// Attempt number, starting at 1.
+    pub number: u32,
+
+    /// Date of the attempt.
+    pub date: NaiveDate,
+
+    /// Transaction created for the attempt, if the API accepted it.
+    pub transaction_id: Option<String>,
+
+    /// Whether the payment succeeded.
+    pub succeeded: bool,
+
+    /// Failure description, if it failed.
+    pub error: Option<String>,
+
+    /// Why the payment was declined, if a transaction was created and failed.
+    #[serde(default)]
+    pub decline: Option<DeclineInfo>,
+}
+
+/// Persistable dunning state for one subscription.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DunningState {
+    /// The subscription being retried.
+    pub subscription_id: String,
+
+    /// Date of the original failed payment.
+    pub failed_on: NaiveDate,
+
+    /// Amount to charge in cents; `None` uses the plan amount plus subscription tax.
+    pub amount: Option<i64>,
+
+    /// Attempts made so far.
+    pub attempts: Vec<DunningAttempt>,
+
+    /// Date of the next scheduled attempt.
+    pub next_attempt: Option<NaiveDate>,
+
+    /// Current status.
+    pub status: DunningStatus,
+}
+
+impl DunningState {
+    /// Whether an attempt, or a pending final action, is due on or before `today`.
+    pub fn is_due(&self, today: NaiveDate) -> bool {
+        match self.status {
+            DunningStatus::Active => self.next_attempt.is_some_and(|d| d <= today),
+            DunningStatus::Processing | DunningStatus::FinalActionPending => true,
+            _ => false,
+        }
+    }
+}
+
+/// Something that happened while processing a dunning state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DunningEvent {
+    /// A retry was charged successfully.
+    RetrySucceeded {
+        /// Subscription ID.
+        subscription_id: String,
+        /// Attempt number.
+        attempt: u32,
+        /// The successful transaction.
+        transaction_id: String,
+        /// Amount charged in cents.
+        amount: i64,
+    },
+
+    /// A retry was accepted but is still being processed; nothing more is
+    /// charged until its outcome is known.
+    RetryProcessing {
+        /// Subscription ID.
+        subscription_id: String,
+        /// Attempt number.
+        attempt: u32,
+        /// The pending transaction.
+        transaction_id: String,
+    },
+
+    /// A retry failed.
+    RetryFailed {
+        /// Subscription ID.
+        subscription_id: String,
+        /// Attempt number.
+        attempt: u32,
+        /// Failure description.
+        error: String,
+        /// Next scheduled attempt, or `None` if this was the last.
+        next_attempt: Option<NaiveDate>,
+    },
+
+    /// The failure limit was reached before all retries were used.
+    FailureLimitReached {
+        /// Subscription ID.
+        subscription_id: String,
+        /// The subscription's failure count.
+        failures: i32,
+        /// The applicable `max_failures`.
+        max_failures: i32,
+    },
+
+    /// A retry was declined for a reason a later retry will not fix, so the
+    /// remaining retries were skipped.
+    DeclineNotRetryable {
+        /// Subscription ID.
+        subscription_id: String,
+        /// The decline.
+        decline: DeclineInfo,
+    },
+
+    /// Dunning is exhausted and the subscription was paused.
+    SubscriptionPaused {
+        /// Subscription ID.
+        subscription_id: String,
+    },
+
+    /// Dunning is exhausted and the subscription was cancelled.
+    SubscriptionCancelled {
+        /// Subscription ID.
+        subscription_id: String,
+    },
+
+    /// Dunning is exhausted but pausing or cancelling the subscription
+    /// failed. The state is [`DunningStatus::FinalActionPending`] and the next
+    /// run tries the final action again.
+    FinalActionFailed {
+        /// Subscription ID.
+        subscription_id: String,
+        /// Why the final action failed.
+        error: String,
+    },
+
+    /// Dunning is exhausted and the subscription was left unchanged.
+    Exhausted {
+        /// Subscription ID.
+        subscription_id: String,
+    },
+
+    /// The subscription was already cancelled, so dunning stopped.
+    Abandoned {
+        /// Subscription ID.
+        subscription_id: String,
+    },
+}
+
+// ============================================================================
+// Engine
+// ============================================================================
+
+/// Runs the dunning schedule for failed subscription payments.
+#[derive(Debug, Clone, Default)]
+pub struct DunningEngine {
+    config: DunningConfig,
+}
+
+impl DunningEngine {
+    /// Create an engine with the given configuration.
+    pub fn new(config: DunningConfig) -> Self {
+        Self { config }
+    }
+
+    /// Get the configuration.
+    pub fn config(&self) -> &DunningConfig {
+        &self.config
+    }
+
+    /// Start dunning for a subscription whose payment failed on `failed_on`.
+    pub fn start(&self, subscription_id: impl Into<String>, failed_on: NaiveDate) -> DunningState {
+        DunningState {
+            subscription_id: subscription_id.into(),
+            failed_on,
+            amount: None,
+            attempts: Vec::new(),
+            next_attempt: self.attempt_date(failed_on, 0),
+            status: DunningStatus::Active,
+        }
+    }
+
+    /// Date of the attempt with the given zero-based index, if scheduled.
+    fn attempt_date(&self, failed_on: NaiveDate, index: usize) -> Option<NaiveDate> {
+        self.config
+            .retry_days
+            .get(index)
+            .map(|days| failed_on + Duration::days(*days as i64))
+    }
+
+    /// Run the attempt that is due for `state`, if any.
+    ///
+    /// Does nothing if the state is not [due](DunningState::is_due). Otherwise
+    /// loads the subscription and either charges it or, when the failure limit
+    /// has been reached, applies the final action. `state` is updated in place
+    /// and should be saved afterwards.
+    ///
+    /// A declined payment is not an error: it is recorded as a failed attempt.
+    /// A payment still pending with the processor is recorded and leaves the
+    /// state [`DunningStatus::Processing`]; later runs check that transaction
+    /// rather than charging again. Errors are returned when the subscription,
+    /// plan or token cannot be loaded, the charge request fails, or the final
+    /// action cannot be applied before anything was charged; `state` is then
+    /// left unchanged so the run can be repeated. Each attempt's sale carries
+    /// the order `DUNNING-{subscription_id}-{attempt}`, and a repeated run
+    /// reuses a sale already made with that order, so a charge whose response
+    /// was lost is not made twice.
+    ///
+    /// Once a retry has been charged, its attempt is always recorded. If the
+    /// final action then fails, `state` becomes
+    /// [`DunningStatus::FinalActionPending`] and a
+    /// [`DunningEvent::FinalActionFailed`] is returned; the next run applies
+    /// only the final action and never charges again.
+    pub async fn process(
+        &self,
+        client: &PayrixClient,
+        state: &mut DunningState,
+        today: NaiveDate,
+    ) -> SubscriptionResult<Vec<DunningEvent>> {
+        self.config.validate()?;
+        if !state.is_due(today) {
+            return Ok(Vec::new());
+        }
+        if state.status == DunningStatus::FinalActionPending {
+            return Ok(vec![self.finish(client, state).await?]);
+        }
+        if state.status == DunningStatus::Processing {
+            return self.check_processing(client, state).await;
+        }
+
+        let subscription: Subscription = client
+            .get_one(EntityType::Subscriptions, &state.subscription_id)
+            .await?
+            .ok_or_else(|| SubscriptionError::SubscriptionNotFound(state.subscription_id.clone()))?;
+
+        if subscription.inactive {
+            state.status = DunningStatus::Abandoned;
+            state.next_attempt = None;
+            return Ok(vec![DunningEvent::Abandoned {
+                subscription_id: state.subscription_id.clone(),
+            }]);
+        }
+
+        let plan_id = subscription
+            .plan
+            .as_ref()
+            .ok_or_else(|| SubscriptionError::CalculationError("Subscription has no plan".to_string()))?;
+        let plan: Plan = client
+            .get_one(EntityType::Plans, plan_id.as_str())
+            .await?
+            .ok_or_else(|| SubscriptionError::PlanNotFound(plan_id.to_string()))?;
+
+        let mut events = Vec::new();
+
+        if let Some((failures, max_failures)) = self.failure_limit(&subscription, &plan) {
+            events.push(DunningEvent::FailureLimitReached {
+                subscription_id: state.subscription_id.clone(),
+                failures,
+                max_failures,
+            });
+            events.push(self.finish(client, state).await?);
+            return Ok(events);
+        }
+
+        let token_id = get_subscription_token_id(client, &state.subscription_id).await?;
+        let amount = state
+            .amount
+            .unwrap_or_else(|| plan.amount.unwrap_or(0) + subscription.tax.unwrap_or(0));
+
+        // Each attempt has its own order, so a run repeated after a lost
+        // response finds the charge instead of making another.
+        let number = state.attempts.len() as u32 + 1;
+        let order = format!("DUNNING-{}-{}", state.subscription_id, number);
+        let txn = match find_order_txn(client, &order).await? {
+            Some(txn) => txn,
+            None => {
+                charge_subscription(client, &subscription, &plan, &token_id, amount, &order).await?
+            }
+        };
+        state.attempts.push(DunningAttempt {
+            number,
+            date: today,
+            transaction_id: Some(txn.id.to_string()),
+            succeeded: false,
+            error: None,
+            decline: None,
+        });
+        events.extend(self.settle_attempt(client, state, &txn).await);
+        Ok(events)
+    }
+
+    /// Check on a retry that was still processing at the last run.
+    async fn check_processing(
+        &self,
+        client: &PayrixClient,
+        state: &mut DunningState,
+    ) -> SubscriptionResult<Vec<DunningEvent>> {
+        let txn_id = state
+            .attempts
+            .last()
+            .and_then(|a| a.transaction_id.clone())
+            .ok_or_else(|| {
+                SubscriptionError::InvalidState("Processing dunning attempt has no transaction".to_string())
+            })?;
+        let txn: Transaction = client
+            .get_one(EntityType::Txns, &txn_id)
+            .await?
+            .ok_or_else(|| SubscriptionError::InvalidState(format!("Transaction {} not found", txn_id)))?;
+        Ok(self.settle_attempt(client, state, &txn).await)
+    }
+
+    /// Record the outcome of the latest attempt's transaction.
+    ///
+    /// A transaction still pending leaves the state
+    /// [`DunningStatus::Processing`] with nothing else scheduled. A failure
+    /// schedules the next retry or, after the last one, applies the final
+    /// action; the attempt is recorded first, so a failure to pause or cancel
+    /// is retried on its own without charging again.
+    async fn settle_attempt(
+        &self,
+        client: &PayrixClient,
+        state: &mut DunningState,
+        txn: &Transaction,
+    ) -> Vec<DunningEvent> {
+        let subscription_id = state.subscription_id.clone();
+        let number = state.attempts.len() as u32;
+        let mut events = Vec::new();
+
+        match txn_outcome(txn) {
+            TxnOutcome::Pending => {
+                state.status = DunningStatus::Processing;
+                state.next_attempt = None;
+                events.push(DunningEvent::RetryProcessing {
+                    subscription_id,
+                    attempt: number,
+                    transaction_id: txn.id.to_string(),
+                });
+                return events;
+            }
+            TxnOutcome::Succeeded => {
+                if let Some(attempt) = state.attempts.last_mut() {
+                    attempt.succeeded = true;
+                }
+                events.push(DunningEvent::RetrySucceeded {
+                    subscription_id,
+                    attempt: number,
+                    transaction_id: txn.id.to_string(),
+                    amount: txn.total.unwrap_or(0),
+                });
+                state.status = DunningStatus::Recovered;
+                state.next_attempt = None;
+                return events;
+            }
+            TxnOutcome::Failed => {}
+        }
+
+        let error = format!("Transaction status: {:?}", txn.status);
+        let decline = fetch_decline(client, txn).await;
+        let hard_decline = decline
+            .clone()
+            .filter(|d| !d.is_retryable() && !self.config.retry_hard_declines);
+        let next_attempt = self
+            .attempt_date(state.failed_on, state.attempts.len())
+            .filter(|_| hard_decline.is_none());
+        if let Some(attempt) = state.attempts.last_mut() {
+            attempt.error = Some(error.clone());
+            attempt.decline = decline;
+        }
+        events.push(DunningEvent::RetryFailed {
+            subscription_id: subscription_id.clone(),
+            attempt: number,
+            error,
+            next_attempt,
+        });
+        if let Some(decline) = hard_decline {
+            events.push(DunningEvent::DeclineNotRetryable {
+                subscription_id: subscription_id.clone(),
+                decline,
+            });
+        }
+
+        state.status = DunningStatus::Active;
+        state.next_attempt = next_attempt;
+        if next_attempt.is_none() {
+            state.status = DunningStatus::FinalActionPending;
+            match self.finish(client, state).await {
+                Ok(event) => events.push(event),
+                Err(e) => events.push(DunningEvent::FinalActionFailed {
+                    subscription_id,
+                    error: e.to_string(),
+                }),
+            }
+        }
+        events
+    }
+
+    /// The subscription's failure count and limit, if the limit is reached.
+    fn failure_limit(&self, subscription: &Subscription, plan: &Plan) -> Option<(i32, i32)> {
+        if !self.config.respect_max_failures {
+            return None;
+        }
+        let max = subscription
+            .max_failures
+            .or(plan.max_failures)
+            .filter(|max| *max > 0)?;
+        let failures = subscription.failures.unwrap_or(0);
+        (failures >= max).then_some((failures, max))
+    }
+
+    /// Apply the final action and mark the state exhausted.
+    async fn finish(
+        &self,
+        client: &PayrixClient,
+        state: &mut DunningState,
+    ) -> SubscriptionResult<DunningEvent> {
+        let subscription_id = state.subscription_id.clone();
+        let event = match self.config.final_action {
+            DunningFinalAction::Pause => {
+                let _: Subscription = client
+                    .update(EntityType::Subscriptions, &subscription_id, &json!({"frozen": 1}))
+                    .await?;
+                DunningEvent::SubscriptionPaused { subscription_id }
+            }
+            DunningFinalAction::Cancel => {
+                let _: Subscription = client
+                    .update(EntityType::Subscriptions, &subscription_id, &json!({"inactive": 1}))
+                    .await?;
+                DunningEvent::SubscriptionCancelled { subscription_id }
+            }
+            DunningFinalAction::None => DunningEvent::Exhausted { subscription_id },
+        };
+
+        state.status = DunningStatus::Exhausted;
+        state.next_attempt = None;
+        Ok(event)
+    }
+}
+
+/// Classify a failed retry, from its results when they can be loaded.
+async fn fetch_decline(client: &PayrixClient, txn: &Transaction) -> Option<DeclineInfo> {
+    match DeclineInfo::fetch(client, txn.id.as_str()).await {
+        Ok(Some(decline)) => Some(decline),
+        _ => DeclineInfo::from_transaction(txn, &[]),
+    }
+}
+
+/// Where a retry's transaction stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TxnOutcome {
+    /// Approved, captured or settled.
+    Succeeded,
+    /// Awaiting the processor, as eCheck sales usually are at first.
+    Pending,
+    /// Declined or returned.
+    Failed,
+}
+
+fn txn_outcome(txn: &Transaction) -> TxnOutcome {
+    match txn.status {
+        Some(TransactionStatus::Approved)
+        | Some(TransactionStatus::Captured)
+        | Some(TransactionStatus::Settled) => TxnOutcome::Succeeded,
+        Some(TransactionStatus::Pending) => TxnOutcome::Pending,
+        _ => TxnOutcome::Failed,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        let config = DunningConfig::default();
+        assert_eq!(config.retry_days, vec![1, 3, 7]);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn config_validation() {
+        let empty = DunningConfig {
+            retry_days: vec![],
+            ..Default::default()
+        };
+        assert!(empty.validate().is_err());
+
+        let unordered = DunningConfig {
+            retry_days: vec![3, 1],
+            ..Default::default()
+        };
+        assert!(unordered.validate().is_err());
+    }
+
+    #[test]
+    fn start_schedules_first_retry() {
+        let engine = DunningEngine::default();
+        let state = engine.start("t1_sbn_12345678901234567890123", date(2024, 3, 10));
+
+        assert_eq!(state.status, DunningStatus::Active);
+        assert_eq!(state.next_attempt, Some(date(2024, 3, 11)));
+        assert!(!state.is_due(date(2024, 3, 10)));
+        assert!(state.is_due(date(2024, 3, 11)));
+        assert!(state.is_due(date(2024, 3, 15)));
+    }
+
+    #[test]
+    fn attempt_dates_follow_schedule() {
+        let engine = DunningEngine::default();
+        let failed_on = date(2024, 3, 10);
+        assert_eq!(engine.attempt_date(failed_on, 1), Some(date(2024, 3, 13)));
+        assert_eq!(engine.attempt_date(failed_on, 2), Some(date(2024, 3, 17)));
+        assert_eq!(engine.attempt_date(failed_on, 3), None);
+    }
+
+    #[test]
+    fn state_round_trips_through_json() {
+        let engine = DunningEngine::default();
+        let mut state = engine.start("t1_sbn_12345678901234567890123", date(2024, 3, 10));
+        state.attempts.push(DunningAttempt {
+            number: 1,
+            date: date(2024, 3, 11),
+            transaction_id: None,
+            succeeded: false,
+            error: Some("Declined".to_string()),
+            decline: None,
+        });
+
+        let json = serde_json::to_string(&state).unwrap();
+        let restored: DunningState = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, state);
+    }
+
+    #[test]
+    fn failure_limit_uses_subscription_then_plan() {
+        let engine = DunningEngine::default();
+        let plan: Plan = serde_json::from_value(json!({
+            "id": "t1_pln_12345678901234567890123",
+            "maxFailures": 3,
+        }))
+        .unwrap();
+        let mut sub: Subscription = serde_json::from_value(json!({
+            "id": "t1_sbn_12345678901234567890123",
+            "failures": 3,
+        }))
+        .unwrap();
+
+        assert_eq!(engine.failure_limit(&sub, &plan), Some((3, 3)));
+
+        sub.max_failures = Some(5);
+        assert_eq!(engine.failure_limit(&sub, &plan), None);
+
+        let lenient = DunningEngine::new(DunningConfig {
+            respect_max_failures: false,
+            ..Default::default()
+        });
+        sub.max_failures = None;
+        assert_eq!(lenient.failure_limit(&sub, &plan), None);
+    }
+
+    #[test]
+    fn attempt_outcome_classification() {
+        let txn = |status: i32| -> Transaction {
+            serde_json::from_value(json!({
+                "id": "t1_txn_12345678901234567890123",
+                "type": 1,
+                "status": status,
+            }))
+            .unwrap()
+        };
+        assert_eq!(txn_outcome(&txn(1)), TxnOutcome::Succeeded);
+        assert_eq!(txn_outcome(&txn(4)), TxnOutcome::Succeeded);
+        assert_eq!(txn_outcome(&txn(0)), TxnOutcome::Pending);
+        assert_eq!(txn_outcome(&txn(2)), TxnOutcome::Failed);
+        assert_eq!(txn_outcome(&txn(5)), TxnOutcome::Failed);
+    }
+}
@@ -0,0 +1,708 @@
+//! Evaluate a merchant's fees and fee rules offline.
+//!
+//! Payrix assesses a [`Fee`] when its schedule's event occurs (a capture, a
+//! refund, a chargeback, ...) and its [`FeeRule`]s match the transaction.
+//! [`FeeEngine`] performs the same evaluation locally against a hypothetical
+//! [`QuoteRequest`], so pricing can be quoted to merchants, compared with the
+//! fee [`Entry`] records Payrix actually created, or changed in a test to see
+//! what a new price would charge.
+//!
+//! # Evaluation Rules
+//!
+//! A fee is assessed when all of the following hold:
+//!
+//! - It is not inactive and the request date falls within `start`..=`finish`.
+//! - Its `schedule` is the request's event. Periodic schedules (daily,
+//!   monthly, boarding, ...) are never triggered by a transaction.
+//! - Every ungrouped rule matches, and each `grouping` has at least one
+//!   matching rule. Rules whose `application` is `collection` are ignored.
+//!
+//! A rule the engine cannot decide - an unsupported rule type, or a rule on
+//! a transaction attribute the request leaves unset - makes its fee
+//! [`SkipReason::Undetermined`] rather than silently passing or failing.
+//!
+//! The assessed amount follows the fee's unit:
+//!
+//! - **Fixed**: `amount` cents.
+//! - **Percent**: `amount` basis points of the event amount.
+//! - **Surcharge**: `amount` basis points, treating the event amount as
+//!   already including the fee.
+//!
+//! Percent and surcharge fees are capped at `maximum`. Fees with `txnFee`
+//! set never exceed the fee supplied on the transaction.
+//!
+//! Volume-based collection calculations depend on history the engine does
+//! not have, so they are not modelled.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use payrix::{PayrixClient, Environment};
+//! use payrix::types::{FeeSchedule, PaymentMethod, TransactionOrigin};
+//! use payrix::workflows::fee_engine::{FeeEngine, QuoteRequest};
+//!
+//! # async fn example() -> payrix::Result<()> {
+//! let client = PayrixClient::new("api-key", Environment::Test)?;
+//! let engine = FeeEngine::fetch(&client, "t1_mer_12345678901234567890123").await?;
+//!
+//! let quote = engine.quote(
+//!     &QuoteRequest::new(10_000, FeeSchedule::Capture)
+//!         .method(PaymentMethod::Visa)
+//!         .origin(TransactionOrigin::Ecommerce)
+//!         .bin("411111"),
+//! );
+//! println!("Fees on $100.00: {:.3} cents", quote.total);
+//! for skipped in &quote.skipped {
+//!     println!("{} not assessed: {:?}", skipped.fee, skipped.reason);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use chrono::{Datelike, NaiveDate, Utc};
+
+use crate::client::PayrixClient;
+use crate::entity::EntityType;
+use crate::error::Result;
+use crate::search::{SearchBuilder, SearchOperator};
+use crate::types::{
+    Entry, Fee, FeeApplication, FeeRule, FeeRuleType, FeeSchedule, FeeType, FeeUnit,
+    PaymentMethod, PayrixId, TransactionOrigin, TransactionType,
+};
+
+/// Fee IDs requested per `fee[in]` rule search.
+const RULE_BATCH_SIZE: usize = 50;
+
+// =============================================================================
+// Section 1: Quote Request
+// =============================================================================
+
+/// A hypothetical transaction event to price.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuoteRequest {
+    /// Event amount in cents.
+    pub amount: i64,
+    /// The event being priced, matched against each fee's `schedule`.
+    pub event: FeeSchedule,
+    /// Date of the event, checked against each fee's `start` and `finish`.
+    pub date: NaiveDate,
+    /// Card brand or bank account type (`METHOD` rules).
+    pub method: Option<PaymentMethod>,
+    /// How the transaction was taken (`ORIGIN` rules).
+    pub origin: Option<TransactionOrigin>,
+    /// Transaction type (`TYPE` rules).
+    pub txn_type: Option<TransactionType>,
+    /// Card BIN, matched by prefix (`BIN` rules).
+    pub bin: Option<String>,
+    /// Merchant category code (`MCC` rules).
+    pub mcc: Option<String>,
+    /// Card issuer country (`ISSUERCOUNTRY` rules).
+    pub issuer_country: Option<String>,
+    /// Merchant country (`MERCHANTCOUNTRY` rules).
+    pub merchant_country: Option<String>,
+    /// Yes/no attributes such as [`FeeRuleType::Swiped`] or
+    /// [`FeeRuleType::International`].
+    pub flags: HashMap<FeeRuleType, bool>,
+    /// Fee supplied on the transaction in cents, for fees with `txnFee` set.
+    pub transaction_fee: Option<i64>,
+}
+
+impl QuoteRequest {
+    /// A request for `amount` cents on `event`, dated today, with no other
+    /// attributes set.
+    pub fn new(amount: i64, event: FeeSchedule) -> Self {
+        Self {
+            amount,
+            event,
+            date: Utc::now().date_naive(),
+            method: None,
+            origin: None,
+            txn_type: None,
+            bin: None,
+            mcc: None,
+            issuer_country: None,
+            merchant_country: None,
+            flags: HashMap::new(),
+            transaction_fee: None,
+        }
+    }
+
+    /// Set the event date.
+    pub fn date(mut self, date: NaiveDate) -> Self {
+        self.date = date;
+        self
+    }
+
+    /// Set the payment method.
+    pub fn method(mut self, method: PaymentMethod) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    /// Set the transaction origin.
+    pub fn origin(mut self, origin: TransactionOrigin) -> Self {
+        self.origin = Some(origin);
+        self
+    }
+
+    /// Set the transaction type.
+    pub fn txn_type(mut self, txn_type: TransactionType) -> Self {
+        self.txn_type = Some(txn_type);
+        self
+    }
+
+    /// Set the card BIN.
+    pub fn bin(mut self, bin: impl Into<String>) -> Self {
+        self.bin = Some(bin.into());
+        self
+    }
+
+    /// Set the merchant category code.
+    pub fn mcc(mut self, mcc: impl Into<String>) -> Self {
+        self.mcc = Some(mcc.into());
+        self
+    }
+
+    /// Set a yes/no attribute, e.g. `flag(FeeRuleType::Swiped, true)`.
+    pub fn flag(mut self, rule_type: FeeRuleType, value: bool) -> Self {
+        self.flags.insert(rule_type, value);
+        self
+    }
+
+    /// Set the fee supplied on the transaction.
+    pub fn transaction_fee(mut self, cents: i64) -> Self {
+        self.transaction_fee = Some(cents);
+        self
+    }
+}
+
+// =============================================================================
+// Section 2: Quote Results
+// =============================================================================
+
+/// A fee the engine would assess.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssessedFee {
+    /// The fee's ID.
+    pub fee: PayrixId,
+    /// The fee's name.
+    pub name: Option<String>,
+    /// Standard fee or third-party assessment.
+    pub fee_type: Option<FeeType>,
+    /// How the amount was computed.
+    pub unit: FeeUnit,
+    /// Amount charged in cents, to three decimal places.
+    pub amount: f64,
+    /// Whether `maximum` or the transaction-supplied fee reduced the amount.
+    pub capped: bool,
+}
+
+/// Why a fee was not assessed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SkipReason {
+    /// The fee is inactive.
+    Inactive,
+    /// The request date is before `start` or after `finish`.
+    OutsideDates,
+    /// The fee is triggered by a different event.
+    Schedule(Option<FeeSchedule>),
+    /// A rule (or every rule in its group) does not match.
+    RuleNotMatched {
+        /// The first rule that failed.
+        rule: PayrixId,
+    },
+    /// A rule could not be evaluated offline.
+    Undetermined {
+        /// The rule that could not be evaluated.
+        rule: PayrixId,
+        /// Its type, if known.
+        rule_type: Option<FeeRuleType>,
+    },
+    /// The fee has `txnFee` set but the request has no transaction fee.
+    MissingTransactionFee,
+}
+
+/// A fee that was not assessed, and why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedFee {
+    /// The fee's ID.
+    pub fee: PayrixId,
+    /// The fee's name.
+    pub name: Option<String>,
+    /// Why it was skipped.
+    pub reason: SkipReason,
+}
+
+/// A fee charged differently than quoted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeVariance {
+    /// The fee's ID.
+    pub fee: PayrixId,
+    /// Amount the quote expected, in cents (zero if not quoted).
+    pub expected: f64,
+    /// Amount the entries charged, in cents (zero if none).
+    pub actual: f64,
+}
+
+impl FeeVariance {
+    /// `actual - expected`; positive means the merchant was overcharged.
+    pub fn difference(&self) -> f64 {
+        self.actual - self.expected
+    }
+}
+
+/// The fees assessed on one [`QuoteRequest`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FeeQuote {
+    /// Fees that apply.
+    pub fees: Vec<AssessedFee>,
+    /// Fees that do not apply, or could not be decided.
+    pub skipped: Vec<SkippedFee>,
+    /// Sum of assessed fees in cents.
+    pub total: f64,
+}
+
+impl FeeQuote {
+    /// Whether every fee could be decided offline.
+    pub fn is_complete(&self) -> bool {
+        !self
+            .skipped
+            .iter()
+            .any(|s| matches!(s.reason, SkipReason::Undetermined { .. }))
+    }
+
+    /// Compare the quote with the fee entries Payrix created for the same
+    /// event.
+    ///
+    /// Entries are grouped by their `fee` and their amounts summed; entries
+    /// without a fee are ignored. Fee entries debit the merchant, so the sign
+    /// is dropped before comparing. Returns the fees whose charged amount
+    /// differs from the quote by more than `tolerance` cents, including fees
+    /// charged but not quoted and fees quoted but not charged.
+    pub fn compare(&self, entries: &[Entry], tolerance: f64) -> Vec<FeeVariance> {
+        let mut charged: Vec<(&PayrixId, f64)> = Vec::new();
+        for entry in entries {
+            let Some(fee) = entry.fee.as_ref() else {
+                continue;
+            };
+            let amount = entry.amount.unwrap_or(0.0);
+            match charged.iter_mut().find(|(id, _)| *id == fee) {
+                Some((_, total)) => *total += amount,
+                None => charged.push((fee, amount)),
+            }
+        }
+
+        let mut variances: Vec<FeeVariance> = self
+            .fees
+            .iter()
+            .map(|assessed| FeeVariance {
+                fee: assessed.fee.clone(),
+                expected: assessed.amount,
+                actual: charged
+                    .iter()
+                    .find(|(id, _)| **id == assessed.fee)
+                    .map_or(0.0, |(_, total)| total.abs()),
+            })
+            .collect();
+        variances.extend(
+            charged
+                .iter()
+                .filter(|(id, _)| !self.fees.iter().any(|a| &a.fee == *id))
+                .map(|(id, total)| FeeVariance {
+                    fee: (*id).clone(),
+                    expected: 0.0,
+                    actual: total.abs(),
+                }),
+        );
+        variances.retain(|v| v.difference().abs() > tolerance);
+        variances
+    }
+}
+
+// =============================================================================
+// Section 3: Engine
+// =============================================================================
+
+/// A merchant's fees and fee rules.
+#[derive(Debug, Clone, Default)]
+pub struct FeeEngine {
+    /// Fees to evaluate.
+    pub fees: Vec<Fee>,
+    /// Rules for those fees, linked by their `fee` field.
+    pub rules: Vec<FeeRule>,
+}
+
+/// Result of evaluating a single rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RuleOutcome {
+    Matched,
+    NotMatched,
+    Undetermined,
+}
+
+impl FeeEngine {
+    /// Create an engine from fees and rules already in hand.
+    pub fn new(fees: Vec<Fee>, rules: Vec<FeeRule>) -> Self {
+        Self { fees, rules }
+    }
+
+    /// Fetch the fees charged for `entity_id` and their rules.
+    pub async fn fetch(client: &PayrixClient, entity_id: &str) -> Result<Self> {
+        let search = SearchBuilder::new().field("forentity", entity_id).build();
+        let fees: Vec<Fee> = client.search(EntityType::Fees, &search).await?;
+
+        let fee_ids: Vec<&str> = fees.iter().map(|f| f.id.as_str()).collect();
+        let mut rules = Vec::new();
+        for batch in fee_ids.chunks(RULE_BATCH_SIZE) {
+            let search = SearchBuilder::new()
+                .field_multi("fee", batch, SearchOperator::In)
+                .build();
+            rules.extend(client.search::<FeeRule>(EntityType::FeeRules, &search).await?);
+        }
+
+        Ok(Self::new(fees, rules))
+    }
+
+    /// Evaluate every fee against `request`.
+    pub fn quote(&self, request: &QuoteRequest) -> FeeQuote {
+        let mut quote = FeeQuote::default();
+        for fee in &self.fees {
+            match self.evaluate(fee, request) {
+                Ok(assessed) => {
+                    quote.total += assessed.amount;
+                    quote.fees.push(assessed);
+                }
+                Err(reason) => quote.skipped.push(SkippedFee {
+                    fee: fee.id.clone(),
+                    name: fee.name.clone(),
+                    reason,
+                }),
+            }
+        }
+        quote.total = round_cents(quote.total);
+        quote
+    }
+
+    fn evaluate(&self, fee: &Fee, request: &QuoteRequest) -> std::result::Result<AssessedFee, SkipReason> {
+        if fee.inactive {
+            return Err(SkipReason::Inactive);
+        }
+        let day = date_number(request.date);
+        if fee.start.is_some_and(|start| day < start) || fee.finish.is_some_and(|finish| day > finish) {
+            return Err(SkipReason::OutsideDates);
+        }
+        if fee.schedule != Some(request.event) {
+            return Err(SkipReason::Schedule(fee.schedule));
+        }
+        self.check_rules(fee, request)?;
+
+        let unit = fee.um.unwrap_or_default();
+        let rate = fee.amount.unwrap_or(0.0);
+        let event_amount = request.amount as f64;
+        let mut amount = match unit {
+            FeeUnit::Fixed => rate,
+            FeeUnit::Percent => event_amount * rate / 10_000.0,
+            FeeUnit::Surcharge => event_amount - event_amount / (1.0 + rate / 10_000.0),
+        };
+
+        let mut capped = false;
+        if let Some(maximum) = fee.maximum.filter(|m| unit != FeeUnit::Fixed && amount > *m) {
+            amount = maximum;
+            capped = true;
+        }
+        if fee.txn_fee == Some(1) {
+            let supplied = request.transaction_fee.ok_or(SkipReason::MissingTransactionFee)? as f64;
+            if amount > supplied {
+                amount = supplied;
+                capped = true;
+            }
+        }
+
+        Ok(AssessedFee {
+            fee: fee.id.clone(),
+            name: fee.name.clone(),
+            fee_type: fee.fee_type,
+            unit,
+            amount: round_cents(amount),
+            capped,
+        })
+    }
+
+    /// Apply the fee's rules: ungrouped rules must all match, and each
+    /// grouping needs one match. A definite failure wins over an undecided rule.
+    fn check_rules(&self, fee: &Fee, request: &QuoteRequest) -> std::result::Result<(), SkipReason> {
+        let rules = self.rules.iter().filter(|r| {
+            !r.inactive
+                && r.fee.as_ref() == Some(&fee.id)
+                && r.application != Some(FeeApplication::Collection)
+        });
+
+        let mut groups: Vec<(&str, Vec<(&FeeRule, RuleOutcome)>)> = Vec::new();
+        let mut failed = None;
+        let mut undetermined = None;
+        for rule in rules {
+            let outcome = evaluate_rule(rule, request);
+            match rule.grouping.as_deref().filter(|g| !g.is_empty()) {
+                Some(name) => match groups.iter_mut().find(|(g, _)| *g == name) {
+                    Some((_, members)) => members.push((rule, outcome)),
+                    None => groups.push((name, vec![(rule, outcome)])),
+                },
+                None => match outcome {
+                    RuleOutcome::Matched => {}
+                    RuleOutcome::NotMatched => {
+                        failed.get_or_insert(rule);
+                    }
+                    RuleOutcome::Undetermined => {
+                        undetermined.get_or_insert(rule);
+                    }
+                },
+            }
+        }
+
+        for (_, members) in &groups {
+            if members.iter().any(|(_, o)| *o == RuleOutcome::Matched) {
+                continue;
+            }
+            match members.iter().find(|(_, o)| *o == RuleOutcome::Undetermined) {
+                Some((rule, _)) => {
+                    undetermined.get_or_insert(*rule);
+                }
+                None => {
+                    failed.get_or_insert(members[0].0);
+                }
+            }
+        }
+
+        if let Some(rule) = failed {
+            return Err(SkipReason::RuleNotMatched { rule: rule.id.clone() });
+        }
+        if let Some(rule) = undetermined {
+            return Err(SkipReason::Undetermined {
+                rule: rule.id.clone(),
+                rule_type: rule.rule_type,
+            });
+        }
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Section 4: Rule Evaluation
+// =============================================================================
+
+fn evaluate_rule(rule: &FeeRule, request: &QuoteRequest) -> RuleOutcome {
+    let Some(rule_type) = rule.rule_type else {
+        return RuleOutcome::Undetermined;
+    };
+    let value = rule.value.as_deref().unwrap_or("").trim();
+
+    let outcome = match rule_type {
+        FeeRuleType::Method => request.method.map(|method| {
+            list(value).any(|v| {
+                v.parse::<i32>().ok() == Some(method as i32)
+                    || normalize(v) == normalize(method.display_name())
+                    || (method == PaymentMethod::AmericanExpress && normalize(v) == "amex")
+            })
+        }),
+        FeeRuleType::Origin => request
+            .origin
+            .map(|origin| list(value).any(|v| v.parse::<i32>().ok() == Some(origin as i32))),
+        FeeRuleType::Type => request
+            .txn_type
+            .map(|txn_type| list(value).any(|v| v.parse::<i32>().ok() == Some(txn_type as i32))),
+        FeeRuleType::Bin => request
+            .bin
+            .as_deref()
+            .map(|bin| list(value).any(|v| !v.is_empty() && bin.starts_with(v))),
+        FeeRuleType::Mcc => request.mcc.as_deref().map(|mcc| list(value).any(|v| v == mcc)),
+        FeeRuleType::IssuerCountry => request
+            .issuer_country
+            .as_deref()
+            .map(|country| list(value).any(|v| v.eq_ignore_ascii_case(country))),
+        FeeRuleType::MerchantCountry => request
+            .merchant_country
+            .as_deref()
+            .map(|country| list(value).any(|v| v.eq_ignore_ascii_case(country))),
+        FeeRuleType::Greater | FeeRuleType::Less | FeeRuleType::Equal | FeeRuleType::NotEqual => {
+            value.parse::<f64>().ok().map(|limit| {
+                let amount = request.amount as f64;
+                match rule_type {
+                    FeeRuleType::Greater => amount > limit,
+                    FeeRuleType::Less => amount < limit,
+                    FeeRuleType::Equal => amount == limit,
+                    _ => amount != limit,
+                }
+            })
+        }
+        FeeRuleType::Swiped
+        | FeeRuleType::Emv
+        | FeeRuleType::Signed
+        | FeeRuleType::International
+        | FeeRuleType::Corporate
+        | FeeRuleType::Imported
+        | FeeRuleType::SameDay
+        | FeeRuleType::Subscription
+        | FeeRuleType::SoftPos
+        | FeeRuleType::DynamicallyRouted => {
+            let expected = match value {
+                "" | "1" => Some(true),
+                "0" => Some(false),
+                _ => value.parse::<bool>().ok(),
+            };
+            expected.and_then(|expected| request.flags.get(&rule_type).map(|flag| *flag == expected))
+        }
+        _ => None,
+    };
+
+    match outcome {
+        Some(true) => RuleOutcome::Matched,
+        Some(false) => RuleOutcome::NotMatched,
+        None => RuleOutcome::Undetermined,
+    }
+}
+
+/// Split a rule value into its comma-separated alternatives.
+fn list(value: &str) -> impl Iterator<Item = &str> {
+    value.split(',').map(str::trim)
+}
+
+/// Lowercase and strip non-alphanumerics, so "American Express" matches "americanexpress".
+fn normalize(value: &str) -> String {
+    value
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// A date as the YYYYMMDD integer used by fee `start` and `finish`.
+fn date_number(date: NaiveDate) -> i32 {
+    date.year() * 10_000 + date.month() as i32 * 100 + date.day() as i32
+}
+
+/// Round to the three decimal places Payrix keeps for cents.
+fn round_cents(amount: f64) -> f64 {
+    (amount * 1000.0).round() / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const FEE_PERCENT: &str = "t1_fee_00000000000000000000001";
+    const FEE_FIXED: &str = "t1_fee_00000000000000000000002";
+    const FEE_AMEX: &str = "t1_fee_00000000000000000000003";
+    const FEE_MONTHLY: &str = "t1_fee_00000000000000000000004";
+
+    fn engine() -> FeeEngine {
+        let fees: Vec<Fee> = serde_json::from_value(json!([
+            { "id": FEE_PERCENT, "schedule": 7, "um": 1, "amount": 290.0, "maximum": 500.0 },
+            { "id": FEE_FIXED, "schedule": 7, "um": 2, "amount": 30.0, "start": 20240101 },
+            { "id": FEE_AMEX, "schedule": 7, "um": 1, "amount": 50.0 },
+            { "id": FEE_MONTHLY, "schedule": 3, "um": 2, "amount": 999.0 },
+        ]))
+        .unwrap();
+        let rules: Vec<FeeRule> = serde_json::from_value(json!([
+            // Fixed fee only on ecommerce card-not-present sales.
+            { "id": "t1_fer_00000000000000000000001", "fee": FEE_FIXED, "type": "ORIGIN", "value": "2" },
+            // Amex fee for amex, or for any card from a 3782 BIN.
+            { "id": "t1_fer_00000000000000000000002", "fee": FEE_AMEX, "type": "METHOD",
+              "value": "amex", "grouping": "brand" },
+            { "id": "t1_fer_00000000000000000000003", "fee": FEE_AMEX, "type": "BIN",
+              "value": "3782", "grouping": "brand" },
+        ]))
+        .unwrap();
+        FeeEngine::new(fees, rules)
+    }
+
+    fn request(amount: i64) -> QuoteRequest {
+        QuoteRequest::new(amount, FeeSchedule::Capture)
+            .date(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap())
+            .method(PaymentMethod::Visa)
+            .origin(TransactionOrigin::Ecommerce)
+            .bin("411111")
+    }
+
+    #[test]
+    fn test_quote_applies_units_rules_and_caps() {
+        let quote = engine().quote(&request(10_000));
+        let ids: Vec<&str> = quote.fees.iter().map(|f| f.fee.as_str()).collect();
+        assert_eq!(ids, vec![FEE_PERCENT, FEE_FIXED]);
+        assert_eq!(quote.fees[0].amount, 290.0);
+        assert_eq!(quote.total, 320.0);
+        assert!(quote.is_complete());
+        assert!(quote.skipped.iter().any(|s| s.fee.as_str() == FEE_MONTHLY
+            && s.reason == SkipReason::Schedule(Some(FeeSchedule::Months))));
+
+        // 2.9% of $1,000 hits the $5 maximum.
+        let quote = engine().quote(&request(100_000));
+        assert_eq!(quote.fees[0].amount, 500.0);
+        assert!(quote.fees[0].capped);
+
+        // Terminal origin drops the fixed fee; an Amex BIN picks up the group.
+        let quote = engine().quote(
+            &request(10_000)
+                .origin(TransactionOrigin::Terminal)
+                .bin("378282"),
+        );
+        let ids: Vec<&str> = quote.fees.iter().map(|f| f.fee.as_str()).collect();
+        assert_eq!(ids, vec![FEE_PERCENT, FEE_AMEX]);
+        assert!(quote.skipped.iter().any(|s| s.fee.as_str() == FEE_FIXED
+            && matches!(s.reason, SkipReason::RuleNotMatched { .. })));
+
+        // Before the fixed fee's start date.
+        let quote = engine().quote(&request(10_000).date(NaiveDate::from_ymd_opt(2023, 12, 31).unwrap()));
+        assert!(quote.skipped.iter().any(|s| s.fee.as_str() == FEE_FIXED
+            && s.reason == SkipReason::OutsideDates));
+    }
+
+    #[test]
+    fn test_surcharge_and_undetermined_rules() {
+        let fees: Vec<Fee> = serde_json::from_value(json!([
+            { "id": FEE_PERCENT, "schedule": 7, "um": 3, "amount": 300.0 },
+            { "id": FEE_FIXED, "schedule": 7, "um": 2, "amount": 10.0 },
+        ]))
+        .unwrap();
+        let rules: Vec<FeeRule> = serde_json::from_value(json!([
+            { "id": "t1_fer_00000000000000000000001", "fee": FEE_FIXED, "type": "SWIPED", "value": "1" },
+        ]))
+        .unwrap();
+        let engine = FeeEngine::new(fees, rules);
+
+        // A 3% surcharge on $103.00 including the fee is $3.00.
+        let quote = engine.quote(&request(10_300));
+        assert_eq!(quote.fees[0].amount, 300.0);
+        assert!(!quote.is_complete());
+        assert_eq!(
+            quote.skipped[0].reason,
+            SkipReason::Undetermined {
+                rule: "t1_fer_00000000000000000000001".parse().unwrap(),
+                rule_type: Some(FeeRuleType::Swiped),
+            }
+        );
+
+        let quote = engine.quote(&request(10_300).flag(FeeRuleType::Swiped, true));
+        assert_eq!(quote.total, 310.0);
+    }
+
+    #[test]
+    fn test_compare_with_entries() {
+        let quote = engine().quote(&request(10_000));
+        let entries: Vec<Entry> = serde_json::from_value(json!([
+            { "id": "t1_ent_00000000000000000000001", "fee": FEE_PERCENT, "amount": -290.0 },
+            { "id": "t1_ent_00000000000000000000002", "fee": FEE_AMEX, "amount": -50.0 },
+            { "id": "t1_ent_00000000000000000000003", "amount": 10000.0 },
+        ]))
+        .unwrap();
+
+        let variances = quote.compare(&entries, 0.5);
+        assert_eq!(variances.len(), 2);
+        assert_eq!(variances[0].fee.as_str(), FEE_FIXED);
+        assert_eq!(variances[0].difference(), -30.0);
+        assert_eq!(variances[1].fee.as_str(), FEE_AMEX);
+        assert_eq!(variances[1].difference(), 50.0);
+    }
+}
@@ -0,0 +1,504 @@
+//! Rebuild a fund's balances from its ledger and audit them against Payrix.
+//!
+//! A [`Fund`] reports `available`, `pending`, `reserved` and `total` balances,
+//! but not how it got there. [`FundLedger`] holds the fund's [`Entry`],
+//! [`PendingEntry`] and [`ReserveEntry`] records and replays them to rebuild
+//! the balances as of any moment, compare them with the live fund, or produce
+//! a daily balance series.
+//!
+//! # Replay Rules
+//!
+//! - **Available** is the sum of ledger entries created up to that moment.
+//!   Reserve holds and releases appear here as ordinary entries
+//!   ([`EventType::ReserveEntry`] debits, [`EventType::ReserveEntryRelease`] credits).
+//! - **Pending** is the sum of pending entries created up to that moment
+//!   that have not yet become a ledger entry. A pending entry stops counting
+//!   when the entry it links to is created (or, if that entry is not in the
+//!   ledger, when the pending entry was last modified).
+//! - **Reserved** is the sum of reserve entries created up to that moment
+//!   whose `release` date has not yet arrived.
+//! - **Total** is the sum of the three.
+//!
+//! All amounts are in cents, as floats with up to three decimal places, like
+//! the fund itself. Records without a parseable `created` timestamp cannot be
+//! placed in time and are counted in [`LedgerSnapshot::undated`] instead.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use payrix::{PayrixClient, Environment};
+//! use payrix::workflows::fund_ledger::{daily_balances_csv, FundLedger};
+//! use chrono::{Local, NaiveDate};
+//!
+//! # async fn example() -> payrix::Result<()> {
+//! let client = PayrixClient::new("api-key", Environment::Test)?;
+//! let ledger = FundLedger::fetch(&client, "t1_fnd_12345678901234567890123").await?;
+//!
+//! // Payrix timestamps are not UTC; this host runs in the platform's time zone.
+//! let audit = ledger.audit(Local::now().naive_local(), 1.0);
+//! for discrepancy in &audit.discrepancies {
+//!     println!(
+//!         "{:?}: Payrix says {:.3}, ledger says {:.3}",
+//!         discrepancy.balance, discrepancy.live, discrepancy.rebuilt
+//!     );
+//! }
+//!
+//! let days = ledger.daily_balances(
+//!     NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+//!     NaiveDate::from_ymd_opt(2024, 3, 31).unwrap(),
+//! );
+//! std::fs::write("march.csv", daily_balances_csv(&days)).unwrap();
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::client::PayrixClient;
+use crate::entity::EntityType;
+use crate::error::{Error, Result};
+use crate::search::{parse_payrix_date, SearchBuilder};
+use crate::types::{Entry, EventType, Fund, PendingEntry, ReserveEntry};
+
+// =============================================================================
+// Section 1: Balance Types
+// =============================================================================
+
+/// One of a fund's balances.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BalanceKind {
+    /// Funds available for disbursement.
+    Available,
+    /// Funds not yet settled.
+    Pending,
+    /// Funds held in reserve.
+    Reserved,
+    /// All of the above.
+    Total,
+}
+
+impl BalanceKind {
+    /// Every balance, in report order.
+    pub const ALL: [BalanceKind; 4] = [
+        BalanceKind::Available,
+        BalanceKind::Pending,
+        BalanceKind::Reserved,
+        BalanceKind::Total,
+    ];
+}
+
+/// A fund's balances in cents.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FundBalances {
+    /// Available for disbursement.
+    pub available: f64,
+    /// Not yet settled.
+    pub pending: f64,
+    /// Held in reserve.
+    pub reserved: f64,
+    /// Sum of the other three.
+    pub total: f64,
+}
+
+impl FundBalances {
+    /// The live balances reported on a [`Fund`], with missing values as zero.
+    pub fn from_fund(fund: &Fund) -> Self {
+        Self {
+            available: fund.available.unwrap_or(0.0),
+            pending: fund.pending.unwrap_or(0.0),
+            reserved: fund.reserved.unwrap_or(0.0),
+            total: fund.total.unwrap_or(0.0),
+        }
+    }
+
+    /// One balance by kind.
+    pub fn get(&self, kind: BalanceKind) -> f64 {
+        match kind {
+            BalanceKind::Available => self.available,
+            BalanceKind::Pending => self.pending,
+            BalanceKind::Reserved => self.reserved,
+            BalanceKind::Total => self.total,
+        }
+    }
+}
+
+/// Net movement of the available balance from one event type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventTotal {
+    /// The event, or `None` for entries without one.
+    pub event: Option<EventType>,
+    /// Number of ledger entries.
+    pub count: usize,
+    /// Sum of their amounts in cents.
+    pub amount: f64,
+}
+
+/// Balances rebuilt as of a moment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LedgerSnapshot {
+    /// The moment the balances apply to.
+    pub as_of: NaiveDateTime,
+    /// The rebuilt balances.
+    pub balances: FundBalances,
+    /// Ledger entries up to `as_of`, grouped by event.
+    pub by_event: Vec<EventTotal>,
+    /// Records skipped because their creation time could not be parsed.
+    pub undated: usize,
+}
+
+/// A rebuilt balance that differs from the live fund.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BalanceDiscrepancy {
+    /// Which balance differs.
+    pub balance: BalanceKind,
+    /// What Payrix reports.
+    pub live: f64,
+    /// What the ledger adds up to.
+    pub rebuilt: f64,
+}
+
+impl BalanceDiscrepancy {
+    /// Live minus rebuilt.
+    pub fn difference(&self) -> f64 {
+        self.live - self.rebuilt
+    }
+}
+
+/// Result of [`FundLedger::audit`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceAudit {
+    /// Balances reported by Payrix.
+    pub live: FundBalances,
+    /// Balances rebuilt from the ledger.
+    pub snapshot: LedgerSnapshot,
+    /// Balances that differ by more than the tolerance.
+    pub discrepancies: Vec<BalanceDiscrepancy>,
+}
+
+impl BalanceAudit {
+    /// Whether every balance matched.
+    pub fn is_consistent(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+}
+
+/// End-of-day balances for one date.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DailyBalance {
+    /// The date.
+    pub date: NaiveDate,
+    /// Balances at the end of that day.
+    pub balances: FundBalances,
+}
+
+// =============================================================================
+// Section 2: Ledger Replay
+// =============================================================================
+
+/// A fund and the records that move its balances.
+#[derive(Debug, Clone)]
+pub struct FundLedger {
+    /// The fund, with its live balances.
+    pub fund: Fund,
+    /// Ledger entries.
+    pub entries: Vec<Entry>,
+    /// Pending entries.
+    pub pending_entries: Vec<PendingEntry>,
+    /// Reserve entries.
+    pub reserve_entries: Vec<ReserveEntry>,
+}
+
+/// Parse a Payrix timestamp (`YYYY-MM-DD HH:MM:SS.SSSS`).
+fn parse_timestamp(value: Option<&str>) -> Option<NaiveDateTime> {
+    value.and_then(|v| NaiveDateTime::parse_from_str(v, "%Y-%m-%d %H:%M:%S%.f").ok())
+}
+
+/// The last representable moment of `date`.
+fn end_of_day(date: NaiveDate) -> NaiveDateTime {
+    date.and_time(NaiveTime::from_hms_nano_opt(23, 59, 59, 999_999_999).unwrap_or(NaiveTime::MIN))
+}
+
+impl FundLedger {
+    /// Build a ledger from records you already have.
+    pub fn new(
+        fund: Fund,
+        entries: Vec<Entry>,
+        pending_entries: Vec<PendingEntry>,
+        reserve_entries: Vec<ReserveEntry>,
+    ) -> Self {
+        Self {
+            fund,
+            entries,
+            pending_entries,
+            reserve_entries,
+        }
+    }
+
+    /// Fetch a fund and all of its entries, pending entries and reserve entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotFound`] if the fund does not exist.
+    pub async fn fetch(client: &PayrixClient, fund_id: &str) -> Result<Self> {
+        let fund: Fund = client
+            .get_one(EntityType::Funds, fund_id)
+            .await?
+            .ok_or_else(|| Error::NotFound(format!("Fund {} not found", fund_id)))?;
+
+        let search = SearchBuilder::new().field("fund", fund_id).build();
+        let entries = client.search(EntityType::Entries, &search).await?;
+        let pending_entries = client.search(EntityType::PendingEntries, &search).await?;
+        let reserve_entries = client.search(EntityType::ReserveEntries, &search).await?;
+
+        Ok(Self::new(fund, entries, pending_entries, reserve_entries))
+    }
+
+    /// Rebuild the balances as of `as_of`.
+    pub fn balances_at(&self, as_of: NaiveDateTime) -> LedgerSnapshot {
+        let mut balances = FundBalances::default();
+        let mut by_event: Vec<EventTotal> = Vec::new();
+        let mut undated = 0;
+
+        let mut entry_times: HashMap<&str, Option<NaiveDateTime>> = HashMap::new();
+        for entry in &self.entries {
+            let created = parse_timestamp(entry.created.as_deref());
+            entry_times.insert(entry.id.as_str(), created);
+            let Some(created) = created else {
+                undated += 1;
+                continue;
+            };
+            if created > as_of {
+                continue;
+            }
+
+            let amount = entry.amount.unwrap_or(0.0);
+            balances.available += amount;
+            match by_event.iter_mut().find(|t| t.event == entry.event) {
+                Some(total) => {
+                    total.count += 1;
+                    total.amount += amount;
+                }
+                None => by_event.push(EventTotal {
+                    event: entry.event,
+                    count: 1,
+                    amount,
+                }),
+            }
+        }
+
+        for pending in &self.pending_entries {
+            let Some(created) = parse_timestamp(pending.created.as_deref()) else {
+                undated += 1;
+                continue;
+            };
+            let settled = pending.entry.as_ref().and_then(|id| {
+                entry_times
+                    .get(id.as_str())
+                    .copied()
+                    .unwrap_or_else(|| parse_timestamp(pending.modified.as_deref()))
+            });
+            if created <= as_of && settled.is_none_or(|settled| settled > as_of) {
+                balances.pending += pending.amount.unwrap_or(0.0);
+            }
+        }
+
+        for reserve in &self.reserve_entries {
+            let Some(created) = parse_timestamp(reserve.created.as_deref()) else {
+                undated += 1;
+                continue;
+            };
+            let released = reserve
+                .release
+                .as_deref()
+                .and_then(parse_payrix_date)
+                .is_some_and(|release| release <= as_of.date());
+            if created <= as_of && !released {
+                balances.reserved += reserve.amount.unwrap_or(0) as f64;
+            }
+        }
+
+        balances.total = balances.available + balances.pending + balances.reserved;
+        by_event.sort_by_key(|t| t.event.map(|e| e as i32));
+
+        LedgerSnapshot {
+            as_of,
+            balances,
+            by_event,
+            undated,
+        }
+    }
+
+    /// Compare the balances rebuilt as of `as_of` with the live fund.
+    ///
+    /// Pass the current time, in the same time zone as Payrix timestamps, to
+    /// audit the fund as it stands. Balances that differ by more than
+    /// `tolerance` cents are reported.
+    pub fn audit(&self, as_of: NaiveDateTime, tolerance: f64) -> BalanceAudit {
+        let live = FundBalances::from_fund(&self.fund);
+        let snapshot = self.balances_at(as_of);
+
+        let discrepancies = BalanceKind::ALL
+            .into_iter()
+            .filter_map(|balance| {
+                let (live, rebuilt) = (live.get(balance), snapshot.balances.get(balance));
+                ((live - rebuilt).abs() > tolerance).then_some(BalanceDiscrepancy {
+                    balance,
+                    live,
+                    rebuilt,
+                })
+            })
+            .collect();
+
+        BalanceAudit {
+            live,
+            snapshot,
+            discrepancies,
+        }
+    }
+
+    /// End-of-day balances for every date from `from` to `to` inclusive.
+    pub fn daily_balances(&self, from: NaiveDate, to: NaiveDate) -> Vec<DailyBalance> {
+        from.iter_days()
+            .take_while(|date| *date <= to)
+            .map(|date| DailyBalance {
+                date,
+                balances: self.balances_at(end_of_day(date)).balances,
+            })
+            .collect()
+    }
+}
+
+// =============================================================================
+// Section 3: Export
+// =============================================================================
+
+/// Render daily balances as CSV, amounts in cents.
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use payrix::workflows::fund_ledger::{daily_balances_csv, DailyBalance, FundBalances};
+///
+/// let days = [DailyBalance {
+///     date: NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+///     balances: FundBalances { available: 1250.5, pending: 0.0, reserved: 300.0, total: 1550.5 },
+/// }];
+/// assert_eq!(
+///     daily_balances_csv(&days),
+///     "date,available,pending,reserved,total\n2024-03-01,1250.500,0.000,300.000,1550.500\n"
+/// );
+/// ```
+pub fn daily_balances_csv(days: &[DailyBalance]) -> String {
+    let mut csv = String::from("date,available,pending,reserved,total\n");
+    for day in days {
+        let b = &day.balances;
+        // Writing to a String cannot fail
+        let _ = writeln!(
+            csv,
+            "{},{:.3},{:.3},{:.3},{:.3}",
+            day.date.format("%Y-%m-%d"),
+            b.available,
+            b.pending,
+            b.reserved,
+            b.total
+        );
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn at(s: &str) -> NaiveDateTime {
+        parse_timestamp(Some(s)).unwrap()
+    }
+
+    fn ledger() -> FundLedger {
+        let fund: Fund = serde_json::from_value(json!({
+            "id": "t1_fnd_12345678901234567890123",
+            "available": 8700.0,
+            "pending": 0.0,
+            "reserved": 0.0,
+            "total": 8700.0,
+        }))
+        .unwrap();
+        let entries: Vec<Entry> = serde_json::from_value(json!([
+            { "id": "t1_ent_00000000000000000000001", "created": "2024-03-01 10:00:00.0000", "event": 7, "amount": 10000.0 },
+            { "id": "t1_ent_00000000000000000000002", "created": "2024-03-01 10:00:00.0000", "event": 13, "amount": -300.0 },
+            { "id": "t1_ent_00000000000000000000003", "created": "2024-03-02 09:00:00.0000", "event": 36, "amount": -1000.0 },
+            { "id": "t1_ent_00000000000000000000004", "created": "2024-03-04 08:00:00.0000", "event": 37, "amount": 1000.0 },
+            { "id": "t1_ent_00000000000000000000005", "created": "2024-03-05 12:00:00.0000", "event": 8, "amount": -1000.0 },
+            { "id": "t1_ent_00000000000000000000006", "event": 18, "amount": 5.0 },
+        ]))
+        .unwrap();
+        let pending: Vec<PendingEntry> = serde_json::from_value(json!([
+            { "id": "t1_pen_00000000000000000000001", "created": "2024-03-03 09:00:00.0000",
+              "entry": "t1_ent_00000000000000000000005", "amount": -1000.0 },
+        ]))
+        .unwrap();
+        let reserves: Vec<ReserveEntry> = serde_json::from_value(json!([
+            { "id": "t1_rse_00000000000000000000001", "created": "2024-03-02 09:00:00.0000",
+              "release": "20240304", "amount": 1000 },
+        ]))
+        .unwrap();
+        FundLedger::new(fund, entries, pending, reserves)
+    }
+
+    #[test]
+    fn test_balances_at() {
+        let ledger = ledger();
+
+        let snapshot = ledger.balances_at(at("2024-03-01 23:00:00.0000"));
+        assert_eq!(snapshot.balances.available, 9700.0);
+        assert_eq!(snapshot.undated, 1);
+        assert_eq!(snapshot.by_event.len(), 2);
+        assert_eq!(snapshot.by_event[0].event, Some(EventType::Capture));
+
+        // Reserve held and a refund pending
+        let snapshot = ledger.balances_at(at("2024-03-03 12:00:00.0000"));
+        assert_eq!(
+            snapshot.balances,
+            FundBalances { available: 8700.0, pending: -1000.0, reserved: 1000.0, total: 8700.0 }
+        );
+
+        // Reserve released, refund settled into the ledger
+        let snapshot = ledger.balances_at(at("2024-03-06 00:00:00.0000"));
+        assert_eq!(
+            snapshot.balances,
+            FundBalances { available: 8700.0, pending: 0.0, reserved: 0.0, total: 8700.0 }
+        );
+    }
+
+    #[test]
+    fn test_audit_reports_differences() {
+        let mut ledger = ledger();
+        let as_of = at("2024-03-06 00:00:00.0000");
+        assert!(ledger.audit(as_of, 0.5).is_consistent());
+
+        ledger.fund.available = Some(8650.0);
+        let audit = ledger.audit(as_of, 0.5);
+        assert_eq!(audit.discrepancies.len(), 1);
+        assert_eq!(audit.discrepancies[0].balance, BalanceKind::Available);
+        assert_eq!(audit.discrepancies[0].difference(), -50.0);
+    }
+
+    #[test]
+    fn test_daily_balances() {
+        let days = ledger().daily_balances(
+            NaiveDate::from_ymd_opt(2024, 2, 29).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 3, 5).unwrap(),
+        );
+        let totals: Vec<f64> = days.iter().map(|d| d.balances.total).collect();
+        assert_eq!(totals, vec![0.0, 9700.0, 9700.0, 8700.0, 8700.0, 8700.0]);
+        assert_eq!(days[2].balances.reserved, 1000.0);
+        assert_eq!(days[4].balances.reserved, 0.0);
+
+        let csv = daily_balances_csv(&days);
+        assert_eq!(csv.lines().count(), 7);
+        assert!(csv.contains("2024-03-02,8700.000,0.000,1000.000,9700.000"));
+    }
+}
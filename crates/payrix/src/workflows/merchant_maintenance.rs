@@ -0,0 +1,717 @@
+//! Post-boarding merchant maintenance.
+//!
+//! Once a merchant is boarded, changes to its bank account, ownership,
+//! address or business profile go through Payrix again and may send the
+//! merchant back to underwriting. This module wraps each change in a validated
+//! operation that:
+//!
+//! - Validates the new data with the same rules as
+//!   [`merchant_onboarding`](super::merchant_onboarding), failing with a
+//!   [`ValidationReport`] message before anything is sent
+//! - Applies the change
+//! - Reports the merchant's boarding status before and after, and whether the
+//!   change put the merchant back under review
+//!
+//! Results include a description of the change with account numbers masked,
+//! safe to log or show in an audit trail.
+//!
+//! # Re-underwriting
+//!
+//! Payrix re-reviews a boarded merchant when its funding account, beneficial
+//! ownership or merchant category changes. DBA and address changes usually
+//! apply without review. [`MaintenanceChange::review_expected`] encodes these
+//! rules; [`MaintenanceResult::review_triggered`] reports what was actually
+//! observed, which can lag the change by a few seconds. Use
+//! [`wait_for_boarding()`](super::boarding_watch::wait_for_boarding) to follow
+//! a review to completion.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use payrix::{PayrixClient, Environment};
+//! use payrix::workflows::merchant_maintenance::{update_merchant_profile, MerchantProfileUpdate};
+//!
+//! # async fn example() -> payrix::Result<()> {
+//! let client = PayrixClient::new("api-key", Environment::Test)?;
+//!
+//! let update = MerchantProfileUpdate::default().with_dba("Acme Coffee Roasters");
+//! let result = update_merchant_profile(&client, "t1_mer_12345678901234567890123", update).await?;
+//!
+//! println!("{}", result.description);
+//! if result.review_triggered {
+//!     println!("Merchant is back in review: {}", result.status_after);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use chrono::Utc;
+use serde_json::json;
+
+use crate::client::PayrixClient;
+use crate::entity::EntityType;
+use crate::error::{Error, Result};
+use crate::types::{Account, Entity, Member, Merchant};
+
+use super::boarding_watch::is_boarding_in_progress;
+use super::merchant_onboarding::{
+    is_valid_mcc, mask_sensitive, validate_account, validate_address, validate_member, Address,
+    BankAccountInfo, BoardingStatus, ChildPayload, MemberInfo, PayrixAccountPayload,
+    PayrixMemberPayload, ValidationCode, ValidationReport,
+};
+
+// ============================================================================
+// Results
+// ============================================================================
+
+/// The kind of maintenance change applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaintenanceChange {
+    /// The entity's primary bank account was replaced.
+    PrimaryAccountReplaced,
+
+    /// A member (owner or control person) was added.
+    MemberAdded,
+
+    /// A member was removed.
+    MemberRemoved,
+
+    /// The business address was updated.
+    AddressUpdated,
+
+    /// The merchant's DBA and/or MCC was updated.
+    ProfileUpdated {
+        /// Whether the DBA changed.
+        dba_changed: bool,
+        /// Whether the MCC changed.
+        mcc_changed: bool,
+    },
+}
+
+impl MaintenanceChange {
+    /// Whether Payrix normally re-underwrites a merchant after this change.
+    ///
+    /// Funding account, ownership and MCC changes are reviewed; DBA and
+    /// address changes are not.
+    pub fn review_expected(&self) -> bool {
+        match self {
+            MaintenanceChange::PrimaryAccountReplaced
+            | MaintenanceChange::MemberAdded
+            | MaintenanceChange::MemberRemoved => true,
+            MaintenanceChange::AddressUpdated => false,
+            MaintenanceChange::ProfileUpdated { mcc_changed, .. } => *mcc_changed,
+        }
+    }
+}
+
+/// Outcome of a maintenance operation.
+#[derive(Debug, Clone)]
+pub struct MaintenanceResult<T> {
+    /// What was changed.
+    pub change: MaintenanceChange,
+
+    /// The created or updated resource.
+    pub resource: T,
+
+    /// Human-readable description of the change, with sensitive values masked.
+    pub description: String,
+
+    /// The merchant's boarding status before the change.
+    pub status_before: BoardingStatus,
+
+    /// The merchant's boarding status right after the change.
+    pub status_after: BoardingStatus,
+
+    /// Whether the merchant left `Boarded` for a review state as a result of
+    /// the change.
+    pub review_triggered: bool,
+}
+
+impl<T> MaintenanceResult<T> {
+    fn new(
+        change: MaintenanceChange,
+        resource: T,
+        description: String,
+        status_before: BoardingStatus,
+        status_after: BoardingStatus,
+    ) -> Self {
+        Self {
+            change,
+            resource,
+            description,
+            status_before,
+            status_after,
+            review_triggered: review_triggered(status_before, status_after),
+        }
+    }
+}
+
+/// Whether a status change indicates the merchant went back under review.
+fn review_triggered(before: BoardingStatus, after: BoardingStatus) -> bool {
+    before != after && (is_boarding_in_progress(after) || after == BoardingStatus::ManualReview)
+}
+
+// ============================================================================
+// Bank Accounts
+// ============================================================================
+
+/// Replace an entity's primary bank account.
+///
+/// The new account is created, the current primary account(s) are demoted,
+/// and then the new account is made primary, so the entity is never left
+/// without an account. The old account is kept (not deleted) so pending
+/// payouts can still settle to it.
+///
+/// `account.is_primary` is ignored; the new account always becomes primary.
+///
+/// # Errors
+///
+/// Returns an error if the account fails validation, the entity has no
+/// merchant, or an API call fails. A failure after the new account is created
+/// leaves it in place as a non-primary account.
+pub async fn replace_primary_account(
+    client: &PayrixClient,
+    entity_id: &str,
+    account: BankAccountInfo,
+) -> Result<MaintenanceResult<Account>> {
+    let mut report = ValidationReport::default();
+    validate_account(&mut report, "account", "Account", &account);
+    report.into_result()?;
+
+    let merchant = merchant_for_entity(client, entity_id).await?;
+    let status_before = boarding_status(&merchant);
+
+    let existing: Vec<Account> = client
+        .search(EntityType::Accounts, &format!("entity[equals]={}", entity_id))
+        .await?;
+    let old_primaries: Vec<&Account> = existing
+        .iter()
+        .filter(|a| a.primary && !a.inactive)
+        .collect();
+
+    let new_mask = match account.account_number {
+        Some(ref number) => mask_sensitive(number),
+        None => "Plaid-linked account".to_string(),
+    };
+    let old_mask = old_primaries
+        .first()
+        .and_then(|a| a.mask.clone())
+        .unwrap_or_else(|| "none".to_string());
+
+    let payload = ChildPayload {
+        entity: Some(entity_id),
+        merchant: None,
+        payload: PayrixAccountPayload::from(BankAccountInfo {
+            is_primary: false,
+            ..account
+        }),
+    };
+    let created: Account = client.create(EntityType::Accounts, &payload).await?;
+
+    for old in &old_primaries {
+        let _: Account = client
+            .update(EntityType::Accounts, old.id.as_str(), &json!({"primary": 0}))
+            .await?;
+    }
+    let promoted: Account = client
+        .update(EntityType::Accounts, created.id.as_str(), &json!({"primary": 1}))
+        .await?;
+
+    let status_after = current_status(client, merchant.id.as_str()).await?;
+    Ok(MaintenanceResult::new(
+        MaintenanceChange::PrimaryAccountReplaced,
+        promoted,
+        format!("Primary account changed from {} to {}", old_mask, new_mask),
+        status_before,
+        status_after,
+    ))
+}
+
+// ============================================================================
+// Members
+// ============================================================================
+
+/// Add a member (owner, control person or principal) to a merchant.
+///
+/// The member is validated like an onboarding member (SSN, date of birth and
+/// age, contact details, address), and the merchant's total ownership
+/// including the new member must not exceed 100%.
+///
+/// # Errors
+///
+/// Returns an error if the member fails validation or an API call fails.
+pub async fn add_member(
+    client: &PayrixClient,
+    merchant_id: &str,
+    member: MemberInfo,
+) -> Result<MaintenanceResult<Member>> {
+    let mut report = ValidationReport::default();
+    validate_member(&mut report, "member", "Member", &member, Utc::now().date_naive());
+
+    let existing = active_members(client, merchant_id).await?;
+    validate_added_ownership(&mut report, &existing, &member);
+    report.into_result()?;
+
+    let status_before = current_status(client, merchant_id).await?;
+
+    let description = format!(
+        "Added member {} {} ({}% ownership, SSN {})",
+        member.first_name,
+        member.last_name,
+        member.ownership_percentage,
+        mask_sensitive(&member.ssn)
+    );
+    let payload = ChildPayload {
+        entity: None,
+        merchant: Some(merchant_id),
+        payload: PayrixMemberPayload::from(member),
+    };
+    let created: Member = client.create(EntityType::Members, &payload).await?;
+
+    let status_after = current_status(client, merchant_id).await?;
+    Ok(MaintenanceResult::new(
+        MaintenanceChange::MemberAdded,
+        created,
+        description,
+        status_before,
+        status_after,
+    ))
+}
+
+/// Remove a member from a merchant.
+///
+/// A merchant must keep at least one member and a control person (a member
+/// with significant responsibility, which members created as a control person
+/// or principal have), and the primary member cannot be removed; mark another
+/// member as primary first.
+///
+/// # Errors
+///
+/// Returns [`Error::NotFound`] if the member is not an active member of the
+/// merchant, a validation error if removing it is not allowed, or an API error.
+pub async fn remove_member(
+    client: &PayrixClient,
+    merchant_id: &str,
+    member_id: &str,
+) -> Result<MaintenanceResult<Member>> {
+    let existing = active_members(client, merchant_id).await?;
+    let member = existing
+        .iter()
+        .find(|m| m.id.as_str() == member_id)
+        .ok_or_else(|| {
+            Error::NotFound(format!(
+                "Member {} not found on merchant {}",
+                member_id, merchant_id
+            ))
+        })?;
+
+    validate_removal(&existing, member).into_result()?;
+
+    let status_before = current_status(client, merchant_id).await?;
+
+    let description = format!(
+        "Removed member {} {} ({}% ownership)",
+        member.first.as_deref().unwrap_or_default(),
+        member.last.as_deref().unwrap_or_default(),
+        percent(member.ownership.unwrap_or(0))
+    );
+    let removed: Member = client.remove(EntityType::Members, member_id).await?;
+
+    let status_after = current_status(client, merchant_id).await?;
+    Ok(MaintenanceResult::new(
+        MaintenanceChange::MemberRemoved,
+        removed,
+        description,
+        status_before,
+        status_after,
+    ))
+}
+
+/// Check that adding `member` keeps total ownership at or below 100%.
+///
+/// Existing members' ownership is in basis points, the new member's in whole
+/// percent.
+fn validate_added_ownership(report: &mut ValidationReport, existing: &[Member], member: &MemberInfo) {
+    let existing_bps: i32 = existing.iter().filter_map(|m| m.ownership).sum();
+    let total_bps = existing_bps + member.ownership_percentage.max(0) * 100;
+    if total_bps > 10_000 {
+        report.push(
+            "member.ownership_percentage",
+            ValidationCode::OwnershipExceeded,
+            format!(
+                "Ownership would total {}% (existing members hold {}%)",
+                percent(total_bps),
+                percent(existing_bps)
+            ),
+        );
+    }
+}
+
+/// Basis points as a percentage, e.g. `2530` as `25.3`.
+fn percent(bps: i32) -> String {
+    (f64::from(bps) / 100.0).to_string()
+}
+
+/// Check that `member` can be removed from a merchant whose active members
+/// are `existing`.
+fn validate_removal(existing: &[Member], member: &Member) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    if existing.len() == 1 {
+        report.push(
+            "member_id",
+            ValidationCode::Required,
+            "A merchant must keep at least one member",
+        );
+        return report;
+    }
+    if member.primary {
+        report.push(
+            "member_id",
+            ValidationCode::PrimaryMemberRemoval,
+            "The primary member cannot be removed; designate another primary member first",
+        );
+    }
+    let control_remains = existing
+        .iter()
+        .any(|m| m.id != member.id && m.significant_responsibility);
+    if member.significant_responsibility && !control_remains {
+        report.push(
+            "member_id",
+            ValidationCode::MissingControlPerson,
+            "The only control person cannot be removed; add another control person first",
+        );
+    }
+    report
+}
+
+// ============================================================================
+// Business Details
+// ============================================================================
+
+/// Update the business address on an entity.
+///
+/// # Errors
+///
+/// Returns an error if the address fails validation (US state and ZIP
+/// formats apply to US addresses), the entity has no merchant, or an API call
+/// fails.
+pub async fn update_business_address(
+    client: &PayrixClient,
+    entity_id: &str,
+    address: Address,
+) -> Result<MaintenanceResult<Entity>> {
+    let mut report = ValidationReport::default();
+    validate_address(&mut report, "address", "Business", &address);
+    report.into_result()?;
+
+    let merchant = merchant_for_entity(client, entity_id).await?;
+    let status_before = boarding_status(&merchant);
+
+    let description = format!(
+        "Business address changed to {}, {}, {} {}",
+        address.line1, address.city, address.state, address.zip
+    );
+    let updated: Entity = client
+        .update(
+            EntityType::Entities,
+            entity_id,
+            &json!({
+                "address1": address.line1,
+                "address2": address.line2,
+                "city": address.city,
+                "state": address.state,
+                "zip": address.zip,
+                "country": address.country,
+            }),
+        )
+        .await?;
+
+    let status_after = current_status(client, merchant.id.as_str()).await?;
+    Ok(MaintenanceResult::new(
+        MaintenanceChange::AddressUpdated,
+        updated,
+        description,
+        status_before,
+        status_after,
+    ))
+}
+
+/// Changes to a merchant's public profile.
+///
+/// Fields left as `None` are not changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MerchantProfileUpdate {
+    /// New "Doing Business As" name.
+    pub dba: Option<String>,
+
+    /// New Merchant Category Code.
+    pub mcc: Option<String>,
+}
+
+impl MerchantProfileUpdate {
+    /// Set the new DBA name.
+    pub fn with_dba(mut self, dba: impl Into<String>) -> Self {
+        self.dba = Some(dba.into());
+        self
+    }
+
+    /// Set the new MCC.
+    pub fn with_mcc(mut self, mcc: impl Into<String>) -> Self {
+        self.mcc = Some(mcc.into());
+        self
+    }
+
+    fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        if self.dba.is_none() && self.mcc.is_none() {
+            report.push("update", ValidationCode::Required, "No profile changes were given");
+        }
+        if self.dba.as_deref().is_some_and(|dba| dba.trim().is_empty()) {
+            report.push("dba", ValidationCode::Required, "DBA name cannot be empty");
+        }
+        if self.mcc.as_deref().is_some_and(|mcc| !is_valid_mcc(mcc)) {
+            report.push(
+                "mcc",
+                ValidationCode::InvalidValue,
+                "MCC must be a valid 4-digit merchant category code",
+            );
+        }
+        report
+    }
+}
+
+/// Update a merchant's DBA and/or MCC.
+///
+/// Values equal to the current ones are not sent. An MCC change usually
+/// triggers re-underwriting.
+///
+/// # Errors
+///
+/// Returns an error if the update is empty or invalid, the merchant does not
+/// exist, or an API call fails.
+pub async fn update_merchant_profile(
+    client: &PayrixClient,
+    merchant_id: &str,
+    update: MerchantProfileUpdate,
+) -> Result<MaintenanceResult<Merchant>> {
+    update.validate().into_result()?;
+
+    let merchant = get_merchant(client, merchant_id).await?;
+    let status_before = boarding_status(&merchant);
+
+    let dba = update.dba.filter(|dba| merchant.dba.as_ref() != Some(dba));
+    let mcc = update.mcc.filter(|mcc| merchant.mcc.as_ref() != Some(mcc));
+    let change = MaintenanceChange::ProfileUpdated {
+        dba_changed: dba.is_some(),
+        mcc_changed: mcc.is_some(),
+    };
+
+    let mut changes = Vec::new();
+    let mut body = serde_json::Map::new();
+    if let Some(dba) = dba {
+        changes.push(format!(
+            "DBA {} -> {}",
+            merchant.dba.as_deref().unwrap_or("none"),
+            dba
+        ));
+        body.insert("dba".to_string(), json!(dba));
+    }
+    if let Some(mcc) = mcc {
+        changes.push(format!(
+            "MCC {} -> {}",
+            merchant.mcc.as_deref().unwrap_or("none"),
+            mcc
+        ));
+        body.insert("mcc".to_string(), json!(mcc));
+    }
+
+    if body.is_empty() {
+        return Ok(MaintenanceResult::new(
+            change,
+            merchant,
+            "Profile unchanged".to_string(),
+            status_before,
+            status_before,
+        ));
+    }
+
+    let updated: Merchant = client
+        .update(EntityType::Merchants, merchant_id, &body)
+        .await?;
+
+    let status_after = current_status(client, merchant_id).await?;
+    Ok(MaintenanceResult::new(
+        change,
+        updated,
+        format!("Profile updated: {}", changes.join(", ")),
+        status_before,
+        status_after,
+    ))
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn boarding_status(merchant: &Merchant) -> BoardingStatus {
+    merchant
+        .status
+        .map(BoardingStatus::from)
+        .unwrap_or(BoardingStatus::NotReady)
+}
+
+async fn get_merchant(client: &PayrixClient, merchant_id: &str) -> Result<Merchant> {
+    client
+        .get_one(EntityType::Merchants, merchant_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Merchant not found: {}", merchant_id)))
+}
+
+async fn current_status(client: &PayrixClient, merchant_id: &str) -> Result<BoardingStatus> {
+    get_merchant(client, merchant_id)
+        .await
+        .map(|m| boarding_status(&m))
+}
+
+async fn merchant_for_entity(client: &PayrixClient, entity_id: &str) -> Result<Merchant> {
+    let merchants: Vec<Merchant> = client
+        .search(EntityType::Merchants, &format!("entity[equals]={}", entity_id))
+        .await?;
+    merchants
+        .into_iter()
+        .find(|m| !m.inactive)
+        .ok_or_else(|| Error::NotFound(format!("No active merchant for entity {}", entity_id)))
+}
+
+async fn active_members(client: &PayrixClient, merchant_id: &str) -> Result<Vec<Member>> {
+    let members: Vec<Member> = client
+        .search(EntityType::Members, &format!("merchant[equals]={}", merchant_id))
+        .await?;
+    Ok(members.into_iter().filter(|m| !m.inactive).collect())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn review_expected_by_change() {
+        assert!(MaintenanceChange::PrimaryAccountReplaced.review_expected());
+        assert!(MaintenanceChange::MemberAdded.review_expected());
+        assert!(MaintenanceChange::MemberRemoved.review_expected());
+        assert!(!MaintenanceChange::AddressUpdated.review_expected());
+        assert!(!MaintenanceChange::ProfileUpdated {
+            dba_changed: true,
+            mcc_changed: false
+        }
+        .review_expected());
+        assert!(MaintenanceChange::ProfileUpdated {
+            dba_changed: false,
+            mcc_changed: true
+        }
+        .review_expected());
+    }
+
+    #[test]
+    fn review_triggered_by_status_change() {
+        use BoardingStatus::*;
+        assert!(review_triggered(Boarded, ManualReview));
+        assert!(review_triggered(Boarded, Pending));
+        assert!(!review_triggered(Boarded, Boarded));
+        assert!(!review_triggered(ManualReview, ManualReview));
+        assert!(!review_triggered(Boarded, Closed));
+    }
+
+    fn member(id: &str, primary: bool, control: bool) -> Member {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "primary": primary as i32,
+            "significantResponsibility": control as i32
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn removal_validation() {
+        let owner = member("t1_mem_owner123456789012345678", true, false);
+        let control = member("t1_mem_control1234567890123456", false, true);
+        let principal = member("t1_mem_principal12345678901234", false, true);
+
+        let report = validate_removal(std::slice::from_ref(&owner), &owner);
+        assert!(report.has_code(ValidationCode::Required));
+
+        let members = vec![owner.clone(), control.clone()];
+        let report = validate_removal(&members, &owner);
+        assert_eq!(report.issues().len(), 1);
+        assert!(report.has_code(ValidationCode::PrimaryMemberRemoval));
+
+        // Removing the only control person
+        let report = validate_removal(&members, &control);
+        assert_eq!(report.issues().len(), 1);
+        assert!(report.has_code(ValidationCode::MissingControlPerson));
+
+        let members = vec![owner, control.clone(), principal];
+        assert!(validate_removal(&members, &control).is_valid());
+    }
+
+    #[test]
+    fn added_ownership_in_basis_points() {
+        use crate::types::MemberType;
+
+        let fixture: serde_json::Value =
+            serde_json::from_str(include_str!("../../tests/mock_data/members.json")).unwrap();
+        let existing: Member = serde_json::from_value(fixture["response"]["data"][0].clone()).unwrap();
+        assert_eq!(existing.ownership, Some(5000));
+
+        let mut new_member = MemberInfo {
+            member_type: MemberType::Owner,
+            first_name: "Jane".to_string(),
+            last_name: "Smith".to_string(),
+            title: None,
+            ownership_percentage: 50,
+            date_of_birth: "19850620".to_string(),
+            ssn: "987654321".to_string(),
+            email: "jane@example.com".to_string(),
+            phone: "3125551234".to_string(),
+            address: Address {
+                line1: "1 Main St".to_string(),
+                line2: None,
+                city: "Chicago".to_string(),
+                state: "IL".to_string(),
+                zip: "60601".to_string(),
+                country: "USA".to_string(),
+            },
+        };
+        let mut report = ValidationReport::default();
+        validate_added_ownership(&mut report, std::slice::from_ref(&existing), &new_member);
+        assert!(report.is_valid());
+
+        new_member.ownership_percentage = 51;
+        let mut report = ValidationReport::default();
+        validate_added_ownership(&mut report, std::slice::from_ref(&existing), &new_member);
+        assert!(report.has_code(ValidationCode::OwnershipExceeded));
+        assert!(report.issues()[0].message.contains("existing members hold 50%"));
+
+        assert_eq!(percent(2530), "25.3");
+    }
+
+    #[test]
+    fn profile_update_validation() {
+        assert!(!MerchantProfileUpdate::default().validate().is_valid());
+        assert!(MerchantProfileUpdate::default()
+            .with_dba("Acme")
+            .validate()
+            .is_valid());
+
+        let report = MerchantProfileUpdate::default()
+            .with_dba("  ")
+            .with_mcc("12")
+            .validate();
+        let paths: Vec<&str> = report.issues().iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, vec!["dba", "mcc"]);
+    }
+}
@@ -36,7 +36,14 @@
 //! 1. Create an [`OnboardMerchantRequest`] with all required information
 //! 2. Call [`onboard_merchant()`] to submit the application
 //! 3. Check the result's `boarding_status` for immediate approval or pending review
-//! 4. Use [`check_boarding_status()`] to poll for status updates if needed
+//! 4. Use [`check_boarding_status()`] to poll for status updates if needed, or
+//!    [`wait_for_boarding()`](super::boarding_watch::wait_for_boarding) to wait until
+//!    underwriting finishes
+//!
+//! To retry safely after a timeout or partial failure, call
+//! [`onboard_merchant_resumable()`] instead of [`onboard_merchant()`]: it reuses
+//! the entity, merchant, accounts and members that already exist and creates
+//! only what is missing.
 //!
 //! # Boarding Status Flow
 //!
@@ -89,7 +96,7 @@
 //!         // Primary operating account - accepts deposits and fee withdrawals
 //!         BankAccountInfo {
 //!             name: Some("Operating Account".to_string()),
-//!             routing_number: Some("123456789".to_string()),
+//!             routing_number: Some("121000358".to_string()),
 //!             account_number: Some("987654321".to_string()),
 //!             holder_type: AccountHolderType::Business,
 //!             account_method: BankAccountMethod::Checking,
@@ -101,7 +108,7 @@
 //!         // Trust account - deposits only (no fee withdrawals)
 //!         BankAccountInfo {
 //!             name: Some("Trust Account".to_string()),
-//!             routing_number: Some("123456789".to_string()),
+//!             routing_number: Some("121000358".to_string()),
 //!             account_number: Some("987654322".to_string()),
 //!             holder_type: AccountHolderType::Business,
 //!             account_method: BankAccountMethod::Checking,
@@ -112,7 +119,7 @@
 //!         },
 //!     ],
 //!     members: vec![MemberInfo {
-//!         member_type: MemberType::Owner,
+//!         member_type: MemberType::ControlPerson,
 //!         first_name: "John".to_string(),
 //!         last_name: "Doe".to_string(),
 //!         title: Some("CEO".to_string()),
@@ -181,7 +188,7 @@
 //! - At least one bank account with routing and account numbers
 //!
 //! **Beneficial Owners:**
-//! - At least one owner/control person with personal details
+//! - At least one control person or principal, plus any owners, with personal details
 //! - SSN and date of birth for identity verification
 //! - Ownership percentage (must total 100% for all owners)
 //!
@@ -189,6 +196,7 @@
 //! - Version of terms accepted
 //! - Timestamp of acceptance
 
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
 use crate::client::PayrixClient;
@@ -196,7 +204,7 @@ use crate::entity::EntityType;
 use crate::error::Result;
 use crate::types::{
     Account, AccountHolderType, AccountType, DateYmd, Entity, Member, MemberType, Merchant,
-    MerchantEnvironment, MerchantStatus, MerchantType,
+    MerchantEnvironment, MerchantStatus, MerchantType, PayrixId,
 };
 
 // ============================================================================
@@ -216,6 +224,14 @@ use crate::types::{
 /// - `accounts` - At least one bank account for funding
 /// - `members` - Beneficial owners/control persons
 /// - `terms_acceptance` - Acknowledgment of terms and conditions
+///
+/// # Storage
+///
+/// The request holds the EIN, SSNs, routing and account numbers in the clear,
+/// so it is deliberately not serializable. To store an application, seal it
+/// as an [`OnboardingDraft`](super::onboarding_draft::OnboardingDraft) with a
+/// [`FieldEncryptor`](super::onboarding_draft::FieldEncryptor); to log one,
+/// use [`redacted()`](Self::redacted).
 #[derive(Debug, Clone)]
 pub struct OnboardMerchantRequest {
     /// Business entity information (legal name, address, tax ID)
@@ -282,7 +298,7 @@ pub struct BusinessInfo {
 ///
 /// Contains information about how the merchant will process payments,
 /// including expected volumes and business category.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MerchantConfig {
     /// "Doing Business As" name.
     ///
@@ -330,7 +346,8 @@ pub struct MerchantConfig {
 /// - Individual + Savings = 9
 /// - Business + Checking = 10
 /// - Business + Savings = 11
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum BankAccountMethod {
     /// Checking account (default)
     #[default]
@@ -369,7 +386,8 @@ pub struct BankAccountInfo {
     /// Bank routing number (ABA number).
     ///
     /// 9-digit routing number, no dashes or spaces.
-    /// Example: "123456789"
+    /// Must pass the ABA check digit.
+    /// Example: "121000358"
     ///
     /// Not required if using Plaid (`plaid_public_token` is provided).
     pub routing_number: Option<String>,
@@ -485,7 +503,7 @@ pub struct MemberInfo {
 /// Physical address.
 ///
 /// Used for both business and residential addresses.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Address {
     /// Street address line 1.
     pub line1: String,
@@ -516,7 +534,7 @@ pub struct Address {
 ///
 /// Documents when and what version of the terms were accepted.
 /// This is required for compliance and legal purposes.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TermsAcceptance {
     /// Version of terms and conditions accepted.
     ///
@@ -727,7 +745,7 @@ struct PayrixOnboardingPayload {
 /// Internal bank account payload for Payrix API.
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct PayrixAccountPayload {
+pub(super) struct PayrixAccountPayload {
     /// Whether this is the primary account (1 = yes, 0 = no)
     primary: i32,
 
@@ -804,7 +822,7 @@ struct PayrixMerchantPayload {
 /// Internal member payload for Payrix API.
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
-struct PayrixMemberPayload {
+pub(super) struct PayrixMemberPayload {
     /// Member type (1=Owner, 2=ControlPerson, 3=Principal)
     #[serde(rename = "type")]
     member_type: MemberType,
@@ -822,6 +840,10 @@ struct PayrixMemberPayload {
     /// Ownership percentage
     ownership: i32,
 
+    /// 1 for control persons and principals, who hold significant
+    /// responsibility for the business
+    significant_responsibility: i32,
+
     /// Date of birth (YYYYMMDD)
     dob: String,
 
@@ -859,7 +881,7 @@ struct PayrixMemberPayload {
 // ============================================================================
 
 /// Helper to mask sensitive strings, showing only last 4 characters.
-fn mask_sensitive(value: &str) -> String {
+pub(super) fn mask_sensitive(value: &str) -> String {
     if value.len() <= 4 {
         "*".repeat(value.len())
     } else {
@@ -919,6 +941,7 @@ impl std::fmt::Debug for PayrixMemberPayload {
             .field("last", &self.last)
             .field("title", &self.title)
             .field("ownership", &self.ownership)
+            .field("significant_responsibility", &self.significant_responsibility)
             .field("dob", &self.dob)
             .field("ssn", &mask_sensitive(&self.ssn))
             .field("email", &self.email)
@@ -956,17 +979,23 @@ impl From<OnboardMerchantRequest> for PayrixOnboardingPayload {
             tc_date: request.terms_acceptance.accepted_at,
             tc_attestation: 1,
             accounts: request.accounts.into_iter().map(|a| a.into()).collect(),
-            merchant: PayrixMerchantPayload {
-                dba: request.merchant.dba,
-                new: if request.merchant.is_new_business { 1 } else { 0 },
-                mcc: request.merchant.mcc,
-                status: 1, // Board Immediately
-                environment: request.merchant.environment,
-                annual_cc_sales: request.merchant.annual_cc_sales,
-                avg_ticket: request.merchant.avg_ticket,
-                established: request.merchant.established.as_str().to_string(),
-                members: request.members.into_iter().map(|m| m.into()).collect(),
-            },
+            merchant: PayrixMerchantPayload::new(request.merchant, request.members),
+        }
+    }
+}
+
+impl PayrixMerchantPayload {
+    fn new(merchant: MerchantConfig, members: Vec<MemberInfo>) -> Self {
+        PayrixMerchantPayload {
+            dba: merchant.dba,
+            new: if merchant.is_new_business { 1 } else { 0 },
+            mcc: merchant.mcc,
+            status: 1, // Board Immediately
+            environment: merchant.environment,
+            annual_cc_sales: merchant.annual_cc_sales,
+            avg_ticket: merchant.avg_ticket,
+            established: merchant.established.as_str().to_string(),
+            members: members.into_iter().map(|m| m.into()).collect(),
         }
     }
 }
@@ -1009,6 +1038,7 @@ impl From<BankAccountInfo> for PayrixAccountPayload {
 impl From<MemberInfo> for PayrixMemberPayload {
     fn from(member: MemberInfo) -> Self {
         PayrixMemberPayload {
+            significant_responsibility: i32::from(is_control_person(&member)),
             member_type: member.member_type,
             first: member.first_name,
             last: member.last_name,
@@ -1083,92 +1113,472 @@ struct MerchantInResponse {
 // Validation
 // ============================================================================
 
-/// Validates an onboarding request before sending to the API.
+/// Machine-readable category of a [`ValidationIssue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationCode {
+    /// A required field or collection is empty.
+    Required,
+    /// The value does not have the expected shape (length, characters, syntax).
+    InvalidFormat,
+    /// The value has the right shape but fails its check digit.
+    InvalidChecksum,
+    /// The value is well-formed but not an accepted value (unknown state, MCC).
+    InvalidValue,
+    /// A number is outside its allowed range.
+    OutOfRange,
+    /// Owners' ownership percentages add up to more than 100.
+    OwnershipExceeded,
+    /// No member has control of the business.
+    MissingControlPerson,
+    /// The primary member would be removed.
+    PrimaryMemberRemoval,
+    /// A member is younger than 18.
+    Underage,
+}
+
+/// A single problem found while validating an [`OnboardMerchantRequest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    /// Path to the offending field, e.g. `members[1].ssn` or `accounts[0].routing_number`.
+    ///
+    /// Indices are zero-based. Checks that span several entries use the
+    /// collection name (`members`, `accounts`).
+    pub path: String,
+
+    /// Category of the problem.
+    pub code: ValidationCode,
+
+    /// Human-readable description, suitable for showing next to a form field.
+    pub message: String,
+}
+
+/// Every problem found in an [`OnboardMerchantRequest`].
 ///
-/// This catches common errors early with clear error messages, rather than
-/// waiting for cryptic API responses.
-fn validate_request(request: &OnboardMerchantRequest) -> Result<()> {
-    // Validate accounts
-    if request.accounts.is_empty() {
-        return Err(crate::error::Error::Config(
-            "At least one bank account is required".into(),
-        ));
+/// The report collects every issue rather than stopping at the first, so a
+/// boarding form can highlight all invalid fields in one pass.
+/// [`onboard_merchant()`] runs the same checks and refuses invalid requests.
+///
+/// # Example
+///
+/// ```no_run
+/// # use payrix::workflows::merchant_onboarding::OnboardMerchantRequest;
+/// # fn example(request: &OnboardMerchantRequest) {
+/// let report = request.validate();
+/// for issue in report.issues() {
+///     println!("{} ({:?}): {}", issue.path, issue.code, issue.message);
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Whether no issues were found.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
     }
 
-    if !request.accounts.iter().any(|a| a.is_primary) {
-        return Err(crate::error::Error::Config(
-            "One account must be marked as primary".into(),
-        ));
+    /// All issues, in the order the fields appear in the request.
+    pub fn issues(&self) -> &[ValidationIssue] {
+        &self.issues
+    }
+
+    /// Issues whose path is `path` or nested under it.
+    ///
+    /// `for_path("members[1]")` returns issues for `members[1].ssn`,
+    /// `members[1].address.zip`, and so on.
+    pub fn for_path<'a>(&'a self, path: &'a str) -> impl Iterator<Item = &'a ValidationIssue> {
+        self.issues.iter().filter(move |issue| {
+            issue
+                .path
+                .strip_prefix(path)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+        })
     }
 
-    // Validate each account has either routing/account numbers OR Plaid token
-    for (i, account) in request.accounts.iter().enumerate() {
-        let has_manual = account.routing_number.is_some() && account.account_number.is_some();
-        let has_plaid = account.plaid_public_token.is_some();
+    /// Whether any issue has the given code.
+    pub fn has_code(&self, code: ValidationCode) -> bool {
+        self.issues.iter().any(|issue| issue.code == code)
+    }
 
-        if !has_manual && !has_plaid {
-            return Err(crate::error::Error::Config(format!(
-                "Account {} requires either routing/account numbers or a Plaid token",
-                i + 1
-            )));
+    /// Convert into a `Result`, failing with every message joined if invalid.
+    pub fn into_result(self) -> Result<()> {
+        if self.is_valid() {
+            Ok(())
+        } else {
+            Err(crate::error::Error::Config(self.to_string()))
         }
+    }
 
-        // Validate routing number format (9 digits)
-        if let Some(ref routing) = account.routing_number {
-            if routing.len() != 9 || !routing.chars().all(|c| c.is_ascii_digit()) {
-                return Err(crate::error::Error::Config(format!(
-                    "Account {} routing number must be exactly 9 digits",
-                    i + 1
-                )));
+    pub(super) fn push(
+        &mut self,
+        path: impl Into<String>,
+        code: ValidationCode,
+        message: impl Into<String>,
+    ) {
+        self.issues.push(ValidationIssue {
+            path: path.into(),
+            code,
+            message: message.into(),
+        });
+    }
+}
+
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, issue) in self.issues.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
             }
+            f.write_str(&issue.message)?;
         }
+        Ok(())
     }
+}
 
-    // Validate members
-    if request.members.is_empty() {
-        return Err(crate::error::Error::Config(
-            "At least one member (owner or control person) is required".into(),
-        ));
+impl OnboardMerchantRequest {
+    /// Validate the request, collecting every issue.
+    ///
+    /// Member ages are checked against today's date (UTC).
+    pub fn validate(&self) -> ValidationReport {
+        self.validate_as_of(chrono::Utc::now().date_naive())
+    }
+
+    /// Validate the request, checking member ages against `today`.
+    pub fn validate_as_of(&self, today: NaiveDate) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        validate_business(&mut report, &self.business);
+        validate_merchant(&mut report, &self.merchant);
+        validate_accounts(&mut report, &self.accounts);
+        validate_members(&mut report, &self.members, today);
+        report
+    }
+}
+
+/// Minimum age for members, in years.
+const MIN_MEMBER_AGE: u32 = 18;
+
+/// US state, territory and military postal codes.
+const US_STATE_CODES: &[&str] = &[
+    "AL", "AK", "AZ", "AR", "CA", "CO", "CT", "DE", "DC", "FL", "GA", "HI", "ID", "IL", "IN",
+    "IA", "KS", "KY", "LA", "ME", "MD", "MA", "MI", "MN", "MS", "MO", "MT", "NE", "NV", "NH",
+    "NJ", "NM", "NY", "NC", "ND", "OH", "OK", "OR", "PA", "RI", "SC", "SD", "TN", "TX", "UT",
+    "VT", "VA", "WA", "WV", "WI", "WY", "AS", "GU", "MP", "PR", "VI", "AA", "AE", "AP",
+];
+
+fn is_digits(value: &str, len: usize) -> bool {
+    value.len() == len && value.chars().all(|c| c.is_ascii_digit())
+}
+
+/// ABA routing number check digit: 3/7/1 weights over the nine digits, sum divisible by 10.
+fn aba_checksum_valid(routing: &str) -> bool {
+    if !is_digits(routing, 9) {
+        return false;
     }
+    let sum: u32 = routing
+        .bytes()
+        .map(|b| u32::from(b - b'0'))
+        .zip([3, 7, 1].iter().cycle())
+        .map(|(digit, weight)| digit * weight)
+        .sum();
+    sum.is_multiple_of(10)
+}
 
-    // Validate SSN format for each member (9 digits)
-    for (i, member) in request.members.iter().enumerate() {
-        if member.ssn.len() != 9 || !member.ssn.chars().all(|c| c.is_ascii_digit()) {
-            return Err(crate::error::Error::Config(format!(
-                "Member {} SSN must be exactly 9 digits (no dashes)",
-                i + 1
-            )));
+fn is_valid_email(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.contains('@')
+        && !email.chars().any(char::is_whitespace)
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !domain.contains("..")
+}
+
+fn is_valid_phone(phone: &str) -> bool {
+    (10..=15).contains(&phone.len()) && phone.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_valid_url(url: &str) -> bool {
+    let Some(rest) = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+    else {
+        return false;
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    !url.chars().any(char::is_whitespace)
+        && host.contains('.')
+        && !host.starts_with('.')
+        && !host.ends_with('.')
+}
+
+/// MCCs are four digits; 0742 (veterinary services) is the lowest assigned code.
+pub(super) fn is_valid_mcc(mcc: &str) -> bool {
+    is_digits(mcc, 4) && mcc.parse::<u32>().is_ok_and(|code| code >= 742)
+}
+
+fn is_us(country: &str) -> bool {
+    matches!(country, "USA" | "US")
+}
+
+fn validate_contact(report: &mut ValidationReport, path: &str, label: &str, email: &str, phone: &str) {
+    if !is_valid_email(email) {
+        report.push(
+            format!("{path}.email"),
+            ValidationCode::InvalidFormat,
+            format!("{label} email address is not valid"),
+        );
+    }
+    if !is_valid_phone(phone) {
+        report.push(
+            format!("{path}.phone"),
+            ValidationCode::InvalidFormat,
+            format!("{label} phone must be 10 to 15 digits (no formatting)"),
+        );
+    }
+}
+
+pub(super) fn validate_address(
+    report: &mut ValidationReport,
+    path: &str,
+    label: &str,
+    address: &Address,
+) {
+    for (field, value) in [("line1", &address.line1), ("city", &address.city)] {
+        if value.trim().is_empty() {
+            report.push(
+                format!("{path}.{field}"),
+                ValidationCode::Required,
+                format!("{label} {field} is required"),
+            );
         }
+    }
+
+    if !is_us(&address.country) {
+        return;
+    }
+
+    if !US_STATE_CODES.contains(&address.state.as_str()) {
+        report.push(
+            format!("{path}.state"),
+            ValidationCode::InvalidValue,
+            format!("{label} state must be a 2-letter US state code"),
+        );
+    }
+
+    let zip_valid = match address.zip.split_once('-') {
+        Some((zip5, plus4)) => is_digits(zip5, 5) && is_digits(plus4, 4),
+        None => is_digits(&address.zip, 5),
+    };
+    if !zip_valid {
+        report.push(
+            format!("{path}.zip"),
+            ValidationCode::InvalidFormat,
+            format!("{label} ZIP code must be 5 digits or ZIP+4 (12345-6789)"),
+        );
+    }
+}
+
+pub(super) fn validate_business(report: &mut ValidationReport, business: &BusinessInfo) {
+    if business.legal_name.trim().is_empty() {
+        report.push(
+            "business.legal_name",
+            ValidationCode::Required,
+            "Business legal name is required",
+        );
+    }
+
+    validate_address(report, "business.address", "Business", &business.address);
+    validate_contact(report, "business", "Business", &business.email, &business.phone);
+
+    if business.website.as_deref().is_some_and(|url| !is_valid_url(url)) {
+        report.push(
+            "business.website",
+            ValidationCode::InvalidFormat,
+            "Business website must be a full http:// or https:// URL",
+        );
+    }
 
-        // Validate date of birth format (YYYYMMDD, 8 digits)
-        if member.date_of_birth.len() != 8
-            || !member.date_of_birth.chars().all(|c| c.is_ascii_digit())
-        {
-            return Err(crate::error::Error::Config(format!(
-                "Member {} date of birth must be in YYYYMMDD format (8 digits)",
-                i + 1
-            )));
+    if !is_digits(&business.ein, 9) {
+        report.push(
+            "business.ein",
+            ValidationCode::InvalidFormat,
+            "EIN must be exactly 9 digits (no dashes)",
+        );
+    }
+}
+
+pub(super) fn validate_merchant(report: &mut ValidationReport, merchant: &MerchantConfig) {
+    if merchant.dba.trim().is_empty() {
+        report.push("merchant.dba", ValidationCode::Required, "DBA name is required");
+    }
+
+    if !is_valid_mcc(&merchant.mcc) {
+        report.push(
+            "merchant.mcc",
+            ValidationCode::InvalidValue,
+            "MCC must be a valid 4-digit merchant category code",
+        );
+    }
+}
+
+pub(super) fn validate_accounts(report: &mut ValidationReport, accounts: &[BankAccountInfo]) {
+    if accounts.is_empty() {
+        report.push(
+            "accounts",
+            ValidationCode::Required,
+            "At least one bank account is required",
+        );
+        return;
+    }
+
+    if !accounts.iter().any(|a| a.is_primary) {
+        report.push(
+            "accounts",
+            ValidationCode::Required,
+            "One account must be marked as primary",
+        );
+    }
+
+    for (i, account) in accounts.iter().enumerate() {
+        validate_account(report, &format!("accounts[{i}]"), &format!("Account {}", i + 1), account);
+    }
+}
+
+/// Validate one bank account: it needs either routing/account numbers OR a
+/// Plaid token, and a routing number must pass the ABA checksum.
+pub(super) fn validate_account(
+    report: &mut ValidationReport,
+    path: &str,
+    label: &str,
+    account: &BankAccountInfo,
+) {
+    let has_manual = account.routing_number.is_some() && account.account_number.is_some();
+    let has_plaid = account.plaid_public_token.is_some();
+
+    if !has_manual && !has_plaid {
+        report.push(
+            path,
+            ValidationCode::Required,
+            format!("{label} requires either routing/account numbers or a Plaid token"),
+        );
+    }
+
+    if let Some(ref routing) = account.routing_number {
+        if !is_digits(routing, 9) {
+            report.push(
+                format!("{path}.routing_number"),
+                ValidationCode::InvalidFormat,
+                format!("{label} routing number must be exactly 9 digits"),
+            );
+        } else if !aba_checksum_valid(routing) {
+            report.push(
+                format!("{path}.routing_number"),
+                ValidationCode::InvalidChecksum,
+                format!("{label} routing number fails the ABA checksum"),
+            );
         }
+    }
+}
+
+pub(super) fn validate_members(report: &mut ValidationReport, members: &[MemberInfo], today: NaiveDate) {
+    if members.is_empty() {
+        report.push(
+            "members",
+            ValidationCode::Required,
+            "At least one member (owner or control person) is required",
+        );
+        return;
+    }
+
+    for (i, member) in members.iter().enumerate() {
+        validate_member(report, &format!("members[{i}]"), &format!("Member {}", i + 1), member, today);
+    }
+
+    let total_ownership: i64 = members
+        .iter()
+        .map(|m| i64::from(m.ownership_percentage.max(0)))
+        .sum();
+    if total_ownership > 100 {
+        report.push(
+            "members",
+            ValidationCode::OwnershipExceeded,
+            format!("Member ownership percentages total {total_ownership}%, more than 100%"),
+        );
+    }
 
-        // Validate ownership percentage is reasonable
-        if member.ownership_percentage < 0 || member.ownership_percentage > 100 {
-            return Err(crate::error::Error::Config(format!(
-                "Member {} ownership percentage must be between 0 and 100",
-                i + 1
-            )));
+    if !members.iter().any(is_control_person) {
+        report.push(
+            "members",
+            ValidationCode::MissingControlPerson,
+            "At least one member must be a control person or principal",
+        );
+    }
+}
+
+/// Whether a member satisfies the control prong of beneficial ownership:
+/// a control person or a principal.
+pub(super) fn is_control_person(member: &MemberInfo) -> bool {
+    matches!(member.member_type, MemberType::ControlPerson | MemberType::Principal)
+}
+
+/// Validate one member's identity, ownership, contact details and address.
+pub(super) fn validate_member(
+    report: &mut ValidationReport,
+    path: &str,
+    label: &str,
+    member: &MemberInfo,
+    today: NaiveDate,
+) {
+    if !is_digits(&member.ssn, 9) {
+        report.push(
+            format!("{path}.ssn"),
+            ValidationCode::InvalidFormat,
+            format!("{label} SSN must be exactly 9 digits (no dashes)"),
+        );
+    }
+
+    match NaiveDate::parse_from_str(&member.date_of_birth, "%Y%m%d") {
+        Ok(dob) if is_digits(&member.date_of_birth, 8) => {
+            if today.years_since(dob).is_none_or(|age| age < MIN_MEMBER_AGE) {
+                report.push(
+                    format!("{path}.date_of_birth"),
+                    ValidationCode::Underage,
+                    format!("{label} must be at least {MIN_MEMBER_AGE} years old"),
+                );
+            }
         }
+        _ => report.push(
+            format!("{path}.date_of_birth"),
+            ValidationCode::InvalidFormat,
+            format!("{label} date of birth must be in YYYYMMDD format (8 digits)"),
+        ),
     }
 
-    // Validate EIN format (9 digits)
-    if request.business.ein.len() != 9
-        || !request.business.ein.chars().all(|c| c.is_ascii_digit())
-    {
-        return Err(crate::error::Error::Config(
-            "EIN must be exactly 9 digits (no dashes)".into(),
-        ));
+    if !(0..=100).contains(&member.ownership_percentage) {
+        report.push(
+            format!("{path}.ownership_percentage"),
+            ValidationCode::OutOfRange,
+            format!("{label} ownership percentage must be between 0 and 100"),
+        );
     }
 
-    Ok(())
+    validate_contact(report, path, label, &member.email, &member.phone);
+    validate_address(report, &format!("{path}.address"), label, &member.address);
+}
+
+/// Validates an onboarding request before sending to the API.
+///
+/// Fails with every issue from [`OnboardMerchantRequest::validate`] joined into
+/// one message; use the report directly to map issues back to form fields.
+fn validate_request(request: &OnboardMerchantRequest) -> Result<()> {
+    request.validate().into_result()
 }
 
 // ============================================================================
@@ -1219,11 +1629,9 @@ fn validate_request(request: &OnboardMerchantRequest) -> Result<()> {
 /// - The merchant may be created but the function returns before completion
 /// - Accounts and members may be created but not returned to the caller
 ///
-/// If you need to handle timeouts, consider:
-/// 1. Using a generous timeout (the Payrix API can take several seconds)
-/// 2. Implementing a recovery mechanism that searches for recently created
-///    entities if the operation fails unexpectedly
-/// 3. Using unique identifiers (like EIN) to detect duplicate submissions
+/// If you need to handle timeouts or retries, use [`onboard_merchant_resumable()`],
+/// which detects what already exists for the business's EIN and legal name and
+/// only creates what is missing.
 ///
 /// # Example
 ///
@@ -1254,40 +1662,59 @@ pub async fn onboard_merchant(
         ));
     }
 
-    let boarding_status = merchant_response
-        .status
-        .map(BoardingStatus::from)
-        .unwrap_or(BoardingStatus::NotReady);
+    fetch_onboarding_result(
+        client,
+        response.id,
+        merchant_response.id,
+        merchant_response.status,
+    )
+    .await
+}
 
+/// Fetch the full entity, merchant, accounts and members for an onboarded merchant.
+///
+/// `status` is the merchant status reported when it was created, if known;
+/// otherwise the fetched merchant's status is used.
+async fn fetch_onboarding_result(
+    client: &PayrixClient,
+    entity_id: String,
+    merchant_id: String,
+    status: Option<MerchantStatus>,
+) -> Result<OnboardMerchantResult> {
     // Fetch the full entity and merchant objects for the result
     let entity: Entity = client
-        .get_one(EntityType::Entities, &response.id)
+        .get_one(EntityType::Entities, &entity_id)
         .await?
         .ok_or_else(|| crate::error::Error::Internal("Entity not found after creation".into()))?;
 
     let merchant: Merchant = client
-        .get_one(EntityType::Merchants, &merchant_response.id)
+        .get_one(EntityType::Merchants, &merchant_id)
         .await?
         .ok_or_else(|| crate::error::Error::Internal("Merchant not found after creation".into()))?;
 
+    let boarding_status = status
+        .or(merchant.status)
+        .map(BoardingStatus::from)
+        .unwrap_or(BoardingStatus::NotReady);
+
     // Get accounts and members
     let accounts: Vec<Account> = client
         .search(
             EntityType::Accounts,
-            &format!("entity[equals]={}", response.id),
+            &format!("entity[equals]={}", entity_id),
         )
         .await?;
 
     let members: Vec<Member> = client
         .search(
             EntityType::Members,
-            &format!("merchant[equals]={}", merchant_response.id),
+            &format!("merchant[equals]={}", merchant_id),
         )
         .await?;
 
     Ok(OnboardMerchantResult {
-        entity_id: response.id,
-        merchant_id: merchant_response.id,
+        entity_id,
+        merchant_id,
         boarding_status,
         entity,
         merchant,
@@ -1363,6 +1790,331 @@ pub async fn check_boarding_status(
     })
 }
 
+// ============================================================================
+// Resumable Onboarding
+// ============================================================================
+
+/// Whether a resource was created by this call or already existed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceOutcome {
+    /// The resource was created by this call.
+    Created(String),
+    /// The resource already existed and was reused.
+    Reused(String),
+}
+
+impl ResourceOutcome {
+    /// The resource ID.
+    pub fn id(&self) -> &str {
+        match self {
+            ResourceOutcome::Created(id) | ResourceOutcome::Reused(id) => id,
+        }
+    }
+
+    /// Whether the resource was created by this call.
+    pub fn is_created(&self) -> bool {
+        matches!(self, ResourceOutcome::Created(_))
+    }
+}
+
+/// Result of [`onboard_merchant_resumable()`].
+///
+/// Records, for each part of the request, whether it was created by this call
+/// or reused from an earlier (possibly interrupted) attempt.
+#[derive(Debug, Clone)]
+pub struct OnboardingOutcome {
+    /// The onboarded merchant, as fetched after all missing parts were created.
+    pub result: OnboardMerchantResult,
+
+    /// The business entity.
+    pub entity: ResourceOutcome,
+
+    /// The merchant.
+    pub merchant: ResourceOutcome,
+
+    /// Bank accounts.
+    ///
+    /// When resuming, these follow the order of the request's `accounts`.
+    pub accounts: Vec<ResourceOutcome>,
+
+    /// Members.
+    ///
+    /// When resuming, these follow the order of the request's `members`.
+    pub members: Vec<ResourceOutcome>,
+}
+
+impl OnboardingOutcome {
+    /// Whether every resource was created by this call (a first-time onboarding).
+    pub fn is_new(&self) -> bool {
+        self.entity.is_created()
+    }
+
+    /// Whether this call created nothing (the onboarding was already complete).
+    pub fn is_unchanged(&self) -> bool {
+        !self.entity.is_created()
+            && !self.merchant.is_created()
+            && !self.accounts.iter().any(ResourceOutcome::is_created)
+            && !self.members.iter().any(ResourceOutcome::is_created)
+    }
+}
+
+/// A child resource payload with its parent reference.
+#[derive(Serialize)]
+pub(super) struct ChildPayload<'a, T> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) entity: Option<&'a str>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) merchant: Option<&'a str>,
+
+    #[serde(flatten)]
+    pub(super) payload: T,
+}
+
+/// Onboard a merchant, resuming from whatever an earlier attempt already created.
+///
+/// Unlike [`onboard_merchant()`], this is safe to call again after a timeout,
+/// cancellation or partial failure:
+///
+/// 1. Looks for an active entity with the same EIN and legal name
+///    (case- and whitespace-insensitive). If none exists, submits the full
+///    nested request exactly like [`onboard_merchant()`].
+/// 2. Reuses the entity's merchant, or creates one (with its members).
+/// 3. Creates any requested bank accounts that are missing. Existing accounts
+///    are matched by Plaid token, or by the last four digits of the account
+///    number and the account name.
+/// 4. Creates any requested members that are missing. Existing members are
+///    matched by first name, last name and date of birth.
+///
+/// Existing resources are never modified; in particular a reused merchant
+/// keeps its current boarding status.
+///
+/// # Errors
+///
+/// Returns an error if the request fails validation or an API call fails.
+/// Anything created before the failure is found and reused on the next call.
+///
+/// # Example
+///
+/// ```no_run
+/// use payrix::{PayrixClient, Environment};
+/// use payrix::workflows::merchant_onboarding::{onboard_merchant_resumable, OnboardMerchantRequest};
+///
+/// # async fn example(client: PayrixClient, request: OnboardMerchantRequest) -> payrix::Result<()> {
+/// let outcome = onboard_merchant_resumable(&client, request).await?;
+///
+/// if outcome.is_new() {
+///     println!("Onboarded merchant {}", outcome.result.merchant_id);
+/// } else {
+///     println!(
+///         "Resumed onboarding of {} ({} accounts and {} members added)",
+///         outcome.result.merchant_id,
+///         outcome.accounts.iter().filter(|a| a.is_created()).count(),
+///         outcome.members.iter().filter(|m| m.is_created()).count(),
+///     );
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn onboard_merchant_resumable(
+    client: &PayrixClient,
+    request: OnboardMerchantRequest,
+) -> Result<OnboardingOutcome> {
+    validate_request(&request)?;
+
+    let Some(entity) = find_existing_entity(client, &request.business).await? else {
+        let result = onboard_merchant(client, request).await?;
+
+        // A fresh nested submission creates everything in one call.
+        return Ok(OnboardingOutcome {
+            entity: ResourceOutcome::Created(result.entity_id.clone()),
+            merchant: ResourceOutcome::Created(result.merchant_id.clone()),
+            accounts: created_ids(result.accounts.iter().map(|a| &a.id)),
+            members: created_ids(result.members.iter().map(|m| &m.id)),
+            result,
+        });
+    };
+
+    let entity_id = entity.id.as_str().to_string();
+    let OnboardMerchantRequest {
+        merchant: merchant_config,
+        accounts: requested_accounts,
+        members: requested_members,
+        ..
+    } = request;
+
+    // Merchant (created together with its members if missing)
+    let existing_merchants: Vec<Merchant> = client
+        .search(EntityType::Merchants, &format!("entity[equals]={}", entity_id))
+        .await?;
+    let existing_merchant = existing_merchants.into_iter().find(|m| !m.inactive);
+
+    let (merchant, members) = match existing_merchant {
+        Some(merchant) => {
+            let merchant_id = merchant.id.as_str().to_string();
+            let members = ensure_members(client, &merchant_id, requested_members).await?;
+            (ResourceOutcome::Reused(merchant_id), members)
+        }
+        None => {
+            let payload = ChildPayload {
+                entity: Some(entity_id.as_str()),
+                merchant: None,
+                payload: PayrixMerchantPayload::new(merchant_config, requested_members),
+            };
+            let created: Merchant = client.create(EntityType::Merchants, &payload).await?;
+            let merchant_id = created.id.as_str().to_string();
+            let members: Vec<Member> = client
+                .search(EntityType::Members, &format!("merchant[equals]={}", merchant_id))
+                .await?;
+            (
+                ResourceOutcome::Created(merchant_id),
+                created_ids(members.iter().map(|m| &m.id)),
+            )
+        }
+    };
+
+    let accounts = ensure_accounts(client, &entity_id, requested_accounts).await?;
+
+    let result =
+        fetch_onboarding_result(client, entity_id.clone(), merchant.id().to_string(), None).await?;
+
+    Ok(OnboardingOutcome {
+        result,
+        entity: ResourceOutcome::Reused(entity_id),
+        merchant,
+        accounts,
+        members,
+    })
+}
+
+/// Find an active entity with the business's EIN and legal name.
+///
+/// If several match, the oldest is used so repeated calls agree.
+async fn find_existing_entity(
+    client: &PayrixClient,
+    business: &BusinessInfo,
+) -> Result<Option<Entity>> {
+    let entities: Vec<Entity> = client
+        .search(EntityType::Entities, &format!("ein[equals]={}", business.ein))
+        .await?;
+
+    let legal_name = normalize_name(&business.legal_name);
+    Ok(entities
+        .into_iter()
+        .filter(|e| !e.inactive && e.ein.as_deref() == Some(business.ein.as_str()))
+        .filter(|e| e.name.as_deref().map(normalize_name).as_deref() == Some(legal_name.as_str()))
+        .min_by(|a, b| a.created.cmp(&b.created)))
+}
+
+/// Create the requested accounts that don't already exist on the entity.
+async fn ensure_accounts(
+    client: &PayrixClient,
+    entity_id: &str,
+    requested: Vec<BankAccountInfo>,
+) -> Result<Vec<ResourceOutcome>> {
+    let existing: Vec<Account> = client
+        .search(EntityType::Accounts, &format!("entity[equals]={}", entity_id))
+        .await?;
+    let mut unmatched: Vec<&Account> = existing.iter().filter(|a| !a.inactive).collect();
+
+    let mut outcomes = Vec::with_capacity(requested.len());
+    for info in requested {
+        if let Some(pos) = unmatched.iter().position(|a| account_matches(a, &info)) {
+            outcomes.push(ResourceOutcome::Reused(unmatched.remove(pos).id.as_str().to_string()));
+            continue;
+        }
+
+        let payload = ChildPayload {
+            entity: Some(entity_id),
+            merchant: None,
+            payload: PayrixAccountPayload::from(info),
+        };
+        let created: Account = client.create(EntityType::Accounts, &payload).await?;
+        outcomes.push(ResourceOutcome::Created(created.id.as_str().to_string()));
+    }
+    Ok(outcomes)
+}
+
+/// Create the requested members that don't already exist on the merchant.
+async fn ensure_members(
+    client: &PayrixClient,
+    merchant_id: &str,
+    requested: Vec<MemberInfo>,
+) -> Result<Vec<ResourceOutcome>> {
+    let existing: Vec<Member> = client
+        .search(EntityType::Members, &format!("merchant[equals]={}", merchant_id))
+        .await?;
+    let mut unmatched: Vec<&Member> = existing.iter().filter(|m| !m.inactive).collect();
+
+    let mut outcomes = Vec::with_capacity(requested.len());
+    for info in requested {
+        if let Some(pos) = unmatched.iter().position(|m| member_matches(m, &info)) {
+            outcomes.push(ResourceOutcome::Reused(unmatched.remove(pos).id.as_str().to_string()));
+            continue;
+        }
+
+        let payload = ChildPayload {
+            entity: None,
+            merchant: Some(merchant_id),
+            payload: PayrixMemberPayload::from(info),
+        };
+        let created: Member = client.create(EntityType::Members, &payload).await?;
+        outcomes.push(ResourceOutcome::Created(created.id.as_str().to_string()));
+    }
+    Ok(outcomes)
+}
+
+/// Outcomes for resources created by a nested submission.
+fn created_ids<'a>(ids: impl Iterator<Item = &'a PayrixId>) -> Vec<ResourceOutcome> {
+    ids.map(|id| ResourceOutcome::Created(id.as_str().to_string()))
+        .collect()
+}
+
+fn normalize_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn digits(value: &str) -> String {
+    value.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+/// The last four digits of `value`, ignoring any other characters.
+fn last_four_digits(value: &str) -> Option<String> {
+    let digits = digits(value);
+    (digits.len() >= 4).then(|| digits[digits.len() - 4..].to_string())
+}
+
+fn account_matches(existing: &Account, info: &BankAccountInfo) -> bool {
+    if let Some(ref token) = info.plaid_public_token {
+        return existing.public_token.as_deref() == Some(token.as_str());
+    }
+
+    let Some(last4) = info.account_number.as_deref().and_then(last_four_digits) else {
+        return false;
+    };
+    let mask_matches = existing.mask.as_deref().and_then(last_four_digits) == Some(last4);
+    let name_matches = match (&existing.name, &info.name) {
+        (Some(a), Some(b)) => normalize_name(a) == normalize_name(b),
+        _ => true,
+    };
+    mask_matches && name_matches
+}
+
+fn member_matches(existing: &Member, info: &MemberInfo) -> bool {
+    let same = |a: &Option<String>, b: &str| {
+        a.as_deref().map(normalize_name).as_deref() == Some(normalize_name(b).as_str())
+    };
+    same(&existing.first, &info.first_name)
+        && same(&existing.last, &info.last_name)
+        && existing
+            .dob
+            .as_deref()
+            .is_none_or(|dob| digits(dob) == info.date_of_birth)
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -1479,7 +2231,7 @@ mod tests {
             },
             accounts: vec![BankAccountInfo {
                 name: Some("Operating".to_string()),
-                routing_number: Some("123456789".to_string()),
+                routing_number: Some("121000358".to_string()),
                 account_number: Some("987654321".to_string()),
                 holder_type: AccountHolderType::Business,
             account_method: BankAccountMethod::Checking,
@@ -1489,7 +2241,7 @@ mod tests {
                 plaid_public_token: None,
             }],
             members: vec![MemberInfo {
-                member_type: MemberType::Owner,
+                member_type: MemberType::ControlPerson,
                 first_name: "John".to_string(),
                 last_name: "Doe".to_string(),
                 title: Some("CEO".to_string()),
@@ -1725,6 +2477,328 @@ mod tests {
         assert!(err.to_string().contains("Member 2"));
     }
 
+    // ============================================================================
+    // Validation Report Tests
+    // ============================================================================
+
+    fn as_of() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()
+    }
+
+    fn second_member(member_type: MemberType, ownership_percentage: i32) -> MemberInfo {
+        let mut member = valid_request().members.remove(0);
+        member.member_type = member_type;
+        member.first_name = "Jane".to_string();
+        member.ownership_percentage = ownership_percentage;
+        member
+    }
+
+    #[test]
+    fn test_report_valid_request() {
+        let report = valid_request().validate_as_of(as_of());
+        assert!(report.is_valid(), "unexpected issues: {}", report);
+        assert!(report.issues().is_empty());
+    }
+
+    #[test]
+    fn test_report_collects_all_issues_with_paths() {
+        let mut request = valid_request();
+        request.accounts[0].routing_number = Some("12345".to_string());
+        request.members[0].ssn = "123-45-6789".to_string();
+        request.business.ein = "12-3456789".to_string();
+
+        let report = request.validate_as_of(as_of());
+        let paths: Vec<&str> = report.issues().iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["business.ein", "accounts[0].routing_number", "members[0].ssn"]
+        );
+        assert!(report.issues().iter().all(|i| i.code == ValidationCode::InvalidFormat));
+
+        let err = report.into_result().unwrap_err().to_string();
+        assert!(err.contains("EIN") && err.contains("routing number") && err.contains("SSN"));
+    }
+
+    #[test]
+    fn test_aba_checksum() {
+        assert!(aba_checksum_valid("121000358"));
+        assert!(aba_checksum_valid("021000021"));
+        assert!(!aba_checksum_valid("123456789"));
+        assert!(!aba_checksum_valid("12100035"));
+
+        let mut request = valid_request();
+        request.accounts[0].routing_number = Some("123456789".to_string());
+        let report = request.validate_as_of(as_of());
+        assert_eq!(report.issues().len(), 1);
+        assert_eq!(report.issues()[0].path, "accounts[0].routing_number");
+        assert_eq!(report.issues()[0].code, ValidationCode::InvalidChecksum);
+    }
+
+    #[test]
+    fn test_report_state_and_zip() {
+        let mut request = valid_request();
+        request.business.address.state = "Illinois".to_string();
+        request.business.address.zip = "6060".to_string();
+        request.members[0].address.zip = "60602-1234".to_string();
+
+        let report = request.validate_as_of(as_of());
+        let codes: Vec<_> = report
+            .issues()
+            .iter()
+            .map(|i| (i.path.as_str(), i.code))
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                ("business.address.state", ValidationCode::InvalidValue),
+                ("business.address.zip", ValidationCode::InvalidFormat),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_report_skips_us_checks_for_other_countries() {
+        let mut request = valid_request();
+        request.business.address.country = "CAN".to_string();
+        request.business.address.state = "ON".to_string();
+        request.business.address.zip = "M5V 2T6".to_string();
+        assert!(request.validate_as_of(as_of()).is_valid());
+    }
+
+    #[test]
+    fn test_report_mcc() {
+        for mcc in ["599", "59A9", "0000", "0500"] {
+            let mut request = valid_request();
+            request.merchant.mcc = mcc.to_string();
+            let report = request.validate_as_of(as_of());
+            assert_eq!(report.for_path("merchant.mcc").count(), 1, "mcc {}", mcc);
+        }
+        for mcc in ["0742", "8111", "5812"] {
+            let mut request = valid_request();
+            request.merchant.mcc = mcc.to_string();
+            assert!(request.validate_as_of(as_of()).is_valid(), "mcc {}", mcc);
+        }
+    }
+
+    #[test]
+    fn test_report_ownership_total() {
+        let mut request = valid_request();
+        request.members[0].ownership_percentage = 60;
+        request.members.push(second_member(MemberType::ControlPerson, 50));
+
+        let report = request.validate_as_of(as_of());
+        assert_eq!(report.issues().len(), 1);
+        assert_eq!(report.issues()[0].path, "members");
+        assert_eq!(report.issues()[0].code, ValidationCode::OwnershipExceeded);
+        assert!(report.issues()[0].message.contains("110%"));
+    }
+
+    #[test]
+    fn test_report_control_person() {
+        // A sole owner still needs the control prong
+        let mut request = valid_request();
+        request.members[0].member_type = MemberType::Owner;
+        assert!(request
+            .validate_as_of(as_of())
+            .has_code(ValidationCode::MissingControlPerson));
+
+        request.members[0].member_type = MemberType::Principal;
+        assert!(request.validate_as_of(as_of()).is_valid());
+
+        request.members[0].member_type = MemberType::Owner;
+        request.members[0].ownership_percentage = 50;
+        request.members.push(second_member(MemberType::Owner, 50));
+        assert!(request
+            .validate_as_of(as_of())
+            .has_code(ValidationCode::MissingControlPerson));
+
+        request.members[1].member_type = MemberType::ControlPerson;
+        assert!(request.validate_as_of(as_of()).is_valid());
+    }
+
+    #[test]
+    fn test_report_member_age() {
+        let mut request = valid_request();
+        request.members[0].date_of_birth = "20060602".to_string();
+        let report = request.validate_as_of(as_of());
+        assert_eq!(report.issues().len(), 1);
+        assert_eq!(report.issues()[0].path, "members[0].date_of_birth");
+        assert_eq!(report.issues()[0].code, ValidationCode::Underage);
+
+        // Turns 18 on the validation date
+        request.members[0].date_of_birth = "20060601".to_string();
+        assert!(request.validate_as_of(as_of()).is_valid());
+    }
+
+    #[test]
+    fn test_report_impossible_dob_is_format_error() {
+        let mut request = valid_request();
+        request.members[0].date_of_birth = "19801345".to_string();
+        let report = request.validate_as_of(as_of());
+        assert_eq!(report.issues()[0].code, ValidationCode::InvalidFormat);
+    }
+
+    #[test]
+    fn test_report_contact_formats() {
+        let mut request = valid_request();
+        request.business.email = "not-an-email".to_string();
+        request.business.phone = "(555) 123-4567".to_string();
+        request.business.website = Some("www.example.com".to_string());
+        request.members[0].email = "john@example".to_string();
+
+        let report = request.validate_as_of(as_of());
+        let paths: Vec<&str> = report.issues().iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "business.email",
+                "business.phone",
+                "business.website",
+                "members[0].email"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_contact_format_helpers() {
+        assert!(is_valid_email("owner@example.co.uk"));
+        assert!(!is_valid_email("owner@@example.com"));
+        assert!(!is_valid_email("@example.com"));
+        assert!(!is_valid_email("owner @example.com"));
+        assert!(is_valid_phone("5551234567"));
+        assert!(!is_valid_phone("555123456"));
+        assert!(is_valid_url("https://example.com/path?q=1"));
+        assert!(is_valid_url("http://shop.example.com"));
+        assert!(!is_valid_url("https://localhost"));
+        assert!(!is_valid_url("ftp://example.com"));
+    }
+
+    #[test]
+    fn test_report_for_path_matches_nested_only() {
+        let mut report = ValidationReport::default();
+        report.push("members[1].ssn", ValidationCode::InvalidFormat, "a");
+        report.push("members[1].address.zip", ValidationCode::InvalidFormat, "b");
+        report.push("members[10].ssn", ValidationCode::InvalidFormat, "c");
+        report.push("members", ValidationCode::OwnershipExceeded, "d");
+
+        assert_eq!(report.for_path("members[1]").count(), 2);
+        assert_eq!(report.for_path("members").count(), 4);
+        assert_eq!(report.for_path("members[1].ssn").count(), 1);
+    }
+
+    #[test]
+    fn test_report_serialization() {
+        let mut request = valid_request();
+        request.members[0].ssn = "1234".to_string();
+        let json = serde_json::to_value(request.validate_as_of(as_of())).unwrap();
+        assert_eq!(json["issues"][0]["path"], "members[0].ssn");
+        assert_eq!(json["issues"][0]["code"], "invalid_format");
+    }
+
+    // ============================================================================
+    // Resumable Onboarding Tests
+    // ============================================================================
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("  Test   LLC "), "test llc");
+        assert_eq!(normalize_name("TEST LLC"), normalize_name("test llc"));
+    }
+
+    #[test]
+    fn test_account_matches_by_mask_and_name() {
+        let info = valid_request().accounts.remove(0);
+        let existing: Account = serde_json::from_value(serde_json::json!({
+            "id": "t1_acc_12345678901234567890123",
+            "name": "operating",
+            "mask": "****4321"
+        }))
+        .unwrap();
+        assert!(account_matches(&existing, &info));
+
+        let other_name: Account = serde_json::from_value(serde_json::json!({
+            "id": "t1_acc_12345678901234567890123",
+            "name": "Trust",
+            "mask": "****4321"
+        }))
+        .unwrap();
+        assert!(!account_matches(&other_name, &info));
+
+        let other_number: Account = serde_json::from_value(serde_json::json!({
+            "id": "t1_acc_12345678901234567890123",
+            "mask": "****9999"
+        }))
+        .unwrap();
+        assert!(!account_matches(&other_number, &info));
+
+        // Separators and non-ASCII input are ignored rather than sliced
+        let mut spaced = info.clone();
+        spaced.account_number = Some("98765–4321".to_string());
+        assert!(account_matches(&existing, &spaced));
+
+        spaced.account_number = Some("ä€1".to_string());
+        assert!(!account_matches(&existing, &spaced));
+    }
+
+    #[test]
+    fn test_account_matches_by_plaid_token() {
+        let mut info = valid_request().accounts.remove(0);
+        info.plaid_public_token = Some("public-token-xxx".to_string());
+        let existing: Account = serde_json::from_value(serde_json::json!({
+            "id": "t1_acc_12345678901234567890123",
+            "publicToken": "public-token-xxx"
+        }))
+        .unwrap();
+        assert!(account_matches(&existing, &info));
+
+        info.plaid_public_token = Some("public-token-yyy".to_string());
+        assert!(!account_matches(&existing, &info));
+    }
+
+    #[test]
+    fn test_member_matches_by_name_and_dob() {
+        let info = valid_request().members.remove(0);
+        let existing: Member = serde_json::from_value(serde_json::json!({
+            "id": "t1_mem_12345678901234567890123",
+            "first": "JOHN",
+            "last": "Doe",
+            "dob": "19800115"
+        }))
+        .unwrap();
+        assert!(member_matches(&existing, &info));
+
+        let different_dob: Member = serde_json::from_value(serde_json::json!({
+            "id": "t1_mem_12345678901234567890123",
+            "first": "John",
+            "last": "Doe",
+            "dob": "19810115"
+        }))
+        .unwrap();
+        assert!(!member_matches(&different_dob, &info));
+    }
+
+    #[test]
+    fn test_child_payload_flattens_with_parent() {
+        let payload = ChildPayload {
+            entity: None,
+            merchant: Some("t1_mer_12345678901234567890123"),
+            payload: PayrixMemberPayload::from(valid_request().members.remove(0)),
+        };
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["merchant"], "t1_mer_12345678901234567890123");
+        assert_eq!(json["first"], "John");
+        assert!(json.get("entity").is_none());
+    }
+
+    #[test]
+    fn test_resource_outcome() {
+        let created = ResourceOutcome::Created("t1_acc_1".to_string());
+        let reused = ResourceOutcome::Reused("t1_acc_2".to_string());
+        assert!(created.is_created());
+        assert!(!reused.is_created());
+        assert_eq!(reused.id(), "t1_acc_2");
+    }
+
     // ============================================================================
     // Boarding Status Tests
     // ============================================================================
@@ -1932,6 +3006,7 @@ mod tests {
         assert_eq!(payload.last, "Smith");
         assert_eq!(payload.title, Some("President".to_string()));
         assert_eq!(payload.ownership, 50);
+        assert_eq!(payload.significant_responsibility, 0);
         assert_eq!(payload.dob, "19850620");
         assert_eq!(payload.ssn, "987654321");
     }
@@ -3184,6 +4259,7 @@ mod tests {
         let ownership = members[0].get("ownership").unwrap().as_i64().unwrap();
 
         assert_eq!(ownership, 0);
+        assert_eq!(members[0]["significantResponsibility"], 1);
     }
 
     #[test]
@@ -0,0 +1,557 @@
+//! Monthly merchant statements.
+//!
+//! Payrix's [`Statement`](crate::types::Statement) only records a period's
+//! totals. [`MerchantStatement`] itemizes the period instead: every sale,
+//! refund, chargeback, fee, adjustment and disbursement for an entity, with a
+//! [`StatementSummary`] of the totals. Statements serialize to JSON with
+//! serde, and [`to_csv`](MerchantStatement::to_csv) and
+//! [`to_html`](MerchantStatement::to_html) render them for merchants.
+//!
+//! # Line Amounts
+//!
+//! Each line's amount is its effect on the merchant's balance in cents:
+//! sales and positive adjustments are credits; refunds, chargebacks and fees
+//! are debits. Disbursements move funds to the merchant's bank account, so
+//! they are shown as debits but kept out of the net total.
+//!
+//! Only captured or settled transactions are included. Sales are card sales
+//! and captures and eCheck sales; refunds are card and eCheck refunds.
+//!
+//! Requires the `financial` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use payrix::{PayrixClient, Environment};
+//! use payrix::workflows::merchant_statement::MerchantStatement;
+//!
+//! # async fn example() -> payrix::Result<()> {
+//! let client = PayrixClient::new("api-key", Environment::Test)?;
+//! let statement = MerchantStatement::fetch_month(&client, "t1_ent_12345678901234567890123", 2024, 3).await?;
+//!
+//! println!("Net: {} cents", statement.summary.net);
+//! std::fs::write("statement.html", statement.to_html()).unwrap();
+//! std::fs::write("statement.csv", statement.to_csv()).unwrap();
+//! std::fs::write("statement.json", serde_json::to_string_pretty(&statement)?).unwrap();
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt::Write;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::client::PayrixClient;
+use crate::entity::EntityType;
+use crate::error::{Error, Result};
+use crate::search::{make_payrix_date, SearchBuilder, SearchOperator};
+use crate::types::{
+    Adjustment, Chargeback, Disbursement, Entry, Merchant, PayrixId, Transaction,
+    TransactionStatus, TransactionType,
+};
+
+use super::chargeback_monitoring::month_bounds;
+
+// =============================================================================
+// Section 1: Statement Types
+// =============================================================================
+
+/// What a statement line records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatementLineKind {
+    /// A captured sale.
+    Sale,
+    /// A refund of an earlier sale.
+    Refund,
+    /// A chargeback against a sale.
+    Chargeback,
+    /// A fee charged to the merchant.
+    Fee,
+    /// A manual adjustment.
+    Adjustment,
+    /// A payout to the merchant's bank account.
+    Disbursement,
+}
+
+impl StatementLineKind {
+    /// Label for statements.
+    pub fn label(&self) -> &'static str {
+        match self {
+            StatementLineKind::Sale => "Sale",
+            StatementLineKind::Refund => "Refund",
+            StatementLineKind::Chargeback => "Chargeback",
+            StatementLineKind::Fee => "Fee",
+            StatementLineKind::Adjustment => "Adjustment",
+            StatementLineKind::Disbursement => "Disbursement",
+        }
+    }
+}
+
+/// One itemized line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatementLine {
+    /// Day the record was created.
+    pub date: NaiveDate,
+    /// What the line records.
+    pub kind: StatementLineKind,
+    /// The transaction, chargeback, entry, adjustment or disbursement ID.
+    pub id: PayrixId,
+    /// Human-readable detail.
+    pub description: String,
+    /// Effect on the merchant's balance in cents.
+    pub amount: i64,
+}
+
+/// Period totals in cents.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatementSummary {
+    /// Number of sales.
+    pub sale_count: usize,
+    /// Total sales.
+    pub gross_sales: i64,
+    /// Number of refunds.
+    pub refund_count: usize,
+    /// Total refunded (negative).
+    pub refunds: i64,
+    /// Number of chargebacks.
+    pub chargeback_count: usize,
+    /// Total charged back (negative).
+    pub chargebacks: i64,
+    /// Total fees (negative).
+    pub fees: i64,
+    /// Net adjustments.
+    pub adjustments: i64,
+    /// Sales less refunds, chargebacks and fees, plus adjustments.
+    pub net: i64,
+    /// Total paid out to the merchant's bank account.
+    pub disbursed: i64,
+}
+
+/// An itemized statement for one entity and period.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerchantStatement {
+    /// The merchant entity.
+    pub entity_id: String,
+    /// The merchant's DBA, if known.
+    pub merchant_name: Option<String>,
+    /// First day of the period.
+    pub period_start: NaiveDate,
+    /// Last day of the period.
+    pub period_end: NaiveDate,
+    /// Totals.
+    pub summary: StatementSummary,
+    /// Every line, by date and then kind.
+    pub lines: Vec<StatementLine>,
+}
+
+/// Records to build a statement from.
+#[derive(Debug, Clone, Default)]
+pub struct StatementData {
+    /// The merchant's DBA.
+    pub merchant_name: Option<String>,
+    /// The merchant's transactions.
+    pub transactions: Vec<Transaction>,
+    /// The merchant's chargebacks.
+    pub chargebacks: Vec<Chargeback>,
+    /// Ledger entries for the entity; those with a `fee` become fee lines.
+    pub entries: Vec<Entry>,
+    /// Adjustments applied to the entity.
+    pub adjustments: Vec<Adjustment>,
+    /// Disbursements to the entity.
+    pub disbursements: Vec<Disbursement>,
+}
+
+// =============================================================================
+// Section 2: Building
+// =============================================================================
+
+impl MerchantStatement {
+    /// Build a statement from records already in hand.
+    ///
+    /// Records created outside `period_start..=period_end`, or without a
+    /// parseable `created` timestamp, are left out.
+    pub fn new(
+        entity_id: impl Into<String>,
+        period_start: NaiveDate,
+        period_end: NaiveDate,
+        data: StatementData,
+    ) -> Self {
+        let in_period = |created: Option<&str>| {
+            created_date(created).filter(|d| (period_start..=period_end).contains(d))
+        };
+        let mut lines = Vec::new();
+
+        for txn in &data.transactions {
+            if !matches!(txn.status, Some(TransactionStatus::Captured) | Some(TransactionStatus::Settled)) {
+                continue;
+            }
+            let kind = match txn.txn_type {
+                TransactionType::CreditCardSale
+                | TransactionType::CreditCardCapture
+                | TransactionType::ECheckSale => StatementLineKind::Sale,
+                TransactionType::CreditCardRefund | TransactionType::ECheckRefund => {
+                    StatementLineKind::Refund
+                }
+                _ => continue,
+            };
+            let Some(date) = in_period(txn.created.as_deref()) else {
+                continue;
+            };
+            let total = txn.total.unwrap_or(0);
+            lines.push(StatementLine {
+                date,
+                kind,
+                id: txn.id.clone(),
+                description: txn.description.clone().unwrap_or_else(|| kind.label().to_string()),
+                amount: if kind == StatementLineKind::Refund { -total } else { total },
+            });
+        }
+
+        for chargeback in &data.chargebacks {
+            let Some(date) = in_period(chargeback.created.as_deref()) else {
+                continue;
+            };
+            let description = match (&chargeback.reason, &chargeback.txn) {
+                (Some(reason), _) => format!("Chargeback: {}", reason),
+                (None, Some(txn)) => format!("Chargeback on {}", txn),
+                (None, None) => "Chargeback".to_string(),
+            };
+            lines.push(StatementLine {
+                date,
+                kind: StatementLineKind::Chargeback,
+                id: chargeback.id.clone(),
+                description,
+                amount: -chargeback.total.unwrap_or(0),
+            });
+        }
+
+        for entry in data.entries.iter().filter(|e| e.fee.is_some()) {
+            let Some(date) = in_period(entry.created.as_deref()) else {
+                continue;
+            };
+            lines.push(StatementLine {
+                date,
+                kind: StatementLineKind::Fee,
+                id: entry.id.clone(),
+                description: entry.description.clone().unwrap_or_else(|| "Fee".to_string()),
+                amount: entry.amount.unwrap_or(0.0).round() as i64,
+            });
+        }
+
+        for adjustment in &data.adjustments {
+            let Some(date) = in_period(adjustment.created.as_deref()) else {
+                continue;
+            };
+            lines.push(StatementLine {
+                date,
+                kind: StatementLineKind::Adjustment,
+                id: adjustment.id.clone(),
+                description: adjustment.description.clone().unwrap_or_else(|| "Adjustment".to_string()),
+                amount: adjustment.amount.unwrap_or(0.0).round() as i64,
+            });
+        }
+
+        for disbursement in &data.disbursements {
+            let Some(date) = in_period(disbursement.created.as_deref()) else {
+                continue;
+            };
+            lines.push(StatementLine {
+                date,
+                kind: StatementLineKind::Disbursement,
+                id: disbursement.id.clone(),
+                description: disbursement
+                    .description
+                    .clone()
+                    .unwrap_or_else(|| "Payout to bank account".to_string()),
+                amount: -disbursement.amount.unwrap_or(0),
+            });
+        }
+
+        lines.sort_by(|a, b| {
+            (a.date, a.kind)
+                .cmp(&(b.date, b.kind))
+                .then_with(|| a.id.as_str().cmp(b.id.as_str()))
+        });
+
+        let mut summary = StatementSummary::default();
+        for line in &lines {
+            match line.kind {
+                StatementLineKind::Sale => {
+                    summary.sale_count += 1;
+                    summary.gross_sales += line.amount;
+                }
+                StatementLineKind::Refund => {
+                    summary.refund_count += 1;
+                    summary.refunds += line.amount;
+                }
+                StatementLineKind::Chargeback => {
+                    summary.chargeback_count += 1;
+                    summary.chargebacks += line.amount;
+                }
+                StatementLineKind::Fee => summary.fees += line.amount,
+                StatementLineKind::Adjustment => summary.adjustments += line.amount,
+                StatementLineKind::Disbursement => summary.disbursed -= line.amount,
+            }
+        }
+        summary.net =
+            summary.gross_sales + summary.refunds + summary.chargebacks + summary.fees + summary.adjustments;
+
+        Self {
+            entity_id: entity_id.into(),
+            merchant_name: data.merchant_name,
+            period_start,
+            period_end,
+            summary,
+            lines,
+        }
+    }
+}
+
+fn created_date(created: Option<&str>) -> Option<NaiveDate> {
+    created
+        .and_then(|c| NaiveDateTime::parse_from_str(c, "%Y-%m-%d %H:%M:%S%.f").ok())
+        .map(|t| t.date())
+}
+
+// =============================================================================
+// Section 3: Rendering
+// =============================================================================
+
+impl MerchantStatement {
+    /// The lines as CSV with the header `date,type,id,description,amount`.
+    ///
+    /// Amounts are in dollars with two decimal places.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("date,type,id,description,amount\n");
+        for line in &self.lines {
+            // Writing to a String cannot fail
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{}",
+                line.date.format("%Y-%m-%d"),
+                line.kind.label(),
+                line.id,
+                csv_field(&line.description),
+                dollars(line.amount)
+            );
+        }
+        csv
+    }
+
+    /// A standalone HTML page with the summary and line items.
+    pub fn to_html(&self) -> String {
+        let title = format!(
+            "Statement for {}, {} to {}",
+            self.merchant_name.as_deref().unwrap_or(&self.entity_id),
+            self.period_start.format("%B %-d, %Y"),
+            self.period_end.format("%B %-d, %Y")
+        );
+        let s = &self.summary;
+
+        let mut html = String::new();
+        // Writing to a String cannot fail
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n</head>\n<body>\n<h1>{title}</h1>\n",
+            title = escape_html(&title)
+        );
+
+        html.push_str("<h2>Summary</h2>\n<table>\n");
+        let rows = [
+            (format!("Sales ({})", s.sale_count), s.gross_sales),
+            (format!("Refunds ({})", s.refund_count), s.refunds),
+            (format!("Chargebacks ({})", s.chargeback_count), s.chargebacks),
+            ("Fees".to_string(), s.fees),
+            ("Adjustments".to_string(), s.adjustments),
+            ("Net".to_string(), s.net),
+            ("Paid to bank".to_string(), s.disbursed),
+        ];
+        for (label, amount) in rows {
+            let _ = writeln!(html, "<tr><th>{}</th><td>{}</td></tr>", label, dollars(amount));
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>Activity</h2>\n<table>\n<tr><th>Date</th><th>Type</th><th>Description</th><th>Amount</th></tr>\n");
+        for line in &self.lines {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                line.date.format("%Y-%m-%d"),
+                line.kind.label(),
+                escape_html(&line.description),
+                dollars(line.amount)
+            );
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+}
+
+/// Cents as a signed dollar amount, e.g. `-12.05`.
+fn dollars(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{}{}.{:02}", sign, cents.unsigned_abs() / 100, cents.unsigned_abs() % 100)
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or newline.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// =============================================================================
+// Section 4: Fetching
+// =============================================================================
+
+impl MerchantStatement {
+    /// Fetch everything for `entity_id` between `from` and `to` (inclusive)
+    /// and build a statement.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entity has no merchant or an API call fails.
+    pub async fn fetch(client: &PayrixClient, entity_id: &str, from: NaiveDate, to: NaiveDate) -> Result<Self> {
+        let merchant: Merchant = client
+            .search::<Merchant>(EntityType::Merchants, &SearchBuilder::new().field("entity", entity_id).build())
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::NotFound(format!("No merchant for entity {}", entity_id)))?;
+
+        let after = make_payrix_date(&(from - Duration::days(1)));
+        let before = make_payrix_date(&(to + Duration::days(1)));
+        let in_range = |field: &str, id: &str| {
+            SearchBuilder::new()
+                .field(field, id)
+                .field_with_op("created", &after, SearchOperator::Greater)
+                .field_with_op("created", &before, SearchOperator::Less)
+                .build()
+        };
+
+        let merchant_search = in_range("merchant", merchant.id.as_str());
+        let entity_search = in_range("entity", entity_id);
+        let data = StatementData {
+            merchant_name: merchant.dba.clone(),
+            transactions: client.search(EntityType::Txns, &merchant_search).await?,
+            chargebacks: client.search(EntityType::Chargebacks, &merchant_search).await?,
+            entries: client.search(EntityType::Entries, &entity_search).await?,
+            adjustments: client.search(EntityType::Adjustments, &entity_search).await?,
+            disbursements: client.search(EntityType::Disbursements, &entity_search).await?,
+        };
+
+        Ok(Self::new(entity_id, from, to, data))
+    }
+
+    /// Fetch the statement for a calendar month.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the month is invalid, the entity has no merchant,
+    /// or an API call fails.
+    pub async fn fetch_month(client: &PayrixClient, entity_id: &str, year: i32, month: u32) -> Result<Self> {
+        let (from, to) = month_bounds(year, month)?;
+        Self::fetch(client, entity_id, from, to).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn statement() -> MerchantStatement {
+        let data = StatementData {
+            merchant_name: Some("Acme <Coffee>".to_string()),
+            transactions: serde_json::from_value(json!([
+                { "id": "t1_txn_00000000000000000000001", "type": 1, "status": 4, "total": 10000,
+                  "created": "2024-03-02 10:00:00.0000", "merchant": "t1_mer_00000000000000000000001" },
+                { "id": "t1_txn_00000000000000000000002", "type": 5, "status": 3, "total": 2500,
+                  "created": "2024-03-05 10:00:00.0000", "description": "Returned mug, chipped" },
+                { "id": "t1_txn_00000000000000000000003", "type": 1, "status": 2, "total": 9999,
+                  "created": "2024-03-05 11:00:00.0000" },
+                { "id": "t1_txn_00000000000000000000004", "type": 1, "status": 4, "total": 9999,
+                  "created": "2024-04-01 00:00:01.0000" },
+            ]))
+            .unwrap(),
+            chargebacks: serde_json::from_value(json!([
+                { "id": "t1_chb_00000000000000000000001", "total": 1500, "reason": "Fraud",
+                  "created": "2024-03-10 09:00:00.0000" },
+            ]))
+            .unwrap(),
+            entries: serde_json::from_value(json!([
+                { "id": "t1_ent_00000000000000000000001", "fee": "t1_fee_00000000000000000000001",
+                  "amount": -320.4, "created": "2024-03-02 10:00:00.0000" },
+                { "id": "t1_ent_00000000000000000000002", "amount": 10000.0,
+                  "created": "2024-03-02 10:00:00.0000" },
+            ]))
+            .unwrap(),
+            adjustments: serde_json::from_value(json!([
+                { "id": "t1_adj_00000000000000000000001", "amount": 500.0, "description": "Goodwill credit",
+                  "created": "2024-03-15 09:00:00.0000" },
+            ]))
+            .unwrap(),
+            disbursements: serde_json::from_value(json!([
+                { "id": "t1_dbm_00000000000000000000001", "amount": 6000, "created": "2024-03-20 09:00:00.0000" },
+            ]))
+            .unwrap(),
+        };
+        let (from, to) = month_bounds(2024, 3).unwrap();
+        MerchantStatement::new("t1_ent_00000000000000000000001", from, to, data)
+    }
+
+    #[test]
+    fn test_summary() {
+        let statement = statement();
+        let s = statement.summary;
+        assert_eq!((s.sale_count, s.gross_sales), (1, 10_000));
+        assert_eq!((s.refund_count, s.refunds), (1, -2_500));
+        assert_eq!((s.chargeback_count, s.chargebacks), (1, -1_500));
+        assert_eq!(s.fees, -320);
+        assert_eq!(s.adjustments, 500);
+        assert_eq!(s.net, 6_180);
+        assert_eq!(s.disbursed, 6_000);
+        assert_eq!(statement.lines.len(), 6);
+        assert_eq!(statement.lines[0].date, NaiveDate::from_ymd_opt(2024, 3, 2).unwrap());
+    }
+
+    #[test]
+    fn test_exports() {
+        let statement = statement();
+
+        let csv = statement.to_csv();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], "date,type,id,description,amount");
+        assert!(rows.contains(&"2024-03-05,Refund,t1_txn_00000000000000000000002,\"Returned mug, chipped\",-25.00"));
+        assert!(rows.contains(&"2024-03-02,Fee,t1_ent_00000000000000000000001,Fee,-3.20"));
+
+        let html = statement.to_html();
+        assert!(html.contains("<h1>Statement for Acme &lt;Coffee&gt;, March 1, 2024 to March 31, 2024</h1>"));
+        assert!(html.contains("<tr><th>Net</th><td>61.80</td></tr>"));
+
+        let json = serde_json::to_value(&statement).unwrap();
+        assert_eq!(json["summary"]["net"], 6_180);
+        assert_eq!(json["lines"][0]["kind"], "sale");
+        let round_trip: MerchantStatement = serde_json::from_value(json).unwrap();
+        assert_eq!(round_trip, statement);
+    }
+}
@@ -11,9 +11,25 @@
 //! # Available Workflows
 //!
 //! - [`merchant_onboarding`] - Onboard new merchants with business info, bank accounts, and owners
+//! - [`onboarding_draft`] - Save partial onboarding applications with encrypted sensitive fields
+//! - [`boarding_watch`] - Wait for merchants to finish boarding, by polling with backoff or via webhooks
+//! - [`merchant_maintenance`] - Change bank accounts, owners, address, DBA or MCC after boarding
 //! - [`dispute_handling`] - Handle chargeback disputes with compile-time state enforcement
+//! - [`dispute_batch`] - Apply one dispute action to many chargebacks concurrently
 //! - [`webhook_setup`] - Set up webhook alerts for real-time event notifications
 //! - [`subscription_management`] - Manage customer subscriptions to recurring payment plans
+//! - [`billing_projection`] - Project a subscription's future billing calendar offline
+//! - [`dunning`] - Retry failed subscription payments on a schedule, then pause or cancel
+//! - [`declines`] - Classify declined payments as soft, hard, needs-new-card or suspected fraud, with retry advice
+//! - [`chargeback_monitoring`] - Track chargeback ratios against card brand dispute thresholds
+//! - [`reconciliation`] - Break a payout down into the sales, fees, refunds and reserves it covers
+//! - [`fund_ledger`] - Rebuild a fund's balances from its entries and audit them against Payrix
+//! - [`fee_engine`] - Quote the fees Payrix would assess on a transaction, offline
+//! - [`payments`] - Authorize, capture, void, refund and reverse payments with status and amount checks
+//! - [`payouts`] - Validate and update payout schedules, and forecast upcoming payout dates and amounts
+//! - [`reserves`] - See a merchant's reserves, holds and upcoming releases, and adjust them
+//! - `settlement_report` - Batch close and settlement totals by merchant, card brand and status (`financial` feature)
+//! - `merchant_statement` - Itemized monthly merchant statements as JSON, CSV or HTML (`financial` feature)
 //!
 //! # Example
 //!
@@ -33,16 +49,55 @@
 //! # }
 //! ```
 
+pub mod billing_projection;
+pub mod boarding_watch;
+pub mod chargeback_monitoring;
+pub mod declines;
+pub mod dispute_batch;
 pub mod dispute_handling;
+pub mod dunning;
+pub mod fee_engine;
+pub mod fund_ledger;
+pub mod merchant_maintenance;
 pub mod merchant_onboarding;
+#[cfg(feature = "financial")]
+pub mod merchant_statement;
+pub mod onboarding_draft;
+pub mod payments;
+pub mod payouts;
+pub mod reconciliation;
+pub mod reserves;
+#[cfg(feature = "financial")]
+pub mod settlement_report;
 pub mod subscription_management;
 pub mod webhook_setup;
 
 // Re-export key types for convenience
 pub use merchant_onboarding::{
-    check_boarding_status, onboard_merchant, Address, BankAccountInfo, BoardingStatus,
-    BoardingStatusResult, BusinessInfo, MemberInfo, MerchantConfig, OnboardMerchantRequest,
-    OnboardMerchantResult, TermsAcceptance,
+    check_boarding_status, onboard_merchant, onboard_merchant_resumable, Address,
+    BankAccountInfo, BoardingStatus, BoardingStatusResult, BusinessInfo, MemberInfo,
+    MerchantConfig, OnboardMerchantRequest, OnboardMerchantResult, OnboardingOutcome,
+    ResourceOutcome, TermsAcceptance, ValidationCode, ValidationIssue, ValidationReport,
+};
+
+// Re-export onboarding draft types
+pub use onboarding_draft::{
+    DraftCompleteness, DraftSection, FieldEncryptor, OnboardingDraft, PlaintextEncryptor,
+    SealedDraft, SensitiveField,
+};
+
+// Re-export boarding watch types
+pub use boarding_watch::{
+    is_boarding_in_progress, wait_for_boarding, watch_boarding, BoardingTransition,
+    BoardingUpdate, BoardingWatchResult, TransitionSource, WaitForBoardingOptions,
+};
+#[cfg(feature = "webhooks")]
+pub use boarding_watch::wait_for_boarding_with_webhooks;
+
+// Re-export merchant maintenance types
+pub use merchant_maintenance::{
+    add_member, remove_member, replace_primary_account, update_business_address,
+    update_merchant_profile, MaintenanceChange, MaintenanceResult, MerchantProfileUpdate,
 };
 
 // Re-export dispute handling types
@@ -54,6 +109,12 @@ pub use dispute_handling::{
     MAX_DOCUMENTS, MAX_DOCUMENT_SIZE, MAX_TOTAL_SIZE,
 };
 
+// Re-export bulk dispute types
+pub use dispute_batch::{
+    DisputeAction, DisputeBatch, DisputeBatchItem, DisputeBatchReport, DisputeFilter,
+    DisputeOutcome, DEFAULT_BATCH_CONCURRENCY,
+};
+
 // Re-export webhook setup types
 pub use webhook_setup::{
     get_webhook_status, remove_webhook_by_id, remove_webhooks, setup_webhooks,
@@ -62,11 +123,81 @@ pub use webhook_setup::{
 
 // Re-export subscription management types
 pub use subscription_management::{
-    add_plan_to_customer, calculate_subscription_revenue, cancel_subscription,
+    add_plan_to_customer, calculate_proration, calculate_subscription_revenue, cancel_subscription,
+    change_plan,
     get_active_subscriptions_for_customer, get_subscribers_for_plan, get_subscription_status,
+    get_subscription_token_id,
     get_upcoming_payments, next_payment, pause_subscription, payments_to_date,
     resume_subscription, retry_failed_payment, update_payment_method, BillingSchedule,
-    NextPayment, PaymentHistory, PlanConfig, PlanReference, SubscribeCustomerConfig,
+    NextPayment, PaymentHistory, PlanChangeResult, PlanConfig, PlanReference, ProrationBreakdown,
+    ProrationMode, SubscribeCustomerConfig,
     SubscribeCustomerResult, SubscriptionError, SubscriptionResult, SubscriptionRevenue,
     SubscriptionState, SubscriptionStatus, TokenConfig, TokenReference, UpcomingPayment,
 };
+
+// Re-export chargeback monitoring types
+pub use chargeback_monitoring::{
+    calculate_chargeback_ratios, chargeback_brand, check_chargeback_ratio, find_flagged_merchants,
+    BrandChargebackRatio, ChargebackDateBasis, ChargebackMonitorConfig, ChargebackRatioLevel,
+    ChargebackRatioReport, RatioThresholds,
+};
+
+// Re-export billing projection types
+pub use billing_projection::{
+    billing_date, project_billing_schedule, BillingCalendar, PauseWindow, ProjectedCharge,
+    ProjectionEnd, ProjectionOptions,
+};
+
+// Re-export dunning types
+pub use dunning::{
+    DunningAttempt, DunningConfig, DunningEngine, DunningEvent, DunningFinalAction, DunningState,
+    DunningStatus,
+};
+
+// Re-export decline handling types
+pub use declines::{DeclineAction, DeclineCategory, DeclineInfo, DeclineReason};
+
+// Re-export reconciliation types
+pub use reconciliation::{
+    reconcile, reconcile_deposits, reconcile_disbursement, LineSource, PayoutBreakdown,
+    PayoutReconciliation, ReconciliationCategory, ReconciliationIssue, ReconciliationLine,
+};
+
+// Re-export fund ledger types
+pub use fund_ledger::{
+    daily_balances_csv, BalanceAudit, BalanceDiscrepancy, BalanceKind, DailyBalance, EventTotal,
+    FundBalances, FundLedger, LedgerSnapshot,
+};
+
+// Re-export fee engine types
+pub use fee_engine::{
+    AssessedFee, FeeEngine, FeeQuote, FeeVariance, QuoteRequest, SkipReason, SkippedFee,
+};
+
+// Re-export reserve and hold types
+pub use reserves::{
+    adjust_reserve, release_date, release_hold, ReserveAdjustment, ReserveSummary,
+    ScheduledRelease,
+};
+
+// Re-export payment lifecycle types
+pub use payments::{AuthorizeRequest, PaymentAction, PaymentResult};
+
+// Re-export payout types
+pub use payouts::{
+    forecast_payouts, update_payout, validate_payout, ForecastedPayout, PayoutForecast,
+    PayoutUpdate,
+};
+
+// Re-export settlement report types
+#[cfg(feature = "financial")]
+pub use settlement_report::{
+    settlement_report, BatchSettlement, GroupTotal, SettlementData, SettlementReport,
+    SettlementReportConfig, SettlementTotals,
+};
+
+// Re-export merchant statement types
+#[cfg(feature = "financial")]
+pub use merchant_statement::{
+    MerchantStatement, StatementData, StatementLine, StatementLineKind, StatementSummary,
+};
@@ -0,0 +1,888 @@
+//! Onboarding application drafts.
+//!
+//! Boarding forms are usually filled in over several sessions. An
+//! [`OnboardingDraft`] holds a partial [`OnboardMerchantRequest`] - each
+//! section is optional until submission - and provides:
+//!
+//! - **Persistence** - [`OnboardingDraft::seal`] encrypts the EIN, SSNs,
+//!   routing and account numbers and Plaid public tokens with a
+//!   caller-supplied [`FieldEncryptor`] and returns a [`SealedDraft`], the only serializable form of a draft
+//! - **Redaction** - [`OnboardingDraft::redacted`] and
+//!   [`OnboardMerchantRequest::redacted`] mask the same fields for logging
+//! - **Completeness** - [`OnboardingDraft::completeness`] scores the draft and
+//!   lists the sections that are missing or still fail validation
+//!
+//! # Example
+//!
+//! ```no_run
+//! use payrix::workflows::onboarding_draft::{
+//!     FieldEncryptor, OnboardingDraft, SealedDraft, SensitiveField,
+//! };
+//!
+//! struct KmsEncryptor;
+//!
+//! impl FieldEncryptor for KmsEncryptor {
+//!     fn encrypt(&self, _field: SensitiveField, plaintext: &str) -> payrix::Result<String> {
+//!         // Call your KMS / envelope encryption here
+//!         # Ok(plaintext.to_string())
+//!     }
+//!
+//!     fn decrypt(&self, _field: SensitiveField, ciphertext: &str) -> payrix::Result<String> {
+//!         # Ok(ciphertext.to_string())
+//!     }
+//! }
+//!
+//! # fn example(draft: OnboardingDraft) -> Result<(), Box<dyn std::error::Error>> {
+//! // Save between sessions
+//! let stored = serde_json::to_string(&draft.seal(&KmsEncryptor)?)?;
+//!
+//! // Later: load, check progress, submit when ready
+//! let sealed: SealedDraft = serde_json::from_str(&stored)?;
+//! let draft = sealed.open(&KmsEncryptor)?;
+//!
+//! let completeness = draft.completeness();
+//! println!("{}% complete, outstanding: {:?}", completeness.score(), completeness.outstanding());
+//!
+//! if completeness.is_ready() {
+//!     let request = draft.into_request()?;
+//!     // onboard_merchant(&client, request).await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+use crate::types::{AccountHolderType, AccountType, MemberType, MerchantType};
+
+use super::merchant_onboarding::{
+    mask_sensitive, validate_accounts, validate_business, validate_members, validate_merchant,
+    Address, BankAccountInfo, BankAccountMethod, BusinessInfo, MemberInfo, MerchantConfig,
+    OnboardMerchantRequest, TermsAcceptance, ValidationCode, ValidationReport,
+};
+
+/// Current [`SealedDraft`] format version.
+pub const DRAFT_FORMAT_VERSION: u32 = 1;
+
+// ============================================================================
+// Field Encryption
+// ============================================================================
+
+/// A field that is encrypted when a draft is sealed and masked when redacted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SensitiveField {
+    /// The business EIN (`business.ein`).
+    Ein,
+    /// A member's SSN (`members[i].ssn`).
+    Ssn,
+    /// A bank routing number (`accounts[i].routing_number`).
+    RoutingNumber,
+    /// A bank account number (`accounts[i].account_number`).
+    AccountNumber,
+    /// A Plaid public token (`accounts[i].plaid_public_token`).
+    PlaidPublicToken,
+}
+
+/// Encrypts and decrypts sensitive draft fields.
+///
+/// Implement this over your key management system. The field kind is passed
+/// so implementations can use separate keys or associated data per field.
+/// Empty values are never passed to the encryptor.
+pub trait FieldEncryptor: Send + Sync {
+    /// Encrypt a plaintext value.
+    fn encrypt(&self, field: SensitiveField, plaintext: &str) -> Result<String>;
+
+    /// Decrypt a value produced by [`encrypt`](Self::encrypt).
+    fn decrypt(&self, field: SensitiveField, ciphertext: &str) -> Result<String>;
+
+    /// Identifier of the key in use, recorded on sealed drafts.
+    ///
+    /// Useful for finding drafts that need re-sealing after a key rotation.
+    fn key_id(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// A [`FieldEncryptor`] that stores values unchanged.
+///
+/// Intended for tests and local development only.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlaintextEncryptor;
+
+impl FieldEncryptor for PlaintextEncryptor {
+    fn encrypt(&self, _field: SensitiveField, plaintext: &str) -> Result<String> {
+        Ok(plaintext.to_string())
+    }
+
+    fn decrypt(&self, _field: SensitiveField, ciphertext: &str) -> Result<String> {
+        Ok(ciphertext.to_string())
+    }
+}
+
+/// Apply `f` to every non-empty sensitive field.
+fn for_each_sensitive(
+    business: Option<&mut BusinessInfo>,
+    accounts: &mut [BankAccountInfo],
+    members: &mut [MemberInfo],
+    mut f: impl FnMut(SensitiveField, &mut String) -> Result<()>,
+) -> Result<()> {
+    if let Some(business) = business.filter(|b| !b.ein.is_empty()) {
+        f(SensitiveField::Ein, &mut business.ein)?;
+    }
+    for account in accounts {
+        if let Some(routing) = account.routing_number.as_mut().filter(|n| !n.is_empty()) {
+            f(SensitiveField::RoutingNumber, routing)?;
+        }
+        if let Some(number) = account.account_number.as_mut().filter(|n| !n.is_empty()) {
+            f(SensitiveField::AccountNumber, number)?;
+        }
+        if let Some(token) = account.plaid_public_token.as_mut().filter(|t| !t.is_empty()) {
+            f(SensitiveField::PlaidPublicToken, token)?;
+        }
+    }
+    for member in members {
+        if !member.ssn.is_empty() {
+            f(SensitiveField::Ssn, &mut member.ssn)?;
+        }
+    }
+    Ok(())
+}
+
+/// Mask sensitive fields in place.
+fn redact(
+    business: Option<&mut BusinessInfo>,
+    accounts: &mut [BankAccountInfo],
+    members: &mut [MemberInfo],
+) {
+    // Masking cannot fail.
+    let _ = for_each_sensitive(business, accounts, members, |_, value| {
+        *value = mask_sensitive(value);
+        Ok(())
+    });
+}
+
+impl OnboardMerchantRequest {
+    /// A copy of the request with the EIN, SSNs, routing and account numbers
+    /// and Plaid tokens masked to their last four characters.
+    ///
+    /// The result is safe to log, but cannot be submitted.
+    pub fn redacted(&self) -> Self {
+        let mut copy = self.clone();
+        redact(Some(&mut copy.business), &mut copy.accounts, &mut copy.members);
+        copy
+    }
+}
+
+// ============================================================================
+// Drafts
+// ============================================================================
+
+/// A section of an onboarding application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DraftSection {
+    /// Legal business details ([`BusinessInfo`]).
+    Business,
+    /// Processing configuration ([`MerchantConfig`]).
+    Merchant,
+    /// Funding bank accounts.
+    Accounts,
+    /// Beneficial owners and control persons.
+    Members,
+    /// Terms and conditions acceptance.
+    Terms,
+}
+
+impl DraftSection {
+    /// All sections, in form order.
+    pub const ALL: [DraftSection; 5] = [
+        DraftSection::Business,
+        DraftSection::Merchant,
+        DraftSection::Accounts,
+        DraftSection::Members,
+        DraftSection::Terms,
+    ];
+
+    /// Validation path prefix for issues in this section.
+    pub fn path(&self) -> &'static str {
+        match self {
+            DraftSection::Business => "business",
+            DraftSection::Merchant => "merchant",
+            DraftSection::Accounts => "accounts",
+            DraftSection::Members => "members",
+            DraftSection::Terms => "terms_acceptance",
+        }
+    }
+}
+
+impl std::fmt::Display for DraftSection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            DraftSection::Business => "business",
+            DraftSection::Merchant => "merchant",
+            DraftSection::Accounts => "bank accounts",
+            DraftSection::Members => "members",
+            DraftSection::Terms => "terms acceptance",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A partially completed onboarding application.
+///
+/// Every section of [`OnboardMerchantRequest`] is optional here, so the draft
+/// can be saved as the applicant works through the form.
+///
+/// The draft holds sensitive fields in the clear and is not serializable;
+/// persist it with [`seal`](Self::seal).
+#[derive(Debug, Clone, Default)]
+pub struct OnboardingDraft {
+    /// Business entity information.
+    pub business: Option<BusinessInfo>,
+
+    /// Merchant processing configuration.
+    pub merchant: Option<MerchantConfig>,
+
+    /// Bank accounts entered so far.
+    pub accounts: Vec<BankAccountInfo>,
+
+    /// Members entered so far.
+    pub members: Vec<MemberInfo>,
+
+    /// Terms and conditions acceptance, once given.
+    pub terms_acceptance: Option<TermsAcceptance>,
+}
+
+impl From<OnboardMerchantRequest> for OnboardingDraft {
+    fn from(request: OnboardMerchantRequest) -> Self {
+        Self {
+            business: Some(request.business),
+            merchant: Some(request.merchant),
+            accounts: request.accounts,
+            members: request.members,
+            terms_acceptance: Some(request.terms_acceptance),
+        }
+    }
+}
+
+impl OnboardingDraft {
+    /// Create an empty draft.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Encrypt sensitive fields for storage.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error from the encryptor.
+    pub fn seal(&self, encryptor: &dyn FieldEncryptor) -> Result<SealedDraft> {
+        let mut draft = self.clone();
+        for_each_sensitive(
+            draft.business.as_mut(),
+            &mut draft.accounts,
+            &mut draft.members,
+            |field, value| {
+                *value = encryptor.encrypt(field, value)?;
+                Ok(())
+            },
+        )?;
+
+        Ok(SealedDraft {
+            version: DRAFT_FORMAT_VERSION,
+            key_id: encryptor.key_id().map(str::to_string),
+            draft: DraftRecord::from(draft),
+        })
+    }
+
+    /// A copy of the draft with sensitive fields masked, safe to log.
+    pub fn redacted(&self) -> Self {
+        let mut copy = self.clone();
+        redact(copy.business.as_mut(), &mut copy.accounts, &mut copy.members);
+        copy
+    }
+
+    /// Score the draft, checking member ages against today's date (UTC).
+    pub fn completeness(&self) -> DraftCompleteness {
+        self.completeness_as_of(chrono::Utc::now().date_naive())
+    }
+
+    /// Score the draft, checking member ages against `today`.
+    ///
+    /// Each present section is validated with the same rules as
+    /// [`OnboardMerchantRequest::validate`].
+    pub fn completeness_as_of(&self, today: NaiveDate) -> DraftCompleteness {
+        let mut report = ValidationReport::default();
+        let mut missing = Vec::new();
+
+        match &self.business {
+            Some(business) => validate_business(&mut report, business),
+            None => missing.push(DraftSection::Business),
+        }
+        match &self.merchant {
+            Some(merchant) => validate_merchant(&mut report, merchant),
+            None => missing.push(DraftSection::Merchant),
+        }
+        if self.accounts.is_empty() {
+            missing.push(DraftSection::Accounts);
+        } else {
+            validate_accounts(&mut report, &self.accounts);
+        }
+        if self.members.is_empty() {
+            missing.push(DraftSection::Members);
+        } else {
+            validate_members(&mut report, &self.members, today);
+        }
+        match &self.terms_acceptance {
+            Some(terms) => validate_terms(&mut report, terms),
+            None => missing.push(DraftSection::Terms),
+        }
+
+        let invalid = DraftSection::ALL
+            .into_iter()
+            .filter(|section| report.for_path(section.path()).next().is_some())
+            .collect();
+
+        DraftCompleteness {
+            missing,
+            invalid,
+            report,
+        }
+    }
+
+    /// Convert a finished draft into a request.
+    ///
+    /// Only checks that every section is present; [`onboard_merchant()`](super::merchant_onboarding::onboard_merchant)
+    /// validates the contents.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`] naming the missing sections.
+    pub fn into_request(self) -> Result<OnboardMerchantRequest> {
+        match (self.business, self.merchant, self.terms_acceptance) {
+            (Some(business), Some(merchant), Some(terms_acceptance))
+                if !self.accounts.is_empty() && !self.members.is_empty() =>
+            {
+                Ok(OnboardMerchantRequest {
+                    business,
+                    merchant,
+                    accounts: self.accounts,
+                    members: self.members,
+                    terms_acceptance,
+                })
+            }
+            (business, merchant, terms) => {
+                let present = [
+                    business.is_some(),
+                    merchant.is_some(),
+                    !self.accounts.is_empty(),
+                    !self.members.is_empty(),
+                    terms.is_some(),
+                ];
+                let missing: Vec<String> = DraftSection::ALL
+                    .into_iter()
+                    .zip(present)
+                    .filter(|(_, present)| !present)
+                    .map(|(section, _)| section.to_string())
+                    .collect();
+                Err(Error::Config(format!(
+                    "Onboarding draft is missing: {}",
+                    missing.join(", ")
+                )))
+            }
+        }
+    }
+}
+
+fn validate_terms(report: &mut ValidationReport, terms: &TermsAcceptance) {
+    if terms.version.trim().is_empty() {
+        report.push(
+            "terms_acceptance.version",
+            ValidationCode::Required,
+            "Terms version is required",
+        );
+    }
+    if terms.accepted_at.trim().is_empty() {
+        report.push(
+            "terms_acceptance.accepted_at",
+            ValidationCode::Required,
+            "Terms acceptance time is required",
+        );
+    }
+}
+
+/// How far a draft is from being submittable.
+#[derive(Debug, Clone)]
+pub struct DraftCompleteness {
+    /// Sections not started.
+    pub missing: Vec<DraftSection>,
+    /// Sections present but failing validation.
+    pub invalid: Vec<DraftSection>,
+    /// Validation issues in the present sections.
+    pub report: ValidationReport,
+}
+
+impl DraftCompleteness {
+    /// Percentage of sections that are present and valid (0-100).
+    pub fn score(&self) -> u8 {
+        let total = DraftSection::ALL.len();
+        let done = total - self.missing.len() - self.invalid.len();
+        (done * 100 / total) as u8
+    }
+
+    /// Sections still needing work - missing or invalid - in form order.
+    pub fn outstanding(&self) -> Vec<DraftSection> {
+        DraftSection::ALL
+            .into_iter()
+            .filter(|s| self.missing.contains(s) || self.invalid.contains(s))
+            .collect()
+    }
+
+    /// Whether every section is present and valid.
+    pub fn is_ready(&self) -> bool {
+        self.missing.is_empty() && self.invalid.is_empty()
+    }
+}
+
+// ============================================================================
+// Sealed Drafts
+// ============================================================================
+
+/// A draft with its sensitive fields encrypted, safe to serialize and store.
+///
+/// Create with [`OnboardingDraft::seal`]; restore with [`SealedDraft::open`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SealedDraft {
+    version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key_id: Option<String>,
+    draft: DraftRecord,
+}
+
+impl SealedDraft {
+    /// Format version the draft was sealed with.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Key identifier reported by the encryptor at sealing time.
+    pub fn key_id(&self) -> Option<&str> {
+        self.key_id.as_deref()
+    }
+
+    /// Decrypt sensitive fields and return the draft.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`] if the draft was written by a newer format
+    /// version, or the first error from the encryptor.
+    pub fn open(self, encryptor: &dyn FieldEncryptor) -> Result<OnboardingDraft> {
+        if self.version > DRAFT_FORMAT_VERSION {
+            return Err(Error::Config(format!(
+                "Onboarding draft format version {} is newer than supported version {}",
+                self.version, DRAFT_FORMAT_VERSION
+            )));
+        }
+
+        let mut draft = OnboardingDraft::from(self.draft);
+        for_each_sensitive(
+            draft.business.as_mut(),
+            &mut draft.accounts,
+            &mut draft.members,
+            |field, value| {
+                *value = encryptor.decrypt(field, value)?;
+                Ok(())
+            },
+        )?;
+        Ok(draft)
+    }
+}
+
+// The stored form of a sealed draft. These mirror the onboarding request
+// types, which are not serializable; the sensitive fields hold ciphertext.
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DraftRecord {
+    #[serde(default)]
+    business: Option<BusinessRecord>,
+    #[serde(default)]
+    merchant: Option<MerchantConfig>,
+    #[serde(default)]
+    accounts: Vec<AccountRecord>,
+    #[serde(default)]
+    members: Vec<MemberRecord>,
+    #[serde(default)]
+    terms_acceptance: Option<TermsAcceptance>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BusinessRecord {
+    business_type: MerchantType,
+    legal_name: String,
+    address: Address,
+    phone: String,
+    email: String,
+    website: Option<String>,
+    ein: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AccountRecord {
+    name: Option<String>,
+    routing_number: Option<String>,
+    account_number: Option<String>,
+    holder_type: AccountHolderType,
+    account_method: BankAccountMethod,
+    transaction_type: AccountType,
+    currency: Option<String>,
+    is_primary: bool,
+    plaid_public_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MemberRecord {
+    member_type: MemberType,
+    first_name: String,
+    last_name: String,
+    title: Option<String>,
+    ownership_percentage: i32,
+    date_of_birth: String,
+    ssn: String,
+    email: String,
+    phone: String,
+    address: Address,
+}
+
+impl From<OnboardingDraft> for DraftRecord {
+    fn from(draft: OnboardingDraft) -> Self {
+        Self {
+            business: draft.business.map(|b| BusinessRecord {
+                business_type: b.business_type,
+                legal_name: b.legal_name,
+                address: b.address,
+                phone: b.phone,
+                email: b.email,
+                website: b.website,
+                ein: b.ein,
+            }),
+            merchant: draft.merchant,
+            accounts: draft
+                .accounts
+                .into_iter()
+                .map(|a| AccountRecord {
+                    name: a.name,
+                    routing_number: a.routing_number,
+                    account_number: a.account_number,
+                    holder_type: a.holder_type,
+                    account_method: a.account_method,
+                    transaction_type: a.transaction_type,
+                    currency: a.currency,
+                    is_primary: a.is_primary,
+                    plaid_public_token: a.plaid_public_token,
+                })
+                .collect(),
+            members: draft
+                .members
+                .into_iter()
+                .map(|m| MemberRecord {
+                    member_type: m.member_type,
+                    first_name: m.first_name,
+                    last_name: m.last_name,
+                    title: m.title,
+                    ownership_percentage: m.ownership_percentage,
+                    date_of_birth: m.date_of_birth,
+                    ssn: m.ssn,
+                    email: m.email,
+                    phone: m.phone,
+                    address: m.address,
+                })
+                .collect(),
+            terms_acceptance: draft.terms_acceptance,
+        }
+    }
+}
+
+impl From<DraftRecord> for OnboardingDraft {
+    fn from(record: DraftRecord) -> Self {
+        Self {
+            business: record.business.map(|b| BusinessInfo {
+                business_type: b.business_type,
+                legal_name: b.legal_name,
+                address: b.address,
+                phone: b.phone,
+                email: b.email,
+                website: b.website,
+                ein: b.ein,
+            }),
+            merchant: record.merchant,
+            accounts: record
+                .accounts
+                .into_iter()
+                .map(|a| BankAccountInfo {
+                    name: a.name,
+                    routing_number: a.routing_number,
+                    account_number: a.account_number,
+                    holder_type: a.holder_type,
+                    account_method: a.account_method,
+                    transaction_type: a.transaction_type,
+                    currency: a.currency,
+                    is_primary: a.is_primary,
+                    plaid_public_token: a.plaid_public_token,
+                })
+                .collect(),
+            members: record
+                .members
+                .into_iter()
+                .map(|m| MemberInfo {
+                    member_type: m.member_type,
+                    first_name: m.first_name,
+                    last_name: m.last_name,
+                    title: m.title,
+                    ownership_percentage: m.ownership_percentage,
+                    date_of_birth: m.date_of_birth,
+                    ssn: m.ssn,
+                    email: m.email,
+                    phone: m.phone,
+                    address: m.address,
+                })
+                .collect(),
+            terms_acceptance: record.terms_acceptance,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{
+        AccountHolderType, AccountType, DateYmd, MemberType, MerchantEnvironment, MerchantType,
+    };
+    use crate::workflows::merchant_onboarding::{Address, BankAccountMethod};
+
+    /// Reverses values and tags them with the field, so tests can tell
+    /// ciphertext from plaintext.
+    struct ReversingEncryptor;
+
+    impl FieldEncryptor for ReversingEncryptor {
+        fn encrypt(&self, field: SensitiveField, plaintext: &str) -> Result<String> {
+            Ok(format!("{field:?}:{}", plaintext.chars().rev().collect::<String>()))
+        }
+
+        fn decrypt(&self, field: SensitiveField, ciphertext: &str) -> Result<String> {
+            let prefix = format!("{field:?}:");
+            ciphertext
+                .strip_prefix(&prefix)
+                .map(|rest| rest.chars().rev().collect())
+                .ok_or_else(|| Error::Internal("bad ciphertext".to_string()))
+        }
+
+        fn key_id(&self) -> Option<&str> {
+            Some("test-key-1")
+        }
+    }
+
+    fn address() -> Address {
+        Address {
+            line1: "123 Main St".to_string(),
+            line2: None,
+            city: "Chicago".to_string(),
+            state: "IL".to_string(),
+            zip: "60601".to_string(),
+            country: "USA".to_string(),
+        }
+    }
+
+    fn request() -> OnboardMerchantRequest {
+        OnboardMerchantRequest {
+            business: BusinessInfo {
+                business_type: MerchantType::LimitedLiabilityCorporation,
+                legal_name: "Test LLC".to_string(),
+                address: address(),
+                phone: "5551234567".to_string(),
+                email: "test@example.com".to_string(),
+                website: None,
+                ein: "123456789".to_string(),
+            },
+            merchant: MerchantConfig {
+                dba: "Test DBA".to_string(),
+                mcc: "5999".to_string(),
+                environment: MerchantEnvironment::Ecommerce,
+                annual_cc_sales: 100000,
+                avg_ticket: 5000,
+                established: DateYmd::new("20200101").unwrap(),
+                is_new_business: false,
+            },
+            accounts: vec![BankAccountInfo {
+                name: Some("Operating".to_string()),
+                routing_number: Some("121000358".to_string()),
+                account_number: Some("000123456".to_string()),
+                holder_type: AccountHolderType::Business,
+                account_method: BankAccountMethod::Checking,
+                transaction_type: AccountType::All,
+                currency: Some("USD".to_string()),
+                is_primary: true,
+                plaid_public_token: None,
+            }],
+            members: vec![MemberInfo {
+                member_type: MemberType::ControlPerson,
+                first_name: "John".to_string(),
+                last_name: "Doe".to_string(),
+                title: Some("CEO".to_string()),
+                ownership_percentage: 100,
+                date_of_birth: "19800115".to_string(),
+                ssn: "111223333".to_string(),
+                email: "john@example.com".to_string(),
+                phone: "5551234567".to_string(),
+                address: address(),
+            }],
+            terms_acceptance: TermsAcceptance {
+                version: "4.21".to_string(),
+                accepted_at: "2024-01-15 10:30:00".to_string(),
+            },
+        }
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, 1).unwrap()
+    }
+
+    #[test]
+    fn test_sealed_draft_round_trip_encrypts_sensitive_fields() {
+        let draft = OnboardingDraft::from(request());
+        let json = serde_json::to_string(&draft.seal(&ReversingEncryptor).unwrap()).unwrap();
+
+        assert!(!json.contains("123456789"));
+        assert!(!json.contains("111223333"));
+        assert!(!json.contains("121000358"));
+        assert!(!json.contains("000123456"));
+        assert!(json.contains("Ein:987654321"));
+        assert!(json.contains("\"key_id\":\"test-key-1\""));
+
+        let sealed: SealedDraft = serde_json::from_str(&json).unwrap();
+        assert_eq!(sealed.version(), DRAFT_FORMAT_VERSION);
+        let opened = sealed.open(&ReversingEncryptor).unwrap();
+        assert_eq!(opened.business.unwrap().ein, "123456789");
+        assert_eq!(opened.members[0].ssn, "111223333");
+        assert_eq!(opened.accounts[0].account_number.as_deref(), Some("000123456"));
+        assert_eq!(opened.accounts[0].routing_number.as_deref(), Some("121000358"));
+    }
+
+    #[test]
+    fn test_seal_skips_empty_values() {
+        let mut draft = OnboardingDraft::new();
+        draft.members.push(MemberInfo {
+            ssn: String::new(),
+            ..request().members.remove(0)
+        });
+
+        let sealed = draft.seal(&ReversingEncryptor).unwrap();
+        let opened = sealed.open(&ReversingEncryptor).unwrap();
+        assert_eq!(opened.members[0].ssn, "");
+    }
+
+    #[test]
+    fn test_open_rejects_newer_version() {
+        let json = r#"{"version":99,"draft":{}}"#;
+        let sealed: SealedDraft = serde_json::from_str(json).unwrap();
+        let err = sealed.open(&PlaintextEncryptor).unwrap_err();
+        assert!(err.to_string().contains("version 99"));
+    }
+
+    #[test]
+    fn test_redacted_masks_sensitive_fields() {
+        let mut request = request();
+        request.accounts[0].plaid_public_token = Some("public-sandbox-abcdef".to_string());
+
+        let redacted = request.redacted();
+        assert_eq!(redacted.business.ein, "*****6789");
+        assert_eq!(redacted.members[0].ssn, "*****3333");
+        assert_eq!(redacted.accounts[0].account_number.as_deref(), Some("*****3456"));
+        assert_eq!(
+            redacted.accounts[0].plaid_public_token.as_deref(),
+            Some("*****************cdef")
+        );
+        // Non-sensitive fields are untouched
+        assert_eq!(redacted.business.legal_name, "Test LLC");
+
+        let logged = format!("{:?}", OnboardingDraft::from(request).redacted());
+        assert!(!logged.contains("111223333"));
+        assert!(!logged.contains("000123456"));
+    }
+
+    #[test]
+    fn test_sealed_draft_never_contains_plaintext() {
+        struct Opaque;
+
+        impl FieldEncryptor for Opaque {
+            fn encrypt(&self, _field: SensitiveField, _plaintext: &str) -> Result<String> {
+                Ok("<sealed>".to_string())
+            }
+
+            fn decrypt(&self, _field: SensitiveField, ciphertext: &str) -> Result<String> {
+                Ok(ciphertext.to_string())
+            }
+        }
+
+        let mut request = request();
+        request.accounts[0].plaid_public_token = Some("public-sandbox-abcdef".to_string());
+        let json = serde_json::to_string(&OnboardingDraft::from(request).seal(&Opaque).unwrap())
+            .unwrap();
+        for raw in ["123456789", "111223333", "121000358", "000123456", "public-sandbox"] {
+            assert!(!json.contains(raw), "sealed draft contains {}", raw);
+        }
+        assert_eq!(json.matches("<sealed>").count(), 5);
+    }
+
+    #[test]
+    fn test_completeness_of_empty_draft() {
+        let completeness = OnboardingDraft::new().completeness_as_of(today());
+        assert_eq!(completeness.score(), 0);
+        assert_eq!(completeness.missing, DraftSection::ALL.to_vec());
+        assert!(completeness.invalid.is_empty());
+        assert!(!completeness.is_ready());
+    }
+
+    #[test]
+    fn test_completeness_lists_missing_and_invalid_sections() {
+        let mut request = request();
+        request.merchant.mcc = "12".to_string();
+        let mut draft = OnboardingDraft::from(request);
+        draft.members.clear();
+        draft.terms_acceptance = None;
+
+        let completeness = draft.completeness_as_of(today());
+        assert_eq!(completeness.missing, vec![DraftSection::Members, DraftSection::Terms]);
+        assert_eq!(completeness.invalid, vec![DraftSection::Merchant]);
+        assert_eq!(completeness.score(), 40);
+        assert_eq!(
+            completeness.outstanding(),
+            vec![DraftSection::Merchant, DraftSection::Members, DraftSection::Terms]
+        );
+        assert!(completeness.report.for_path("merchant.mcc").next().is_some());
+    }
+
+    #[test]
+    fn test_complete_draft_converts_to_request() {
+        let draft = OnboardingDraft::from(request());
+        let completeness = draft.completeness_as_of(today());
+        assert!(completeness.is_ready());
+        assert_eq!(completeness.score(), 100);
+
+        let request = draft.into_request().unwrap();
+        assert!(request.validate_as_of(today()).is_valid());
+    }
+
+    #[test]
+    fn test_into_request_names_missing_sections() {
+        let mut draft = OnboardingDraft::from(request());
+        draft.business = None;
+        draft.accounts.clear();
+
+        let err = draft.into_request().unwrap_err();
+        assert!(err.to_string().contains("business, bank accounts"));
+    }
+}
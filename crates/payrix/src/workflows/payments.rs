@@ -0,0 +1,518 @@
+//! Authorize, capture, void, refund and reverse card and eCheck payments.
+//!
+//! Every follow-up to a payment is a new transaction pointing at the
+//! original through `fortxn`, and its `type` depends on the original:
+//!
+//! | Action      | Card original                    | eCheck original |
+//! |-------------|----------------------------------|-----------------|
+//! | [`capture`] | `CreditCardCapture` (of an auth) | -               |
+//! | [`void`]    | `CreditCardReverseAuth`          | -               |
+//! | [`reverse`] | `CreditCardReverseAuth`          | -               |
+//! | [`refund`]  | `CreditCardRefund`               | `ECheckRefund`  |
+//!
+//! The helpers choose the type, check the original's [`TransactionStatus`],
+//! and check the amount against what is left: captures cannot exceed the
+//! authorization, and refunds and reversals cannot exceed what was approved
+//! less earlier refunds and reversals linked to it (and, for reversals, less
+//! anything captured from it). Problems are reported as
+//! [`Error::Validation`] before anything is sent.
+//!
+//! - [`void`] cancels an approved sale or authorization in full before any of
+//!   it is captured.
+//! - [`reverse`] releases all or part of an authorization, or of a sale that
+//!   has not settled yet.
+//! - [`refund`] returns all or part of a captured or settled payment.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use payrix::{PayrixClient, Environment};
+//! use payrix::workflows::payments::{authorize, capture, refund, AuthorizeRequest};
+//!
+//! # async fn example() -> payrix::Result<()> {
+//! let client = PayrixClient::new("api-key", Environment::Test)?;
+//!
+//! let auth = authorize(
+//!     &client,
+//!     AuthorizeRequest::new("t1_mer_12345678901234567890123", "t1_tok_12345678901234567890123", 10_000),
+//! )
+//! .await?;
+//!
+//! // Ship part of the order and capture $75 of the $100 authorized.
+//! let captured = capture(&client, auth.transaction.id.as_str(), Some(7_500)).await?;
+//!
+//! // Later, refund $20 of it.
+//! let refunded = refund(&client, captured.transaction.id.as_str(), Some(2_000)).await?;
+//! println!("{} cents still refundable", refunded.remaining);
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::client::PayrixClient;
+use crate::entity::EntityType;
+use crate::error::{Error, Result};
+use crate::search::SearchBuilder;
+use crate::types::{PayrixId, Transaction, TransactionOrigin, TransactionStatus, TransactionType};
+
+// =============================================================================
+// Section 1: Types
+// =============================================================================
+
+/// A payment lifecycle operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PaymentAction {
+    /// Hold funds on a card without capturing them.
+    Authorize,
+    /// Capture a previous authorization.
+    Capture,
+    /// Cancel an uncaptured sale or authorization in full.
+    Void,
+    /// Return funds from a captured or settled payment.
+    Refund,
+    /// Release all or part of an unsettled sale or authorization.
+    Reverse,
+}
+
+impl PaymentAction {
+    fn verb(self) -> &'static str {
+        match self {
+            Self::Authorize => "authorize",
+            Self::Capture => "capture",
+            Self::Void => "void",
+            Self::Refund => "refund",
+            Self::Reverse => "reverse",
+        }
+    }
+}
+
+/// The result of a payment operation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentResult {
+    /// What was done.
+    pub action: PaymentAction,
+    /// The transaction acted on, for everything but [`PaymentAction::Authorize`].
+    pub original: Option<PayrixId>,
+    /// Amount requested, in cents.
+    pub amount: i64,
+    /// Amount of the original still refundable or reversible after this
+    /// operation, in cents. For an authorization, the amount authorized.
+    pub remaining: i64,
+    /// The transaction Payrix created.
+    pub transaction: Transaction,
+}
+
+impl PaymentResult {
+    /// Whether Payrix accepted the transaction.
+    pub fn is_approved(&self) -> bool {
+        self.transaction.status != Some(TransactionStatus::Failed)
+    }
+}
+
+/// A card authorization.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizeRequest {
+    /// Merchant ID.
+    pub merchant: String,
+    /// Token of the card to authorize.
+    pub token: String,
+    /// Amount to authorize, in cents.
+    pub amount: i64,
+    /// Where the payment came from (defaults to eCommerce).
+    pub origin: Option<TransactionOrigin>,
+    /// Merchant order reference.
+    pub order: Option<String>,
+    /// Description of the payment.
+    pub description: Option<String>,
+}
+
+impl AuthorizeRequest {
+    /// Authorize `amount` cents on `token` for `merchant`.
+    pub fn new(merchant: impl Into<String>, token: impl Into<String>, amount: i64) -> Self {
+        Self {
+            merchant: merchant.into(),
+            token: token.into(),
+            amount,
+            origin: None,
+            order: None,
+            description: None,
+        }
+    }
+
+    /// Set the transaction origin.
+    pub fn with_origin(mut self, origin: TransactionOrigin) -> Self {
+        self.origin = Some(origin);
+        self
+    }
+
+    /// Set the order reference.
+    pub fn with_order(mut self, order: impl Into<String>) -> Self {
+        self.order = Some(order.into());
+        self
+    }
+
+    /// Set the description.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+// =============================================================================
+// Section 2: Checks
+// =============================================================================
+
+/// Whether a transaction type moves money by eCheck rather than card.
+pub fn is_echeck(txn_type: TransactionType) -> bool {
+    matches!(
+        txn_type,
+        TransactionType::ECheckSale
+            | TransactionType::ECheckRefund
+            | TransactionType::ECheckRedeposit
+            | TransactionType::ECheckAccountVerification
+    )
+}
+
+/// Amount Payrix approved for a transaction, in cents.
+fn approved_amount(txn: &Transaction) -> i64 {
+    txn.approved.or(txn.total).unwrap_or(0)
+}
+
+/// Whether a linked transaction still counts against its original.
+fn is_live(txn: &Transaction) -> bool {
+    txn.status != Some(TransactionStatus::Failed) && !txn.inactive
+}
+
+/// Amount of `original` already refunded or reversed, from the transactions
+/// linked to it through `fortxn`, or Payrix's own `refunded` total if higher.
+pub fn returned_amount(original: &Transaction, linked: &[Transaction]) -> i64 {
+    let linked_total: i64 = linked
+        .iter()
+        .filter(|t| is_live(t))
+        .filter(|t| {
+            matches!(
+                t.txn_type,
+                TransactionType::CreditCardRefund
+                    | TransactionType::ECheckRefund
+                    | TransactionType::CreditCardReverseAuth
+            )
+        })
+        .map(approved_amount)
+        .sum();
+    linked_total.max(original.refunded.map_or(0, i64::from))
+}
+
+/// Amount of `original` that can still be refunded or reversed, in cents.
+pub fn remaining_refundable(original: &Transaction, linked: &[Transaction]) -> i64 {
+    (approved_amount(original) - returned_amount(original, linked)).max(0)
+}
+
+/// Amount of an authorization already captured, from the live captures
+/// linked to it.
+fn captured_amount(linked: &[Transaction]) -> i64 {
+    linked
+        .iter()
+        .filter(|t| is_live(t) && t.txn_type == TransactionType::CreditCardCapture)
+        .map(approved_amount)
+        .sum()
+}
+
+/// Amount of `original` that can still be reversed, in cents: what is left
+/// to refund less anything captured from it.
+pub fn remaining_reversible(original: &Transaction, linked: &[Transaction]) -> i64 {
+    (remaining_refundable(original, linked) - captured_amount(linked)).max(0)
+}
+
+/// Work out the transaction type and amount for `action` on `original`.
+///
+/// `linked` are the transactions whose `fortxn` is the original. `amount`
+/// of `None` means everything that is left.
+fn prepare(
+    action: PaymentAction,
+    original: &Transaction,
+    linked: &[Transaction],
+    amount: Option<i64>,
+) -> Result<(TransactionType, i64)> {
+    let id = original.id.as_str();
+    let status = original.status.unwrap_or_default();
+    let echeck = is_echeck(original.txn_type);
+    let remaining = remaining_refundable(original, linked);
+
+    let invalid = |message: String| Err(Error::Validation(message));
+
+    let (txn_type, available) = match action {
+        PaymentAction::Authorize => {
+            return invalid("Authorizations are created with authorize(), not from a transaction".to_string());
+        }
+        PaymentAction::Capture => {
+            if original.txn_type != TransactionType::CreditCardAuth {
+                return invalid(format!("Transaction {} is not an authorization", id));
+            }
+            if status != TransactionStatus::Approved {
+                return invalid(format!("Authorization {} is {:?} and cannot be captured", id, status));
+            }
+            if captured_amount(linked) > 0 {
+                return invalid(format!("Authorization {} has already been captured", id));
+            }
+            (TransactionType::CreditCardCapture, remaining)
+        }
+        PaymentAction::Void => {
+            if echeck {
+                return invalid(format!("eCheck {} cannot be voided; refund it once settled", id));
+            }
+            if status != TransactionStatus::Approved {
+                return invalid(format!("Transaction {} is {:?} and can only be voided while approved", id, status));
+            }
+            if captured_amount(linked) > 0 {
+                return invalid(format!("Authorization {} has been captured; refund the capture instead", id));
+            }
+            if amount.is_some_and(|a| a != remaining) {
+                return invalid("A void cancels the whole amount; use reverse() for part of it".to_string());
+            }
+            (TransactionType::CreditCardReverseAuth, remaining)
+        }
+        PaymentAction::Reverse => {
+            if echeck {
+                return invalid(format!("eCheck {} cannot be reversed; refund it once settled", id));
+            }
+            if !matches!(status, TransactionStatus::Approved | TransactionStatus::Captured) {
+                return invalid(format!("Transaction {} is {:?} and cannot be reversed", id, status));
+            }
+            (TransactionType::CreditCardReverseAuth, remaining_reversible(original, linked))
+        }
+        PaymentAction::Refund => {
+            if original.txn_type == TransactionType::CreditCardAuth {
+                return invalid(format!("Authorization {} was never charged; void or reverse it instead", id));
+            }
+            if !matches!(status, TransactionStatus::Captured | TransactionStatus::Settled) {
+                return invalid(format!("Transaction {} is {:?} and cannot be refunded", id, status));
+            }
+            let txn_type = if echeck {
+                TransactionType::ECheckRefund
+            } else {
+                TransactionType::CreditCardRefund
+            };
+            (txn_type, remaining)
+        }
+    };
+
+    let amount = amount.unwrap_or(available);
+    if amount <= 0 {
+        return invalid(format!("Nothing left to {} on transaction {}", action.verb(), id));
+    }
+    if amount > available {
+        return invalid(format!(
+            "Cannot {} {} cents on transaction {}: only {} cents remain",
+            action.verb(),
+            amount,
+            id,
+            available
+        ));
+    }
+    Ok((txn_type, amount))
+}
+
+// =============================================================================
+// Section 3: Operations
+// =============================================================================
+
+/// Authorize a card payment without capturing it.
+///
+/// # Errors
+///
+/// Returns an error if the amount is not positive or the API call fails.
+pub async fn authorize(client: &PayrixClient, request: AuthorizeRequest) -> Result<PaymentResult> {
+    if request.amount <= 0 {
+        return Err(Error::Validation("Authorization amount must be positive".to_string()));
+    }
+
+    let mut body = Map::new();
+    body.insert("merchant".into(), json!(request.merchant));
+    body.insert("token".into(), json!(request.token));
+    body.insert("type".into(), json!(TransactionType::CreditCardAuth));
+    body.insert("total".into(), json!(request.amount));
+    body.insert("origin".into(), json!(request.origin.unwrap_or(TransactionOrigin::Ecommerce)));
+    if let Some(order) = &request.order {
+        body.insert("order".into(), json!(order));
+    }
+    if let Some(description) = &request.description {
+        body.insert("description".into(), json!(description));
+    }
+
+    let transaction: Transaction = client.create(EntityType::Txns, &Value::Object(body)).await?;
+    Ok(PaymentResult {
+        action: PaymentAction::Authorize,
+        original: None,
+        amount: request.amount,
+        remaining: approved_amount(&transaction),
+        transaction,
+    })
+}
+
+/// Capture an authorization, in full or for a smaller `amount`.
+///
+/// # Errors
+///
+/// Returns an error if the transaction is not an approved, uncaptured
+/// authorization, the amount exceeds it, or an API call fails.
+pub async fn capture(client: &PayrixClient, auth_id: &str, amount: Option<i64>) -> Result<PaymentResult> {
+    follow_up(client, PaymentAction::Capture, auth_id, amount).await
+}
+
+/// Cancel an approved, uncaptured card sale or authorization in full.
+///
+/// # Errors
+///
+/// Returns an error if the transaction is an eCheck or no longer approved,
+/// or an API call fails.
+pub async fn void(client: &PayrixClient, txn_id: &str) -> Result<PaymentResult> {
+    follow_up(client, PaymentAction::Void, txn_id, None).await
+}
+
+/// Refund a captured or settled payment, in full or for a smaller `amount`.
+///
+/// # Errors
+///
+/// Returns an error if the transaction has not been captured, the amount
+/// exceeds what is left after earlier refunds, or an API call fails.
+pub async fn refund(client: &PayrixClient, txn_id: &str, amount: Option<i64>) -> Result<PaymentResult> {
+    follow_up(client, PaymentAction::Refund, txn_id, amount).await
+}
+
+/// Reverse an unsettled card sale or authorization, in full or in part.
+///
+/// # Errors
+///
+/// Returns an error if the transaction is an eCheck or already settled,
+/// the amount exceeds what is left, or an API call fails.
+pub async fn reverse(client: &PayrixClient, txn_id: &str, amount: Option<i64>) -> Result<PaymentResult> {
+    follow_up(client, PaymentAction::Reverse, txn_id, amount).await
+}
+
+async fn follow_up(
+    client: &PayrixClient,
+    action: PaymentAction,
+    txn_id: &str,
+    amount: Option<i64>,
+) -> Result<PaymentResult> {
+    let original: Transaction = client
+        .get_one(EntityType::Txns, txn_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Transaction {} not found", txn_id)))?;
+    let search = SearchBuilder::new().field("fortxn", txn_id).build();
+    let linked: Vec<Transaction> = client.search(EntityType::Txns, &search).await?;
+
+    let (txn_type, amount) = prepare(action, &original, &linked, amount)?;
+
+    let mut body = Map::new();
+    body.insert("fortxn".into(), json!(txn_id));
+    body.insert("type".into(), json!(txn_type));
+    body.insert("total".into(), json!(amount));
+    if let Some(merchant) = &original.merchant {
+        body.insert("merchant".into(), json!(merchant));
+    }
+
+    let transaction: Transaction = client.create(EntityType::Txns, &Value::Object(body)).await?;
+
+    let before = match action {
+        PaymentAction::Void | PaymentAction::Reverse => remaining_reversible(&original, &linked),
+        _ => remaining_refundable(&original, &linked),
+    };
+    let remaining = match action {
+        // A capture turns the authorization into a payment of `amount`.
+        PaymentAction::Capture => amount,
+        _ if transaction.status == Some(TransactionStatus::Failed) => before,
+        _ => before - amount,
+    };
+    Ok(PaymentResult {
+        action,
+        original: Some(original.id),
+        amount,
+        remaining,
+        transaction,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn txn(id: u32, txn_type: i32, status: i32, total: i64) -> Transaction {
+        serde_json::from_value(json!({
+            "id": format!("t1_txn_{:023}", id),
+            "merchant": "t1_mer_00000000000000000000001",
+            "type": txn_type,
+            "status": status,
+            "total": total,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_refund_limits_and_type() {
+        let sale = txn(1, 1, 4, 10_000);
+        let refunds = vec![txn(2, 5, 3, 3_000), txn(3, 5, 2, 5_000)];
+
+        // The failed refund does not count.
+        assert_eq!(remaining_refundable(&sale, &refunds), 7_000);
+        assert_eq!(
+            prepare(PaymentAction::Refund, &sale, &refunds, None).unwrap(),
+            (TransactionType::CreditCardRefund, 7_000)
+        );
+        let err = prepare(PaymentAction::Refund, &sale, &refunds, Some(8_000)).unwrap_err();
+        assert!(err.to_string().contains("only 7000 cents remain"));
+
+        let echeck = txn(4, 7, 4, 2_500);
+        assert_eq!(
+            prepare(PaymentAction::Refund, &echeck, &[], Some(1_000)).unwrap(),
+            (TransactionType::ECheckRefund, 1_000)
+        );
+
+        // Payrix's own refunded total is used when linked refunds are missing.
+        let mut partly = txn(5, 1, 4, 10_000);
+        partly.refunded = Some(9_000);
+        assert_eq!(remaining_refundable(&partly, &[]), 1_000);
+
+        assert!(prepare(PaymentAction::Refund, &txn(6, 1, 1, 1_000), &[], None).is_err());
+    }
+
+    #[test]
+    fn test_capture_void_and_reverse() {
+        let auth = txn(1, 2, 1, 10_000);
+        assert_eq!(
+            prepare(PaymentAction::Capture, &auth, &[], Some(7_500)).unwrap(),
+            (TransactionType::CreditCardCapture, 7_500)
+        );
+        assert!(prepare(PaymentAction::Capture, &auth, &[], Some(12_000)).is_err());
+        assert!(prepare(PaymentAction::Capture, &auth, &[txn(2, 3, 3, 5_000)], None).is_err());
+        assert!(prepare(PaymentAction::Capture, &txn(3, 1, 1, 100), &[], None).is_err());
+
+        assert_eq!(
+            prepare(PaymentAction::Void, &auth, &[], None).unwrap(),
+            (TransactionType::CreditCardReverseAuth, 10_000)
+        );
+        assert!(prepare(PaymentAction::Void, &txn(4, 1, 4, 100), &[], None).is_err());
+        assert!(prepare(PaymentAction::Void, &txn(5, 7, 1, 100), &[], None).is_err());
+
+        let reversed = vec![txn(6, 4, 1, 4_000)];
+        assert_eq!(
+            prepare(PaymentAction::Reverse, &auth, &reversed, Some(6_000)).unwrap(),
+            (TransactionType::CreditCardReverseAuth, 6_000)
+        );
+        assert!(prepare(PaymentAction::Reverse, &txn(7, 1, 4, 100), &[], None).is_err());
+
+        // After capturing 7_500 of the 10_000 authorized, only 2_500 can be
+        // released, and the authorization can no longer be voided.
+        let captured = vec![txn(8, 3, 3, 7_500)];
+        assert_eq!(remaining_reversible(&auth, &captured), 2_500);
+        assert_eq!(
+            prepare(PaymentAction::Reverse, &auth, &captured, None).unwrap(),
+            (TransactionType::CreditCardReverseAuth, 2_500)
+        );
+        assert!(prepare(PaymentAction::Reverse, &auth, &captured, Some(3_000)).is_err());
+        let err = prepare(PaymentAction::Void, &auth, &captured, None).unwrap_err();
+        assert!(err.to_string().contains("has been captured"));
+    }
+}
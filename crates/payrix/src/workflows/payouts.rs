@@ -0,0 +1,650 @@
+//! Manage a merchant's payout configuration and forecast upcoming payouts.
+//!
+//! A [`Payout`] tells Payrix when and how much to disburse from a merchant's
+//! [`Fund`] to their bank account:
+//!
+//! - `schedule` × `schedule_factor` sets the interval (every 2 weeks,
+//!   every month, ...) counted from `start`.
+//! - `um` and `amount` set the size: a fixed amount in cents, or a
+//!   percentage of the balance in basis points.
+//! - `float` is left in the fund; `minimum` and `maximum` bound each
+//!   disbursement, with anything over the maximum rolling to the next one.
+//! - `skip_off_days` drops payouts that fall on weekends and banking
+//!   holidays; otherwise they are processed the next business day.
+//! - `same_day` deposits funds the day they are processed rather than the
+//!   next business day.
+//!
+//! [`forecast`] applies these rules offline to a balance and returns the next
+//! payout dates and amounts; [`forecast_payouts`] does the same for every
+//! payout of an entity using its live fund balances. Weekends and US Federal
+//! Reserve holidays ([`us_bank_holidays`]) are treated as off days.
+//!
+//! [`validate_payout`] checks a configuration is consistent, and
+//! [`update_payout`] applies a [`PayoutUpdate`] after validating the result.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use payrix::{PayrixClient, Environment};
+//! use payrix::types::{PayoutSchedule, PayoutUnit};
+//! use payrix::workflows::payouts::{forecast_payouts, update_payout, PayoutUpdate};
+//!
+//! # async fn example() -> payrix::Result<()> {
+//! let client = PayrixClient::new("api-key", Environment::Test)?;
+//!
+//! for forecast in forecast_payouts(&client, "t1_ent_12345678901234567890123", 5).await? {
+//!     for payout in &forecast.payouts {
+//!         println!("{}: {} cents, arriving {}", payout.date, payout.amount, payout.arrives);
+//!     }
+//! }
+//!
+//! // Pay out 100% of the balance every week, keeping $50 in the fund.
+//! let update = PayoutUpdate::default()
+//!     .with_schedule(PayoutSchedule::Weekly, 1)
+//!     .with_amount(PayoutUnit::Percent, 10_000)
+//!     .with_float(5_000);
+//! update_payout(&client, "t1_pay_12345678901234567890123", update).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use chrono::{Datelike, Days, Months, NaiveDate, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::client::PayrixClient;
+use crate::entity::EntityType;
+use crate::error::{Error, Result};
+use crate::search::{make_payrix_date, parse_payrix_date, SearchBuilder};
+use crate::types::{Fund, Payout, PayoutSchedule, PayoutUnit};
+
+use super::merchant_onboarding::{ValidationCode, ValidationReport};
+
+/// Basis points in 100%.
+const FULL_PERCENT: i64 = 10_000;
+
+/// Most schedule occurrences examined for one forecast, so a schedule that
+/// never lands on a business day cannot loop forever.
+const MAX_OCCURRENCES: u32 = 100_000;
+
+// =============================================================================
+// Section 1: Business Days
+// =============================================================================
+
+/// US Federal Reserve bank holidays observed in `year`, in date order.
+///
+/// Holidays falling on a Sunday are observed the following Monday. Holidays
+/// falling on a Saturday are not moved, matching the Federal Reserve's
+/// schedule. Juneteenth is included from 2022, its first observed year.
+pub fn us_bank_holidays(year: i32) -> Vec<NaiveDate> {
+    let fixed = |month: u32, day: u32| NaiveDate::from_ymd_opt(year, month, day);
+    let nth = |month: u32, weekday: Weekday, n: u8| NaiveDate::from_weekday_of_month_opt(year, month, weekday, n);
+
+    let last_monday_of_may = fixed(5, 31).map(|d| d - Days::new(u64::from(d.weekday().num_days_from_monday())));
+
+    let mut holidays: Vec<NaiveDate> = [
+        fixed(1, 1),              // New Year's Day
+        nth(1, Weekday::Mon, 3),  // Martin Luther King Jr. Day
+        nth(2, Weekday::Mon, 3),  // Washington's Birthday
+        last_monday_of_may,       // Memorial Day
+        // Juneteenth
+        fixed(6, 19).filter(|_| year >= 2022),
+        fixed(7, 4),              // Independence Day
+        nth(9, Weekday::Mon, 1),  // Labor Day
+        nth(10, Weekday::Mon, 2), // Columbus Day
+        fixed(11, 11),            // Veterans Day
+        nth(11, Weekday::Thu, 4), // Thanksgiving Day
+        fixed(12, 25),            // Christmas Day
+    ]
+    .into_iter()
+    .flatten()
+    .map(|d| if d.weekday() == Weekday::Sun { d + Days::new(1) } else { d })
+    .collect();
+    holidays.sort();
+    holidays
+}
+
+/// Whether banks are open on `date`: a weekday that is not a US bank holiday.
+pub fn is_business_day(date: NaiveDate) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !us_bank_holidays(date.year()).contains(&date)
+}
+
+/// The first business day on or after `date`.
+pub fn next_business_day(date: NaiveDate) -> NaiveDate {
+    date.iter_days()
+        .find(|d| is_business_day(*d))
+        .unwrap_or(date)
+}
+
+// =============================================================================
+// Section 2: Forecasting
+// =============================================================================
+
+/// One forecast disbursement.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForecastedPayout {
+    /// The schedule date this payout comes from.
+    pub scheduled: NaiveDate,
+    /// The business day the disbursement is processed.
+    pub date: NaiveDate,
+    /// The business day the funds are expected in the bank account.
+    pub arrives: NaiveDate,
+    /// Amount disbursed, in cents. Negative amounts debit the account; zero
+    /// means the balance did not reach the payout's minimum.
+    pub amount: i64,
+    /// Fund balance left after this payout, in cents.
+    pub balance_after: i64,
+}
+
+/// The forecast for one payout configuration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PayoutForecast {
+    /// The payout configuration.
+    pub payout: Payout,
+    /// Available balance the forecast started from, in cents.
+    pub available: i64,
+    /// Upcoming disbursements, in date order.
+    pub payouts: Vec<ForecastedPayout>,
+}
+
+impl PayoutForecast {
+    /// Total forecast to be disbursed, in cents.
+    pub fn total(&self) -> i64 {
+        self.payouts.iter().map(|p| p.amount).sum()
+    }
+}
+
+/// The `n`th scheduled date of `payout` counted from `start` (0 is `start`).
+fn occurrence(payout: &Payout, start: NaiveDate, n: u32) -> Option<NaiveDate> {
+    let factor = u32::try_from(payout.schedule_factor.unwrap_or(1).max(1)).ok()?;
+    let step = n.checked_mul(factor)?;
+    match payout.schedule.unwrap_or_default() {
+        PayoutSchedule::Daily => start.checked_add_days(Days::new(step.into())),
+        PayoutSchedule::Weekly => start.checked_add_days(Days::new(u64::from(step) * 7)),
+        PayoutSchedule::Monthly => start.checked_add_months(Months::new(step)),
+        PayoutSchedule::Annually => start.checked_add_months(Months::new(step.checked_mul(12)?)),
+        PayoutSchedule::Single => Some(start).filter(|_| n == 0),
+    }
+}
+
+/// How much `payout` would disburse from a fund holding `balance` cents.
+///
+/// The payout's `float` stays in the fund. A fixed (`Actual`) amount pays up
+/// to that amount; a `Percent` pays that share of the rest; a payout with no
+/// amount pays it all. `PercentNegative` only applies to a negative balance
+/// and returns a negative amount (a debit from the bank account). Amounts are
+/// capped at `maximum`, and below `minimum` nothing is paid.
+pub fn payout_amount(payout: &Payout, balance: i64) -> i64 {
+    apply_limits(payout, unlimited_amount(payout, balance))
+}
+
+/// The payout amount for `balance` before `minimum` and `maximum` apply.
+fn unlimited_amount(payout: &Payout, balance: i64) -> i64 {
+    let distributable = balance - i64::from(payout.float_balance.unwrap_or(0));
+    match payout.um.unwrap_or_default() {
+        PayoutUnit::Actual => payout.amount.map_or(distributable, |a| a.min(distributable)).max(0),
+        PayoutUnit::Percent => {
+            distributable.max(0) * payout.amount.unwrap_or(FULL_PERCENT).clamp(0, FULL_PERCENT) / FULL_PERCENT
+        }
+        PayoutUnit::PercentNegative => {
+            distributable.min(0) * payout.amount.unwrap_or(FULL_PERCENT).clamp(0, FULL_PERCENT) / FULL_PERCENT
+        }
+    }
+}
+
+/// Cap `amount` at the payout's `maximum`, or pay nothing below its `minimum`.
+fn apply_limits(payout: &Payout, amount: i64) -> i64 {
+    let capped = payout.maximum.map_or(amount.abs(), |max| amount.abs().min(max));
+    if payout.minimum.is_some_and(|min| capped < min) {
+        0
+    } else {
+        capped * amount.signum()
+    }
+}
+
+/// Forecast the next `count` disbursements of `payout` on or after `from`.
+///
+/// Starts from an available `balance` in cents and assumes no new funds
+/// arrive, so later payouts only pay what earlier ones rolled over. Schedule
+/// dates on off days are dropped when `skip_off_days` is set, and otherwise
+/// moved to the next business day; schedule dates that move onto the same
+/// day are one disbursement under the earliest `scheduled` date, paying
+/// their amounts together with `minimum` and `maximum` applied once to the
+/// total. Inactive and frozen payouts forecast nothing.
+pub fn forecast(payout: &Payout, balance: i64, from: NaiveDate, count: usize) -> Vec<ForecastedPayout> {
+    let mut payouts = Vec::new();
+    if payout.inactive || payout.frozen {
+        return payouts;
+    }
+
+    let start = payout
+        .start
+        .and_then(|s| parse_payrix_date(&s.to_string()))
+        .unwrap_or(from);
+    let mut balance = balance;
+    // Balance before the latest disbursement, and its amount before limits.
+    let mut day_start = balance;
+    let mut day_unlimited = 0;
+
+    for n in 0..MAX_OCCURRENCES {
+        if payouts.len() >= count {
+            break;
+        }
+        let Some(scheduled) = occurrence(payout, start, n) else {
+            break;
+        };
+        if scheduled < from || (payout.skip_off_days && !is_business_day(scheduled)) {
+            continue;
+        }
+
+        let date = next_business_day(scheduled);
+        if let Some(last) = payouts.last_mut().filter(|p| p.date == date) {
+            day_unlimited += unlimited_amount(payout, day_start - day_unlimited);
+            last.amount = apply_limits(payout, day_unlimited);
+            balance = day_start - last.amount;
+            last.balance_after = balance;
+            continue;
+        }
+        let arrives = if payout.same_day {
+            date
+        } else {
+            next_business_day(date + Days::new(1))
+        };
+        day_start = balance;
+        day_unlimited = unlimited_amount(payout, balance);
+        let amount = apply_limits(payout, day_unlimited);
+        balance -= amount;
+        payouts.push(ForecastedPayout {
+            scheduled,
+            date,
+            arrives,
+            amount,
+            balance_after: balance,
+        });
+    }
+    payouts
+}
+
+/// Forecast the next `count` disbursements of every payout of an entity.
+///
+/// Each payout starts from the available balance of the entity's funds in
+/// the payout's currency (all funds when it has none). Payouts are
+/// forecast independently, so an entity with several payouts drawing on the
+/// same fund will see each one start from the full balance.
+///
+/// # Errors
+///
+/// Returns an error if an API call fails.
+pub async fn forecast_payouts(client: &PayrixClient, entity_id: &str, count: usize) -> Result<Vec<PayoutForecast>> {
+    let search = SearchBuilder::new().field("entity", entity_id).build();
+    let payouts: Vec<Payout> = client.search(EntityType::Payouts, &search).await?;
+    let funds: Vec<Fund> = client.search(EntityType::Funds, &search).await?;
+
+    let today = Utc::now().date_naive();
+    Ok(payouts
+        .into_iter()
+        .map(|payout| {
+            let available = funds
+                .iter()
+                .filter(|f| !f.inactive)
+                .filter(|f| payout.currency.is_none() || f.currency == payout.currency)
+                .map(|f| f.available.unwrap_or(0.0))
+                .sum::<f64>()
+                .round() as i64;
+            let payouts = forecast(&payout, available, today, count);
+            PayoutForecast {
+                payout,
+                available,
+                payouts,
+            }
+        })
+        .collect())
+}
+
+// =============================================================================
+// Section 3: Configuration
+// =============================================================================
+
+/// Check a payout configuration for values Payrix would reject or that
+/// would never disburse.
+pub fn validate_payout(payout: &Payout) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let um = payout.um.unwrap_or_default();
+
+    if payout.schedule_factor.is_some_and(|f| f < 1) {
+        report.push("scheduleFactor", ValidationCode::OutOfRange, "Schedule factor must be at least 1");
+    }
+    if payout.schedule == Some(PayoutSchedule::Single) && payout.start.is_none() {
+        report.push("start", ValidationCode::Required, "A single payout needs a start date");
+    }
+    if payout.start.is_some_and(|s| parse_payrix_date(&s.to_string()).is_none()) {
+        report.push("start", ValidationCode::InvalidFormat, "Start must be a YYYYMMDD date");
+    }
+    match payout.amount {
+        Some(amount) if um != PayoutUnit::Actual && !(0..=FULL_PERCENT).contains(&amount) => {
+            report.push("amount", ValidationCode::OutOfRange, "Percent amount must be 0-10000 basis points");
+        }
+        Some(amount) if amount < 0 => {
+            report.push("amount", ValidationCode::OutOfRange, "Amount cannot be negative");
+        }
+        _ => {}
+    }
+    if um == PayoutUnit::Actual && payout.currency.is_none() {
+        report.push("currency", ValidationCode::Required, "A fixed amount payout needs a currency");
+    }
+    if payout.minimum.is_some_and(|min| min < 0) {
+        report.push("minimum", ValidationCode::OutOfRange, "Minimum cannot be negative");
+    }
+    if payout.maximum.is_some_and(|max| max < 0) {
+        report.push("maximum", ValidationCode::OutOfRange, "Maximum cannot be negative");
+    }
+    if payout
+        .minimum
+        .zip(payout.maximum)
+        .is_some_and(|(min, max)| max < min)
+    {
+        report.push("maximum", ValidationCode::InvalidValue, "Maximum is below the minimum");
+    }
+    if payout.float_balance.is_some_and(|f| f < 0) {
+        report.push("float", ValidationCode::OutOfRange, "Float cannot be negative");
+    }
+    report
+}
+
+/// Changes to a payout's configuration.
+///
+/// Fields left as `None` are not changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PayoutUpdate {
+    /// How often to pay out.
+    pub schedule: Option<PayoutSchedule>,
+    /// Multiplier for the schedule.
+    pub schedule_factor: Option<i32>,
+    /// First payout date.
+    pub start: Option<NaiveDate>,
+    /// Whether `amount` is a fixed amount or a percentage.
+    pub um: Option<PayoutUnit>,
+    /// Amount in cents, or percentage in basis points.
+    pub amount: Option<i64>,
+    /// Smallest disbursement, in cents.
+    pub minimum: Option<i64>,
+    /// Largest disbursement, in cents.
+    pub maximum: Option<i64>,
+    /// Balance to leave in the fund, in cents.
+    pub float_balance: Option<i32>,
+    /// Whether to skip payouts on weekends and holidays.
+    pub skip_off_days: Option<bool>,
+    /// Whether to deposit funds the day they are processed.
+    pub same_day: Option<bool>,
+}
+
+impl PayoutUpdate {
+    /// Set the schedule, e.g. `(PayoutSchedule::Weekly, 2)` for every two weeks.
+    pub fn with_schedule(mut self, schedule: PayoutSchedule, factor: i32) -> Self {
+        self.schedule = Some(schedule);
+        self.schedule_factor = Some(factor);
+        self
+    }
+
+    /// Set the first payout date.
+    pub fn with_start(mut self, start: NaiveDate) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Set the payout size: cents for `Actual`, basis points for percentages.
+    pub fn with_amount(mut self, um: PayoutUnit, amount: i64) -> Self {
+        self.um = Some(um);
+        self.amount = Some(amount);
+        self
+    }
+
+    /// Set the minimum and maximum disbursement, in cents.
+    pub fn with_limits(mut self, minimum: Option<i64>, maximum: Option<i64>) -> Self {
+        self.minimum = minimum;
+        self.maximum = maximum;
+        self
+    }
+
+    /// Set the balance left in the fund, in cents.
+    pub fn with_float(mut self, float_balance: i32) -> Self {
+        self.float_balance = Some(float_balance);
+        self
+    }
+
+    /// Set whether payouts on off days are skipped.
+    pub fn with_skip_off_days(mut self, skip: bool) -> Self {
+        self.skip_off_days = Some(skip);
+        self
+    }
+
+    /// Set whether funds are deposited the same day.
+    pub fn with_same_day(mut self, same_day: bool) -> Self {
+        self.same_day = Some(same_day);
+        self
+    }
+
+    /// The payout as it will be after this update.
+    fn apply(&self, payout: &Payout) -> Payout {
+        let mut updated = payout.clone();
+        updated.schedule = self.schedule.or(updated.schedule);
+        updated.schedule_factor = self.schedule_factor.or(updated.schedule_factor);
+        updated.start = self
+            .start
+            .and_then(|d| make_payrix_date(&d).parse().ok())
+            .or(updated.start);
+        updated.um = self.um.or(updated.um);
+        updated.amount = self.amount.or(updated.amount);
+        updated.minimum = self.minimum.or(updated.minimum);
+        updated.maximum = self.maximum.or(updated.maximum);
+        updated.float_balance = self.float_balance.or(updated.float_balance);
+        updated.skip_off_days = self.skip_off_days.unwrap_or(updated.skip_off_days);
+        updated.same_day = self.same_day.unwrap_or(updated.same_day);
+        updated
+    }
+
+    fn validate(&self, payout: &Payout, today: NaiveDate) -> ValidationReport {
+        let mut report = validate_payout(&self.apply(payout));
+        if *self == Self::default() {
+            report.push("update", ValidationCode::Required, "No payout changes were given");
+        }
+        if payout.frozen {
+            report.push("payout", ValidationCode::InvalidValue, "Payout is frozen");
+        }
+        if self.start.is_some_and(|start| start < today) {
+            report.push("start", ValidationCode::InvalidValue, "Start date cannot be in the past");
+        }
+        report
+    }
+
+    fn payload(&self) -> Value {
+        let mut body = Map::new();
+        if let Some(schedule) = self.schedule {
+            body.insert("schedule".into(), json!(schedule));
+        }
+        if let Some(factor) = self.schedule_factor {
+            body.insert("scheduleFactor".into(), json!(factor));
+        }
+        if let Some(start) = self.start {
+            let day: i32 = make_payrix_date(&start).parse().unwrap_or_default();
+            body.insert("start".into(), json!(day));
+        }
+        if let Some(um) = self.um {
+            body.insert("um".into(), json!(um));
+        }
+        if let Some(amount) = self.amount {
+            body.insert("amount".into(), json!(amount));
+        }
+        if let Some(minimum) = self.minimum {
+            body.insert("minimum".into(), json!(minimum));
+        }
+        if let Some(maximum) = self.maximum {
+            body.insert("maximum".into(), json!(maximum));
+        }
+        if let Some(float_balance) = self.float_balance {
+            body.insert("float".into(), json!(float_balance));
+        }
+        if let Some(skip) = self.skip_off_days {
+            body.insert("skipOffDays".into(), json!(i32::from(skip)));
+        }
+        if let Some(same_day) = self.same_day {
+            body.insert("sameDay".into(), json!(i32::from(same_day)));
+        }
+        Value::Object(body)
+    }
+}
+
+/// Change a payout's schedule, amount, limits or off-day handling.
+///
+/// The update is merged with the current configuration and the result is
+/// checked with [`validate_payout`] before anything is sent.
+///
+/// # Errors
+///
+/// Returns an error if the update is empty or leaves the payout invalid, the
+/// payout does not exist or is frozen, or an API call fails.
+pub async fn update_payout(client: &PayrixClient, payout_id: &str, update: PayoutUpdate) -> Result<Payout> {
+    let payout: Payout = client
+        .get_one(EntityType::Payouts, payout_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Payout {} not found", payout_id)))?;
+    update.validate(&payout, Utc::now().date_naive()).into_result()?;
+
+    client
+        .update(EntityType::Payouts, payout_id, &update.payload())
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        parse_payrix_date(s).unwrap()
+    }
+
+    fn payout(json: Value) -> Payout {
+        let mut base = json!({ "id": "t1_pay_00000000000000000000001", "currency": "USD" });
+        base.as_object_mut().unwrap().extend(json.as_object().unwrap().clone());
+        serde_json::from_value(base).unwrap()
+    }
+
+    #[test]
+    fn test_us_bank_holidays() {
+        // New Year's Day 2023 fell on a Sunday and was observed Monday;
+        // Veterans Day fell on a Saturday and was not moved.
+        let holidays: Vec<String> = us_bank_holidays(2023).iter().map(make_payrix_date).collect();
+        assert_eq!(
+            holidays,
+            vec![
+                "20230102", "20230116", "20230220", "20230529", "20230619", "20230704",
+                "20230904", "20231009", "20231111", "20231123", "20231225",
+            ]
+        );
+        assert_eq!(us_bank_holidays(2021).len(), 10);
+
+        assert!(!is_business_day(date("20240704")));
+        assert!(!is_business_day(date("20240706")));
+        assert_eq!(next_business_day(date("20240830")), date("20240830"));
+        assert_eq!(next_business_day(date("20240831")), date("20240903"));
+    }
+
+    #[test]
+    fn test_payout_amount() {
+        let fixed = payout(json!({ "um": 2, "amount": 50000, "float": 1000, "minimum": 5000 }));
+        assert_eq!(payout_amount(&fixed, 100_000), 50_000);
+        assert_eq!(payout_amount(&fixed, 30_000), 29_000);
+        assert_eq!(payout_amount(&fixed, 5_500), 0);
+
+        let percent = payout(json!({ "um": 1, "amount": 5000, "maximum": 20000 }));
+        assert_eq!(payout_amount(&percent, 30_000), 15_000);
+        assert_eq!(payout_amount(&percent, 100_000), 20_000);
+        assert_eq!(payout_amount(&percent, -100), 0);
+
+        let negative = payout(json!({ "um": 3, "amount": 10000 }));
+        assert_eq!(payout_amount(&negative, 10_000), 0);
+        assert_eq!(payout_amount(&negative, -2_500), -2_500);
+    }
+
+    #[test]
+    fn test_forecast_off_days() {
+        // Weekly on Thursdays from Thanksgiving 2024, $100 at a time.
+        let weekly = json!({ "schedule": 2, "start": 20241128, "um": 2, "amount": 10000 });
+
+        let moved = forecast(&payout(weekly.clone()), 25_000, date("20241101"), 3);
+        let dates: Vec<_> = moved.iter().map(|p| (p.date, p.arrives, p.amount)).collect();
+        assert_eq!(
+            dates,
+            vec![
+                (date("20241129"), date("20241202"), 10_000),
+                (date("20241205"), date("20241206"), 10_000),
+                (date("20241212"), date("20241213"), 5_000),
+            ]
+        );
+        assert_eq!(moved[2].balance_after, 0);
+
+        let mut skipping = weekly;
+        skipping["skipOffDays"] = json!(1);
+        skipping["sameDay"] = json!(1);
+        let skipped = forecast(&payout(skipping), 25_000, date("20241101"), 2);
+        assert_eq!(skipped[0].scheduled, date("20241205"));
+        assert_eq!(skipped[0].arrives, date("20241205"));
+        assert_eq!(skipped[1].scheduled, date("20241212"));
+
+        // Daily over a weekend: Saturday and Sunday roll onto Monday's
+        // disbursement rather than forecasting three on the same day.
+        let daily = payout(json!({ "schedule": 1, "start": 20240607, "um": 2, "amount": 1000 }));
+        let rolled = forecast(&daily, 10_000, date("20240607"), 3);
+        let days: Vec<_> = rolled.iter().map(|p| (p.scheduled, p.date, p.amount, p.balance_after)).collect();
+        assert_eq!(
+            days,
+            vec![
+                (date("20240607"), date("20240607"), 1_000, 9_000),
+                (date("20240608"), date("20240610"), 3_000, 6_000),
+                (date("20240611"), date("20240611"), 1_000, 5_000),
+            ]
+        );
+
+        // Limits apply to the combined disbursement, not to each date.
+        let limited = payout(json!({
+            "schedule": 1, "start": 20240607, "um": 2, "amount": 1000, "minimum": 1500, "maximum": 2500
+        }));
+        let rolled = forecast(&limited, 10_000, date("20240607"), 3);
+        let days: Vec<_> = rolled.iter().map(|p| (p.date, p.amount, p.balance_after)).collect();
+        assert_eq!(
+            days,
+            vec![
+                (date("20240607"), 0, 10_000),
+                (date("20240610"), 2_500, 7_500),
+                (date("20240611"), 0, 7_500),
+            ]
+        );
+
+        let single = payout(json!({ "schedule": 5, "start": 20240102 }));
+        assert_eq!(forecast(&single, 1_000, date("20240101"), 5).len(), 1);
+        assert!(forecast(&single, 1_000, date("20240103"), 5).is_empty());
+    }
+
+    #[test]
+    fn test_update_validation() {
+        let current = payout(json!({ "schedule": 1, "um": 2, "amount": 10000, "minimum": 5000 }));
+        let today = date("20240601");
+
+        assert!(PayoutUpdate::default()
+            .with_schedule(PayoutSchedule::Weekly, 2)
+            .with_limits(Some(1000), Some(50000))
+            .validate(&current, today)
+            .is_valid());
+
+        let report = PayoutUpdate::default()
+            .with_amount(PayoutUnit::Percent, 12_000)
+            .with_limits(None, Some(1000))
+            .with_start(date("20240501"))
+            .validate(&current, today);
+        assert_eq!(report.for_path("amount").count(), 1);
+        assert_eq!(report.for_path("maximum").count(), 1);
+        assert_eq!(report.for_path("start").count(), 1);
+
+        assert!(PayoutUpdate::default().validate(&current, today).has_code(ValidationCode::Required));
+    }
+}
@@ -0,0 +1,600 @@
+//! Payout reconciliation: explain a bank deposit in terms of the activity it paid out.
+//!
+//! A [`Disbursement`] is a single deposit to a merchant's bank account. Payrix
+//! records what went into it as [`DisbursementEntry`] rows, each pointing at a
+//! ledger [`Entry`] (or a pending or reserve entry), which in turn points at
+//! the transaction, fee, chargeback, refund or adjustment that caused it.
+//! This module walks that chain and totals the deposit by category so finance
+//! can match it against the bank statement.
+//!
+//! # How Lines Are Classified
+//!
+//! Each disbursement entry becomes one [`ReconciliationLine`]:
+//!
+//! - Lines for a reserve entry are a **reserve hold** when negative and a
+//!   **reserve release** when positive.
+//! - Otherwise the ledger entry's links decide: a fee (or `isFee`) is a
+//!   **fee**, then a chargeback, refund or adjustment link, then a transaction
+//!   link is a **sale**.
+//! - Lines without a ledger entry (pending entries) fall back to the event type.
+//!
+//! Amounts are signed from the merchant's side: sales and releases are
+//! positive, fees, refunds, chargebacks and holds are negative. The sum of all
+//! lines should equal `Disbursement.amount`; a difference is reported as a
+//! [`ReconciliationIssue::AmountMismatch`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use payrix::{PayrixClient, Environment};
+//! use payrix::workflows::reconciliation::reconcile_disbursement;
+//!
+//! # async fn example() -> payrix::Result<()> {
+//! let client = PayrixClient::new("api-key", Environment::Test)?;
+//!
+//! let report = reconcile_disbursement(&client, "t1_dbm_12345678901234567890123").await?;
+//! let totals = &report.breakdown;
+//! println!("Sales:    {:>10}", totals.gross_sales);
+//! println!("Refunds:  {:>10}", totals.refunds);
+//! println!("Fees:     {:>10}", totals.fees);
+//! println!("Net:      {:>10}", totals.net);
+//!
+//! for issue in &report.issues {
+//!     println!("Needs review: {:?}", issue);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate};
+
+use crate::client::PayrixClient;
+use crate::entity::EntityType;
+use crate::error::{Error, Result};
+use crate::search::{make_payrix_date, SearchBuilder, SearchOperator};
+use crate::types::{Disbursement, DisbursementEntry, Entry, EventType, PayrixId};
+
+/// Ledger entries requested per `id[in]` search.
+const ENTRY_BATCH_SIZE: usize = 50;
+
+// =============================================================================
+// Section 1: Result Types
+// =============================================================================
+
+/// What a line of a payout represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReconciliationCategory {
+    /// Card or eCheck sales.
+    Sale,
+    /// Refunds to customers.
+    Refund,
+    /// Chargebacks, reversals and representments.
+    Chargeback,
+    /// Processing, interchange and other fees.
+    Fee,
+    /// Funds moved into a reserve.
+    ReserveHold,
+    /// Funds released from a reserve.
+    ReserveRelease,
+    /// Manual balance adjustments.
+    Adjustment,
+    /// Anything else (payout returns, remainders, ...).
+    Other,
+}
+
+/// The record a payout line traces back to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineSource {
+    /// A transaction (`t1_txn_...`).
+    Transaction(PayrixId),
+    /// A fee (`t1_fee_...`).
+    Fee(PayrixId),
+    /// A chargeback (`t1_chb_...`).
+    Chargeback(PayrixId),
+    /// A refund (`t1_rfd_...`).
+    Refund(PayrixId),
+    /// An adjustment (`t1_adj_...`).
+    Adjustment(PayrixId),
+    /// A reserve entry (`t1_rse_...`).
+    ReserveEntry(PayrixId),
+    /// A pending entry not yet in the ledger.
+    PendingEntry(PayrixId),
+}
+
+/// One disbursement entry, classified.
+#[derive(Debug, Clone)]
+pub struct ReconciliationLine {
+    /// The disbursement entry ID.
+    pub disbursement_entry: PayrixId,
+    /// The ledger entry it draws on, if any.
+    pub entry: Option<PayrixId>,
+    /// The event that created the entry.
+    pub event: Option<EventType>,
+    /// How the line is counted.
+    pub category: ReconciliationCategory,
+    /// The record the line traces back to.
+    pub source: Option<LineSource>,
+    /// Signed amount in cents (up to three decimal places).
+    pub amount: f64,
+    /// Description from the ledger or disbursement entry.
+    pub description: Option<String>,
+}
+
+/// Payout totals by category, in cents.
+///
+/// Each total is signed and rounded to whole cents; `net` is the rounded sum
+/// of the unrounded line amounts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PayoutBreakdown {
+    /// Sales.
+    pub gross_sales: i64,
+    /// Refunds (normally negative).
+    pub refunds: i64,
+    /// Chargebacks net of reversals (normally negative).
+    pub chargebacks: i64,
+    /// Fees (normally negative).
+    pub fees: i64,
+    /// Reserve holds (negative).
+    pub reserve_holds: i64,
+    /// Reserve releases (positive).
+    pub reserve_releases: i64,
+    /// Adjustments.
+    pub adjustments: i64,
+    /// Uncategorised lines.
+    pub other: i64,
+    /// Total of every line: what should have been deposited.
+    pub net: i64,
+}
+
+impl PayoutBreakdown {
+    /// Total the lines by category.
+    pub fn from_lines(lines: &[ReconciliationLine]) -> Self {
+        let mut totals: HashMap<ReconciliationCategory, f64> = HashMap::new();
+        for line in lines {
+            *totals.entry(line.category).or_default() += line.amount;
+        }
+        let total = |category| totals.get(&category).copied().unwrap_or(0.0).round() as i64;
+
+        Self {
+            gross_sales: total(ReconciliationCategory::Sale),
+            refunds: total(ReconciliationCategory::Refund),
+            chargebacks: total(ReconciliationCategory::Chargeback),
+            fees: total(ReconciliationCategory::Fee),
+            reserve_holds: total(ReconciliationCategory::ReserveHold),
+            reserve_releases: total(ReconciliationCategory::ReserveRelease),
+            adjustments: total(ReconciliationCategory::Adjustment),
+            other: total(ReconciliationCategory::Other),
+            net: lines.iter().map(|l| l.amount).sum::<f64>().round() as i64,
+        }
+    }
+}
+
+/// Something that keeps a payout from reconciling cleanly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReconciliationIssue {
+    /// The lines do not add up to the disbursement amount.
+    AmountMismatch {
+        /// `Disbursement.amount`.
+        expected: i64,
+        /// Sum of the lines.
+        actual: i64,
+    },
+    /// The disbursement has no amount to check against.
+    MissingDisbursementAmount,
+    /// A disbursement entry points at a ledger entry that could not be fetched.
+    MissingEntry {
+        /// The disbursement entry.
+        disbursement_entry: PayrixId,
+        /// The ledger entry it references.
+        entry: PayrixId,
+    },
+    /// A disbursement entry has no amount; it was counted as zero.
+    MissingLineAmount(PayrixId),
+}
+
+/// A disbursement explained line by line.
+#[derive(Debug, Clone)]
+pub struct PayoutReconciliation {
+    /// The disbursement (deposit) being reconciled.
+    pub disbursement: Disbursement,
+    /// Every disbursement entry, classified.
+    pub lines: Vec<ReconciliationLine>,
+    /// Totals by category.
+    pub breakdown: PayoutBreakdown,
+    /// Problems found; empty when the payout reconciles.
+    pub issues: Vec<ReconciliationIssue>,
+}
+
+impl PayoutReconciliation {
+    /// Whether the lines account for the whole deposit with nothing missing.
+    pub fn is_reconciled(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Lines in one category.
+    pub fn lines_in(
+        &self,
+        category: ReconciliationCategory,
+    ) -> impl Iterator<Item = &ReconciliationLine> {
+        self.lines.iter().filter(move |l| l.category == category)
+    }
+}
+
+// =============================================================================
+// Section 2: Classification
+// =============================================================================
+
+/// Convert a raw event number (as on [`DisbursementEntry`]) to an [`EventType`].
+fn event_type(event: i32) -> Option<EventType> {
+    serde_json::from_value(serde_json::Value::from(event)).ok()
+}
+
+/// Category implied by an event alone.
+fn category_for_event(event: Option<EventType>) -> ReconciliationCategory {
+    use ReconciliationCategory as C;
+
+    match event {
+        Some(
+            EventType::Capture
+            | EventType::Authorization
+            | EventType::Settlement
+            | EventType::CardSettlement
+            | EventType::EcheckSettlement
+            | EventType::ECheckSale
+            | EventType::TerminalTransaction,
+        ) => C::Sale,
+        Some(
+            EventType::Refund
+            | EventType::ECheckRefund
+            | EventType::EntryRefund
+            | EventType::PendingRefundCancelled,
+        ) => C::Refund,
+        Some(
+            EventType::Chargeback
+            | EventType::Retrieval
+            | EventType::Arbitration
+            | EventType::Prearbitration
+            | EventType::Reversal
+            | EventType::Representment,
+        ) => C::Chargeback,
+        Some(EventType::Interchange | EventType::Processor | EventType::Fanf) => C::Fee,
+        Some(EventType::ReserveEntry) => C::ReserveHold,
+        Some(EventType::ReserveEntryRelease) => C::ReserveRelease,
+        Some(EventType::Adjustment) => C::Adjustment,
+        _ => C::Other,
+    }
+}
+
+/// Classify one ledger entry by what it links to, falling back to its event.
+fn classify_entry(entry: &Entry) -> (ReconciliationCategory, Option<LineSource>) {
+    use ReconciliationCategory as C;
+
+    if let Some(fee) = &entry.fee {
+        return (C::Fee, Some(LineSource::Fee(fee.clone())));
+    }
+    let source = if let Some(id) = &entry.chargeback {
+        Some((C::Chargeback, LineSource::Chargeback(id.clone())))
+    } else if let Some(id) = &entry.refund {
+        Some((C::Refund, LineSource::Refund(id.clone())))
+    } else if let Some(id) = &entry.adjustment {
+        Some((C::Adjustment, LineSource::Adjustment(id.clone())))
+    } else {
+        entry
+            .txn
+            .as_ref()
+            .map(|id| (C::Sale, LineSource::Transaction(id.clone())))
+    };
+
+    // A fee charged on a transaction links to the transaction
+    if entry.is_fee == Some(1) {
+        return (C::Fee, source.map(|(_, s)| s));
+    }
+    match source {
+        Some((category, source)) => (category, Some(source)),
+        None => (category_for_event(entry.event), None),
+    }
+}
+
+/// Classify a disbursement entry, using its ledger entry when available.
+fn classify_line(
+    disbursement_entry: &DisbursementEntry,
+    entry: Option<&Entry>,
+) -> ReconciliationLine {
+    let amount = disbursement_entry.amount.unwrap_or(0.0);
+    let event = entry
+        .and_then(|e| e.event)
+        .or_else(|| disbursement_entry.event.and_then(event_type));
+
+    let (category, source) = if let Some(reserve_entry) = &disbursement_entry.reserve_entry {
+        let category = if amount < 0.0 {
+            ReconciliationCategory::ReserveHold
+        } else {
+            ReconciliationCategory::ReserveRelease
+        };
+        (category, Some(LineSource::ReserveEntry(reserve_entry.clone())))
+    } else if let Some(entry) = entry {
+        classify_entry(entry)
+    } else {
+        (
+            category_for_event(event),
+            disbursement_entry
+                .pending_entry
+                .as_ref()
+                .map(|id| LineSource::PendingEntry(id.clone())),
+        )
+    };
+
+    ReconciliationLine {
+        disbursement_entry: disbursement_entry.id.clone(),
+        entry: disbursement_entry.entry.clone(),
+        event,
+        category,
+        source,
+        amount,
+        description: entry
+            .and_then(|e| e.description.clone())
+            .or_else(|| disbursement_entry.description.clone()),
+    }
+}
+
+/// Reconcile a disbursement from already-fetched records.
+///
+/// `ledger` holds the [`Entry`] records referenced by `entries`; any that are
+/// missing are reported as [`ReconciliationIssue::MissingEntry`] and the line
+/// is classified from the disbursement entry's event instead.
+pub fn reconcile(
+    disbursement: Disbursement,
+    entries: &[DisbursementEntry],
+    ledger: &[Entry],
+) -> PayoutReconciliation {
+    let ledger: HashMap<&str, &Entry> = ledger.iter().map(|e| (e.id.as_str(), e)).collect();
+    let mut issues = Vec::new();
+
+    let lines: Vec<ReconciliationLine> = entries
+        .iter()
+        .map(|disbursement_entry| {
+            if disbursement_entry.amount.is_none() {
+                issues.push(ReconciliationIssue::MissingLineAmount(
+                    disbursement_entry.id.clone(),
+                ));
+            }
+            let entry = disbursement_entry.entry.as_ref().and_then(|id| {
+                let found = ledger.get(id.as_str()).copied();
+                if found.is_none() {
+                    issues.push(ReconciliationIssue::MissingEntry {
+                        disbursement_entry: disbursement_entry.id.clone(),
+                        entry: id.clone(),
+                    });
+                }
+                found
+            });
+            classify_line(disbursement_entry, entry)
+        })
+        .collect();
+
+    let breakdown = PayoutBreakdown::from_lines(&lines);
+    match disbursement.amount {
+        Some(expected) if expected != breakdown.net => {
+            issues.push(ReconciliationIssue::AmountMismatch {
+                expected,
+                actual: breakdown.net,
+            });
+        }
+        Some(_) => {}
+        None => issues.push(ReconciliationIssue::MissingDisbursementAmount),
+    }
+
+    PayoutReconciliation {
+        disbursement,
+        lines,
+        breakdown,
+        issues,
+    }
+}
+
+// =============================================================================
+// Section 3: API Operations
+// =============================================================================
+
+/// Reconcile one disbursement.
+///
+/// Fetches the disbursement, its disbursement entries and the ledger entries
+/// they reference, then classifies them with [`reconcile`].
+///
+/// # Errors
+///
+/// Returns [`Error::NotFound`] if the disbursement does not exist.
+pub async fn reconcile_disbursement(
+    client: &PayrixClient,
+    disbursement_id: &str,
+) -> Result<PayoutReconciliation> {
+    let disbursement: Disbursement = client
+        .get_one(EntityType::Disbursements, disbursement_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Disbursement {} not found", disbursement_id)))?;
+
+    reconcile_fetched(client, disbursement).await
+}
+
+/// Reconcile every disbursement to an entity created between `from` and `to`
+/// (inclusive), oldest first.
+pub async fn reconcile_deposits(
+    client: &PayrixClient,
+    entity_id: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<PayoutReconciliation>> {
+    let search = SearchBuilder::new()
+        .field("entity", entity_id)
+        .field_with_op("created", &make_payrix_date(&(from - Duration::days(1))), SearchOperator::Greater)
+        .field_with_op("created", &make_payrix_date(&(to + Duration::days(1))), SearchOperator::Less)
+        .field_with_op("created", "asc", SearchOperator::Sort)
+        .build();
+    let disbursements: Vec<Disbursement> = client
+        .search(EntityType::Disbursements, &search)
+        .await?;
+
+    let mut reports = Vec::with_capacity(disbursements.len());
+    for disbursement in disbursements {
+        reports.push(reconcile_fetched(client, disbursement).await?);
+    }
+    Ok(reports)
+}
+
+async fn reconcile_fetched(
+    client: &PayrixClient,
+    disbursement: Disbursement,
+) -> Result<PayoutReconciliation> {
+    let search = SearchBuilder::new()
+        .field("disbursement", disbursement.id.as_str())
+        .build();
+    let entries: Vec<DisbursementEntry> = client
+        .search(EntityType::DisbursementEntries, &search)
+        .await?;
+
+    let mut entry_ids: Vec<&str> = entries
+        .iter()
+        .filter_map(|e| e.entry.as_ref().map(PayrixId::as_str))
+        .collect();
+    entry_ids.sort_unstable();
+    entry_ids.dedup();
+
+    let mut ledger: Vec<Entry> = Vec::with_capacity(entry_ids.len());
+    for batch in entry_ids.chunks(ENTRY_BATCH_SIZE) {
+        let search = SearchBuilder::new()
+            .field_multi("id", batch, SearchOperator::In)
+            .build();
+        ledger.extend(client.search::<Entry>(EntityType::Entries, &search).await?);
+    }
+
+    Ok(reconcile(disbursement, &entries, &ledger))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn disbursement(amount: i64) -> Disbursement {
+        serde_json::from_value(json!({
+            "id": "t1_dbm_12345678901234567890123",
+            "amount": amount,
+        }))
+        .unwrap()
+    }
+
+    fn line(id: u32, amount: f64, extra: serde_json::Value) -> DisbursementEntry {
+        let mut value = json!({
+            "id": format!("t1_dbe_{:023}", id),
+            "amount": amount,
+        });
+        value.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn entry(id: u32, extra: serde_json::Value) -> Entry {
+        let mut value = json!({ "id": format!("t1_ent_{:023}", id) });
+        value.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn entry_ref(id: u32) -> serde_json::Value {
+        json!({ "entry": format!("t1_ent_{:023}", id) })
+    }
+
+    #[test]
+    fn test_breakdown_by_linked_record() {
+        let entries = vec![
+            line(1, 10000.0, entry_ref(1)),
+            line(2, -290.0, entry_ref(2)),
+            line(3, -2500.0, entry_ref(3)),
+            line(4, -1500.0, entry_ref(4)),
+            line(5, 250.0, entry_ref(5)),
+            line(6, -36.0, entry_ref(6)),
+        ];
+        let ledger = vec![
+            entry(1, json!({ "txn": "t1_txn_12345678901234567890123", "event": 7 })),
+            entry(2, json!({ "txn": "t1_txn_12345678901234567890123", "isFee": 1, "event": 13 })),
+            entry(3, json!({ "refund": "t1_rfd_12345678901234567890123", "event": 8 })),
+            entry(4, json!({ "chargeback": "t1_chb_12345678901234567890123", "event": 11 })),
+            entry(5, json!({ "adjustment": "t1_adj_12345678901234567890123", "event": 18 })),
+            entry(6, json!({ "fee": "t1_fee_12345678901234567890123", "event": 5 })),
+        ];
+
+        let report = reconcile(disbursement(5924), &entries, &ledger);
+
+        let b = report.breakdown;
+        assert_eq!(b.gross_sales, 10000);
+        assert_eq!(b.fees, -326);
+        assert_eq!(b.refunds, -2500);
+        assert_eq!(b.chargebacks, -1500);
+        assert_eq!(b.adjustments, 250);
+        assert_eq!(b.net, 5924);
+        assert!(report.is_reconciled(), "{:?}", report.issues);
+
+        let fee = report.lines_in(ReconciliationCategory::Fee).next().unwrap();
+        assert_eq!(
+            fee.source,
+            Some(LineSource::Transaction("t1_txn_12345678901234567890123".parse().unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_reserve_and_pending_lines() {
+        let entries = vec![
+            line(1, 10000.0, entry_ref(1)),
+            line(2, -1000.0, json!({ "reserveEntry": "t1_rse_12345678901234567890123" })),
+            line(3, 400.0, json!({ "reserveEntry": "t1_rse_12345678901234567890124" })),
+            line(4, -300.0, json!({ "pendingEntry": "t1_pen_12345678901234567890123", "event": 8 })),
+        ];
+        let ledger = vec![entry(1, json!({ "txn": "t1_txn_12345678901234567890123" }))];
+
+        let report = reconcile(disbursement(9100), &entries, &ledger);
+
+        assert_eq!(report.breakdown.reserve_holds, -1000);
+        assert_eq!(report.breakdown.reserve_releases, 400);
+        assert_eq!(report.breakdown.refunds, -300);
+        assert!(matches!(
+            report.lines[3].source,
+            Some(LineSource::PendingEntry(_))
+        ));
+        assert!(report.is_reconciled());
+    }
+
+    #[test]
+    fn test_flags_mismatch_and_missing_entries() {
+        let entries = vec![
+            line(1, 10000.0, entry_ref(1)),
+            line(2, -300.0, json!({ "entry": "t1_ent_00000000000000000000099", "event": 14 })),
+        ];
+        let ledger = vec![entry(1, json!({ "txn": "t1_txn_12345678901234567890123" }))];
+
+        let report = reconcile(disbursement(9800), &entries, &ledger);
+
+        // The missing entry is still classified from the disbursement entry's event
+        assert_eq!(report.lines[1].category, ReconciliationCategory::Fee);
+        assert_eq!(
+            report.issues,
+            vec![
+                ReconciliationIssue::MissingEntry {
+                    disbursement_entry: "t1_dbe_00000000000000000000002".parse().unwrap(),
+                    entry: "t1_ent_00000000000000000000099".parse().unwrap(),
+                },
+                ReconciliationIssue::AmountMismatch {
+                    expected: 9800,
+                    actual: 9700,
+                },
+            ]
+        );
+        assert!(!report.is_reconciled());
+    }
+
+    #[test]
+    fn test_event_type_conversion() {
+        assert_eq!(event_type(8), Some(EventType::Refund));
+        assert_eq!(event_type(99999), None);
+        assert_eq!(category_for_event(None), ReconciliationCategory::Other);
+    }
+}
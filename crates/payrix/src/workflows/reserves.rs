@@ -0,0 +1,489 @@
+//! See why a merchant's money is held and when it will be released.
+//!
+//! Funds can be held back from a merchant in three ways:
+//!
+//! - A [`Reserve`] withholds a percentage of each transaction, up to `max`,
+//!   and releases it on a [`ReleaseSchedule`] (e.g. 90 days after it was held).
+//! - A [`Hold`] stops a transaction or entity from funding until it is
+//!   reviewed and released.
+//! - Each amount moved into reserve is recorded as a [`ReserveEntry`] with the
+//!   date it will be released.
+//!
+//! [`summary`] gathers all three for a merchant entity into a
+//! [`ReserveSummary`]: the active reserves, the open holds with their source,
+//! and a calendar of upcoming releases. Reserve entries without a release
+//! date are placed on the calendar using their reserve's schedule and
+//! `release_factor` (see [`release_date`]).
+//!
+//! [`release_hold`] and [`adjust_reserve`] change holds and reserves after
+//! checking the change makes sense.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use payrix::{PayrixClient, Environment};
+//! use payrix::workflows::reserves::{adjust_reserve, summary, ReserveAdjustment};
+//!
+//! # async fn example() -> payrix::Result<()> {
+//! let client = PayrixClient::new("api-key", Environment::Test)?;
+//! let summary = summary(&client, "t1_ent_12345678901234567890123").await?;
+//!
+//! for hold in &summary.holds {
+//!     println!("Hold {} from {:?}", hold.id, hold.hold_source);
+//! }
+//! for (date, amount) in summary.calendar() {
+//!     println!("{}: {} cents released", date, amount);
+//! }
+//!
+//! // Lower the first reserve to 5%.
+//! if let Some(reserve) = summary.reserves.first() {
+//!     let update = ReserveAdjustment::default().with_percent(500);
+//!     adjust_reserve(&client, reserve.id.as_str(), update).await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::{Days, Months, NaiveDate, NaiveDateTime, Utc};
+use serde_json::{json, Map, Value};
+
+use crate::client::PayrixClient;
+use crate::entity::EntityType;
+use crate::error::{Error, Result};
+use crate::search::{make_payrix_date, parse_payrix_date, SearchBuilder};
+use crate::types::{
+    Hold, PayrixId, ReleaseAction, ReleaseSchedule, Reserve, ReserveEntry, ReserveStatus,
+};
+
+use super::merchant_onboarding::{ValidationCode, ValidationReport};
+
+// =============================================================================
+// Section 1: Summary Types
+// =============================================================================
+
+/// An amount in reserve and the date it is due back to the merchant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledRelease {
+    /// The reserve entry holding the funds.
+    pub entry: PayrixId,
+    /// The reserve that withheld the funds, if any.
+    pub reserve: Option<PayrixId>,
+    /// The hold that withheld the funds, if any.
+    pub hold: Option<PayrixId>,
+    /// Amount in cents.
+    pub amount: i64,
+    /// Release date.
+    pub date: NaiveDate,
+    /// Whether the date was projected from the reserve's schedule rather than
+    /// read from the entry.
+    pub projected: bool,
+}
+
+/// Everything holding back a merchant's funds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReserveSummary {
+    /// The merchant entity.
+    pub entity_id: String,
+    /// Date the summary describes.
+    pub as_of: NaiveDate,
+    /// Reserves currently withholding funds.
+    pub reserves: Vec<Reserve>,
+    /// Holds not yet released.
+    pub holds: Vec<Hold>,
+    /// Funds in reserve with a release date after `as_of`, earliest first.
+    pub releases: Vec<ScheduledRelease>,
+    /// Funds in reserve with no release date, in cents.
+    pub unscheduled: i64,
+}
+
+impl ReserveSummary {
+    /// Build a summary from records already in hand.
+    ///
+    /// Inactive or finished reserves and released holds are dropped. Reserve
+    /// entries are kept only while they still hold funds: positive amounts
+    /// not yet moved out by a later entry and not yet past their release date.
+    pub fn new(
+        entity_id: impl Into<String>,
+        as_of: NaiveDate,
+        reserves: Vec<Reserve>,
+        holds: Vec<Hold>,
+        entries: &[ReserveEntry],
+    ) -> Self {
+        let moved_out: HashSet<&PayrixId> =
+            entries.iter().filter_map(|e| e.reserve_entry.as_ref()).collect();
+
+        let mut releases = Vec::new();
+        let mut unscheduled = 0;
+        for entry in entries {
+            let amount = entry.amount.unwrap_or(0);
+            if amount <= 0 || moved_out.contains(&entry.id) {
+                continue;
+            }
+
+            let scheduled = entry.release.as_deref().and_then(parse_payrix_date);
+            let date = scheduled.map(|d| (d, false)).or_else(|| {
+                let reserve = reserves.iter().find(|r| Some(&r.id) == entry.reserve.as_ref())?;
+                let held_on = parse_date(entry.created.as_deref())?;
+                release_date(reserve, held_on).map(|d| (d, true))
+            });
+            match date {
+                Some((date, _)) if date <= as_of => {}
+                Some((date, projected)) => releases.push(ScheduledRelease {
+                    entry: entry.id.clone(),
+                    reserve: entry.reserve.clone(),
+                    hold: entry.hold.clone(),
+                    amount,
+                    date,
+                    projected,
+                }),
+                None => unscheduled += amount,
+            }
+        }
+        releases.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.entry.as_str().cmp(b.entry.as_str())));
+
+        Self {
+            entity_id: entity_id.into(),
+            as_of,
+            reserves: reserves.into_iter().filter(|r| is_reserve_active(r, as_of)).collect(),
+            holds: holds.into_iter().filter(is_hold_open).collect(),
+            releases,
+            unscheduled,
+        }
+    }
+
+    /// Total funds in reserve, scheduled or not, in cents.
+    pub fn total_reserved(&self) -> i64 {
+        self.releases.iter().map(|r| r.amount).sum::<i64>() + self.unscheduled
+    }
+
+    /// Scheduled releases totalled by date, earliest first.
+    pub fn calendar(&self) -> Vec<(NaiveDate, i64)> {
+        let mut days: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+        for release in &self.releases {
+            *days.entry(release.date).or_default() += release.amount;
+        }
+        days.into_iter().collect()
+    }
+
+    /// Funds released on or before `date`, in cents.
+    pub fn released_by(&self, date: NaiveDate) -> i64 {
+        self.releases.iter().filter(|r| r.date <= date).map(|r| r.amount).sum()
+    }
+}
+
+// =============================================================================
+// Section 2: Release Schedule
+// =============================================================================
+
+/// When funds withheld by `reserve` on `held_on` are due to be released.
+///
+/// The reserve's `release` schedule is multiplied by `release_factor`
+/// (default 1): `Days` with a factor of 90 releases 90 days later. Returns
+/// `None` for reserves that never release, or when the date overflows.
+pub fn release_date(reserve: &Reserve, held_on: NaiveDate) -> Option<NaiveDate> {
+    let factor = u32::try_from(reserve.release_factor.unwrap_or(1).max(1)).ok()?;
+    match reserve.release.as_ref()? {
+        ReleaseSchedule::Never => None,
+        ReleaseSchedule::Days => held_on.checked_add_days(Days::new(factor.into())),
+        ReleaseSchedule::Weeks => held_on.checked_add_days(Days::new(u64::from(factor) * 7)),
+        ReleaseSchedule::Months => held_on.checked_add_months(Months::new(factor)),
+        ReleaseSchedule::Years => held_on.checked_add_months(Months::new(factor.checked_mul(12)?)),
+    }
+}
+
+/// Whether a reserve is withholding funds on `date`.
+pub fn is_reserve_active(reserve: &Reserve, date: NaiveDate) -> bool {
+    let day = make_payrix_date(&date).parse::<i32>().unwrap_or(0);
+    !reserve.inactive
+        && reserve.status != Some(ReserveStatus::Inactive)
+        && reserve.finish.is_none_or(|finish| day <= finish)
+}
+
+/// Whether a hold has not been released.
+pub fn is_hold_open(hold: &Hold) -> bool {
+    !hold.inactive && hold.released.is_none() && hold.release_action.is_none()
+}
+
+fn parse_date(value: Option<&str>) -> Option<NaiveDate> {
+    value
+        .and_then(|v| NaiveDateTime::parse_from_str(v, "%Y-%m-%d %H:%M:%S%.f").ok())
+        .map(|t| t.date())
+}
+
+// =============================================================================
+// Section 3: Fetching
+// =============================================================================
+
+/// Summarize the reserves, holds and scheduled releases for a merchant entity.
+///
+/// # Errors
+///
+/// Returns an error if an API call fails.
+pub async fn summary(client: &PayrixClient, entity_id: &str) -> Result<ReserveSummary> {
+    let search = SearchBuilder::new().field("entity", entity_id).build();
+    let reserves: Vec<Reserve> = client.search(EntityType::Reserves, &search).await?;
+    let holds: Vec<Hold> = client.search(EntityType::Holds, &search).await?;
+
+    let search = SearchBuilder::new().field("onentity", entity_id).build();
+    let entries: Vec<ReserveEntry> = client.search(EntityType::ReserveEntries, &search).await?;
+
+    Ok(ReserveSummary::new(
+        entity_id,
+        Utc::now().date_naive(),
+        reserves,
+        holds,
+        &entries,
+    ))
+}
+
+// =============================================================================
+// Section 4: Operations
+// =============================================================================
+
+/// Release an open hold.
+///
+/// # Errors
+///
+/// Returns an error if the hold does not exist or is already released, if
+/// `action` is [`ReleaseAction::Expired`] (set only by Payrix), or if an API
+/// call fails.
+pub async fn release_hold(client: &PayrixClient, hold_id: &str, action: ReleaseAction) -> Result<Hold> {
+    if action == ReleaseAction::Expired {
+        return Err(Error::Validation("Holds expire automatically and cannot be released as expired".to_string()));
+    }
+
+    let hold: Hold = client
+        .get_one(EntityType::Holds, hold_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Hold {} not found", hold_id)))?;
+    if !is_hold_open(&hold) {
+        return Err(Error::Validation(format!("Hold {} is already released", hold_id)));
+    }
+
+    client
+        .update(EntityType::Holds, hold_id, &json!({ "releaseAction": action }))
+        .await
+}
+
+/// Changes to a reserve's terms.
+///
+/// Fields left as `None` are not changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReserveAdjustment {
+    /// Percentage withheld, in basis points (0-10000).
+    pub percent: Option<i32>,
+    /// Maximum amount to hold, in cents.
+    pub max: Option<i64>,
+    /// Release schedule.
+    pub release: Option<ReleaseSchedule>,
+    /// Multiplier for the release schedule.
+    pub release_factor: Option<i32>,
+    /// Last day the reserve withholds funds.
+    pub finish: Option<NaiveDate>,
+    /// Active, under review or inactive.
+    pub status: Option<ReserveStatus>,
+}
+
+impl ReserveAdjustment {
+    /// Set the percentage withheld, in basis points.
+    pub fn with_percent(mut self, percent: i32) -> Self {
+        self.percent = Some(percent);
+        self
+    }
+
+    /// Set the maximum amount held, in cents.
+    pub fn with_max(mut self, max: i64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// Set the release schedule, e.g. `(ReleaseSchedule::Days, 90)`.
+    pub fn with_release(mut self, release: ReleaseSchedule, factor: i32) -> Self {
+        self.release = Some(release);
+        self.release_factor = Some(factor);
+        self
+    }
+
+    /// Set the last day the reserve withholds funds.
+    pub fn with_finish(mut self, finish: NaiveDate) -> Self {
+        self.finish = Some(finish);
+        self
+    }
+
+    /// Set the reserve status.
+    pub fn with_status(mut self, status: ReserveStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    fn validate(&self, reserve: &Reserve, today: NaiveDate) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        if *self == Self::default() {
+            report.push("adjustment", ValidationCode::Required, "No reserve changes were given");
+        }
+        if reserve.frozen {
+            report.push("reserve", ValidationCode::InvalidValue, "Reserve is frozen");
+        }
+        if self.percent.is_some_and(|p| !(0..=10_000).contains(&p)) {
+            report.push("percent", ValidationCode::OutOfRange, "Percent must be 0-10000 basis points");
+        }
+        if self.max.is_some_and(|max| max < 0) {
+            report.push("max", ValidationCode::OutOfRange, "Maximum cannot be negative");
+        }
+        if self.release_factor.is_some_and(|f| f < 1) {
+            report.push("releaseFactor", ValidationCode::OutOfRange, "Release factor must be at least 1");
+        }
+        if let Some(finish) = self.finish {
+            let start = reserve.start.and_then(|s| parse_payrix_date(&s.to_string()));
+            if finish < today {
+                report.push("finish", ValidationCode::InvalidValue, "Finish date cannot be in the past");
+            } else if start.is_some_and(|start| finish < start) {
+                report.push("finish", ValidationCode::InvalidValue, "Finish date is before the reserve starts");
+            }
+        }
+        report
+    }
+
+    fn payload(&self) -> Value {
+        let mut body = Map::new();
+        if let Some(percent) = self.percent {
+            body.insert("percent".into(), json!(percent));
+        }
+        if let Some(max) = self.max {
+            body.insert("max".into(), json!(max));
+        }
+        if let Some(release) = &self.release {
+            body.insert("release".into(), json!(release));
+        }
+        if let Some(factor) = self.release_factor {
+            body.insert("releaseFactor".into(), json!(factor));
+        }
+        if let Some(finish) = self.finish {
+            let day: i32 = make_payrix_date(&finish).parse().unwrap_or_default();
+            body.insert("finish".into(), json!(day));
+        }
+        if let Some(status) = self.status {
+            body.insert("status".into(), json!(status));
+        }
+        Value::Object(body)
+    }
+}
+
+/// Change a reserve's percentage, maximum, release schedule, end date or status.
+///
+/// # Errors
+///
+/// Returns an error if the adjustment is empty or invalid, the reserve does
+/// not exist or is frozen, or an API call fails.
+pub async fn adjust_reserve(
+    client: &PayrixClient,
+    reserve_id: &str,
+    adjustment: ReserveAdjustment,
+) -> Result<Reserve> {
+    let reserve: Reserve = client
+        .get_one(EntityType::Reserves, reserve_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Reserve {} not found", reserve_id)))?;
+    adjustment.validate(&reserve, Utc::now().date_naive()).into_result()?;
+
+    client
+        .update(EntityType::Reserves, reserve_id, &adjustment.payload())
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        parse_payrix_date(s).unwrap()
+    }
+
+    fn reserve(json: Value) -> Reserve {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_release_date() {
+        let held = date("20240131");
+        let r = |release: &str, factor: i32| {
+            reserve(json!({ "id": "t1_rsv_00000000000000000000001", "release": release, "releaseFactor": factor }))
+        };
+        assert_eq!(release_date(&r("days", 90), held), Some(date("20240430")));
+        assert_eq!(release_date(&r("weeks", 2), held), Some(date("20240214")));
+        assert_eq!(release_date(&r("months", 1), held), Some(date("20240229")));
+        assert_eq!(release_date(&r("years", 1), held), Some(date("20250131")));
+        assert_eq!(release_date(&r("never", 1), held), None);
+    }
+
+    #[test]
+    fn test_summary() {
+        let reserves = vec![
+            reserve(json!({ "id": "t1_rsv_00000000000000000000001", "percent": 1000, "max": 500000,
+                            "release": "days", "releaseFactor": 30, "status": 1 })),
+            reserve(json!({ "id": "t1_rsv_00000000000000000000002", "finish": 20240101 })),
+        ];
+        let holds: Vec<Hold> = serde_json::from_value(json!([
+            { "id": "t1_hld_00000000000000000000001", "action": 4, "holdSource": "MANUAL" },
+            { "id": "t1_hld_00000000000000000000002", "released": "2024-03-01 10:00:00", "releaseAction": 1 },
+        ]))
+        .unwrap();
+        let entries: Vec<ReserveEntry> = serde_json::from_value(json!([
+            // Projected: held Mar 1, released 30 days later.
+            { "id": "t1_rse_00000000000000000000001", "reserve": "t1_rsv_00000000000000000000001",
+              "created": "2024-03-01 10:00:00.0000", "amount": 1000 },
+            // Explicit date.
+            { "id": "t1_rse_00000000000000000000002", "hold": "t1_hld_00000000000000000000001",
+              "created": "2024-03-02 10:00:00.0000", "release": "20240320", "amount": 2500 },
+            // Already released, then moved out.
+            { "id": "t1_rse_00000000000000000000003", "created": "2024-02-01 10:00:00.0000",
+              "release": "20240310", "amount": 700 },
+            { "id": "t1_rse_00000000000000000000004", "reserveEntry": "t1_rse_00000000000000000000005",
+              "amount": -400 },
+            { "id": "t1_rse_00000000000000000000005", "release": "20240401", "amount": 400 },
+            // No reserve schedule and no date.
+            { "id": "t1_rse_00000000000000000000006", "amount": 300 },
+        ]))
+        .unwrap();
+
+        let summary = ReserveSummary::new("t1_ent_00000000000000000000001", date("20240315"), reserves, holds, &entries);
+        assert_eq!(summary.reserves.len(), 1);
+        assert_eq!(summary.holds.len(), 1);
+        assert_eq!(summary.holds[0].id.as_str(), "t1_hld_00000000000000000000001");
+
+        let calendar = summary.calendar();
+        assert_eq!(calendar, vec![(date("20240320"), 2500), (date("20240331"), 1000)]);
+        assert!(summary.releases[1].projected);
+        assert_eq!(summary.unscheduled, 300);
+        assert_eq!(summary.total_reserved(), 3800);
+        assert_eq!(summary.released_by(date("20240325")), 2500);
+    }
+
+    #[test]
+    fn test_adjustment_validation() {
+        let today = date("20240315");
+        let r = reserve(json!({ "id": "t1_rsv_00000000000000000000001", "start": 20240401 }));
+
+        assert!(!ReserveAdjustment::default().validate(&r, today).is_valid());
+
+        let report = ReserveAdjustment::default()
+            .with_percent(12_000)
+            .with_release(ReleaseSchedule::Days, 0)
+            .with_finish(date("20240320"))
+            .validate(&r, today);
+        assert!(report.has_code(ValidationCode::OutOfRange));
+        assert_eq!(report.issues().len(), 3);
+
+        let ok = ReserveAdjustment::default()
+            .with_percent(500)
+            .with_release(ReleaseSchedule::Days, 90)
+            .with_finish(date("20241231"));
+        assert!(ok.validate(&r, today).is_valid());
+        assert_eq!(
+            ok.payload(),
+            json!({ "percent": 500, "release": "days", "releaseFactor": 90, "finish": 20241231 })
+        );
+    }
+}
@@ -0,0 +1,551 @@
+//! Batch close and settlement reporting.
+//!
+//! Card transactions are grouped into a [`Batch`] per merchant, which closes
+//! at its `closeTime` and is sent to the processor. This module answers "what
+//! settled yesterday, per merchant, by card brand" by gathering the batches
+//! for a date range with their transactions, and following each batch to:
+//!
+//! - the processor [`Settlement`] whose `ref` matches the batch's
+//!   `processingId` or `ref`
+//! - the [`Disbursement`]s that paid its transactions out, via the ledger
+//!   [`Entry`] created for each transaction
+//!
+//! Transactions are totalled by [`PaymentMethod`], [`TransactionType`] and
+//! [`TransactionStatus`], per batch, per merchant and overall, as net
+//! settlement: refunds and reversals count against sales. Batches still
+//! open past their expected close time (plus
+//! [`SettlementReportConfig::close_grace`]) are flagged as stuck.
+//!
+//! Requires the `financial` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use payrix::{PayrixClient, Environment};
+//! use payrix::workflows::settlement_report::{settlement_report, SettlementReportConfig};
+//! use chrono::{Duration, Local};
+//!
+//! # async fn example() -> payrix::Result<()> {
+//! let client = PayrixClient::new("api-key", Environment::Test)?;
+//! // Payrix timestamps are not UTC; this host runs in the platform's time zone.
+//! let now = Local::now().naive_local();
+//! let yesterday = now.date() - Duration::days(1);
+//!
+//! let config = SettlementReportConfig::default();
+//! let report = settlement_report(&client, yesterday, yesterday, None, &config, now).await?;
+//! for (merchant, totals) in report.by_merchant() {
+//!     println!("{:?}: {} transactions, {} cents", merchant, totals.count, totals.amount);
+//!     for brand in &totals.by_method {
+//!         println!("  {:?}: {} cents", brand.key, brand.amount);
+//!     }
+//! }
+//! for batch in report.stuck_batches() {
+//!     println!("Batch {} is still open", batch.batch.id);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::client::PayrixClient;
+use crate::entity::EntityType;
+use crate::error::Result;
+use crate::search::{make_payrix_date, SearchBuilder, SearchOperator};
+use crate::types::{
+    Batch, BatchStatus, Disbursement, Entry, PaymentMethod, PayrixId, Settlement,
+    TransactionExpanded, TransactionStatus, TransactionType,
+};
+
+/// IDs requested per `[in]` search.
+const ID_BATCH_SIZE: usize = 50;
+
+// =============================================================================
+// Section 1: Totals
+// =============================================================================
+
+/// Count and amount of the transactions sharing one key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupTotal<K> {
+    /// The grouping value (`None` when the transaction does not have one).
+    pub key: K,
+    /// Number of transactions.
+    pub count: usize,
+    /// Net of transaction `total`s in cents, refunds and reversals negative.
+    pub amount: i64,
+}
+
+/// Transaction totals, overall and broken down three ways.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettlementTotals {
+    /// Number of transactions.
+    pub count: usize,
+    /// Net settlement in cents: sales less refunds and reversals.
+    pub amount: i64,
+    /// By card brand or bank account type, from the expanded payment.
+    pub by_method: Vec<GroupTotal<Option<PaymentMethod>>>,
+    /// By transaction type.
+    pub by_type: Vec<GroupTotal<Option<TransactionType>>>,
+    /// By transaction status.
+    pub by_status: Vec<GroupTotal<Option<TransactionStatus>>>,
+}
+
+impl SettlementTotals {
+    /// Add one transaction, subtracting it if it is a refund or reversal.
+    pub fn add(&mut self, txn: &TransactionExpanded) {
+        let total = txn.total.unwrap_or(0);
+        let amount = match txn.txn_type {
+            Some(
+                TransactionType::CreditCardRefund
+                | TransactionType::ECheckRefund
+                | TransactionType::CreditCardReverseAuth,
+            ) => -total,
+            _ => total,
+        };
+        self.count += 1;
+        self.amount += amount;
+        add_to(&mut self.by_method, txn.payment.as_ref().and_then(|p| p.method), amount);
+        add_to(&mut self.by_type, txn.txn_type, amount);
+        add_to(&mut self.by_status, txn.status, amount);
+    }
+
+    /// Add every transaction counted in `other`.
+    pub fn merge(&mut self, other: &SettlementTotals) {
+        self.count += other.count;
+        self.amount += other.amount;
+        for group in &other.by_method {
+            merge_into(&mut self.by_method, group);
+        }
+        for group in &other.by_type {
+            merge_into(&mut self.by_type, group);
+        }
+        for group in &other.by_status {
+            merge_into(&mut self.by_status, group);
+        }
+    }
+
+    /// The total for one payment method, if any transactions used it.
+    pub fn method(&self, method: PaymentMethod) -> Option<&GroupTotal<Option<PaymentMethod>>> {
+        self.by_method.iter().find(|g| g.key == Some(method))
+    }
+}
+
+fn add_to<K: PartialEq>(groups: &mut Vec<GroupTotal<K>>, key: K, amount: i64) {
+    match groups.iter_mut().find(|g| g.key == key) {
+        Some(group) => {
+            group.count += 1;
+            group.amount += amount;
+        }
+        None => groups.push(GroupTotal { key, count: 1, amount }),
+    }
+}
+
+fn merge_into<K: PartialEq + Clone>(groups: &mut Vec<GroupTotal<K>>, other: &GroupTotal<K>) {
+    match groups.iter_mut().find(|g| g.key == other.key) {
+        Some(group) => {
+            group.count += other.count;
+            group.amount += other.amount;
+        }
+        None => groups.push(other.clone()),
+    }
+}
+
+// =============================================================================
+// Section 2: Report Types
+// =============================================================================
+
+/// Settings for building a [`SettlementReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettlementReportConfig {
+    /// How long a batch may stay open past its expected close time before it
+    /// is flagged as stuck.
+    pub close_grace: Duration,
+}
+
+impl Default for SettlementReportConfig {
+    fn default() -> Self {
+        Self {
+            close_grace: Duration::hours(1),
+        }
+    }
+}
+
+/// One batch with its transactions totalled and its settlement links.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchSettlement {
+    /// The batch.
+    pub batch: Batch,
+    /// Its transactions.
+    pub totals: SettlementTotals,
+    /// Settlements whose `ref` matches the batch.
+    pub settlements: Vec<PayrixId>,
+    /// Disbursements that paid out the batch's transactions.
+    pub disbursements: Vec<PayrixId>,
+    /// When the batch should have closed: its `closeTime`, or the end of its
+    /// `date` when no close time is set.
+    pub expected_close: Option<NaiveDateTime>,
+    /// Whether the batch is still open more than the grace period after
+    /// `expected_close`.
+    pub stuck: bool,
+}
+
+impl BatchSettlement {
+    /// Whether the batch has been matched to a processor settlement.
+    pub fn is_settled(&self) -> bool {
+        !self.settlements.is_empty()
+    }
+}
+
+/// Records to build a report from.
+#[derive(Debug, Clone, Default)]
+pub struct SettlementData {
+    /// Batches in the report.
+    pub batches: Vec<Batch>,
+    /// Their transactions, with `payment` expanded for brand totals.
+    pub transactions: Vec<TransactionExpanded>,
+    /// Ledger entries for those transactions, linking them to disbursements.
+    pub entries: Vec<Entry>,
+    /// Processor settlements to match against the batches.
+    pub settlements: Vec<Settlement>,
+    /// Disbursements referenced by the entries.
+    pub disbursements: Vec<Disbursement>,
+}
+
+/// Batches, totals and settlement links for a date range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SettlementReport {
+    /// First day covered.
+    pub from: NaiveDate,
+    /// Last day covered.
+    pub to: NaiveDate,
+    /// Every batch, in the order given.
+    pub batches: Vec<BatchSettlement>,
+    /// Totals across all batches.
+    pub totals: SettlementTotals,
+    /// Settlements matched to at least one batch.
+    pub settlements: Vec<Settlement>,
+    /// Disbursements that paid out the batches' transactions.
+    pub disbursements: Vec<Disbursement>,
+}
+
+impl SettlementReport {
+    /// Build a report from records already in hand, judging stuck batches as
+    /// of `now`.
+    ///
+    /// `now` is compared with batch `closeTime`s, so it must be in the same
+    /// time zone as Payrix timestamps rather than UTC.
+    ///
+    /// Transactions are assigned to batches by their `batch` field;
+    /// transactions for other batches are ignored.
+    pub fn new(
+        from: NaiveDate,
+        to: NaiveDate,
+        data: SettlementData,
+        config: &SettlementReportConfig,
+        now: NaiveDateTime,
+    ) -> Self {
+        let mut disbursements_by_txn: HashMap<&str, Vec<&PayrixId>> = HashMap::new();
+        for entry in &data.entries {
+            let (Some(txn), Some(disbursement)) = (entry.txn.as_ref(), entry.disbursement.as_ref()) else {
+                continue;
+            };
+            let ids = disbursements_by_txn.entry(txn.as_str()).or_default();
+            if !ids.contains(&disbursement) {
+                ids.push(disbursement);
+            }
+        }
+
+        let mut totals = SettlementTotals::default();
+        let mut batches = Vec::with_capacity(data.batches.len());
+        for batch in data.batches {
+            let mut batch_totals = SettlementTotals::default();
+            let mut disbursements: Vec<PayrixId> = Vec::new();
+            for txn in data.transactions.iter().filter(|t| t.batch.as_ref() == Some(&batch.id)) {
+                batch_totals.add(txn);
+                for id in disbursements_by_txn.get(txn.id.as_str()).into_iter().flatten() {
+                    if !disbursements.contains(id) {
+                        disbursements.push((*id).clone());
+                    }
+                }
+            }
+            totals.merge(&batch_totals);
+
+            let settlements = data
+                .settlements
+                .iter()
+                .filter(|s| s.reference.as_deref().is_some_and(|r| batch_refs(&batch).any(|b| b == r)))
+                .map(|s| s.id.clone())
+                .collect();
+            let expected_close = expected_close(&batch);
+            let stuck = batch.status == Some(BatchStatus::Open)
+                && expected_close.is_some_and(|close| now > close + config.close_grace);
+
+            batches.push(BatchSettlement {
+                batch,
+                totals: batch_totals,
+                settlements,
+                disbursements,
+                expected_close,
+                stuck,
+            });
+        }
+
+        let settlements = data
+            .settlements
+            .into_iter()
+            .filter(|s| batches.iter().any(|b| b.settlements.contains(&s.id)))
+            .collect();
+        let disbursements = data
+            .disbursements
+            .into_iter()
+            .filter(|d| batches.iter().any(|b| b.disbursements.contains(&d.id)))
+            .collect();
+
+        Self {
+            from,
+            to,
+            batches,
+            totals,
+            settlements,
+            disbursements,
+        }
+    }
+
+    /// Totals per merchant, in order of first appearance.
+    pub fn by_merchant(&self) -> Vec<(Option<&PayrixId>, SettlementTotals)> {
+        let mut merchants: Vec<(Option<&PayrixId>, SettlementTotals)> = Vec::new();
+        for batch in &self.batches {
+            let merchant = batch.batch.merchant.as_ref();
+            match merchants.iter_mut().find(|(m, _)| *m == merchant) {
+                Some((_, totals)) => totals.merge(&batch.totals),
+                None => merchants.push((merchant, batch.totals.clone())),
+            }
+        }
+        merchants
+    }
+
+    /// Batches still open past their expected close time.
+    pub fn stuck_batches(&self) -> impl Iterator<Item = &BatchSettlement> {
+        self.batches.iter().filter(|b| b.stuck)
+    }
+
+    /// Closed batches not yet matched to a settlement.
+    pub fn unsettled_batches(&self) -> impl Iterator<Item = &BatchSettlement> {
+        self.batches
+            .iter()
+            .filter(|b| b.batch.status != Some(BatchStatus::Open) && !b.is_settled())
+    }
+}
+
+/// The batch's processor references, for matching settlements.
+fn batch_refs(batch: &Batch) -> impl Iterator<Item = &str> {
+    [batch.processing_id.as_deref(), batch.reference.as_deref()]
+        .into_iter()
+        .flatten()
+        .filter(|r| !r.is_empty())
+}
+
+fn expected_close(batch: &Batch) -> Option<NaiveDateTime> {
+    batch
+        .close_time
+        .as_deref()
+        .and_then(|t| NaiveDateTime::parse_from_str(t, "%Y-%m-%d %H:%M:%S").ok())
+        .or_else(|| {
+            let date = NaiveDate::parse_from_str(batch.date.as_deref()?, "%Y-%m-%d").ok()?;
+            Some(date.and_time(NaiveTime::from_hms_opt(23, 59, 59)?))
+        })
+}
+
+// =============================================================================
+// Section 3: Fetching
+// =============================================================================
+
+/// Build a settlement report for batches dated between `from` and `to`
+/// (inclusive), optionally for a single merchant.
+///
+/// Batches are selected by their `date`, the day they belong to, rather than
+/// when the record was created. `now` is used to flag stuck batches; see
+/// [`SettlementReport::new`].
+///
+/// Fetches the batches, their transactions with `payment` expanded, the
+/// transactions' ledger entries and disbursements, and settlements whose
+/// `ref` matches a batch.
+///
+/// # Errors
+///
+/// Returns an error if an API call fails.
+pub async fn settlement_report(
+    client: &PayrixClient,
+    from: NaiveDate,
+    to: NaiveDate,
+    merchant_id: Option<&str>,
+    config: &SettlementReportConfig,
+    now: NaiveDateTime,
+) -> Result<SettlementReport> {
+    let mut search = SearchBuilder::new()
+        .field_with_op("date", &make_payrix_date(&(from - Duration::days(1))), SearchOperator::Greater)
+        .field_with_op("date", &make_payrix_date(&(to + Duration::days(1))), SearchOperator::Less);
+    if let Some(merchant_id) = merchant_id {
+        search = search.field("merchant", merchant_id);
+    }
+    let batches: Vec<Batch> = client.search(EntityType::Batches, &search.build()).await?;
+
+    let batch_ids: Vec<&str> = batches.iter().map(|b| b.id.as_str()).collect();
+    let mut transactions: Vec<TransactionExpanded> = Vec::new();
+    for chunk in batch_ids.chunks(ID_BATCH_SIZE) {
+        let search = SearchBuilder::new()
+            .field_multi("batch", chunk, SearchOperator::In)
+            .build();
+        let mut params = HashMap::new();
+        params.insert("expand[payment][]".to_string(), String::new());
+        transactions.extend(
+            client
+                .get_all_with_params::<TransactionExpanded>(EntityType::Txns, params, Some(&search))
+                .await?,
+        );
+    }
+
+    let txn_ids: Vec<&str> = transactions.iter().map(|t| t.id.as_str()).collect();
+    let mut entries: Vec<Entry> = Vec::new();
+    for chunk in txn_ids.chunks(ID_BATCH_SIZE) {
+        let search = SearchBuilder::new()
+            .field_multi("txn", chunk, SearchOperator::In)
+            .build();
+        entries.extend(client.search::<Entry>(EntityType::Entries, &search).await?);
+    }
+
+    let mut disbursement_ids: Vec<&str> = entries
+        .iter()
+        .filter_map(|e| e.disbursement.as_ref().map(PayrixId::as_str))
+        .collect();
+    disbursement_ids.sort_unstable();
+    disbursement_ids.dedup();
+    let mut disbursements: Vec<Disbursement> = Vec::new();
+    for chunk in disbursement_ids.chunks(ID_BATCH_SIZE) {
+        let search = SearchBuilder::new()
+            .field_multi("id", chunk, SearchOperator::In)
+            .build();
+        disbursements.extend(client.search::<Disbursement>(EntityType::Disbursements, &search).await?);
+    }
+
+    let refs: Vec<&str> = batches.iter().flat_map(batch_refs).collect();
+    let mut settlements: Vec<Settlement> = Vec::new();
+    for chunk in refs.chunks(ID_BATCH_SIZE) {
+        let search = SearchBuilder::new()
+            .field_multi("ref", chunk, SearchOperator::In)
+            .build();
+        settlements.extend(client.search::<Settlement>(EntityType::Settlements, &search).await?);
+    }
+
+    let data = SettlementData {
+        batches,
+        transactions,
+        entries,
+        settlements,
+        disbursements,
+    };
+    Ok(SettlementReport::new(from, to, data, config, now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const BATCH_A: &str = "t1_bth_00000000000000000000001";
+    const BATCH_B: &str = "t1_bth_00000000000000000000002";
+    const MERCHANT_1: &str = "t1_mer_00000000000000000000001";
+    const MERCHANT_2: &str = "t1_mer_00000000000000000000002";
+
+    fn txn(id: u32, batch: &str, method: PaymentMethod, txn_type: TransactionType, total: i64) -> TransactionExpanded {
+        serde_json::from_value(json!({
+            "id": format!("t1_txn_{:023}", id),
+            "batch": batch,
+            "type": txn_type as i32,
+            "status": TransactionStatus::Settled as i32,
+            "total": total,
+            "payment": { "method": method as i32 },
+        }))
+        .unwrap()
+    }
+
+    fn data() -> SettlementData {
+        SettlementData {
+            batches: serde_json::from_value(json!([
+                { "id": BATCH_A, "merchant": MERCHANT_1, "status": "closed", "processingId": "PROC-1",
+                  "closeTime": "2024-03-10 23:00:00" },
+                { "id": BATCH_B, "merchant": MERCHANT_2, "status": "open", "date": "2024-03-10",
+                  "closeTime": "2024-03-10 23:00:00" },
+            ]))
+            .unwrap(),
+            transactions: vec![
+                txn(1, BATCH_A, PaymentMethod::Visa, TransactionType::CreditCardSale, 10_000),
+                txn(2, BATCH_A, PaymentMethod::Visa, TransactionType::CreditCardSale, 5_000),
+                txn(3, BATCH_A, PaymentMethod::Mastercard, TransactionType::CreditCardRefund, 2_000),
+                txn(4, BATCH_B, PaymentMethod::Visa, TransactionType::CreditCardSale, 1_000),
+            ],
+            entries: serde_json::from_value(json!([
+                { "id": "t1_ent_00000000000000000000001", "txn": format!("t1_txn_{:023}", 1),
+                  "disbursement": "t1_dbm_00000000000000000000001" },
+                { "id": "t1_ent_00000000000000000000002", "txn": format!("t1_txn_{:023}", 2),
+                  "disbursement": "t1_dbm_00000000000000000000001" },
+            ]))
+            .unwrap(),
+            settlements: serde_json::from_value(json!([
+                { "id": "t1_stl_00000000000000000000001", "ref": "PROC-1", "amount": 13000 },
+                { "id": "t1_stl_00000000000000000000002", "ref": "OTHER" },
+            ]))
+            .unwrap(),
+            disbursements: serde_json::from_value(json!([
+                { "id": "t1_dbm_00000000000000000000001", "amount": 15000 },
+            ]))
+            .unwrap(),
+        }
+    }
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_totals_and_links() {
+        let day = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let report = SettlementReport::new(day, day, data(), &SettlementReportConfig::default(), at("2024-03-10 23:30:00"));
+
+        assert_eq!(report.totals.count, 4);
+        assert_eq!(report.totals.amount, 14_000);
+        let visa = report.totals.method(PaymentMethod::Visa).unwrap();
+        assert_eq!((visa.count, visa.amount), (3, 16_000));
+        let mastercard = report.totals.method(PaymentMethod::Mastercard).unwrap();
+        assert_eq!((mastercard.count, mastercard.amount), (1, -2_000));
+
+        let batch = &report.batches[0];
+        assert_eq!(batch.totals.by_type.len(), 2);
+        assert_eq!(batch.settlements[0].as_str(), "t1_stl_00000000000000000000001");
+        assert_eq!(batch.disbursements[0].as_str(), "t1_dbm_00000000000000000000001");
+        assert_eq!(report.settlements.len(), 1);
+        assert_eq!(report.disbursements.len(), 1);
+
+        let merchants = report.by_merchant();
+        assert_eq!(merchants.len(), 2);
+        assert_eq!(merchants[0].0.map(PayrixId::as_str), Some(MERCHANT_1));
+        // Net of the refund, matching the processor settlement
+        assert_eq!(merchants[0].1.amount, 13_000);
+    }
+
+    #[test]
+    fn test_stuck_batches() {
+        let day = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let config = SettlementReportConfig::default();
+
+        let report = SettlementReport::new(day, day, data(), &config, at("2024-03-10 23:30:00"));
+        assert_eq!(report.stuck_batches().count(), 0);
+
+        let report = SettlementReport::new(day, day, data(), &config, at("2024-03-11 00:30:00"));
+        let stuck: Vec<&str> = report.stuck_batches().map(|b| b.batch.id.as_str()).collect();
+        assert_eq!(stuck, vec![BATCH_B]);
+        assert_eq!(report.unsettled_batches().count(), 0);
+    }
+}
@@ -77,6 +77,9 @@ use crate::types::{
     Token, Transaction, TransactionStatus, TransactionType,
 };
 
+use super::billing_projection::billing_date;
+use super::payments::remaining_refundable;
+
 // ============================================================================
 // Error Types
 // ============================================================================
@@ -1463,6 +1466,13 @@ pub async fn get_upcoming_payments(
 /// Retry a failed subscription payment.
 ///
 /// Creates a new Sale transaction for the subscription with an optional amount override.
+/// The payment token is the one linked to the subscription through
+/// `subscriptionTokens` (see [`get_subscription_token_id`]).
+///
+/// For scheduled retries with failure limits, see the
+/// [`dunning`](super::dunning) module. If the returned transaction failed,
+/// [`DeclineInfo::fetch`](super::declines::DeclineInfo::fetch) says why and
+/// whether another retry can succeed.
 ///
 /// # Arguments
 ///
@@ -1501,37 +1511,42 @@ pub async fn retry_failed_payment(
     let plan = plan.ok_or_else(|| SubscriptionError::PlanNotFound(plan_id.to_string()))?;
 
     let amount = amount_override.unwrap_or_else(|| plan.amount.unwrap_or(0));
+    let token_id = get_subscription_token_id(client, subscription_id).await?;
 
-    // Get a token for this subscription
-    // Note: This is simplified - we'd ideally query subscription_tokens
-    let payment_history = payments_to_date(client, subscription_id).await?;
-
-    // Find a token from previous transactions
-    let token_id = payment_history
-        .transactions
-        .iter()
-        .find_map(|txn| txn.token.as_ref())
-        .ok_or_else(|| {
-            SubscriptionError::CalculationError(
-                "No payment token found for subscription".to_string(),
-            )
-        })?;
+    let order = format!("RETRY-{}", subscription.id.as_str());
+    charge_subscription(client, &subscription, &plan, &token_id, amount, &order).await
+}
 
-    // Get merchant from plan
+/// Create a one-off sale for a subscription on its linked token.
+///
+/// Used for retries and dunning. The sale is tagged with `order`, and is an
+/// eCheck sale when the token is a bank account.
+pub(crate) async fn charge_subscription(
+    client: &PayrixClient,
+    subscription: &Subscription,
+    plan: &Plan,
+    token_id: &str,
+    amount: i64,
+    order: &str,
+) -> SubscriptionResult<Transaction> {
     let merchant_id = plan
         .merchant
         .as_ref()
         .ok_or_else(|| SubscriptionError::CalculationError("Plan has no merchant".to_string()))?;
 
-    // Create retry transaction
+    let token: Token = client
+        .get_one(EntityType::Tokens, token_id)
+        .await?
+        .ok_or_else(|| SubscriptionError::TokenNotFound(token_id.to_string()))?;
+
     let txn_json = json!({
         "merchant": merchant_id.as_str(),
-        "type": TransactionType::CreditCardSale as i32,
-        "token": token_id.as_str(),
+        "type": sale_type(&token) as i32,
+        "token": token_id,
         "total": amount,
-        "subscription": subscription_id,
+        "subscription": subscription.id.as_str(),
         "origin": subscription.origin.map(|o| o as i32).unwrap_or(2),
-        "order": format!("RETRY-{}", subscription_id),
+        "order": order,
         "description": "Subscription payment retry"
     });
 
@@ -1540,6 +1555,572 @@ pub async fn retry_failed_payment(
     Ok(transaction)
 }
 
+// ============================================================================
+// Plan Changes
+// ============================================================================
+
+/// How price differences are handled when changing plans mid-cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProrationMode {
+    /// No proration: the customer keeps the current plan until the end of the
+    /// paid period and the new plan bills from the next billing date.
+    #[default]
+    None,
+
+    /// Charge or refund the prorated difference immediately.
+    ///
+    /// An upgrade charges the customer's token for the difference; a
+    /// downgrade refunds the difference against the most recent payment, up
+    /// to what is left of it after earlier refunds.
+    Immediate,
+}
+
+/// Prorated amounts for a plan change.
+///
+/// All amounts are in cents and exclude subscription tax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProrationBreakdown {
+    /// Date the change is calculated for.
+    pub change_date: NaiveDate,
+
+    /// First day of the current billing period.
+    pub period_start: NaiveDate,
+
+    /// Next billing date, which ends the current period.
+    pub period_end: NaiveDate,
+
+    /// Days in the current billing period.
+    pub days_in_period: i64,
+
+    /// Days from the change date (inclusive) to the end of the period.
+    pub days_remaining: i64,
+
+    /// Current plan amount per period.
+    pub current_amount: i64,
+
+    /// New plan amount per period.
+    pub new_amount: i64,
+
+    /// Value of the unused part of the current period on the current plan.
+    pub unused_credit: i64,
+
+    /// Cost of the rest of the period on the new plan.
+    pub new_plan_charge: i64,
+
+    /// `new_plan_charge - unused_credit`: positive is owed by the customer,
+    /// negative is owed to the customer.
+    pub net_amount: i64,
+}
+
+/// Result of changing a subscription's plan.
+#[derive(Debug, Clone)]
+pub struct PlanChangeResult {
+    /// The previous subscription, now cancelled.
+    pub previous_subscription: Subscription,
+
+    /// The new subscription on the new plan.
+    pub subscription: Subscription,
+
+    /// The previous plan.
+    pub previous_plan: Plan,
+
+    /// The new plan.
+    pub new_plan: Plan,
+
+    /// Proration mode used.
+    pub mode: ProrationMode,
+
+    /// Prorated amounts, calculated for every mode so the change can be audited.
+    pub breakdown: ProrationBreakdown,
+
+    /// The charge or refund created for [`ProrationMode::Immediate`], if any.
+    pub adjustment: Option<Transaction>,
+}
+
+/// Calculate the prorated amounts for moving a subscription to a new plan.
+///
+/// The current period is the billing cycle containing `change_date`, derived
+/// from the subscription start date and the current plan schedule. The unused
+/// part of the period is credited at the current plan's rate and charged at the
+/// new plan's rate, where the new rate is the new plan's amount spread over its
+/// own period length starting at `period_start`. Amounts are rounded to the
+/// nearest cent.
+///
+/// A subscription that has not started yet has nothing to prorate; its
+/// breakdown covers its first period with zero amounts.
+///
+/// # Errors
+///
+/// Returns an error if the subscription has no valid start date, or either
+/// plan has no amount or an invalid schedule factor.
+pub fn calculate_proration(
+    subscription: &Subscription,
+    current_plan: &Plan,
+    new_plan: &Plan,
+    change_date: NaiveDate,
+) -> SubscriptionResult<ProrationBreakdown> {
+    let start = subscription
+        .start
+        .and_then(|d| parse_payrix_date(&d.to_string()))
+        .ok_or_else(|| SubscriptionError::InvalidDate("Subscription has no start date".to_string()))?;
+
+    let (schedule, factor, current_amount) = plan_terms(current_plan)?;
+    let (new_schedule, new_factor, new_amount) = plan_terms(new_plan)?;
+
+    let period_date = |anchor, schedule, factor, cycle| {
+        billing_date(anchor, schedule, factor, cycle).ok_or_else(|| {
+            SubscriptionError::CalculationError("Billing date out of range".to_string())
+        })
+    };
+
+    // Find the billing cycle containing the change date
+    let mut cycle = 0;
+    let mut period_start = start;
+    let mut period_end = period_date(start, schedule, factor, 1)?;
+    while period_end <= change_date {
+        cycle += 1;
+        period_start = period_end;
+        period_end = period_date(start, schedule, factor, cycle + 1)?;
+    }
+
+    let days_in_period = (period_end - period_start).num_days();
+    if change_date < start {
+        return Ok(ProrationBreakdown {
+            change_date,
+            period_start,
+            period_end,
+            days_in_period,
+            days_remaining: days_in_period,
+            current_amount,
+            new_amount,
+            unused_credit: 0,
+            new_plan_charge: 0,
+            net_amount: 0,
+        });
+    }
+
+    let days_remaining = (period_end - change_date).num_days();
+    let new_period_days =
+        (period_date(period_start, new_schedule, new_factor, 1)? - period_start).num_days();
+
+    let unused_credit = prorate(current_amount, days_remaining, days_in_period);
+    let new_plan_charge = prorate(new_amount, days_remaining, new_period_days);
+
+    Ok(ProrationBreakdown {
+        change_date,
+        period_start,
+        period_end,
+        days_in_period,
+        days_remaining,
+        current_amount,
+        new_amount,
+        unused_credit,
+        new_plan_charge,
+        net_amount: new_plan_charge - unused_credit,
+    })
+}
+
+/// Move a subscription to a different plan.
+///
+/// Payrix does not allow the plan of an existing subscription to be changed,
+/// so the change is made by creating a new subscription on the new plan,
+/// linking it to the same payment token, and cancelling the old one. The new
+/// subscription starts on the next billing date and copies the old one's
+/// finish date, tax, descriptor, description and origin.
+///
+/// With [`ProrationMode::Immediate`] the prorated difference is charged or
+/// refunded only once the swap has succeeded. If the swap fails part way, or
+/// the adjustment fails or is declined, the replacement subscription is
+/// deactivated and the old one reactivated, so the customer stays on their
+/// current plan.
+///
+/// The adjustment's order is derived from the subscription, the new plan and
+/// `today`, and an existing transaction with that order is reused instead of
+/// creating another one. Retrying a change that failed after the adjustment
+/// was sent therefore never charges or refunds twice.
+///
+/// # Arguments
+///
+/// * `client` - The Payrix client
+/// * `subscription_id` - The subscription to change
+/// * `new_plan_id` - The plan to move to
+/// * `mode` - How to handle the price difference
+/// * `today` - Date of the change, used for proration
+///
+/// # Errors
+///
+/// - [`SubscriptionError::InvalidState`] if the subscription is cancelled, is
+///   already on the new plan, a refund is due but there is no payment to
+///   refund against or it has been fully refunded, or the adjustment is
+///   declined
+/// - [`SubscriptionError::TokenNotFound`] if no payment token is linked to the
+///   subscription
+pub async fn change_plan(
+    client: &PayrixClient,
+    subscription_id: &str,
+    new_plan_id: &str,
+    mode: ProrationMode,
+    today: NaiveDate,
+) -> SubscriptionResult<PlanChangeResult> {
+    let subscription: Subscription = client
+        .get_one(EntityType::Subscriptions, subscription_id)
+        .await?
+        .ok_or_else(|| SubscriptionError::SubscriptionNotFound(subscription_id.to_string()))?;
+
+    if subscription.inactive {
+        return Err(SubscriptionError::InvalidState(
+            "Cannot change the plan of a cancelled subscription".to_string(),
+        ));
+    }
+
+    let current_plan_id = subscription
+        .plan
+        .as_ref()
+        .ok_or_else(|| SubscriptionError::CalculationError("Subscription has no plan".to_string()))?;
+    if current_plan_id.as_str() == new_plan_id {
+        return Err(SubscriptionError::InvalidState(
+            "Subscription is already on this plan".to_string(),
+        ));
+    }
+
+    let previous_plan: Plan = client
+        .get_one(EntityType::Plans, current_plan_id.as_str())
+        .await?
+        .ok_or_else(|| SubscriptionError::PlanNotFound(current_plan_id.to_string()))?;
+    let new_plan: Plan = client
+        .get_one(EntityType::Plans, new_plan_id)
+        .await?
+        .ok_or_else(|| SubscriptionError::PlanNotFound(new_plan_id.to_string()))?;
+
+    let token_id = get_subscription_token_id(client, subscription_id).await?;
+
+    let breakdown = calculate_proration(&subscription, &previous_plan, &new_plan, today)?;
+
+    // 1. Prepare the adjustment up front so a missing merchant or refund
+    // target fails before anything is changed
+    let order = format!(
+        "PLANCHG-{}-{}-{}",
+        subscription_id,
+        new_plan_id,
+        today.format("%Y%m%d")
+    );
+    let adjustment_json = if mode == ProrationMode::Immediate && breakdown.net_amount != 0 {
+        Some(
+            plan_change_adjustment(client, &subscription, &previous_plan, &token_id, &breakdown, &order)
+                .await?,
+        )
+    } else {
+        None
+    };
+
+    // 2. Create the replacement subscription starting at the next billing date
+    // (or the original start date if the subscription hasn't started yet)
+    let next_date = if today < breakdown.period_start {
+        breakdown.period_start
+    } else {
+        breakdown.period_end
+    };
+    let next_start = next_date.year() * 10000 + next_date.month() as i32 * 100 + next_date.day() as i32;
+    let mut sub_json = json!({
+        "plan": new_plan.id.as_str(),
+        "start": next_start,
+        "origin": subscription.origin.map(|o| o as i32).unwrap_or(2)
+    });
+    if let Some(finish) = subscription.finish {
+        sub_json["finish"] = json!(finish);
+    }
+    if let Some(tax) = subscription.tax {
+        sub_json["tax"] = json!(tax);
+    }
+    if let Some(ref descriptor) = subscription.descriptor {
+        sub_json["descriptor"] = json!(descriptor);
+    }
+    if let Some(ref txn_desc) = subscription.txn_description {
+        sub_json["txnDescription"] = json!(txn_desc);
+    }
+    let new_subscription: Subscription =
+        client.create(EntityType::Subscriptions, &sub_json).await?;
+    let new_id = new_subscription.id.as_str();
+
+    // 3. Link the existing payment token
+    let link: Result<serde_json::Value, _> = client
+        .create(
+            EntityType::SubscriptionTokens,
+            &json!({
+                "subscription": new_id,
+                "token": token_id
+            }),
+        )
+        .await;
+    if let Err(e) = link {
+        undo_plan_change(client, subscription_id, new_id, false).await;
+        return Err(e.into());
+    }
+
+    // 4. Cancel the old subscription
+    let cancelled: Result<Subscription, _> = client
+        .update(
+            EntityType::Subscriptions,
+            subscription_id,
+            &json!({"inactive": 1}),
+        )
+        .await;
+    let previous_subscription = match cancelled {
+        Ok(previous) => previous,
+        Err(e) => {
+            undo_plan_change(client, subscription_id, new_id, false).await;
+            return Err(e.into());
+        }
+    };
+
+    // 5. Settle the difference, reusing an adjustment from an earlier attempt
+    let adjustment = match adjustment_json {
+        Some(txn_json) => {
+            let settled = settle_plan_change(client, &order, &txn_json).await;
+            match settled {
+                Ok(txn) if txn.status != Some(TransactionStatus::Failed) => Some(txn),
+                Ok(txn) => {
+                    undo_plan_change(client, subscription_id, new_id, true).await;
+                    return Err(SubscriptionError::InvalidState(format!(
+                        "Plan change adjustment {} was declined",
+                        txn.id
+                    )));
+                }
+                Err(e) => {
+                    undo_plan_change(client, subscription_id, new_id, true).await;
+                    return Err(e);
+                }
+            }
+        }
+        None => None,
+    };
+
+    Ok(PlanChangeResult {
+        previous_subscription,
+        subscription: new_subscription,
+        previous_plan,
+        new_plan,
+        mode,
+        breakdown,
+        adjustment,
+    })
+}
+
+/// Build the charge or refund for a plan change's prorated difference.
+async fn plan_change_adjustment(
+    client: &PayrixClient,
+    subscription: &Subscription,
+    plan: &Plan,
+    token_id: &str,
+    breakdown: &ProrationBreakdown,
+    order: &str,
+) -> SubscriptionResult<serde_json::Value> {
+    let merchant_id = plan
+        .merchant
+        .as_ref()
+        .ok_or_else(|| SubscriptionError::CalculationError("Plan has no merchant".to_string()))?;
+
+    if breakdown.net_amount > 0 {
+        let token: Token = client
+            .get_one(EntityType::Tokens, token_id)
+            .await?
+            .ok_or_else(|| SubscriptionError::TokenNotFound(token_id.to_string()))?;
+        return Ok(json!({
+            "merchant": merchant_id.as_str(),
+            "type": sale_type(&token) as i32,
+            "token": token_id,
+            "total": breakdown.net_amount,
+            "origin": subscription.origin.map(|o| o as i32).unwrap_or(2),
+            "order": order,
+            "description": "Plan change proration"
+        }));
+    }
+
+    let history = payments_to_date(client, subscription.id.as_str()).await?;
+    let original = history
+        .transactions
+        .iter()
+        .filter(|t| {
+            matches!(
+                t.txn_type,
+                TransactionType::CreditCardSale | TransactionType::ECheckSale
+            ) && matches!(
+                t.status,
+                Some(TransactionStatus::Captured) | Some(TransactionStatus::Settled)
+            )
+        })
+        .max_by(|a, b| a.created.cmp(&b.created))
+        .ok_or_else(|| {
+            SubscriptionError::InvalidState(
+                "No settled payment to refund the plan change credit against".to_string(),
+            )
+        })?;
+    let refund_type = if original.txn_type == TransactionType::ECheckSale {
+        TransactionType::ECheckRefund
+    } else {
+        TransactionType::CreditCardRefund
+    };
+    let search = SearchBuilder::new()
+        .field("fortxn", original.id.as_str())
+        .build();
+    let linked: Vec<Transaction> = client.search(EntityType::Txns, &search).await?;
+    let refundable = remaining_refundable(original, &linked);
+    if refundable == 0 {
+        return Err(SubscriptionError::InvalidState(format!(
+            "Payment {} has already been fully refunded",
+            original.id
+        )));
+    }
+    Ok(json!({
+        "merchant": merchant_id.as_str(),
+        "type": refund_type as i32,
+        "fortxn": original.id.as_str(),
+        "total": (-breakdown.net_amount).min(refundable),
+        "order": order,
+        "description": "Plan change proration credit"
+    }))
+}
+
+/// Send a plan change adjustment unless one with the same order already exists.
+async fn settle_plan_change(
+    client: &PayrixClient,
+    order: &str,
+    txn_json: &serde_json::Value,
+) -> SubscriptionResult<Transaction> {
+    if let Some(txn) = find_order_txn(client, order).await? {
+        return Ok(txn);
+    }
+
+    Ok(client.create(EntityType::Txns, txn_json).await?)
+}
+
+/// A transaction already made with `order` that did not fail, if any.
+///
+/// Looked up before charging so an operation retried after a lost response
+/// reuses the charge instead of making another.
+pub(crate) async fn find_order_txn(
+    client: &PayrixClient,
+    order: &str,
+) -> SubscriptionResult<Option<Transaction>> {
+    let search = SearchBuilder::new().field("order", order).build();
+    let existing: Vec<Transaction> = client.search(EntityType::Txns, &search).await?;
+    Ok(existing
+        .into_iter()
+        .find(|t| t.status != Some(TransactionStatus::Failed)))
+}
+
+/// Roll back a partly applied plan change.
+///
+/// Deactivates the replacement subscription and, if the old one was already
+/// cancelled, reactivates it. Failures are logged rather than returned so the
+/// error that caused the rollback reaches the caller.
+async fn undo_plan_change(
+    client: &PayrixClient,
+    subscription_id: &str,
+    new_subscription_id: &str,
+    reactivate: bool,
+) {
+    let deactivated: Result<Subscription, _> = client
+        .update(
+            EntityType::Subscriptions,
+            new_subscription_id,
+            &json!({"inactive": 1}),
+        )
+        .await;
+    if let Err(ref e) = deactivated {
+        tracing::warn!(
+            subscription_id = %new_subscription_id,
+            error = %e,
+            "Failed to deactivate replacement subscription after a failed plan change"
+        );
+    }
+
+    if reactivate {
+        let reactivated: Result<Subscription, _> = client
+            .update(
+                EntityType::Subscriptions,
+                subscription_id,
+                &json!({"inactive": 0}),
+            )
+            .await;
+        if let Err(ref e) = reactivated {
+            tracing::warn!(
+                subscription_id = %subscription_id,
+                error = %e,
+                "Failed to reactivate subscription after a failed plan change"
+            );
+        }
+    }
+}
+
+/// Sale type for charging `token`: an eCheck sale for bank accounts, a card
+/// sale otherwise.
+fn sale_type(token: &Token) -> TransactionType {
+    if token.payment.is_some_and(|m| m.is_bank()) {
+        TransactionType::ECheckSale
+    } else {
+        TransactionType::CreditCardSale
+    }
+}
+
+/// Find the payment token linked to a subscription.
+///
+/// Looks up the subscription's `subscriptionTokens` links and returns the
+/// token of the most recently created active link.
+///
+/// # Errors
+///
+/// Returns [`SubscriptionError::TokenNotFound`] if no active link exists.
+pub async fn get_subscription_token_id(
+    client: &PayrixClient,
+    subscription_id: &str,
+) -> SubscriptionResult<String> {
+    let search = SearchBuilder::new()
+        .field("subscription", subscription_id)
+        .build();
+
+    let links: Vec<serde_json::Value> = client
+        .search(EntityType::SubscriptionTokens, &search)
+        .await?;
+
+    links
+        .iter()
+        .filter(|link| link.get("inactive").and_then(|v| v.as_i64()).unwrap_or(0) == 0)
+        .max_by(|a, b| {
+            let created = |v: &serde_json::Value| v.get("created").and_then(|c| c.as_str()).map(String::from);
+            created(a).cmp(&created(b))
+        })
+        .and_then(|link| link.get("token").and_then(|t| t.as_str()))
+        .map(String::from)
+        .ok_or_else(|| {
+            SubscriptionError::TokenNotFound(format!("no token linked to subscription {}", subscription_id))
+        })
+}
+
+/// Schedule, factor and amount of a plan, with defaults applied.
+fn plan_terms(plan: &Plan) -> SubscriptionResult<(PlanSchedule, u32, i64)> {
+    let amount = plan
+        .amount
+        .ok_or_else(|| SubscriptionError::CalculationError(format!("Plan {} has no amount", plan.id)))?;
+    let factor = plan.schedule_factor.unwrap_or(1);
+    if factor <= 0 {
+        return Err(SubscriptionError::CalculationError(format!(
+            "Invalid schedule factor: {}",
+            factor
+        )));
+    }
+    Ok((plan.schedule.unwrap_or(PlanSchedule::Monthly), factor as u32, amount))
+}
+
+/// `amount * days / period_days`, rounded to the nearest cent.
+fn prorate(amount: i64, days: i64, period_days: i64) -> i64 {
+    if period_days <= 0 {
+        return 0;
+    }
+    let scaled = amount as i128 * days as i128;
+    ((scaled * 2 + period_days as i128) / (period_days as i128 * 2)) as i64
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -1839,4 +2420,95 @@ mod tests {
         let next = calculate_next_from_today(today, PlanSchedule::Weekly, 2);
         assert_eq!(next, NaiveDate::from_ymd_opt(2024, 6, 29).unwrap());
     }
+
+    fn proration_plan(schedule: PlanSchedule, amount: i64) -> Plan {
+        serde_json::from_value(json!({
+            "id": "t1_pln_12345678901234567890123",
+            "schedule": schedule as i32,
+            "scheduleFactor": 1,
+            "amount": amount,
+        }))
+        .unwrap()
+    }
+
+    fn proration_subscription(start: i32) -> Subscription {
+        serde_json::from_value(json!({
+            "id": "t1_sbn_12345678901234567890123",
+            "start": start,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn proration_upgrade_mid_cycle() {
+        // 30-day period (June), changed with 15 days remaining
+        let sub = proration_subscription(20240501);
+        let current = proration_plan(PlanSchedule::Monthly, 3000);
+        let new = proration_plan(PlanSchedule::Monthly, 6000);
+        let today = NaiveDate::from_ymd_opt(2024, 6, 16).unwrap();
+
+        let breakdown = calculate_proration(&sub, &current, &new, today).unwrap();
+
+        assert_eq!(breakdown.period_start, NaiveDate::from_ymd_opt(2024, 6, 1).unwrap());
+        assert_eq!(breakdown.period_end, NaiveDate::from_ymd_opt(2024, 7, 1).unwrap());
+        assert_eq!(breakdown.days_in_period, 30);
+        assert_eq!(breakdown.days_remaining, 15);
+        assert_eq!(breakdown.unused_credit, 1500);
+        assert_eq!(breakdown.new_plan_charge, 3000);
+        assert_eq!(breakdown.net_amount, 1500);
+    }
+
+    #[test]
+    fn proration_downgrade_is_negative() {
+        let sub = proration_subscription(20240501);
+        let current = proration_plan(PlanSchedule::Monthly, 6000);
+        let new = proration_plan(PlanSchedule::Monthly, 3000);
+        let today = NaiveDate::from_ymd_opt(2024, 6, 16).unwrap();
+
+        let breakdown = calculate_proration(&sub, &current, &new, today).unwrap();
+        assert_eq!(breakdown.net_amount, -1500);
+    }
+
+    #[test]
+    fn proration_on_billing_date_covers_full_period() {
+        let sub = proration_subscription(20240501);
+        let current = proration_plan(PlanSchedule::Monthly, 3100);
+        let new = proration_plan(PlanSchedule::Monthly, 6200);
+        let today = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+
+        let breakdown = calculate_proration(&sub, &current, &new, today).unwrap();
+        assert_eq!(breakdown.days_remaining, 31);
+        assert_eq!(breakdown.unused_credit, 3100);
+        assert_eq!(breakdown.new_plan_charge, 6200);
+    }
+
+    #[test]
+    fn proration_across_schedules_rounds_to_cent() {
+        // Monthly -> annual: the rest of the month at the annual daily rate
+        let sub = proration_subscription(20240101);
+        let current = proration_plan(PlanSchedule::Monthly, 1000);
+        let new = proration_plan(PlanSchedule::Annually, 10000);
+        let today = NaiveDate::from_ymd_opt(2024, 3, 21).unwrap();
+
+        let breakdown = calculate_proration(&sub, &current, &new, today).unwrap();
+        assert_eq!(breakdown.days_in_period, 31);
+        assert_eq!(breakdown.days_remaining, 11);
+        // 1000 * 11 / 31 = 354.8
+        assert_eq!(breakdown.unused_credit, 355);
+        // 10000 * 11 / 365 (Mar 1 2024 - Mar 1 2025) = 301.4
+        assert_eq!(breakdown.new_plan_charge, 301);
+        assert_eq!(breakdown.net_amount, -54);
+    }
+
+    #[test]
+    fn proration_before_start_is_zero() {
+        let sub = proration_subscription(20240701);
+        let current = proration_plan(PlanSchedule::Monthly, 3000);
+        let new = proration_plan(PlanSchedule::Monthly, 6000);
+        let today = NaiveDate::from_ymd_opt(2024, 6, 16).unwrap();
+
+        let breakdown = calculate_proration(&sub, &current, &new, today).unwrap();
+        assert_eq!(breakdown.period_end, NaiveDate::from_ymd_opt(2024, 8, 1).unwrap());
+        assert_eq!(breakdown.net_amount, 0);
+    }
 }
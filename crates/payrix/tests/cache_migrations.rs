@@ -0,0 +1,225 @@
+//! Cache schema migration tests.
+//!
+//! These run against a real PostgreSQL database, each test in its own
+//! throwaway schema:
+//!
+//! ```bash
+//! TEST_CACHE_DATABASE_URL=postgres://localhost/payrix_test \
+//!     cargo test --features cache --test cache_migrations -- --ignored
+//! ```
+
+#![cfg(feature = "cache")]
+
+use payrix::cache::{ensure_schema, schema_version, SCHEMA_VERSION};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use std::env;
+
+/// Connect with `search_path` pointed at a fresh schema.
+async fn isolated_pool(name: &str) -> PgPool {
+    let url = env::var("TEST_CACHE_DATABASE_URL").expect("TEST_CACHE_DATABASE_URL must be set");
+    let schema = format!("payrix_{}_{}", name, std::process::id());
+
+    let admin = PgPool::connect(&url).await.unwrap();
+    sqlx::query(&format!("DROP SCHEMA IF EXISTS {schema} CASCADE"))
+        .execute(&admin)
+        .await
+        .unwrap();
+    sqlx::query(&format!("CREATE SCHEMA {schema}"))
+        .execute(&admin)
+        .await
+        .unwrap();
+
+    PgPoolOptions::new()
+        .max_connections(1)
+        .after_connect(move |conn, _| {
+            let schema = schema.clone();
+            Box::pin(async move {
+                sqlx::query(&format!("SET search_path TO {schema}"))
+                    .execute(conn)
+                    .await?;
+                Ok(())
+            })
+        })
+        .connect(&url)
+        .await
+        .unwrap()
+}
+
+/// The cache schema as deployed before migrations were versioned.
+const V1_SCHEMA: &[&str] = &[
+    "CREATE TABLE payrix_chargebacks (
+        id VARCHAR(50) PRIMARY KEY,
+        data JSONB NOT NULL,
+        merchant_id VARCHAR(50),
+        txn_id VARCHAR(50),
+        cycle VARCHAR(50),
+        status INTEGER,
+        total BIGINT,
+        reason_code VARCHAR(50),
+        created_at TIMESTAMPTZ,
+        modified_at TIMESTAMPTZ,
+        synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    )",
+    "CREATE INDEX idx_chargebacks_merchant ON payrix_chargebacks(merchant_id)",
+    "CREATE TABLE payrix_transactions (
+        id VARCHAR(50) PRIMARY KEY,
+        data JSONB NOT NULL,
+        merchant_id VARCHAR(50),
+        token_id VARCHAR(50),
+        status INTEGER,
+        type INTEGER,
+        total BIGINT,
+        created_at TIMESTAMPTZ,
+        modified_at TIMESTAMPTZ,
+        synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    )",
+    "CREATE INDEX idx_transactions_merchant ON payrix_transactions(merchant_id)",
+    "CREATE TABLE payrix_merchants (
+        id VARCHAR(50) PRIMARY KEY,
+        data JSONB NOT NULL,
+        entity_id VARCHAR(50),
+        status INTEGER,
+        dba VARCHAR(255),
+        created_at TIMESTAMPTZ,
+        modified_at TIMESTAMPTZ,
+        synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    )",
+    "CREATE TABLE payrix_customers (
+        id VARCHAR(50) PRIMARY KEY,
+        data JSONB NOT NULL,
+        merchant_id VARCHAR(50),
+        email VARCHAR(255),
+        first_name VARCHAR(100),
+        last_name VARCHAR(100),
+        created_at TIMESTAMPTZ,
+        modified_at TIMESTAMPTZ,
+        synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    )",
+    "CREATE TABLE payrix_tokens (
+        id VARCHAR(50) PRIMARY KEY,
+        data JSONB NOT NULL,
+        customer_id VARCHAR(50),
+        payment_type INTEGER,
+        status INTEGER,
+        created_at TIMESTAMPTZ,
+        modified_at TIMESTAMPTZ,
+        synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    )",
+    "CREATE TABLE payrix_sync_log (
+        id SERIAL PRIMARY KEY,
+        entity_type VARCHAR(50) NOT NULL,
+        operation VARCHAR(20) NOT NULL,
+        entity_id VARCHAR(50),
+        started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+        completed_at TIMESTAMPTZ,
+        records_processed INTEGER DEFAULT 0,
+        error_message TEXT
+    )",
+];
+
+/// One row per v1 table.
+const V1_ROWS: &[&str] = &[
+    r#"INSERT INTO payrix_chargebacks (id, data, merchant_id, total, reason_code)
+       VALUES ('t1_chb_v1', '{"id": "t1_chb_v1", "reasonCode": "4837"}', 't1_mer_v1', 2500, '4837')"#,
+    r#"INSERT INTO payrix_transactions (id, data, merchant_id, status, type, total)
+       VALUES ('t1_txn_v1', '{"id": "t1_txn_v1"}', 't1_mer_v1', 1, 1, 2500)"#,
+    r#"INSERT INTO payrix_merchants (id, data, entity_id, dba)
+       VALUES ('t1_mer_v1', '{"id": "t1_mer_v1"}', 't1_ent_v1', 'Acme')"#,
+    r#"INSERT INTO payrix_customers (id, data, merchant_id, email)
+       VALUES ('t1_cus_v1', '{"id": "t1_cus_v1"}', 't1_mer_v1', 'jane@example.com')"#,
+    r#"INSERT INTO payrix_tokens (id, data, customer_id, status)
+       VALUES ('t1_tok_v1', '{"id": "t1_tok_v1"}', 't1_cus_v1', 1)"#,
+    "INSERT INTO payrix_sync_log (entity_type, operation, completed_at)
+       VALUES ('chargebacks', 'full', NOW())",
+];
+
+async fn column_exists(pool: &PgPool, table: &str, column: &str) -> bool {
+    sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM information_schema.columns \
+         WHERE table_schema = current_schema() AND table_name = $1 AND column_name = $2)",
+    )
+    .bind(table)
+    .bind(column)
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+#[ignore = "requires TEST_CACHE_DATABASE_URL environment variable"]
+async fn test_upgrade_from_unversioned_v1_schema() {
+    let pool = isolated_pool("upgrade_v1").await;
+
+    // A deployment created before versioning: the original tables with data,
+    // no version table
+    for sql in V1_SCHEMA {
+        sqlx::query(sql).execute(&pool).await.unwrap();
+    }
+    for sql in V1_ROWS {
+        sqlx::query(sql).execute(&pool).await.unwrap();
+    }
+    assert_eq!(schema_version(&pool).await.unwrap(), 0);
+
+    ensure_schema(&pool).await.unwrap();
+
+    assert_eq!(schema_version(&pool).await.unwrap(), SCHEMA_VERSION);
+    assert!(column_exists(&pool, "payrix_sync_log", "watermark").await);
+    assert!(column_exists(&pool, "payrix_plans", "merchant_id").await);
+    for table in [
+        "payrix_chargebacks",
+        "payrix_transactions",
+        "payrix_merchants",
+        "payrix_customers",
+        "payrix_tokens",
+        "payrix_sync_log",
+    ] {
+        let rows: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, 1, "existing rows in {table} are kept");
+    }
+    let (total, reason): (i64, String) = sqlx::query_as(
+        "SELECT total, data->>'reasonCode' FROM payrix_chargebacks WHERE id = 't1_chb_v1'",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!((total, reason.as_str()), (2500, "4837"));
+    let email: String =
+        sqlx::query_scalar("SELECT email FROM payrix_customers WHERE id = 't1_cus_v1'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(email, "jane@example.com");
+    let watermark: Option<String> = sqlx::query_scalar("SELECT watermark FROM payrix_sync_log")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(watermark, None);
+
+    // Running again applies nothing
+    ensure_schema(&pool).await.unwrap();
+    let applied: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM payrix_schema_version")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(applied, i64::from(SCHEMA_VERSION));
+}
+
+#[tokio::test]
+#[ignore = "requires TEST_CACHE_DATABASE_URL environment variable"]
+async fn test_refuses_newer_schema() {
+    let pool = isolated_pool("newer").await;
+    ensure_schema(&pool).await.unwrap();
+
+    sqlx::query("INSERT INTO payrix_schema_version (version, description) VALUES ($1, 'future')")
+        .bind(SCHEMA_VERSION + 1)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let err = ensure_schema(&pool).await.unwrap_err();
+    assert!(err.to_string().contains("newer than version"));
+}
@@ -0,0 +1,114 @@
+//! Cache read policy tests.
+//!
+//! These run against a real PostgreSQL database, in a throwaway schema, with
+//! the Payrix API mocked:
+//!
+//! ```bash
+//! TEST_CACHE_DATABASE_URL=postgres://localhost/payrix_test \
+//!     cargo test --features cache --test cache_policy -- --ignored
+//! ```
+
+#![cfg(feature = "cache")]
+
+use std::env;
+use std::time::Duration;
+
+use payrix::cache::{CacheConfig, CachePolicy, EntityCache};
+use payrix::{Config, Environment, Merchant, PayrixClient};
+use serde_json::json;
+use sqlx::PgPool;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const MERCHANT_ID: &str = "t1_mer_12345678901234567890123";
+
+/// A cache whose tables live in a fresh schema, backed by a mock API.
+async fn isolated_cache(name: &str, server: &MockServer, policy: CachePolicy) -> EntityCache {
+    let url = env::var("TEST_CACHE_DATABASE_URL").expect("TEST_CACHE_DATABASE_URL must be set");
+    let schema = format!("payrix_{}_{}", name, std::process::id());
+
+    let admin = PgPool::connect(&url).await.unwrap();
+    sqlx::query(&format!("DROP SCHEMA IF EXISTS {schema} CASCADE"))
+        .execute(&admin)
+        .await
+        .unwrap();
+    sqlx::query(&format!("CREATE SCHEMA {schema}"))
+        .execute(&admin)
+        .await
+        .unwrap();
+
+    let separator = if url.contains('?') { '&' } else { '?' };
+    let url = format!("{url}{separator}options=-csearch_path%3D{schema}");
+    let config = Config::new("test-api-key", Environment::Test)
+        .with_base_url(format!("{}/", server.uri()));
+    let client = PayrixClient::with_config(config).unwrap();
+
+    EntityCache::with_config(
+        CacheConfig::new(url).with_max_connections(1).with_policy(policy),
+        client,
+    )
+    .await
+    .unwrap()
+}
+
+/// Cache a merchant and backdate its `synced_at` by an hour.
+async fn cache_stale_merchant(cache: &EntityCache) {
+    let merchant: Merchant = serde_json::from_value(json!({
+        "id": MERCHANT_ID,
+        "entity": "t1_ent_12345678901234567890123",
+        "dba": "Cached DBA"
+    }))
+    .unwrap();
+    cache.upsert(&merchant).await.unwrap();
+    sqlx::query("UPDATE payrix_merchants SET synced_at = NOW() - INTERVAL '1 hour'")
+        .execute(cache.pool())
+        .await
+        .unwrap();
+}
+
+async fn mount_failing_api(server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path(format!("/merchants/{MERCHANT_ID}")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "response": {
+                "data": [],
+                "details": { "requestId": 1 },
+                "errors": [{ "code": 15, "msg": "Service unavailable" }]
+            }
+        })))
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+#[ignore = "requires TEST_CACHE_DATABASE_URL"]
+async fn test_max_age_serves_stale_record_when_refresh_fails() {
+    let server = MockServer::start().await;
+    mount_failing_api(&server).await;
+    let cache = isolated_cache(
+        "max_age_stale",
+        &server,
+        CachePolicy::MaxAge(Duration::from_secs(60)),
+    )
+    .await;
+    cache_stale_merchant(&cache).await;
+
+    let merchant = cache
+        .get_or_fetch_merchant(MERCHANT_ID)
+        .await
+        .unwrap()
+        .expect("stale copy should be served");
+    assert_eq!(merchant.dba.as_deref(), Some("Cached DBA"));
+}
+
+#[tokio::test]
+#[ignore = "requires TEST_CACHE_DATABASE_URL"]
+async fn test_api_only_surfaces_refresh_errors() {
+    let server = MockServer::start().await;
+    mount_failing_api(&server).await;
+    let cache = isolated_cache("api_only_error", &server, CachePolicy::ApiOnly).await;
+    cache_stale_merchant(&cache).await;
+
+    assert!(cache.get_or_fetch_merchant(MERCHANT_ID).await.is_err());
+}
@@ -8,7 +8,7 @@ mod common;
 
 use payrix::{Config, Environment, EntityType, PayrixClient};
 use serde_json::json;
-use wiremock::matchers::{header, method, path};
+use wiremock::matchers::{body_partial_json, header, method, path, query_param};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 // =============================================================================
@@ -762,3 +762,1762 @@ async fn test_typed_transaction_response() {
     let txn = &transactions[0];
     assert_eq!(txn.total, Some(1000));
 }
+
+// =============================================================================
+// Workflow Tests
+// =============================================================================
+
+/// Test a bulk accept-liability run against the mock server.
+#[tokio::test]
+async fn test_dispute_batch_accept_liability() {
+    use payrix::workflows::dispute_batch::{
+        DisputeAction, DisputeBatch, DisputeFilter, DisputeOutcome,
+    };
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/chargebacks"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![
+            json!({
+                "id": "t1_chb_small123456789012345678",
+                "merchant": "t1_mer_mock12345678901234567",
+                "total": 1500,
+                "cycle": "first",
+                "status": "open",
+                "actionable": 1
+            }),
+            json!({
+                "id": "t1_chb_large123456789012345678",
+                "merchant": "t1_mer_mock12345678901234567",
+                "total": 50000,
+                "cycle": "first",
+                "status": "open",
+                "actionable": 1
+            }),
+        ])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/chargebackMessages"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_chm_mock12345678901234567",
+            "chargeback": "t1_chb_small123456789012345678",
+            "type": "acceptLiability"
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/chargebacks/t1_chb_small123456789012345678"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_chb_small123456789012345678",
+            "merchant": "t1_mer_mock12345678901234567",
+            "total": 1500,
+            "cycle": "first",
+            "status": "closed",
+            "actionable": 0
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_mock_client(&mock_server);
+    let filter = DisputeFilter::new()
+        .merchant("t1_mer_mock12345678901234567")
+        .max_amount(2500);
+    let batch = DisputeBatch::new(filter, DisputeAction::AcceptLiability);
+
+    let preview = batch
+        .clone()
+        .dry_run(true)
+        .execute(&client)
+        .await
+        .expect("Dry run failed");
+    assert_eq!(preview.would_apply(), 1);
+    assert_eq!(preview.applied(), 0);
+
+    let report = batch.execute(&client).await.expect("Batch failed");
+    assert_eq!(report.items.len(), 1);
+    assert_eq!(report.applied(), 1);
+    assert_eq!(report.applied_total(), 1500);
+    assert_eq!(
+        report.items[0].outcome,
+        DisputeOutcome::Applied { new_state: "terminal" }
+    );
+}
+
+/// Mount the subscription, plan, token link and token lookups used by `change_plan`.
+async fn mount_plan_change_lookups(mock_server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/subscriptions/t1_sbn_old12345678901234567890"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbn_old12345678901234567890",
+            "plan": "t1_pln_basic123456789012345678",
+            "start": 20240101,
+            "tax": 50
+        })])))
+        .mount(mock_server)
+        .await;
+
+    for (id, amount) in [
+        ("t1_pln_basic123456789012345678", 1000),
+        ("t1_pln_premium12345678901234567", 5000),
+    ] {
+        Mock::given(method("GET"))
+            .and(path(format!("/plans/{}", id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+                "id": id,
+                "merchant": "t1_mer_mock12345678901234567",
+                "schedule": 3,
+                "scheduleFactor": 1,
+                "amount": amount
+            })])))
+            .mount(mock_server)
+            .await;
+    }
+
+    Mock::given(method("GET"))
+        .and(path("/subscriptionTokens"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbt_mock12345678901234567",
+            "subscription": "t1_sbn_old12345678901234567890",
+            "token": "t1_tok_mock12345678901234567"
+        })])))
+        .mount(mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/tokens/t1_tok_mock12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_tok_mock12345678901234567",
+            "payment": 2
+        })])))
+        .mount(mock_server)
+        .await;
+}
+
+/// Test an immediate plan upgrade: replace the subscription, then charge.
+#[tokio::test]
+async fn test_change_plan_immediate_upgrade() {
+    use chrono::NaiveDate;
+    use payrix::workflows::subscription_management::{change_plan, ProrationMode};
+
+    let mock_server = MockServer::start().await;
+
+    mount_plan_change_lookups(&mock_server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/txns"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(empty_response()))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/txns"))
+        .and(body_partial_json(json!({
+            "order": "PLANCHG-t1_sbn_old12345678901234567890-t1_pln_premium12345678901234567-20240310"
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_mock12345678901234567",
+            "type": 1,
+            "status": 1
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/subscriptions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbn_new12345678901234567890",
+            "plan": "t1_pln_premium12345678901234567",
+            "tax": 50
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/subscriptionTokens"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbt_new12345678901234567890"
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("PUT"))
+        .and(path("/subscriptions/t1_sbn_old12345678901234567890"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbn_old12345678901234567890",
+            "plan": "t1_pln_basic123456789012345678",
+            "inactive": 1
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_mock_client(&mock_server);
+    let result = change_plan(
+        &client,
+        "t1_sbn_old12345678901234567890",
+        "t1_pln_premium12345678901234567",
+        ProrationMode::Immediate,
+        NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+    )
+    .await
+    .expect("Plan change failed");
+
+    assert!(result.breakdown.net_amount > 0);
+    assert!(result.adjustment.is_some());
+    assert!(result.previous_subscription.inactive);
+    assert_eq!(result.subscription.id.as_str(), "t1_sbn_new12345678901234567890");
+}
+
+/// Test that retrying a plan change reuses the adjustment sent by the first attempt.
+#[tokio::test]
+async fn test_change_plan_retry_reuses_adjustment() {
+    use chrono::NaiveDate;
+    use payrix::workflows::subscription_management::{change_plan, ProrationMode};
+
+    let mock_server = MockServer::start().await;
+    mount_plan_change_lookups(&mock_server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/txns"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_first1234567890123456",
+            "type": 1,
+            "status": 1,
+            "order": "PLANCHG-t1_sbn_old12345678901234567890-t1_pln_premium12345678901234567-20240310"
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/txns"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_second123456789012345",
+            "status": 1
+        })])))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/subscriptions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbn_new12345678901234567890",
+            "plan": "t1_pln_premium12345678901234567"
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/subscriptionTokens"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbt_new12345678901234567890"
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("PUT"))
+        .and(path("/subscriptions/t1_sbn_old12345678901234567890"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbn_old12345678901234567890",
+            "inactive": 1
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_mock_client(&mock_server);
+    let result = change_plan(
+        &client,
+        "t1_sbn_old12345678901234567890",
+        "t1_pln_premium12345678901234567",
+        ProrationMode::Immediate,
+        NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+    )
+    .await
+    .expect("Plan change failed");
+
+    let adjustment = result.adjustment.expect("Expected the earlier adjustment");
+    assert_eq!(adjustment.id.as_str(), "t1_txn_first1234567890123456");
+}
+
+/// Test that a failed cancellation rolls back the plan change without charging.
+#[tokio::test]
+async fn test_change_plan_failed_cancel_rolls_back() {
+    use chrono::NaiveDate;
+    use payrix::workflows::subscription_management::{change_plan, ProrationMode};
+
+    let mock_server = MockServer::start().await;
+    mount_plan_change_lookups(&mock_server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/txns"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_mock12345678901234567",
+            "status": 1
+        })])))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/subscriptions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbn_new12345678901234567890",
+            "plan": "t1_pln_premium12345678901234567"
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/subscriptionTokens"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbt_new12345678901234567890"
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("PUT"))
+        .and(path("/subscriptions/t1_sbn_old12345678901234567890"))
+        .respond_with(ResponseTemplate::new(500).set_body_json(error_response(500, "Internal error")))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("PUT"))
+        .and(path("/subscriptions/t1_sbn_new12345678901234567890"))
+        .and(body_partial_json(json!({"inactive": 1})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbn_new12345678901234567890",
+            "inactive": 1
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_mock_client(&mock_server);
+    let result = change_plan(
+        &client,
+        "t1_sbn_old12345678901234567890",
+        "t1_pln_premium12345678901234567",
+        ProrationMode::Immediate,
+        NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+    )
+    .await;
+
+    assert!(result.is_err());
+}
+
+/// Test that a downgrade credit is capped at what is left after earlier refunds.
+#[tokio::test]
+async fn test_change_plan_credit_capped_by_earlier_refunds() {
+    use chrono::NaiveDate;
+    use payrix::workflows::subscription_management::{change_plan, ProrationMode};
+
+    let mock_server = MockServer::start().await;
+    mount_plan_change_lookups(&mock_server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/plans/t1_pln_lite1234567890123456789"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_pln_lite1234567890123456789",
+            "merchant": "t1_mer_mock12345678901234567",
+            "schedule": 3,
+            "scheduleFactor": 1,
+            "amount": 100
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/txns"))
+        .and(header("search", "subscription[equals]=t1_sbn_old12345678901234567890"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_sale12345678901234567",
+            "type": 1,
+            "status": 4,
+            "total": 1050,
+            "created": "2024-03-01 10:00:00.0000"
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/txns"))
+        .and(header("search", "fortxn[equals]=t1_txn_sale12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_refund123456789012345",
+            "type": 5,
+            "status": 1,
+            "total": 900,
+            "fortxn": "t1_txn_sale12345678901234567"
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/txns"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(empty_response()))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/txns"))
+        .and(body_partial_json(json!({
+            "type": 5,
+            "fortxn": "t1_txn_sale12345678901234567",
+            "total": 150
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_credit123456789012345",
+            "type": 5,
+            "status": 1,
+            "total": 150
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/subscriptions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbn_new12345678901234567890",
+            "plan": "t1_pln_lite1234567890123456789"
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/subscriptionTokens"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbt_new12345678901234567890"
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("PUT"))
+        .and(path("/subscriptions/t1_sbn_old12345678901234567890"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbn_old12345678901234567890",
+            "inactive": 1
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_mock_client(&mock_server);
+    let result = change_plan(
+        &client,
+        "t1_sbn_old12345678901234567890",
+        "t1_pln_lite1234567890123456789",
+        ProrationMode::Immediate,
+        NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+    )
+    .await
+    .expect("Plan change failed");
+
+    assert!(-result.breakdown.net_amount > 150);
+    let credit = result.adjustment.expect("Expected a credit");
+    assert_eq!(credit.total, Some(150));
+}
+
+/// Mount an empty order lookup for a dunning attempt, so it charges.
+async fn mount_no_dunning_txn(mock_server: &MockServer, subscription_id: &str, attempt: u32) {
+    Mock::given(method("GET"))
+        .and(path("/txns"))
+        .and(header("search", format!("order[equals]=DUNNING-{}-{}", subscription_id, attempt).as_str()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(empty_response()))
+        .mount(mock_server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_dunning_final_retry_pauses_subscription() {
+    use chrono::NaiveDate;
+    use payrix::workflows::dunning::{
+        DunningConfig, DunningEngine, DunningEvent, DunningStatus,
+    };
+
+    let mock_server = MockServer::start().await;
+    mount_no_dunning_txn(&mock_server, "t1_sbn_mock12345678901234567", 1).await;
+
+    Mock::given(method("GET"))
+        .and(path("/subscriptions/t1_sbn_mock12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbn_mock12345678901234567",
+            "plan": "t1_pln_mock12345678901234567",
+            "failures": 1,
+            "tax": 50
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/plans/t1_pln_mock12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_pln_mock12345678901234567",
+            "merchant": "t1_mer_mock12345678901234567",
+            "schedule": 3,
+            "scheduleFactor": 1,
+            "amount": 1000,
+            "maxFailures": 5
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/subscriptionTokens"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbt_mock12345678901234567",
+            "subscription": "t1_sbn_mock12345678901234567",
+            "token": "t1_tok_mock12345678901234567"
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/tokens/t1_tok_mock12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_tok_mock12345678901234567",
+            "payment": 2
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/txns"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_mock12345678901234567",
+            "type": 1,
+            "status": 2,
+            "total": 1050
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("PUT"))
+        .and(path("/subscriptions/t1_sbn_mock12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbn_mock12345678901234567",
+            "frozen": 1
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_mock_client(&mock_server);
+    let engine = DunningEngine::new(DunningConfig {
+        retry_days: vec![2],
+        ..Default::default()
+    });
+    let failed_on = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+    let mut state = engine.start("t1_sbn_mock12345678901234567", failed_on);
+
+    let events = engine
+        .process(&client, &mut state, NaiveDate::from_ymd_opt(2024, 3, 12).unwrap())
+        .await
+        .expect("Dunning failed");
+
+    assert_eq!(events.len(), 2);
+    assert!(matches!(
+        events[0],
+        DunningEvent::RetryFailed { next_attempt: None, .. }
+    ));
+    assert!(matches!(events[1], DunningEvent::SubscriptionPaused { .. }));
+    assert_eq!(state.status, DunningStatus::Exhausted);
+    assert_eq!(state.attempts.len(), 1);
+}
+
+/// Test that a failed pause is retried on the next run without charging again,
+/// and that a bank account token is charged with an eCheck sale.
+#[tokio::test]
+async fn test_dunning_failed_pause_is_retried_without_charging() {
+    use chrono::NaiveDate;
+    use payrix::workflows::dunning::{
+        DunningConfig, DunningEngine, DunningEvent, DunningStatus,
+    };
+
+    let mock_server = MockServer::start().await;
+    mount_no_dunning_txn(&mock_server, "t1_sbn_ach123456789012345678", 1).await;
+
+    Mock::given(method("GET"))
+        .and(path("/subscriptions/t1_sbn_ach123456789012345678"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbn_ach123456789012345678",
+            "plan": "t1_pln_ach123456789012345678"
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/plans/t1_pln_ach123456789012345678"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_pln_ach123456789012345678",
+            "merchant": "t1_mer_ach123456789012345678",
+            "schedule": 3,
+            "amount": 1000
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/subscriptionTokens"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbt_ach123456789012345678",
+            "subscription": "t1_sbn_ach123456789012345678",
+            "token": "t1_tok_ach123456789012345678"
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/tokens/t1_tok_ach123456789012345678"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_tok_ach123456789012345678",
+            "payment": 8
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/txns"))
+        .and(body_partial_json(json!({"type": 7})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_ach123456789012345678",
+            "type": 7,
+            "status": 2,
+            "total": 1000
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("PUT"))
+        .and(path("/subscriptions/t1_sbn_ach123456789012345678"))
+        .respond_with(ResponseTemplate::new(500).set_body_json(error_response(500, "Internal error")))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("PUT"))
+        .and(path("/subscriptions/t1_sbn_ach123456789012345678"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbn_ach123456789012345678",
+            "frozen": 1
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_mock_client(&mock_server);
+    let engine = DunningEngine::new(DunningConfig {
+        retry_days: vec![2],
+        ..Default::default()
+    });
+    let mut state = engine.start(
+        "t1_sbn_ach123456789012345678",
+        NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+    );
+    let today = NaiveDate::from_ymd_opt(2024, 3, 12).unwrap();
+
+    let events = engine
+        .process(&client, &mut state, today)
+        .await
+        .expect("Dunning failed");
+    assert!(matches!(events.last(), Some(DunningEvent::FinalActionFailed { .. })));
+    assert_eq!(state.status, DunningStatus::FinalActionPending);
+    assert_eq!(state.attempts.len(), 1);
+    assert!(state.is_due(today));
+
+    let events = engine
+        .process(&client, &mut state, today)
+        .await
+        .expect("Dunning failed");
+    assert_eq!(events.len(), 1);
+    assert!(matches!(events[0], DunningEvent::SubscriptionPaused { .. }));
+    assert_eq!(state.status, DunningStatus::Exhausted);
+    assert_eq!(state.attempts.len(), 1);
+}
+
+/// Mount the subscription, plan, token link and token for a dunning test.
+async fn mount_dunning_subscription(mock_server: &MockServer, suffix: &str, payment: i32) {
+    Mock::given(method("GET"))
+        .and(path(format!("/subscriptions/t1_sbn_{suffix}")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": format!("t1_sbn_{suffix}"),
+            "plan": format!("t1_pln_{suffix}")
+        })])))
+        .mount(mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path(format!("/plans/t1_pln_{suffix}")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": format!("t1_pln_{suffix}"),
+            "merchant": format!("t1_mer_{suffix}"),
+            "schedule": 3,
+            "amount": 1000
+        })])))
+        .mount(mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/subscriptionTokens"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": format!("t1_sbt_{suffix}"),
+            "subscription": format!("t1_sbn_{suffix}"),
+            "token": format!("t1_tok_{suffix}")
+        })])))
+        .mount(mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path(format!("/tokens/t1_tok_{suffix}")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": format!("t1_tok_{suffix}"),
+            "payment": payment
+        })])))
+        .mount(mock_server)
+        .await;
+}
+
+/// Test that a pending eCheck retry is checked on later runs, not charged again.
+#[tokio::test]
+async fn test_dunning_pending_retry_is_not_charged_again() {
+    use chrono::NaiveDate;
+    use payrix::workflows::dunning::{DunningEngine, DunningEvent, DunningStatus};
+
+    let mock_server = MockServer::start().await;
+    mount_dunning_subscription(&mock_server, "pend12345678901234567", 8).await;
+    mount_no_dunning_txn(&mock_server, "t1_sbn_pend12345678901234567", 1).await;
+
+    Mock::given(method("POST"))
+        .and(path("/txns"))
+        .and(body_partial_json(json!({
+            "type": 7,
+            "order": "DUNNING-t1_sbn_pend12345678901234567-1"
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_pend12345678901234567",
+            "type": 7,
+            "status": 0,
+            "total": 1000
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/txns/t1_txn_pend12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_pend12345678901234567",
+            "type": 7,
+            "status": 0,
+            "total": 1000
+        })])))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/txns/t1_txn_pend12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_pend12345678901234567",
+            "type": 7,
+            "status": 1,
+            "total": 1000
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_mock_client(&mock_server);
+    let engine = DunningEngine::default();
+    let mut state = engine.start(
+        "t1_sbn_pend12345678901234567",
+        NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+    );
+    let today = NaiveDate::from_ymd_opt(2024, 3, 11).unwrap();
+
+    let events = engine.process(&client, &mut state, today).await.expect("Dunning failed");
+    assert!(matches!(
+        events.as_slice(),
+        [DunningEvent::RetryProcessing { attempt: 1, .. }]
+    ));
+    assert_eq!(state.status, DunningStatus::Processing);
+    assert_eq!(state.attempts.len(), 1);
+    assert!(!state.attempts[0].succeeded);
+
+    // Still pending a few days later: nothing is charged.
+    let later = NaiveDate::from_ymd_opt(2024, 3, 14).unwrap();
+    assert!(state.is_due(later));
+    let events = engine.process(&client, &mut state, later).await.expect("Dunning failed");
+    assert!(matches!(events.as_slice(), [DunningEvent::RetryProcessing { .. }]));
+    assert_eq!(state.attempts.len(), 1);
+
+    let events = engine.process(&client, &mut state, later).await.expect("Dunning failed");
+    assert!(matches!(
+        events.as_slice(),
+        [DunningEvent::RetrySucceeded { attempt: 1, amount: 1000, .. }]
+    ));
+    assert_eq!(state.status, DunningStatus::Recovered);
+    assert!(state.attempts[0].succeeded);
+}
+
+/// Test that a run repeated after a lost charge response reuses the charge.
+#[tokio::test]
+async fn test_dunning_reuses_charge_for_attempt_order() {
+    use chrono::NaiveDate;
+    use payrix::workflows::dunning::{DunningEngine, DunningEvent, DunningStatus};
+
+    let mock_server = MockServer::start().await;
+    mount_dunning_subscription(&mock_server, "lost12345678901234567", 2).await;
+
+    Mock::given(method("GET"))
+        .and(path("/txns"))
+        .and(header("search", "order[equals]=DUNNING-t1_sbn_lost12345678901234567-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_lost12345678901234567",
+            "type": 1,
+            "status": 1,
+            "total": 1000,
+            "order": "DUNNING-t1_sbn_lost12345678901234567-1"
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/txns"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_mock_client(&mock_server);
+    let engine = DunningEngine::default();
+    let mut state = engine.start(
+        "t1_sbn_lost12345678901234567",
+        NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+    );
+
+    let events = engine
+        .process(&client, &mut state, NaiveDate::from_ymd_opt(2024, 3, 11).unwrap())
+        .await
+        .expect("Dunning failed");
+    assert!(matches!(
+        &events[0],
+        DunningEvent::RetrySucceeded { transaction_id, .. } if transaction_id == "t1_txn_lost12345678901234567"
+    ));
+    assert_eq!(state.status, DunningStatus::Recovered);
+}
+
+#[tokio::test]
+async fn test_dunning_stops_on_hard_decline() {
+    use chrono::NaiveDate;
+    use payrix::workflows::declines::DeclineReason;
+    use payrix::workflows::dunning::{DunningEngine, DunningEvent, DunningStatus};
+
+    let mock_server = MockServer::start().await;
+    mount_no_dunning_txn(&mock_server, "t1_sbn_hard12345678901234567", 1).await;
+
+    Mock::given(method("GET"))
+        .and(path("/subscriptions/t1_sbn_hard12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbn_hard12345678901234567",
+            "plan": "t1_pln_hard12345678901234567"
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/plans/t1_pln_hard12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_pln_hard12345678901234567",
+            "merchant": "t1_mer_hard12345678901234567",
+            "schedule": 3,
+            "amount": 1000
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/subscriptionTokens"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbt_hard12345678901234567",
+            "subscription": "t1_sbn_hard12345678901234567",
+            "token": "t1_tok_hard12345678901234567"
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/tokens/t1_tok_hard12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_tok_hard12345678901234567",
+            "payment": 2
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/txns"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_hard12345678901234567",
+            "type": 1,
+            "status": 2,
+            "total": 1000
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/txns/t1_txn_hard12345678901234567"))
+        .and(query_param("expand[txnResults][]", ""))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_hard12345678901234567",
+            "type": 1,
+            "status": 2,
+            "total": 1000,
+            "txnResults": [{
+                "id": "t1_txr_hard12345678901234567",
+                "type": 1,
+                "code": 21,
+                "message": "14 INVALID CARD NUMBER"
+            }]
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("PUT"))
+        .and(path("/subscriptions/t1_sbn_hard12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbn_hard12345678901234567",
+            "frozen": 1
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_mock_client(&mock_server);
+    let engine = DunningEngine::default();
+    let failed_on = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+    let mut state = engine.start("t1_sbn_hard12345678901234567", failed_on);
+
+    // The first of three retries hits an invalid card, so the rest are skipped.
+    let events = engine
+        .process(&client, &mut state, NaiveDate::from_ymd_opt(2024, 3, 11).unwrap())
+        .await
+        .expect("Dunning failed");
+
+    assert_eq!(events.len(), 3);
+    assert!(matches!(
+        events[0],
+        DunningEvent::RetryFailed { next_attempt: None, .. }
+    ));
+    assert!(matches!(
+        &events[1],
+        DunningEvent::DeclineNotRetryable { decline, .. } if decline.reason == DeclineReason::InvalidAccount
+    ));
+    assert!(matches!(events[2], DunningEvent::SubscriptionPaused { .. }));
+    assert_eq!(state.status, DunningStatus::Exhausted);
+    let decline = state.attempts[0].decline.as_ref().unwrap();
+    assert_eq!(decline.processor_message.as_deref(), Some("14 INVALID CARD NUMBER"));
+}
+
+/// Test that a hard decline is recorded before the pause, so a failed pause
+/// is retried on its own and the card is not charged again.
+#[tokio::test]
+async fn test_dunning_hard_decline_survives_failed_pause() {
+    use chrono::NaiveDate;
+    use payrix::workflows::declines::DeclineReason;
+    use payrix::workflows::dunning::{DunningEngine, DunningEvent, DunningStatus};
+
+    let mock_server = MockServer::start().await;
+    mount_no_dunning_txn(&mock_server, "t1_sbn_hdpf12345678901234567", 1).await;
+
+    Mock::given(method("GET"))
+        .and(path("/subscriptions/t1_sbn_hdpf12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbn_hdpf12345678901234567",
+            "plan": "t1_pln_hdpf12345678901234567"
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/plans/t1_pln_hdpf12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_pln_hdpf12345678901234567",
+            "merchant": "t1_mer_hdpf12345678901234567",
+            "schedule": 3,
+            "amount": 1000
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/subscriptionTokens"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbt_hdpf12345678901234567",
+            "subscription": "t1_sbn_hdpf12345678901234567",
+            "token": "t1_tok_hdpf12345678901234567"
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/tokens/t1_tok_hdpf12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_tok_hdpf12345678901234567",
+            "payment": 2
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/txns"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_hdpf12345678901234567",
+            "type": 1,
+            "status": 2,
+            "total": 1000
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/txns/t1_txn_hdpf12345678901234567"))
+        .and(query_param("expand[txnResults][]", ""))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_hdpf12345678901234567",
+            "type": 1,
+            "status": 2,
+            "total": 1000,
+            "txnResults": [{
+                "id": "t1_txr_hdpf12345678901234567",
+                "type": 1,
+                "code": 21,
+                "message": "14 INVALID CARD NUMBER"
+            }]
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("PUT"))
+        .and(path("/subscriptions/t1_sbn_hdpf12345678901234567"))
+        .respond_with(ResponseTemplate::new(500).set_body_json(error_response(500, "Internal error")))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("PUT"))
+        .and(path("/subscriptions/t1_sbn_hdpf12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_sbn_hdpf12345678901234567",
+            "frozen": 1
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_mock_client(&mock_server);
+    let engine = DunningEngine::default();
+    let failed_on = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+    let mut state = engine.start("t1_sbn_hdpf12345678901234567", failed_on);
+    let today = NaiveDate::from_ymd_opt(2024, 3, 11).unwrap();
+
+    let events = engine
+        .process(&client, &mut state, today)
+        .await
+        .expect("Dunning failed");
+
+    assert_eq!(events.len(), 3);
+    assert!(matches!(
+        &events[1],
+        DunningEvent::DeclineNotRetryable { decline, .. } if decline.reason == DeclineReason::InvalidAccount
+    ));
+    assert!(matches!(events[2], DunningEvent::FinalActionFailed { .. }));
+    assert_eq!(state.status, DunningStatus::FinalActionPending);
+    assert_eq!(state.attempts.len(), 1);
+    let decline = state.attempts[0].decline.as_ref().unwrap();
+    assert_eq!(decline.reason, DeclineReason::InvalidAccount);
+
+    // The next run only pauses; the POST mock allows a single charge.
+    let events = engine
+        .process(&client, &mut state, today)
+        .await
+        .expect("Dunning failed");
+    assert_eq!(events.len(), 1);
+    assert!(matches!(events[0], DunningEvent::SubscriptionPaused { .. }));
+    assert_eq!(state.status, DunningStatus::Exhausted);
+    assert_eq!(state.attempts.len(), 1);
+}
+
+#[tokio::test]
+async fn test_onboard_merchant_resumable_adds_missing_account() {
+    use payrix::types::{
+        AccountHolderType, AccountType, DateYmd, MemberType, MerchantEnvironment, MerchantType,
+    };
+    use payrix::workflows::merchant_onboarding::{
+        onboard_merchant_resumable, Address, BankAccountInfo, BankAccountMethod, BusinessInfo,
+        MemberInfo, MerchantConfig, OnboardMerchantRequest, ResourceOutcome, TermsAcceptance,
+    };
+
+    let mock_server = MockServer::start().await;
+
+    let address = Address {
+        line1: "123 Main St".to_string(),
+        line2: None,
+        city: "Chicago".to_string(),
+        state: "IL".to_string(),
+        zip: "60601".to_string(),
+        country: "USA".to_string(),
+    };
+    let account = |name: &str, number: &str, primary: bool| BankAccountInfo {
+        name: Some(name.to_string()),
+        routing_number: Some("121000358".to_string()),
+        account_number: Some(number.to_string()),
+        holder_type: AccountHolderType::Business,
+        account_method: BankAccountMethod::Checking,
+        transaction_type: AccountType::All,
+        currency: Some("USD".to_string()),
+        is_primary: primary,
+        plaid_public_token: None,
+    };
+    let request = OnboardMerchantRequest {
+        business: BusinessInfo {
+            business_type: MerchantType::LimitedLiabilityCorporation,
+            legal_name: "Test LLC".to_string(),
+            address: address.clone(),
+            phone: "5551234567".to_string(),
+            email: "test@example.com".to_string(),
+            website: None,
+            ein: "123456789".to_string(),
+        },
+        merchant: MerchantConfig {
+            dba: "Test DBA".to_string(),
+            mcc: "5999".to_string(),
+            environment: MerchantEnvironment::Ecommerce,
+            annual_cc_sales: 100000,
+            avg_ticket: 5000,
+            established: DateYmd::new("20200101").unwrap(),
+            is_new_business: false,
+        },
+        accounts: vec![
+            account("Operating", "987654321", true),
+            account("Trust", "987655555", false),
+        ],
+        members: vec![MemberInfo {
+            member_type: MemberType::ControlPerson,
+            first_name: "John".to_string(),
+            last_name: "Doe".to_string(),
+            title: None,
+            ownership_percentage: 100,
+            date_of_birth: "19800115".to_string(),
+            ssn: "123456789".to_string(),
+            email: "john@example.com".to_string(),
+            phone: "5551234567".to_string(),
+            address,
+        }],
+        terms_acceptance: TermsAcceptance {
+            version: "4.21".to_string(),
+            accepted_at: "2024-01-15 10:30:00".to_string(),
+        },
+    };
+
+    let entity = json!({
+        "id": "t1_ent_mock12345678901234567",
+        "name": "TEST LLC",
+        "ein": "123456789"
+    });
+    let merchant = json!({
+        "id": "t1_mer_mock12345678901234567",
+        "entity": "t1_ent_mock12345678901234567",
+        "status": 2
+    });
+
+    Mock::given(method("GET"))
+        .and(path("/entities"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![entity.clone()])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/entities/t1_ent_mock12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![entity])))
+        .mount(&mock_server)
+        .await;
+
+    for merchant_path in ["/merchants", "/merchants/t1_mer_mock12345678901234567"] {
+        Mock::given(method("GET"))
+            .and(path(merchant_path))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(payrix_response(vec![merchant.clone()])),
+            )
+            .mount(&mock_server)
+            .await;
+    }
+
+    Mock::given(method("GET"))
+        .and(path("/members"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_mem_mock12345678901234567",
+            "merchant": "t1_mer_mock12345678901234567",
+            "first": "John",
+            "last": "Doe",
+            "dob": "19800115"
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/accounts"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_acc_oper12345678901234567",
+            "entity": "t1_ent_mock12345678901234567",
+            "name": "Operating",
+            "mask": "****4321",
+            "primary": 1
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/accounts"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_acc_trust1234567890123456",
+            "entity": "t1_ent_mock12345678901234567",
+            "name": "Trust"
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    for create_path in ["/entities", "/merchants", "/members"] {
+        Mock::given(method("POST"))
+            .and(path(create_path))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+    }
+
+    let client = create_mock_client(&mock_server);
+    let outcome = onboard_merchant_resumable(&client, request)
+        .await
+        .expect("Resumable onboarding failed");
+
+    assert!(!outcome.is_new());
+    assert_eq!(
+        outcome.entity,
+        ResourceOutcome::Reused("t1_ent_mock12345678901234567".to_string())
+    );
+    assert_eq!(
+        outcome.merchant,
+        ResourceOutcome::Reused("t1_mer_mock12345678901234567".to_string())
+    );
+    assert_eq!(
+        outcome.accounts,
+        vec![
+            ResourceOutcome::Reused("t1_acc_oper12345678901234567".to_string()),
+            ResourceOutcome::Created("t1_acc_trust1234567890123456".to_string()),
+        ]
+    );
+    assert_eq!(
+        outcome.members,
+        vec![ResourceOutcome::Reused("t1_mem_mock12345678901234567".to_string())]
+    );
+    assert_eq!(outcome.result.merchant_id, "t1_mer_mock12345678901234567");
+}
+
+#[tokio::test]
+async fn test_wait_for_boarding_records_transitions() {
+    use payrix::workflows::boarding_watch::{
+        wait_for_boarding, watch_boarding, BoardingUpdate, WaitForBoardingOptions,
+    };
+    use payrix::workflows::merchant_onboarding::BoardingStatus;
+    use std::time::Duration;
+
+    let mock_server = MockServer::start().await;
+
+    for status in [1, 6] {
+        Mock::given(method("GET"))
+            .and(path("/merchants/t1_mer_mock12345678901234567"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+                "id": "t1_mer_mock12345678901234567",
+                "entity": "t1_ent_mock12345678901234567",
+                "status": status
+            })])))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+    }
+    Mock::given(method("GET"))
+        .and(path("/merchants/t1_mer_mock12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_mer_mock12345678901234567",
+            "entity": "t1_ent_mock12345678901234567",
+            "status": 2
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_mock_client(&mock_server);
+    let opts = WaitForBoardingOptions::default()
+        .with_initial_interval(Duration::from_millis(5))
+        .with_timeout(Duration::from_secs(5));
+
+    let result = wait_for_boarding(&client, "t1_mer_mock12345678901234567", &opts)
+        .await
+        .expect("Boarding watch failed");
+
+    assert!(result.is_boarded());
+    assert!(!result.timed_out);
+    assert_eq!(result.polls, 3);
+    let statuses: Vec<_> = result.history.iter().map(|t| (t.from, t.to)).collect();
+    assert_eq!(
+        statuses,
+        vec![
+            (None, BoardingStatus::Submitted),
+            (Some(BoardingStatus::Submitted), BoardingStatus::Pending),
+            (Some(BoardingStatus::Pending), BoardingStatus::Boarded),
+        ]
+    );
+
+    // The stream variant reports the (now boarded) merchant and a missing one
+    let mut updates = watch_boarding(
+        client,
+        vec![
+            "t1_mer_mock12345678901234567".to_string(),
+            "t1_mer_missing123456789012345".to_string(),
+        ],
+        opts,
+    );
+    let (mut finished, mut failed) = (0, 0);
+    while let Some(update) = updates.recv().await {
+        match update {
+            BoardingUpdate::Finished(result) => {
+                assert!(result.is_boarded());
+                finished += 1;
+            }
+            BoardingUpdate::Failed { merchant_id, .. } => {
+                assert_eq!(merchant_id, "t1_mer_missing123456789012345");
+                failed += 1;
+            }
+            BoardingUpdate::Transition { .. } => {}
+        }
+    }
+    assert_eq!((finished, failed), (1, 1));
+}
+
+#[tokio::test]
+async fn test_replace_primary_account_reports_review() {
+    use payrix::types::{AccountHolderType, AccountType};
+    use payrix::workflows::merchant_maintenance::{replace_primary_account, MaintenanceChange};
+    use payrix::workflows::merchant_onboarding::{BankAccountInfo, BankAccountMethod, BoardingStatus};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/merchants"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_mer_mock12345678901234567",
+            "entity": "t1_ent_mock12345678901234567",
+            "status": 2
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/merchants/t1_mer_mock12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_mer_mock12345678901234567",
+            "entity": "t1_ent_mock12345678901234567",
+            "status": 3
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/accounts"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_acc_old12345678901234567890",
+            "entity": "t1_ent_mock12345678901234567",
+            "mask": "****4321",
+            "primary": 1
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/accounts"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_acc_new12345678901234567890",
+            "entity": "t1_ent_mock12345678901234567",
+            "primary": 0
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    for (account_id, primary) in [
+        ("t1_acc_old12345678901234567890", 0),
+        ("t1_acc_new12345678901234567890", 1),
+    ] {
+        Mock::given(method("PUT"))
+            .and(path(format!("/accounts/{}", account_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+                "id": account_id,
+                "primary": primary
+            })])))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+    }
+
+    let client = create_mock_client(&mock_server);
+    let account = BankAccountInfo {
+        name: Some("New Operating".to_string()),
+        routing_number: Some("121000358".to_string()),
+        account_number: Some("555566667777".to_string()),
+        holder_type: AccountHolderType::Business,
+        account_method: BankAccountMethod::Checking,
+        transaction_type: AccountType::All,
+        currency: Some("USD".to_string()),
+        is_primary: true,
+        plaid_public_token: None,
+    };
+
+    let result = replace_primary_account(&client, "t1_ent_mock12345678901234567", account)
+        .await
+        .expect("Account replacement failed");
+
+    assert_eq!(result.change, MaintenanceChange::PrimaryAccountReplaced);
+    assert!(result.resource.primary);
+    assert_eq!(result.status_before, BoardingStatus::Boarded);
+    assert_eq!(result.status_after, BoardingStatus::ManualReview);
+    assert!(result.review_triggered);
+    assert!(result.description.contains("****4321"));
+    assert!(result.description.contains("7777"));
+    assert!(!result.description.contains("555566667777"));
+}
+
+#[tokio::test]
+async fn test_reconcile_disbursement_breakdown() {
+    use payrix::workflows::reconciliation::{reconcile_disbursement, ReconciliationIssue};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/disbursements/t1_dbm_mock12345678901234567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_dbm_mock12345678901234567",
+            "entity": "t1_ent_mock12345678901234567",
+            "amount": 9500
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/disbursementEntries"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![
+            json!({
+                "id": "t1_dbe_mock12345678901234561",
+                "disbursement": "t1_dbm_mock12345678901234567",
+                "entry": "t1_ent_sale12345678901234567",
+                "amount": 10000
+            }),
+            json!({
+                "id": "t1_dbe_mock12345678901234562",
+                "disbursement": "t1_dbm_mock12345678901234567",
+                "entry": "t1_ent_fee123456789012345678",
+                "amount": -300
+            }),
+            json!({
+                "id": "t1_dbe_mock12345678901234563",
+                "disbursement": "t1_dbm_mock12345678901234567",
+                "reserveEntry": "t1_rse_mock12345678901234567",
+                "amount": -500
+            }),
+        ])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/entries"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![
+            json!({
+                "id": "t1_ent_sale12345678901234567",
+                "txn": "t1_txn_mock12345678901234567",
+                "event": 7
+            }),
+            json!({
+                "id": "t1_ent_fee123456789012345678",
+                "fee": "t1_fee_mock12345678901234567",
+                "event": 14
+            }),
+        ])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_mock_client(&mock_server);
+    let report = reconcile_disbursement(&client, "t1_dbm_mock12345678901234567")
+        .await
+        .expect("Reconciliation failed");
+
+    assert_eq!(report.breakdown.gross_sales, 10000);
+    assert_eq!(report.breakdown.fees, -300);
+    assert_eq!(report.breakdown.reserve_holds, -500);
+    assert_eq!(report.breakdown.net, 9200);
+    assert_eq!(
+        report.issues,
+        vec![ReconciliationIssue::AmountMismatch {
+            expected: 9500,
+            actual: 9200
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_release_hold_only_releases_open_holds() {
+    use payrix::types::ReleaseAction;
+    use payrix::workflows::reserves::release_hold;
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/holds/t1_hld_open1234567890123456789"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_hld_open1234567890123456789",
+            "action": 3
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/holds/t1_hld_done1234567890123456789"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_hld_done1234567890123456789",
+            "released": "2024-03-01 10:00:00",
+            "releaseAction": 1
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("PUT"))
+        .and(path("/holds/t1_hld_open1234567890123456789"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_hld_open1234567890123456789",
+            "action": 3,
+            "releaseAction": 1
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_mock_client(&mock_server);
+    let hold = release_hold(&client, "t1_hld_open1234567890123456789", ReleaseAction::Approved)
+        .await
+        .unwrap();
+    assert_eq!(hold.release_action, Some(ReleaseAction::Approved));
+
+    let err = release_hold(&client, "t1_hld_done1234567890123456789", ReleaseAction::Approved)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("already released"));
+}
+
+#[tokio::test]
+async fn test_export_csv_pages_and_resumes() {
+    use payrix::export::{ExportFormat, ExportRequest, Exporter};
+
+    let mock_server = MockServer::start().await;
+
+    let mut first_page = payrix_response(vec![json!({
+        "id": "t1_txn_00000000000000000000001",
+        "type": 1,
+        "total": 1000,
+        "payment": { "method": 2 }
+    })]);
+    first_page["response"]["details"]["page"]["hasMore"] = json!(true);
+
+    Mock::given(method("GET"))
+        .and(path("/txns"))
+        .and(query_param("page[number]", "1"))
+        .and(query_param("expand[payment][]", ""))
+        .and(header("search", "id[sort]=asc"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(first_page))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/txns"))
+        .and(query_param("page[number]", "1"))
+        .and(header(
+            "search",
+            "id[greater]=t1_txn_00000000000000000000001&id[sort]=asc",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_00000000000000000000002",
+            "type": 5,
+            "total": 250,
+            "payment": { "method": 3 }
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_mock_client(&mock_server);
+    let request = ExportRequest::new(EntityType::Txns)
+        .fields(&["id", "type", "total", "payment.method"])
+        .format(ExportFormat::Csv)
+        .page_size(1);
+
+    // Write one page, then resume from the saved cursor.
+    let mut out = Vec::new();
+    let mut exporter = Exporter::new(&client, request.clone());
+    assert!(exporter.write_page(&mut out).await.unwrap());
+    let cursor = exporter.cursor().clone();
+    assert_eq!(cursor.next_page, 2);
+    assert_eq!(cursor.last_id.as_deref(), Some("t1_txn_00000000000000000000001"));
+    assert_eq!(cursor.records, 1);
+    assert_eq!(cursor.bytes, out.len() as u64);
+
+    // A crash mid-page leaves rows the cursor does not count; truncating to
+    // the saved length before resuming drops them.
+    out.extend_from_slice(b"t1_txn_000000000000");
+    out.truncate(cursor.bytes as usize);
+
+    let mut exporter = Exporter::resume(&client, request, cursor);
+    assert_eq!(exporter.write_all(&mut out).await.unwrap(), 2);
+    assert!(exporter.cursor().complete);
+
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "id,type,total,payment.method\n\
+         t1_txn_00000000000000000000001,CreditCardSale,1000,Visa\n\
+         t1_txn_00000000000000000000002,CreditCardRefund,250,Mastercard\n"
+    );
+}
+
+#[tokio::test]
+async fn test_update_payout_validates_merged_config() {
+    use payrix::types::PayoutUnit;
+    use payrix::workflows::payouts::{update_payout, PayoutUpdate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/payouts/t1_pay_12345678901234567890123"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_pay_12345678901234567890123",
+            "schedule": 1,
+            "um": 1,
+            "amount": 10000,
+            "minimum": 5000
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("PUT"))
+        .and(path("/payouts/t1_pay_12345678901234567890123"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_pay_12345678901234567890123",
+            "schedule": 1,
+            "um": 1,
+            "amount": 5000,
+            "minimum": 5000
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_mock_client(&mock_server);
+
+    // A maximum below the existing minimum is rejected before any update.
+    let err = update_payout(
+        &client,
+        "t1_pay_12345678901234567890123",
+        PayoutUpdate::default().with_limits(None, Some(1000)),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("Maximum is below the minimum"));
+
+    let payout = update_payout(
+        &client,
+        "t1_pay_12345678901234567890123",
+        PayoutUpdate::default().with_amount(PayoutUnit::Percent, 5000),
+    )
+    .await
+    .unwrap();
+    assert_eq!(payout.amount, Some(5000));
+}
+
+#[tokio::test]
+async fn test_refund_counts_earlier_refunds() {
+    use payrix::workflows::payments::{refund, PaymentAction};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/txns/t1_txn_sale123456789012345678901"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_sale123456789012345678901",
+            "merchant": "t1_mer_12345678901234567890123",
+            "type": 1,
+            "status": 4,
+            "total": 10000
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/txns"))
+        .and(header("search", "fortxn[equals]=t1_txn_sale123456789012345678901"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_rfnd123456789012345678901",
+            "type": 5,
+            "status": 3,
+            "total": 7000,
+            "fortxn": "t1_txn_sale123456789012345678901"
+        })])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/txns"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payrix_response(vec![json!({
+            "id": "t1_txn_rfnd223456789012345678901",
+            "type": 5,
+            "status": 1,
+            "total": 3000,
+            "fortxn": "t1_txn_sale123456789012345678901"
+        })])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_mock_client(&mock_server);
+
+    let err = refund(&client, "t1_txn_sale123456789012345678901", Some(4000))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("only 3000 cents remain"));
+
+    let result = refund(&client, "t1_txn_sale123456789012345678901", None)
+        .await
+        .unwrap();
+    assert_eq!(result.action, PaymentAction::Refund);
+    assert_eq!(result.amount, 3000);
+    assert_eq!(result.remaining, 0);
+    assert!(result.is_approved());
+}
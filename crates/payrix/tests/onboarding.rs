@@ -59,7 +59,7 @@ fn create_test_onboarding_request() -> OnboardMerchantRequest {
             plaid_public_token: None,
         }],
         members: vec![MemberInfo {
-            member_type: MemberType::Owner,
+            member_type: MemberType::ControlPerson,
             first_name: "Test".to_string(),
             last_name: "Owner".to_string(),
             title: Some("CEO".to_string()),